@@ -8,15 +8,25 @@ use axum::{Router, routing::get, response::Html};
 // 导入所有模型以便在文档中使用
 use models::{
     // 用户相关
-    User, UserProfile, RegisterDto, LoginDto, UpdateProfileDto, AuthResponse,
+    User, UserProfile, RegisterDto, LoginDto, UpdateProfileDto, AuthResponse, UserRole, GrantRoleDto,
+    RefreshTokenDto, SendCodeDto, SendCodeResponse, PhoneLoginDto,
+    ChangePasswordDto, ForgotPasswordDto, ResetPasswordDto,
+    UserIdentity, AuthorizationUrlResponse, OAuthCallbackDto,
+    AccountExport, AccountExportProfile, ExportFormat, ExportQuery, DeleteAccountDto, AccountDeletionResponse,
+    AvatarUploadForm, AvatarUploadResponse, AvatarVariant,
     // 模板相关
-    Template, TemplateStep, LocationTag, CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery,
+    Template, TemplateStep, CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery,
+    TemplateVersion, ResolvedTemplate, TemplateSearchResult, SearchHighlight, SetOfficialDto,
     // 清单相关
-    UserChecklist, StepProgress, ChecklistProgress, ForkTemplateDto, UpdateStepDto, UserChecklistResponse,
+    UserChecklist, StepProgress, StepStatus, StepState, ChecklistProgress, ForkTemplateDto, UpdateStepDto, UserChecklistResponse,
+    ChecklistStep, AddChecklistStepDto, UpdateChecklistStepDto, ReorderChecklistStepsDto,
+    ChecklistSyncReport, SyncedStep, ChecklistSyncResponse,
+    // 地区相关
+    Location, LocationLevel, LocationQuery,
 };
 
-// 导入 ApiResponse 用于文档
-use common::ApiResponse;
+// 导入统一响应结构用于文档
+use common::{ApiResponse, ApiError, ErrorCode, ErrorResponse, FieldError};
 
 /// 主 OpenAPI 文档定义
 /// 
@@ -44,32 +54,87 @@ use common::ApiResponse;
         // 认证相关
         crate::handlers::auth::register,
         crate::handlers::auth::login,
+        crate::handlers::auth::send_login_code,
+        crate::handlers::auth::login_with_code,
+        crate::handlers::auth::refresh,
+        crate::handlers::auth::logout,
+        crate::handlers::auth::logout_all,
+        crate::handlers::auth::forgot_password,
+        crate::handlers::auth::reset_password,
+        crate::handlers::oauth::list_providers,
+        crate::handlers::oauth::authorize,
+        crate::handlers::oauth::login,
         
         // 用户相关
         crate::handlers::user::get_current_user,
         crate::handlers::user::update_profile,
+        crate::handlers::user::upload_avatar,
+        crate::handlers::user::change_password,
+        crate::handlers::user::export_data,
+        crate::handlers::user::delete_account,
+        crate::handlers::oauth::list_identities,
+        crate::handlers::oauth::authorize_link,
+        crate::handlers::oauth::link,
+        crate::handlers::oauth::unlink,
         
         // 模板相关
         crate::handlers::template::list_templates,
         crate::handlers::template::search_templates,
         crate::handlers::template::get_template,
         crate::handlers::template::create_template,
+        crate::handlers::template::update_template,
+        crate::handlers::template::delete_template,
+        crate::handlers::template::list_template_versions,
+        crate::handlers::template::get_template_version,
+        crate::handlers::template::get_resolved_template,
         
         // 清单相关
         crate::handlers::checklist::get_user_checklists,
         crate::handlers::checklist::fork_template,
         crate::handlers::checklist::get_checklist,
         crate::handlers::checklist::update_step,
+        crate::handlers::checklist::add_custom_step,
+        crate::handlers::checklist::edit_step,
+        crate::handlers::checklist::remove_step,
+        crate::handlers::checklist::reorder_steps,
+        crate::handlers::checklist::preview_sync,
+        crate::handlers::checklist::apply_sync,
+        
+        // 地区相关
+        crate::handlers::location::list_locations,
+        
+        // 管理相关
+        crate::handlers::admin::set_template_official,
+        crate::handlers::admin::grant_role,
     ),
     // 定义所有要文档化的组件（数据模型）
     components(schemas(
+        // 失败响应
+        ErrorResponse,
+        ApiError,
+        ErrorCode,
+        FieldError,
+        
         // 通用响应
         ApiResponse<UserProfile>,
         ApiResponse<AuthResponse>,
+        ApiResponse<SendCodeResponse>,
+        ApiResponse<AuthorizationUrlResponse>,
+        ApiResponse<Vec<UserIdentity>>,
+        ApiResponse<Vec<String>>,
+        ApiResponse<AccountDeletionResponse>,
+        ApiResponse<AvatarUploadResponse>,
         ApiResponse<Template>,
         ApiResponse<Vec<Template>>,
+        ApiResponse<TemplateVersion>,
+        ApiResponse<Vec<TemplateVersion>>,
+        ApiResponse<ResolvedTemplate>,
+        ApiResponse<Vec<TemplateSearchResult>>,
         ApiResponse<UserChecklistResponse>,
         ApiResponse<Vec<UserChecklistResponse>>,
+        ApiResponse<ChecklistSyncReport>,
+        ApiResponse<ChecklistSyncResponse>,
+        ApiResponse<Vec<Location>>,
         
         // 用户模型
         User,
@@ -78,30 +143,71 @@ use common::ApiResponse;
         LoginDto,
         UpdateProfileDto,
         AuthResponse,
+        UserRole,
+        GrantRoleDto,
+        RefreshTokenDto,
+        SendCodeDto,
+        SendCodeResponse,
+        PhoneLoginDto,
+        ChangePasswordDto,
+        ForgotPasswordDto,
+        ResetPasswordDto,
+        UserIdentity,
+        AuthorizationUrlResponse,
+        OAuthCallbackDto,
+        AccountExport,
+        AccountExportProfile,
+        ExportFormat,
+        ExportQuery,
+        DeleteAccountDto,
+        AccountDeletionResponse,
+        AvatarUploadForm,
+        AvatarUploadResponse,
+        AvatarVariant,
         
         // 模板模型
         Template,
         TemplateStep,
-        LocationTag,
         CreateTemplateDto,
         UpdateTemplateDto,
         TemplateSearchQuery,
+        TemplateVersion,
+        ResolvedTemplate,
+        TemplateSearchResult,
+        SearchHighlight,
+        SetOfficialDto,
         
         // 清单模型
         UserChecklist,
+        ChecklistStep,
         StepProgress,
+        StepStatus,
+        StepState,
         ChecklistProgress,
         ForkTemplateDto,
         UpdateStepDto,
         UserChecklistResponse,
+        AddChecklistStepDto,
+        UpdateChecklistStepDto,
+        ReorderChecklistStepsDto,
+        ChecklistSyncReport,
+        SyncedStep,
+        ChecklistSyncResponse,
+        
+        // 地区模型
+        Location,
+        LocationLevel,
+        LocationQuery,
     )),
     // 定义标签（用于API分组）
     tags(
         (name = "健康检查", description = "服务健康状态检查"),
-        (name = "认证", description = "用户注册、登录相关接口"),
-        (name = "用户", description = "用户资料管理"),
-        (name = "模板", description = "经验模板浏览、创建"),
-        (name = "清单", description = "个人清单管理、进度追踪"),
+        (name = "认证", description = "用户注册、登录（密码/短信验证码/第三方账号）、刷新令牌、退出登录和找回密码相关接口"),
+        (name = "用户", description = "用户资料管理、上传头像、修改密码、第三方账号绑定、导出个人数据、注销账号"),
+        (name = "模板", description = "经验模板浏览、创建、编辑"),
+        (name = "清单", description = "个人清单管理、自定义步骤、进度追踪、同步上游模板"),
+        (name = "地区", description = "地区列表（国家 → 省 → 城市）"),
+        (name = "管理", description = "管理员接口：设置官方模板、授予角色"),
     ),
     // 定义安全方案（JWT 认证）
     modifiers(&SecurityAddon)
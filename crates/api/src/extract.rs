@@ -0,0 +1,34 @@
+/// 请求提取器
+/// 
+/// 与axum的`Json`、`Path`、`Query`用法相同，区别在于提取失败时（请求体不是合法JSON、
+/// 路径参数不是UUID等）返回`AppError`，客户端收到与其他错误一致的失败响应，而不是纯文本。
+/// 
+/// ## 使用示例
+/// ```rust,ignore
+/// use crate::extract::{Json, Path};
+/// 
+/// pub async fn update_template(
+///     Path(id): Path<Uuid>,
+///     Json(dto): Json<UpdateTemplateDto>,
+/// ) -> ApiResult<Template> {
+///     // ...
+/// }
+/// ```
+
+use axum::extract::{FromRequest, FromRequestParts};
+use common::AppError;
+
+/// JSON请求体
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(AppError))]
+pub struct Json<T>(pub T);
+
+/// 路径参数
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(AppError))]
+pub struct Path<T>(pub T);
+
+/// 查询字符串参数
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(AppError))]
+pub struct Query<T>(pub T);
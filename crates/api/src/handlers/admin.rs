@@ -0,0 +1,120 @@
+use axum::extract::State;
+use models::{Template, SetOfficialDto, UserProfile, GrantRoleDto};
+use common::{ApiResponse, ApiResult, ErrorResponse};
+use crate::{extract::{Json, Path}, middleware::AdminUser, state::AppState};
+use uuid::Uuid;
+
+/// 设置或取消官方模板
+/// 
+/// ## 端点
+/// PUT /api/admin/templates/:id/official
+/// 
+/// ## 认证
+/// 需要JWT token，且角色为`admin`
+/// 
+/// ## 请求体
+/// ```json
+/// { "is_official": true }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 返回更新后的模板
+/// - 401 Unauthorized: 未登录
+/// - 403 Forbidden: 不是管理员
+/// - 404 Not Found: 模板不存在或已删除
+/// 
+/// ## 注意事项
+/// - 官方标记不属于模板内容，不会发布新版本
+/// - 列表和搜索在同一地区档内优先展示官方模板
+#[utoipa::path(
+    put,
+    path = "/api/admin/templates/{id}/official",
+    params(
+        ("id" = Uuid, Path, description = "模板UUID")
+    ),
+    request_body = SetOfficialDto,
+    responses(
+        (status = 200, description = "设置成功", body = ApiResponse<Template>),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "不是管理员", body = ErrorResponse),
+        (status = 404, description = "模板不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "管理"
+)]
+pub async fn set_template_official(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,  // 只有管理员可以访问
+    Path(id): Path<Uuid>,
+    Json(dto): Json<SetOfficialDto>,
+) -> ApiResult<Template> {
+    // 从依赖注入容器获取模板服务
+    let template_service = &state.module.template_service;
+    
+    let template = template_service
+        .set_official(id, dto.is_official, &admin.actor())
+        .await?;
+
+    Ok(ApiResponse::success(template, "设置成功"))
+}
+
+/// 授予用户角色
+/// 
+/// ## 端点
+/// PUT /api/admin/users/:id/role
+/// 
+/// ## 认证
+/// 需要JWT token，且角色为`admin`
+/// 
+/// ## 请求体
+/// ```json
+/// { "role": "city_curator", "role_scope": "CN-GZ" }
+/// ```
+/// 
+/// ## 角色
+/// - `user`: 普通用户
+/// - `contributor`: 贡献者，可以创建模板
+/// - `city_curator`: 城市主编，另外可以修改、删除`role_scope`地区及其下级地区的模板（必须提供`role_scope`）
+/// - `admin`: 管理员
+/// 
+/// ## 响应
+/// - 200 OK: 返回更新后的用户资料
+/// - 400 Bad Request: `role_scope`缺失、多余或地区不存在；不能修改自己的角色
+/// - 401 Unauthorized: 未登录
+/// - 403 Forbidden: 不是管理员
+/// - 404 Not Found: 用户不存在
+/// 
+/// ## 注意事项
+/// 角色记录在JWT中，用户刷新令牌或重新登录后新角色才会生效
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{id}/role",
+    params(
+        ("id" = Uuid, Path, description = "用户UUID")
+    ),
+    request_body = GrantRoleDto,
+    responses(
+        (status = 200, description = "授予成功", body = ApiResponse<UserProfile>),
+        (status = 400, description = "角色参数错误", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "不是管理员", body = ErrorResponse),
+        (status = 404, description = "用户不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "管理"
+)]
+pub async fn grant_role(
+    State(state): State<AppState>,
+    AdminUser(admin): AdminUser,  // 只有管理员可以访问
+    Path(id): Path<Uuid>,
+    Json(dto): Json<GrantRoleDto>,
+) -> ApiResult<UserProfile> {
+    // 从依赖注入容器获取用户服务
+    let user_service = &state.module.user_service;
+    
+    let profile = user_service
+        .grant_role(id, dto, &admin.actor())
+        .await?;
+
+    Ok(ApiResponse::success(profile, "授予成功"))
+}
@@ -1,7 +1,14 @@
-use axum::{extract::State, http::StatusCode, Json};
-use models::{RegisterDto, LoginDto, AuthResponse};
-use common::ApiResponse;
-use crate::state::AppState;
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
+};
+use std::net::{IpAddr, SocketAddr};
+use models::{
+    RegisterDto, LoginDto, AuthResponse, RefreshTokenDto, SendCodeDto, SendCodeResponse, PhoneLoginDto,
+    ForgotPasswordDto, ResetPasswordDto,
+};
+use common::{ApiResponse, ApiResult, AppError, ErrorResponse};
+use crate::{extract::Json, middleware::CurrentUser, state::AppState};
 
 /// 用户注册处理器
 /// 
@@ -19,40 +26,42 @@ use crate::state::AppState;
 /// ```
 /// 
 /// ## 响应
-/// - 200 OK: 注册成功，返回用户信息和JWT token
-/// - 400 Bad Request: 验证失败或用户已存在
+/// - 200 OK: 注册成功，返回用户信息、访问令牌和刷新令牌
+/// - 400 Bad Request: 验证失败
+/// - 409 Conflict: 手机号或邮箱已注册
 /// 
 /// ## 业务逻辑
 /// 1. 验证输入数据（手机号/邮箱格式、密码长度等）
 /// 2. 检查用户是否已存在
-/// 3. 使用bcrypt加密密码
+/// 3. 使用Argon2id加密密码
 /// 4. 创建用户记录
-/// 5. 生成JWT token
-/// 6. 返回用户信息和token
+/// 5. 生成访问令牌和刷新令牌（记录User-Agent）
+/// 6. 返回用户信息和令牌
 #[utoipa::path(
     post,
     path = "/api/auth/register",
     request_body = RegisterDto,
     responses(
         (status = 200, description = "注册成功", body = ApiResponse<AuthResponse>),
-        (status = 400, description = "验证失败或用户已存在")
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 409, description = "手机号或邮箱已注册", body = ErrorResponse)
     ),
     tag = "认证"
 )]
 pub async fn register(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(dto): Json<RegisterDto>,
-) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+) -> ApiResult<AuthResponse> {
     // 从依赖注入容器获取用户服务
     let user_service = &state.module.user_service;
     
     // 调用业务逻辑层处理注册
     let response = user_service
-        .register(dto)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .register(dto, user_agent(&headers))
+        .await?;
 
-    Ok(Json(response))
+    Ok(ApiResponse::success(response, "注册成功"))
 }
 
 /// 用户登录处理器
@@ -70,43 +79,400 @@ pub async fn register(
 /// ```
 /// 
 /// ## 响应
-/// - 200 OK: 登录成功，返回用户信息和JWT token
+/// - 200 OK: 登录成功，返回用户信息、访问令牌和刷新令牌
+/// - 400 Bad Request: 验证失败
 /// - 401 Unauthorized: 用户名或密码错误
+/// - 429 Too Many Requests: 连续登录失败次数太多，需要等待后重试
 /// 
 /// ## 业务逻辑
-/// 1. 根据手机号或邮箱查找用户
-/// 2. 验证密码（bcrypt.verify）
-/// 3. 生成JWT token（包含用户ID和过期时间）
-/// 4. 返回用户信息和token
+/// 1. 检查账号和客户端IP是否需要等待（退避或锁定中）
+/// 2. 根据手机号或邮箱查找用户
+/// 3. 验证密码（Argon2id；早期账号的bcrypt哈希验证成功后升级为Argon2id）
+/// 4. 失败时累加账号和IP的失败次数；成功时清零账号的失败次数
+/// 5. 生成JWT访问令牌（包含用户ID、角色和过期时间）
+/// 6. 生成刷新令牌（每次登录都是一个新的设备会话）
+/// 7. 返回用户信息和令牌
 /// 
 /// ## 安全性
-/// - 密码使用bcrypt验证，不会明文存储
-/// - JWT token设置过期时间（默认24小时）
-/// - 登录失败不泄露具体原因（用户不存在 vs 密码错误）
+/// - 密码使用Argon2id加密存储，不会明文存储
+/// - 访问令牌有效期很短（默认15分钟），过期后使用刷新令牌续期
+/// - 登录失败不泄露具体原因（用户不存在 vs 密码错误），账号不存在时同样做一次哈希验证，响应时间一致
+/// - 同一账号连续失败3次后指数退避（1、2、4……秒，最多60秒），10次后锁定15分钟；
+///   同一IP连续失败20次后退避，50次后锁定15分钟。账号不存在时同样计数
+/// - 每次锁定都写入`login_lockouts`审计表
 #[utoipa::path(
     post,
     path = "/api/auth/login",
     request_body = LoginDto,
     responses(
         (status = 200, description = "登录成功", body = ApiResponse<AuthResponse>),
-        (status = 401, description = "用户名或密码错误")
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "用户名或密码错误", body = ErrorResponse),
+        (status = 429, description = "登录失败次数太多", body = ErrorResponse)
     ),
     tag = "认证"
 )]
 pub async fn login(
     State(state): State<AppState>,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
     Json(dto): Json<LoginDto>,
-) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+) -> ApiResult<AuthResponse> {
     // 从依赖注入容器获取用户服务
     let user_service = &state.module.user_service;
     
     // 调用业务逻辑层处理登录
-    // 如果验证失败，返回401 Unauthorized
     let response = user_service
-        .login(dto)
-        .await
-        .map_err(|e| (StatusCode::UNAUTHORIZED, e.to_string()))?;
+        .login(dto, user_agent(&headers), client_ip(&state, &headers, peer))
+        .await?;
 
-    Ok(Json(response))
+    Ok(ApiResponse::success(response, "登录成功"))
 }
 
+
+/// 发送登录验证码处理器
+/// 
+/// ## 端点
+/// POST /api/auth/sms/code
+/// 
+/// ## 请求体
+/// ```json
+/// { "phone": "13800138000" }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 已发送，返回验证码有效期和重新发送的间隔
+/// - 400 Bad Request: 手机号格式错误
+/// - 404 Not Found: 没有启用验证码登录（`SMS_BACKEND=disabled`）
+/// - 429 Too Many Requests: 发送太频繁（每个手机号60秒一次、每小时最多5次）
+/// 
+/// ## 注意事项
+/// - 发送新验证码后，之前的验证码立即作废
+/// - 未配置短信网关时验证码只写入服务端日志（`auth`目标）
+#[utoipa::path(
+    post,
+    path = "/api/auth/sms/code",
+    request_body = SendCodeDto,
+    responses(
+        (status = 200, description = "发送成功", body = ApiResponse<SendCodeResponse>),
+        (status = 400, description = "手机号格式错误", body = ErrorResponse),
+        (status = 404, description = "没有启用验证码登录", body = ErrorResponse),
+        (status = 429, description = "发送太频繁", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn send_login_code(
+    State(state): State<AppState>,
+    Json(dto): Json<SendCodeDto>,
+) -> ApiResult<SendCodeResponse> {
+    // 从依赖注入容器获取用户服务
+    let user_service = &state.module.user_service;
+    
+    let response = user_service
+        .send_login_code(dto)
+        .await?;
+
+    Ok(ApiResponse::success(response, "发送成功"))
+}
+
+/// 验证码登录处理器
+/// 
+/// ## 端点
+/// POST /api/auth/sms/login
+/// 
+/// ## 请求体
+/// ```json
+/// {
+///   "phone": "13800138000",  // 手机号
+///   "code": "123456",        // 短信验证码
+///   "nickname": "张三"        // 昵称（可选，只在自动注册时使用）
+/// }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 登录成功，返回用户信息、访问令牌和刷新令牌
+/// - 400 Bad Request: 请求体验证失败
+/// - 401 Unauthorized: 验证码错误、已过期或已使用
+/// 
+/// ## 业务逻辑
+/// 1. 校验该手机号最新的验证码（有效期5分钟）
+/// 2. 手机号未注册时自动注册（默认昵称为"用户" + 手机号后4位）
+/// 3. 生成访问令牌和刷新令牌
+/// 
+/// ## 安全性
+/// - 验证码只能使用一次，输错5次后作废，需要重新获取
+/// - 所有失败情况返回相同的错误信息
+#[utoipa::path(
+    post,
+    path = "/api/auth/sms/login",
+    request_body = PhoneLoginDto,
+    responses(
+        (status = 200, description = "登录成功", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "验证码错误、已过期或已使用", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn login_with_code(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(dto): Json<PhoneLoginDto>,
+) -> ApiResult<AuthResponse> {
+    // 从依赖注入容器获取用户服务
+    let user_service = &state.module.user_service;
+    
+    let response = user_service
+        .login_with_code(dto, user_agent(&headers))
+        .await?;
+
+    Ok(ApiResponse::success(response, "登录成功"))
+}
+
+/// 刷新令牌处理器
+/// 
+/// ## 端点
+/// POST /api/auth/refresh
+/// 
+/// ## 请求体
+/// ```json
+/// { "refresh_token": "q1w2e3..." }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 返回新的访问令牌和刷新令牌
+/// - 400 Bad Request: 请求体验证失败
+/// - 401 Unauthorized: 刷新令牌无效、已过期或已被吊销
+/// 
+/// ## 业务逻辑
+/// 1. 按哈希查找刷新令牌
+/// 2. 吊销旧的刷新令牌，签发同一会话的新刷新令牌（轮换）
+/// 3. 重新读取用户，按最新的角色签发访问令牌
+/// 
+/// ## 安全性
+/// - 每个刷新令牌只能使用一次，客户端必须保存新返回的刷新令牌
+/// - 已使用过的刷新令牌再次出现会被视为被盗用，该会话的所有刷新令牌都会被吊销
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    request_body = RefreshTokenDto,
+    responses(
+        (status = 200, description = "刷新成功", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "刷新令牌无效、已过期或已被吊销", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn refresh(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(dto): Json<RefreshTokenDto>,
+) -> ApiResult<AuthResponse> {
+    // 从依赖注入容器获取会话服务
+    let session_service = &state.module.session_service;
+    
+    let response = session_service
+        .refresh(dto, user_agent(&headers))
+        .await?;
+
+    Ok(ApiResponse::success(response, "刷新成功"))
+}
+
+/// 退出登录处理器
+/// 
+/// ## 端点
+/// POST /api/auth/logout
+/// 
+/// ## 请求体
+/// ```json
+/// { "refresh_token": "q1w2e3..." }
+/// ```
+/// 
+/// ## 响应
+/// - 204 No Content: 退出成功
+/// - 400 Bad Request: 请求体验证失败
+/// - 401 Unauthorized: 刷新令牌无效
+/// 
+/// ## 注意事项
+/// - 吊销该刷新令牌所在会话（同一次登录轮换出的所有刷新令牌），其他设备不受影响
+/// - 已签发的访问令牌无法吊销，会在短暂的有效期后自然失效，客户端应立即丢弃
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    request_body = RefreshTokenDto,
+    responses(
+        (status = 204, description = "退出成功"),
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "刷新令牌无效", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn logout(
+    State(state): State<AppState>,
+    Json(dto): Json<RefreshTokenDto>,
+) -> Result<StatusCode, AppError> {
+    // 从依赖注入容器获取会话服务
+    let session_service = &state.module.session_service;
+    
+    session_service
+        .logout(dto)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 退出所有设备处理器
+/// 
+/// ## 端点
+/// POST /api/auth/logout-all
+/// 
+/// ## 认证
+/// 需要JWT token
+/// 
+/// ## 响应
+/// - 204 No Content: 已吊销当前用户的所有刷新令牌
+/// - 401 Unauthorized: 未登录
+/// 
+/// ## 注意事项
+/// 所有设备（包括当前设备）都需要重新登录；已签发的访问令牌会在短暂的有效期后失效
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout-all",
+    responses(
+        (status = 204, description = "退出成功"),
+        (status = 401, description = "未认证", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "认证"
+)]
+pub async fn logout_all(
+    State(state): State<AppState>,
+    current_user: CurrentUser,  // 自动验证JWT并提取用户ID
+) -> Result<StatusCode, AppError> {
+    // 从依赖注入容器获取会话服务
+    let session_service = &state.module.session_service;
+    
+    session_service
+        .logout_all(current_user.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 申请找回密码处理器
+/// 
+/// ## 端点
+/// POST /api/auth/password/forgot
+/// 
+/// ## 请求体
+/// ```json
+/// { "email": "user@example.com" }
+/// ```
+/// 
+/// ## 响应
+/// - 202 Accepted: 已受理（邮箱未注册时也返回202）
+/// - 400 Bad Request: 邮箱格式错误
+/// 
+/// ## 业务逻辑
+/// 1. 邮箱已注册时生成找回密码令牌（30分钟有效），数据库只保存哈希
+/// 2. 向该邮箱发送重置链接（`PASSWORD_RESET_URL?token=...`），旧的链接作废
+/// 
+/// ## 安全性
+/// - 不论邮箱是否注册都返回相同的响应，防止探测账号
+/// - 查找账号、生成令牌和发送邮件都在返回响应后在后台进行，响应时间也不会暴露邮箱是否注册
+/// - 同一账号60秒内只发送一封邮件
+#[utoipa::path(
+    post,
+    path = "/api/auth/password/forgot",
+    request_body = ForgotPasswordDto,
+    responses(
+        (status = 202, description = "已受理"),
+        (status = 400, description = "验证失败", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    Json(dto): Json<ForgotPasswordDto>,
+) -> Result<StatusCode, AppError> {
+    // 从依赖注入容器获取密码服务
+    let credential_service = &state.module.credential_service;
+    
+    credential_service
+        .request_password_reset(dto)
+        .await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// 重置密码处理器
+/// 
+/// ## 端点
+/// POST /api/auth/password/reset
+/// 
+/// ## 请求体
+/// ```json
+/// { "token": "<邮件链接中的token>", "new_password": "newpassword123" }
+/// ```
+/// 
+/// ## 响应
+/// - 204 No Content: 重置成功
+/// - 400 Bad Request: 请求体验证失败
+/// - 401 Unauthorized: 令牌无效、已过期或已使用
+/// 
+/// ## 注意事项
+/// - 令牌只能使用一次，重置成功后该用户其他尚未使用的重置链接也一并作废
+/// - 重置成功后吊销该用户的所有刷新令牌，所有设备都需要用新密码重新登录
+#[utoipa::path(
+    post,
+    path = "/api/auth/password/reset",
+    request_body = ResetPasswordDto,
+    responses(
+        (status = 204, description = "重置成功"),
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "令牌无效、已过期或已使用", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(dto): Json<ResetPasswordDto>,
+) -> Result<StatusCode, AppError> {
+    // 从依赖注入容器获取密码服务
+    let credential_service = &state.module.credential_service;
+    
+    credential_service
+        .reset_password(dto)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// User-Agent的最大长度（与`refresh_tokens.user_agent`列一致）
+const MAX_USER_AGENT_LEN: usize = 255;
+
+/// 读取请求的User-Agent，记录在刷新令牌上用于区分设备
+pub(crate) fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(USER_AGENT)
+        .and_then(|h| h.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect())
+}
+
+/// 读取客户端IP，用于按IP的登录失败限制
+/// 
+/// 信任`X-Forwarded-For`时取它的最后一个地址（由最近一层反向代理追加，客户端无法伪造），
+/// 否则取连接的对端地址
+pub(crate) fn client_ip(
+    state: &AppState,
+    headers: &HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
+) -> Option<IpAddr> {
+    let forwarded = state.trust_forwarded_for
+        .then(|| headers.get("x-forwarded-for"))
+        .flatten()
+        .and_then(|h| h.to_str().ok())
+        .and_then(|v| v.rsplit(',').next())
+        .and_then(|ip| ip.trim().parse().ok());
+
+    forwarded.or(peer.map(|ConnectInfo(addr)| addr.ip()))
+}
@@ -1,11 +1,11 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    Json,
+use axum::extract::State;
+use models::{
+    UserChecklistResponse, ForkTemplateDto, UpdateStepDto,
+    AddChecklistStepDto, UpdateChecklistStepDto, ReorderChecklistStepsDto,
+    ChecklistSyncReport, ChecklistSyncResponse,
 };
-use models::{UserChecklistResponse, ForkTemplateDto, UpdateStepDto};
-use common::ApiResponse;
-use crate::{middleware::CurrentUser, state::AppState};
+use common::{ApiResponse, ApiResult, ErrorResponse};
+use crate::{extract::{Json, Path}, middleware::CurrentUser, state::AppState};
 use uuid::Uuid;
 
 /// 获取当前用户的所有清单
@@ -52,8 +52,8 @@ use uuid::Uuid;
     path = "/api/checklists",
     responses(
         (status = 200, description = "获取成功", body = ApiResponse<Vec<UserChecklistResponse>>),
-        (status = 401, description = "未认证"),
-        (status = 500, description = "服务器错误")
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "清单"
@@ -61,17 +61,16 @@ use uuid::Uuid;
 pub async fn get_user_checklists(
     State(state): State<AppState>,
     current_user: CurrentUser,  // JWT认证自动注入
-) -> Result<Json<Vec<UserChecklistResponse>>, (StatusCode, String)> {
+) -> ApiResult<Vec<UserChecklistResponse>> {
     // 从依赖注入容器获取清单服务
     let checklist_service = &state.module.checklist_service;
     
     // 查询当前用户的所有清单
     let checklists = checklist_service
         .get_user_checklists(current_user.user_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .await?;
 
-    Ok(Json(checklists))
+    Ok(ApiResponse::success(checklists, "获取成功"))
 }
 
 /// Fork模板到个人清单
@@ -91,8 +90,9 @@ pub async fn get_user_checklists(
 /// 
 /// ## 响应
 /// - 200 OK: Fork成功，返回新创建的清单
-/// - 400 Bad Request: 模板不存在或参数错误
+/// - 400 Bad Request: 参数错误
 /// - 401 Unauthorized: 未登录
+/// - 404 Not Found: 模板不存在或已删除
 /// 
 /// ## 业务逻辑
 /// 1. 验证模板是否存在
@@ -102,7 +102,7 @@ pub async fn get_user_checklists(
 /// 5. 返回新清单和初始进度（0%）
 /// 
 /// ## 注意事项
-/// - V0.0.1版本：Fork后的清单不可修改步骤
+/// - Fork后的清单拥有自己的步骤，可以添加、隐藏、删除、重排和改写
 /// - 同一模板可以被同一用户多次Fork
 /// - Fork的是模板的快照，后续模板修改不影响已Fork的清单
 #[utoipa::path(
@@ -111,8 +111,9 @@ pub async fn get_user_checklists(
     request_body = ForkTemplateDto,
     responses(
         (status = 200, description = "Fork成功", body = ApiResponse<UserChecklistResponse>),
-        (status = 400, description = "模板不存在"),
-        (status = 401, description = "未认证")
+        (status = 400, description = "参数错误", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 404, description = "模板不存在", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "清单"
@@ -121,17 +122,16 @@ pub async fn fork_template(
     State(state): State<AppState>,
     current_user: CurrentUser,
     Json(dto): Json<ForkTemplateDto>,
-) -> Result<Json<UserChecklistResponse>, (StatusCode, String)> {
+) -> ApiResult<UserChecklistResponse> {
     // 从依赖注入容器获取清单服务
     let checklist_service = &state.module.checklist_service;
     
     // 执行Fork操作
     let checklist = checklist_service
         .fork_template(current_user.user_id, dto)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .await?;
 
-    Ok(Json(checklist))
+    Ok(ApiResponse::success(checklist, "Fork成功"))
 }
 
 /// 获取单个清单详情
@@ -154,7 +154,7 @@ pub async fn fork_template(
 ///     "title": "第一次在北京租房",
 ///     "progress_status": [
 ///       {
-///         "step_index": 0,
+///         "step_id": "uuid",
 ///         "completed": true,
 ///         "completed_at": "2024-10-21T12:00:00Z"
 ///       }
@@ -170,12 +170,13 @@ pub async fn fork_template(
 /// 
 /// ## 业务逻辑
 /// 1. 根据清单ID查询数据库
-/// 2. 计算当前完成进度
-/// 3. 返回清单详情和进度统计
+/// 2. 校验清单属于当前用户
+/// 3. 计算当前完成进度
+/// 4. 返回清单详情和进度统计
 /// 
 /// ## 权限说明
-/// - V0.0.1版本：任何人都可以查看任何清单
-/// - TODO V0.1+：只能查看自己的清单或公开分享的清单
+/// - 需要JWT token，只能查看自己的清单
+/// - TODO：公开分享的清单
 #[utoipa::path(
     get,
     path = "/api/checklists/{id}",
@@ -184,25 +185,28 @@ pub async fn fork_template(
     ),
     responses(
         (status = 200, description = "获取成功", body = ApiResponse<UserChecklistResponse>),
-        (status = 404, description = "清单不存在"),
-        (status = 500, description = "服务器错误")
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "不是清单的所有者", body = ErrorResponse),
+        (status = 404, description = "清单不存在", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
     ),
+    security(("bearer_auth" = [])),
     tag = "清单"
 )]
 pub async fn get_checklist(
     State(state): State<AppState>,
+    current_user: CurrentUser,
     Path(id): Path<Uuid>,  // 从URL路径提取清单ID
-) -> Result<Json<UserChecklistResponse>, (StatusCode, String)> {
+) -> ApiResult<UserChecklistResponse> {
     // 从依赖注入容器获取清单服务
     let checklist_service = &state.module.checklist_service;
     
-    // 查询清单详情
+    // 查询清单详情（只能查看自己的清单）
     let checklist = checklist_service
-        .get_checklist(id)
-        .await
-        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+        .get_checklist(id, current_user.user_id)
+        .await?;
 
-    Ok(Json(checklist))
+    Ok(ApiResponse::success(checklist, "获取成功"))
 }
 
 /// 更新清单中某个步骤的完成状态
@@ -216,21 +220,28 @@ pub async fn get_checklist(
 /// ## 请求体
 /// ```json
 /// {
-///   "step_index": 0,      // 步骤索引（从0开始）
-///   "completed": true     // 完成状态：true=已完成，false=未完成
+///   "step_id": "uuid",    // 步骤ID（模板步骤的id）
+///   "completed": true,    // 完成状态：true=已完成，false=未完成
+///   "force": false        // 可选，前置步骤未完成时仍强制标记完成
 /// }
 /// ```
 /// 
+/// ## 认证
+/// 需要JWT token，只能修改自己的清单
+/// 
 /// ## 响应
 /// - 200 OK: 更新成功，返回更新后的清单和进度
-/// - 400 Bad Request: 步骤索引无效或参数错误
+/// - 400 Bad Request: 前置步骤未完成或参数错误
+/// - 403 Forbidden: 不是清单的所有者
+/// - 404 Not Found: 清单或步骤不存在
 /// 
 /// ## 业务逻辑
-/// 1. 查找指定的清单
-/// 2. 更新指定步骤的完成状态
-/// 3. 如果标记为完成，记录完成时间
-/// 4. 重新计算整体进度
-/// 5. 返回更新后的清单
+/// 1. 查找指定的清单并校验所有者
+/// 2. 标记完成时检查前置步骤（`force = true`时跳过）
+/// 3. 更新指定步骤的完成状态
+/// 4. 如果标记为完成，记录完成时间
+/// 5. 重新计算整体进度和每个步骤的 locked / available / done 状态
+/// 6. 返回更新后的清单
 /// 
 /// ## 核心功能
 /// 这是"进度追踪"的核心功能，用户通过勾选步骤来：
@@ -240,9 +251,9 @@ pub async fn get_checklist(
 /// 
 /// ## 示例场景
 /// 用户完成了"第一次租房"清单中的"确定预算"步骤：
-/// ```
+/// ```text
 /// PUT /api/checklists/{id}/steps
-/// { "step_index": 0, "completed": true }
+/// { "step_id": "<确定预算的步骤ID>", "completed": true }
 /// 
 /// → 进度从 0% 更新为 10%（假设共10步）
 /// → completed_at 记录为当前时间
@@ -256,25 +267,370 @@ pub async fn get_checklist(
     request_body = UpdateStepDto,
     responses(
         (status = 200, description = "更新成功", body = ApiResponse<UserChecklistResponse>),
-        (status = 400, description = "步骤索引无效"),
-        (status = 404, description = "清单不存在")
+        (status = 400, description = "前置步骤未完成", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "不是清单的所有者", body = ErrorResponse),
+        (status = 404, description = "清单或步骤不存在", body = ErrorResponse)
     ),
+    security(("bearer_auth" = [])),
     tag = "清单"
 )]
 pub async fn update_step(
     State(state): State<AppState>,
+    current_user: CurrentUser,
     Path(id): Path<Uuid>,  // 从URL路径提取清单ID
     Json(dto): Json<UpdateStepDto>,
-) -> Result<Json<UserChecklistResponse>, (StatusCode, String)> {
+) -> ApiResult<UserChecklistResponse> {
+    // 从依赖注入容器获取清单服务
+    let checklist_service = &state.module.checklist_service;
+    
+    // 更新步骤状态（只能修改自己的清单）
+    let checklist = checklist_service
+        .update_step(id, current_user.user_id, dto)
+        .await?;
+
+    Ok(ApiResponse::success(checklist, "更新成功"))
+}
+
+/// 在清单中添加自定义步骤
+/// 
+/// ## 端点
+/// POST /api/checklists/:id/steps
+/// 
+/// ## 认证
+/// 需要JWT token，只能修改自己的清单
+/// 
+/// ## 请求体
+/// ```json
+/// {
+///   "title": "问问同事的租房经验",   // 1-500字符
+///   "description": "特别是通勤时间",  // 可选
+///   "position": 1                    // 可选，插入位置（从0开始），默认追加到末尾
+/// }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 返回更新后的清单和进度
+/// - 400 Bad Request: 参数验证失败
+/// - 403 Forbidden: 不是清单的所有者
+/// - 404 Not Found: 清单不存在
+/// 
+/// ## 注意事项
+/// - 新步骤标记为`is_custom = true`，初始为未完成，会计入进度
+/// - 自定义步骤不受上游模板同步影响
+#[utoipa::path(
+    post,
+    path = "/api/checklists/{id}/steps",
+    params(
+        ("id" = Uuid, Path, description = "清单UUID")
+    ),
+    request_body = AddChecklistStepDto,
+    responses(
+        (status = 200, description = "添加成功", body = ApiResponse<UserChecklistResponse>),
+        (status = 400, description = "参数验证失败", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限", body = ErrorResponse),
+        (status = 404, description = "清单不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "清单"
+)]
+pub async fn add_custom_step(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<Uuid>,
+    Json(dto): Json<AddChecklistStepDto>,
+) -> ApiResult<UserChecklistResponse> {
+    // 从依赖注入容器获取清单服务
+    let checklist_service = &state.module.checklist_service;
+    
+    // 插入自定义步骤
+    let checklist = checklist_service
+        .add_custom_step(id, current_user.user_id, dto)
+        .await?;
+
+    Ok(ApiResponse::success(checklist, "添加成功"))
+}
+
+/// 修改清单中的步骤（改写、隐藏或恢复）
+/// 
+/// ## 端点
+/// PATCH /api/checklists/:id/steps/:step_id
+/// 
+/// ## 认证
+/// 需要JWT token，只能修改自己的清单
+/// 
+/// ## 请求体
+/// 所有字段可选，只更新提供的字段：
+/// ```json
+/// {
+///   "title": "确定预算（不超过月薪1/3）",
+///   "description": "...",
+///   "hidden": true
+/// }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 返回更新后的清单和进度
+/// - 400 Bad Request: 参数验证失败
+/// - 403 Forbidden: 不是清单的所有者
+/// - 404 Not Found: 清单或步骤不存在
+/// 
+/// ## 注意事项
+/// - 隐藏的步骤保留完成状态，但不计入进度；设置`hidden: false`即可恢复
+/// - 改写过的模板步骤在同步时保留用户的内容
+#[utoipa::path(
+    patch,
+    path = "/api/checklists/{id}/steps/{step_id}",
+    params(
+        ("id" = Uuid, Path, description = "清单UUID"),
+        ("step_id" = Uuid, Path, description = "步骤ID")
+    ),
+    request_body = UpdateChecklistStepDto,
+    responses(
+        (status = 200, description = "修改成功", body = ApiResponse<UserChecklistResponse>),
+        (status = 400, description = "参数验证失败", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限", body = ErrorResponse),
+        (status = 404, description = "清单或步骤不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "清单"
+)]
+pub async fn edit_step(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path((id, step_id)): Path<(Uuid, Uuid)>,
+    Json(dto): Json<UpdateChecklistStepDto>,
+) -> ApiResult<UserChecklistResponse> {
+    // 从依赖注入容器获取清单服务
+    let checklist_service = &state.module.checklist_service;
+    
+    // 修改步骤
+    let checklist = checklist_service
+        .edit_step(id, current_user.user_id, step_id, dto)
+        .await?;
+
+    Ok(ApiResponse::success(checklist, "修改成功"))
+}
+
+/// 从清单中删除步骤
+/// 
+/// ## 端点
+/// DELETE /api/checklists/:id/steps/:step_id
+/// 
+/// ## 认证
+/// 需要JWT token，只能修改自己的清单
+/// 
+/// ## 响应
+/// - 200 OK: 返回更新后的清单和进度
+/// - 403 Forbidden: 不是清单的所有者
+/// - 404 Not Found: 清单或步骤不存在
+/// 
+/// ## 注意事项
+/// - 删除会同时丢弃该步骤的完成记录，且不可恢复；只想暂时不看可以用隐藏
+/// - 删除的模板步骤在同步时不会被重新加回
+#[utoipa::path(
+    delete,
+    path = "/api/checklists/{id}/steps/{step_id}",
+    params(
+        ("id" = Uuid, Path, description = "清单UUID"),
+        ("step_id" = Uuid, Path, description = "步骤ID")
+    ),
+    responses(
+        (status = 200, description = "删除成功", body = ApiResponse<UserChecklistResponse>),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限", body = ErrorResponse),
+        (status = 404, description = "清单或步骤不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "清单"
+)]
+pub async fn remove_step(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path((id, step_id)): Path<(Uuid, Uuid)>,
+) -> ApiResult<UserChecklistResponse> {
     // 从依赖注入容器获取清单服务
     let checklist_service = &state.module.checklist_service;
     
-    // 更新步骤状态
+    // 删除步骤
     let checklist = checklist_service
-        .update_step(id, dto)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .remove_step(id, current_user.user_id, step_id)
+        .await?;
 
-    Ok(Json(checklist))
+    Ok(ApiResponse::success(checklist, "删除成功"))
 }
 
+/// 重新排列清单中的步骤
+/// 
+/// ## 端点
+/// PUT /api/checklists/:id/steps/order
+/// 
+/// ## 认证
+/// 需要JWT token，只能修改自己的清单
+/// 
+/// ## 请求体
+/// ```json
+/// {
+///   "step_ids": ["uuid-2", "uuid-1", "uuid-3"]  // 必须包含所有步骤（含隐藏的）各一次
+/// }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 返回更新后的清单和进度
+/// - 400 Bad Request: 步骤列表与清单不一致
+/// - 403 Forbidden: 不是清单的所有者
+/// - 404 Not Found: 清单不存在
+#[utoipa::path(
+    put,
+    path = "/api/checklists/{id}/steps/order",
+    params(
+        ("id" = Uuid, Path, description = "清单UUID")
+    ),
+    request_body = ReorderChecklistStepsDto,
+    responses(
+        (status = 200, description = "排序成功", body = ApiResponse<UserChecklistResponse>),
+        (status = 400, description = "步骤列表与清单不一致", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限", body = ErrorResponse),
+        (status = 404, description = "清单不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "清单"
+)]
+pub async fn reorder_steps(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<Uuid>,
+    Json(dto): Json<ReorderChecklistStepsDto>,
+) -> ApiResult<UserChecklistResponse> {
+    // 从依赖注入容器获取清单服务
+    let checklist_service = &state.module.checklist_service;
+    
+    // 按新顺序保存
+    let checklist = checklist_service
+        .reorder_steps(id, current_user.user_id, dto)
+        .await?;
+
+    Ok(ApiResponse::success(checklist, "排序成功"))
+}
+
+/// 预览清单与来源模板的同步结果
+/// 
+/// ## 端点
+/// GET /api/checklists/:id/sync
+/// 
+/// ## 认证
+/// 需要JWT token，只能同步自己的清单
+/// 
+/// ## 响应
+/// - 200 OK: 返回同步报告（不修改清单）
+/// - 403 Forbidden: 不是清单的所有者
+/// - 404 Not Found: 清单、来源模板或Fork版本不存在
+/// 
+/// ## 响应示例
+/// ```json
+/// {
+///   "from_version": 1,
+///   "to_version": 2,
+///   "up_to_date": false,
+///   "kept": [
+///     { "step_id": "uuid-1", "title": "确定预算", "from_index": 0, "to_index": 0, "completed": true }
+///   ],
+///   "updated": [],
+///   "added": [
+///     { "step_id": "uuid-3", "title": "办理居住证", "from_index": null, "to_index": 1, "completed": false }
+///   ],
+///   "removed": [
+///     { "step_id": "uuid-2", "title": "找中介", "from_index": 1, "to_index": null, "completed": false }
+///   ]
+/// }
+/// ```
+/// 
+/// ## 合并规则
+/// 以Fork时的模板版本为基准，对比模板最新版本和用户的清单：
+/// - 按步骤ID匹配，仍然存在的步骤保留完成状态和隐藏状态
+/// - 模板改了内容而用户没改过的步骤采用新内容（`updated`），用户改写过的保留用户的版本
+/// - 新增的步骤追加到末尾，未完成
+/// - 被移除的步骤列在`removed`中，`completed = true`表示同步后会丢失这条完成记录
+/// - 用户的自定义步骤原样保留，用户删除过的步骤不会被加回
+#[utoipa::path(
+    get,
+    path = "/api/checklists/{id}/sync",
+    params(
+        ("id" = Uuid, Path, description = "清单UUID")
+    ),
+    responses(
+        (status = 200, description = "预览成功", body = ApiResponse<ChecklistSyncReport>),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限", body = ErrorResponse),
+        (status = 404, description = "清单或模板不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "清单"
+)]
+pub async fn preview_sync(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ChecklistSyncReport> {
+    // 从依赖注入容器获取清单服务
+    let checklist_service = &state.module.checklist_service;
+    
+    // 计算同步报告（只读）
+    let report = checklist_service
+        .preview_sync(id, current_user.user_id)
+        .await?;
+
+    Ok(ApiResponse::success(report, "预览成功"))
+}
+
+/// 将清单同步到来源模板的最新版本
+/// 
+/// ## 端点
+/// POST /api/checklists/:id/sync
+/// 
+/// ## 认证
+/// 需要JWT token，只能同步自己的清单
+/// 
+/// ## 响应
+/// - 200 OK: 同步成功，返回同步报告和更新后的清单
+/// - 403 Forbidden: 不是清单的所有者
+/// - 404 Not Found: 清单、来源模板或Fork版本不存在
+/// 
+/// ## 业务逻辑
+/// 1. 按与预览相同的规则重新计算合并（预览之后的勾选也会被保留）
+/// 2. 写回合并后的`steps`和`progress_status`
+/// 3. 将`source_template_version`更新为最新版本号
+/// 4. 已是最新版本时不做修改，直接返回
+#[utoipa::path(
+    post,
+    path = "/api/checklists/{id}/sync",
+    params(
+        ("id" = Uuid, Path, description = "清单UUID")
+    ),
+    responses(
+        (status = 200, description = "同步成功", body = ApiResponse<ChecklistSyncResponse>),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限", body = ErrorResponse),
+        (status = 404, description = "清单或模板不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "清单"
+)]
+pub async fn apply_sync(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ChecklistSyncResponse> {
+    // 从依赖注入容器获取清单服务
+    let checklist_service = &state.module.checklist_service;
+    
+    // 执行同步
+    let result = checklist_service
+        .apply_sync(id, current_user.user_id)
+        .await?;
+
+    Ok(ApiResponse::success(result, "同步成功"))
+}
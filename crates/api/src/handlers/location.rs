@@ -0,0 +1,61 @@
+use axum::extract::State;
+use models::{Location, LocationQuery};
+use common::{ApiResponse, ApiResult, ErrorResponse};
+use crate::{extract::Query, state::AppState};
+
+/// 列出地区
+/// 
+/// ## 端点
+/// GET /api/locations?parent=CN-GD&keyword=guang
+/// 
+/// ## 查询参数
+/// - `parent`: 上级地区代码（可选）- 只返回它的直接下级
+/// - `keyword`: 关键词（可选）- 匹配代码、中文名、拼音前缀或别名
+/// 
+/// ## 认证
+/// 无需认证（公开接口）
+/// 
+/// ## 响应示例
+/// ```json
+/// [
+///   {
+///     "code": "CN-GZ",
+///     "parent_code": "CN-GD",
+///     "level": "city",
+///     "name": "广州",
+///     "pinyin": "guangzhou",
+///     "aliases": ["广州市", "穗", "羊城"]
+///   }
+/// ]
+/// ```
+/// 
+/// ## 层级
+/// 国家（`country`）→ 省（`province`）→ 城市（`city`），直辖市直接挂在全国下面。
+/// 
+/// ## 使用场景
+/// - 选择常驻城市（`home_city`只接受`city`层级）
+/// - 创建模板时选择地理标签
+/// - 把用户输入的城市名换成地区代码再搜索模板
+#[utoipa::path(
+    get,
+    path = "/api/locations",
+    params(LocationQuery),
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<Vec<Location>>),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
+    ),
+    tag = "地区"
+)]
+pub async fn list_locations(
+    State(state): State<AppState>,
+    Query(query): Query<LocationQuery>,
+) -> ApiResult<Vec<Location>> {
+    // 从依赖注入容器获取地区服务
+    let location_service = &state.module.location_service;
+    
+    let locations = location_service
+        .list_locations(query)
+        .await?;
+
+    Ok(ApiResponse::success(locations, "获取成功"))
+}
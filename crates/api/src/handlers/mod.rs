@@ -7,13 +7,16 @@
 /// 
 /// - `health`: 健康检查端点
 /// - `auth`: 用户认证（注册、登录）
+/// - `oauth`: 第三方登录（OAuth2/OIDC）和第三方账号绑定
 /// - `user`: 用户资料管理
 /// - `template`: 经验模板CRUD
 /// - `checklist`: 用户清单和进度追踪
+/// - `location`: 地区列表
+/// - `admin`: 管理接口（设置官方模板、授予角色）
 /// 
 /// ## 架构层次
 /// 
-/// ```
+/// ```text
 /// Handler层（本模块）
 ///     ↓ 调用
 /// Service层（业务逻辑）
@@ -29,8 +32,14 @@
 /// 1. ✅ 解析HTTP请求（路径参数、查询参数、请求体）
 /// 2. ✅ 提取认证信息（JWT token）
 /// 3. ✅ 调用业务逻辑层（Service）
-/// 4. ✅ 处理错误并转换为HTTP状态码
-/// 5. ✅ 返回JSON响应
+/// 4. ✅ 用`?`传播`AppError`（由`AppError`统一转换为HTTP状态码和失败响应）
+/// 5. ✅ 返回`ApiResponse`包装的JSON响应
+/// 
+/// ## 响应格式
+/// 
+/// 返回数据的接口统一返回`ApiResult<T>`：成功时数据放在`data`中，失败时`error.code`是机器可读的错误码。
+/// 各handler文档中的响应示例只列出`data`部分。请求体、路径参数和查询参数使用`crate::extract`中的提取器，
+/// 格式错误时同样返回失败响应。
 /// 
 /// Handlers不应：
 /// 1. ❌ 直接访问数据库
@@ -38,7 +47,10 @@
 /// 3. ❌ 执行复杂的数据转换（应在Service或Model层）
 pub mod health;
 pub mod auth;
+pub mod oauth;
 pub mod user;
 pub mod template;
 pub mod checklist;
+pub mod location;
+pub mod admin;
 
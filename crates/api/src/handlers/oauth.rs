@@ -0,0 +1,302 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use models::{AuthResponse, AuthorizationUrlResponse, OAuthCallbackDto, UserIdentity};
+use common::{ApiResponse, ApiResult, AppError, ErrorResponse};
+use crate::{extract::{Json, Path}, handlers::auth::user_agent, middleware::CurrentUser, state::AppState};
+
+/// 列出已配置的第三方登录提供方
+/// 
+/// ## 端点
+/// GET /api/auth/oauth/providers
+/// 
+/// ## 响应示例
+/// ```json
+/// ["google"]
+/// ```
+/// 
+/// 前端按列表显示第三方登录按钮；没有配置任何提供方时返回空数组
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/providers",
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<Vec<String>>)
+    ),
+    tag = "认证"
+)]
+pub async fn list_providers(
+    State(state): State<AppState>,
+) -> ApiResult<Vec<String>> {
+    Ok(ApiResponse::success(state.module.oauth_service.providers(), "获取成功"))
+}
+
+/// 发起第三方登录
+/// 
+/// ## 端点
+/// POST /api/auth/oauth/:provider/authorize
+/// 
+/// ## 响应
+/// - 200 OK: 返回提供方的授权地址
+/// - 404 Not Found: 提供方未配置
+/// 
+/// ## 登录流程
+/// 1. 调用本接口，把用户带到返回的`authorization_url`
+/// 2. 用户在提供方登录并同意授权后，提供方跳转到配置的回调地址（带`code`和`state`参数）
+/// 3. 把`code`和`state`提交到`POST /api/auth/oauth/:provider/callback`
+/// 
+/// ## 安全性
+/// - 使用授权码模式 + PKCE（S256），校验码只保存在服务端
+/// - `state`只能使用一次，10分钟内有效
+#[utoipa::path(
+    post,
+    path = "/api/auth/oauth/{provider}/authorize",
+    params(
+        ("provider" = String, Path, description = "提供方名称（见`GET /api/auth/oauth/providers`）")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<AuthorizationUrlResponse>),
+        (status = 404, description = "提供方未配置", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn authorize(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> ApiResult<AuthorizationUrlResponse> {
+    // 从依赖注入容器获取第三方登录服务
+    let oauth_service = &state.module.oauth_service;
+
+    let response = oauth_service
+        .authorize(&provider, None)
+        .await?;
+
+    Ok(ApiResponse::success(response, "获取成功"))
+}
+
+/// 第三方登录回调
+/// 
+/// ## 端点
+/// POST /api/auth/oauth/:provider/callback
+/// 
+/// ## 请求体
+/// ```json
+/// { "code": "4/0AX4XfWh...", "state": "pK3v..." }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 登录成功，返回用户信息、访问令牌和刷新令牌
+/// - 400 Bad Request: 提供方没有返回已验证的邮箱
+/// - 401 Unauthorized: `state`无效或已过期、授权码无效、ID令牌校验失败
+/// - 404 Not Found: 提供方未配置
+/// - 409 Conflict: 邮箱已被其他账号注册（需要先登录该账号再绑定）
+/// 
+/// ## 业务逻辑
+/// 1. 取出并删除`state`对应的授权请求
+/// 2. 用授权码和PKCE校验码换取ID令牌，校验签名、`iss`、`aud`、`exp`和`nonce`
+/// 3. 第三方账号已绑定：登录绑定的用户
+/// 4. 未绑定：用提供方返回的已验证邮箱自动注册并绑定
+/// 
+/// ## 注意事项
+/// 邮箱已被注册时不会自动关联，需要先用原来的方式登录，再在个人资料中绑定
+#[utoipa::path(
+    post,
+    path = "/api/auth/oauth/{provider}/callback",
+    params(
+        ("provider" = String, Path, description = "提供方名称")
+    ),
+    request_body = OAuthCallbackDto,
+    responses(
+        (status = 200, description = "登录成功", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "授权无效或已过期", body = ErrorResponse),
+        (status = 404, description = "提供方未配置", body = ErrorResponse),
+        (status = 409, description = "邮箱已注册", body = ErrorResponse)
+    ),
+    tag = "认证"
+)]
+pub async fn login(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    headers: HeaderMap,
+    Json(dto): Json<OAuthCallbackDto>,
+) -> ApiResult<AuthResponse> {
+    // 从依赖注入容器获取第三方登录服务
+    let oauth_service = &state.module.oauth_service;
+
+    let response = oauth_service
+        .login(&provider, dto, user_agent(&headers))
+        .await?;
+
+    Ok(ApiResponse::success(response, "登录成功"))
+}
+
+/// 列出当前用户绑定的第三方账号
+/// 
+/// ## 端点
+/// GET /api/users/me/identities
+/// 
+/// ## 认证
+/// 需要JWT token
+#[utoipa::path(
+    get,
+    path = "/api/users/me/identities",
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<Vec<UserIdentity>>),
+        (status = 401, description = "未认证", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn list_identities(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+) -> ApiResult<Vec<UserIdentity>> {
+    // 从依赖注入容器获取第三方登录服务
+    let oauth_service = &state.module.oauth_service;
+
+    let identities = oauth_service
+        .list_identities(current_user.user_id)
+        .await?;
+
+    Ok(ApiResponse::success(identities, "获取成功"))
+}
+
+/// 发起绑定第三方账号
+/// 
+/// ## 端点
+/// POST /api/users/me/identities/:provider/authorize
+/// 
+/// ## 认证
+/// 需要JWT token
+/// 
+/// ## 响应
+/// - 200 OK: 返回提供方的授权地址
+/// - 401 Unauthorized: 未登录
+/// - 404 Not Found: 提供方未配置
+/// 
+/// 流程与第三方登录相同，授权后把`code`和`state`提交到
+/// `POST /api/users/me/identities/:provider/callback`。
+/// 绑定用的`state`只能由发起绑定的用户使用，不能用于登录。
+#[utoipa::path(
+    post,
+    path = "/api/users/me/identities/{provider}/authorize",
+    params(
+        ("provider" = String, Path, description = "提供方名称")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<AuthorizationUrlResponse>),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 404, description = "提供方未配置", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn authorize_link(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(provider): Path<String>,
+) -> ApiResult<AuthorizationUrlResponse> {
+    // 从依赖注入容器获取第三方登录服务
+    let oauth_service = &state.module.oauth_service;
+
+    let response = oauth_service
+        .authorize(&provider, Some(current_user.user_id))
+        .await?;
+
+    Ok(ApiResponse::success(response, "获取成功"))
+}
+
+/// 完成绑定第三方账号
+/// 
+/// ## 端点
+/// POST /api/users/me/identities/:provider/callback
+/// 
+/// ## 认证
+/// 需要JWT token（必须是发起绑定的用户）
+/// 
+/// ## 请求体
+/// ```json
+/// { "code": "4/0AX4XfWh...", "state": "pK3v..." }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 绑定成功，返回当前用户绑定的所有第三方账号
+/// - 401 Unauthorized: 未登录、`state`无效或已过期、授权码无效、ID令牌校验失败
+/// - 404 Not Found: 提供方未配置
+/// - 409 Conflict: 该第三方账号已绑定其他用户，或已绑定了该提供方的另一个账号
+#[utoipa::path(
+    post,
+    path = "/api/users/me/identities/{provider}/callback",
+    params(
+        ("provider" = String, Path, description = "提供方名称")
+    ),
+    request_body = OAuthCallbackDto,
+    responses(
+        (status = 200, description = "绑定成功", body = ApiResponse<Vec<UserIdentity>>),
+        (status = 401, description = "授权无效或已过期", body = ErrorResponse),
+        (status = 404, description = "提供方未配置", body = ErrorResponse),
+        (status = 409, description = "第三方账号已被绑定", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn link(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(provider): Path<String>,
+    Json(dto): Json<OAuthCallbackDto>,
+) -> ApiResult<Vec<UserIdentity>> {
+    // 从依赖注入容器获取第三方登录服务
+    let oauth_service = &state.module.oauth_service;
+
+    let identities = oauth_service
+        .link(&provider, dto, current_user.user_id)
+        .await?;
+
+    Ok(ApiResponse::success(identities, "绑定成功"))
+}
+
+/// 解绑第三方账号
+/// 
+/// ## 端点
+/// DELETE /api/users/me/identities/:provider
+/// 
+/// ## 认证
+/// 需要JWT token
+/// 
+/// ## 响应
+/// - 204 No Content: 解绑成功
+/// - 401 Unauthorized: 未登录
+/// - 404 Not Found: 没有绑定该提供方的账号
+/// 
+/// ## 注意事项
+/// 通过第三方登录自动注册的账号没有密码，解绑后可以通过找回密码邮件设置密码再登录
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/identities/{provider}",
+    params(
+        ("provider" = String, Path, description = "提供方名称")
+    ),
+    responses(
+        (status = 204, description = "解绑成功"),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 404, description = "没有绑定该提供方的账号", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn unlink(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(provider): Path<String>,
+) -> Result<StatusCode, AppError> {
+    // 从依赖注入容器获取第三方登录服务
+    let oauth_service = &state.module.oauth_service;
+
+    oauth_service
+        .unlink(&provider, current_user.user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
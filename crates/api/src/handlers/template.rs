@@ -1,11 +1,10 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::State,
     http::StatusCode,
-    Json,
 };
-use models::{Template, CreateTemplateDto, TemplateSearchQuery};
-use common::ApiResponse;
-use crate::{middleware::CurrentUser, state::AppState};
+use models::{Template, TemplateVersion, ResolvedTemplate, CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery, TemplateSearchResult};
+use common::{ApiResponse, ApiResult, AppError, ErrorResponse};
+use crate::{extract::{Json, Path, Query}, middleware::{CurrentUser, OptionalCurrentUser}, state::AppState};
 use uuid::Uuid;
 
 /// 列出所有模板（分页）
@@ -18,10 +17,11 @@ use uuid::Uuid;
 /// - `page_size`: 每页数量（可选，默认20）
 /// 
 /// ## 认证
-/// 无需认证（公开接口）
+/// 无需认证（公开接口）；带上token时按用户的常驻城市排序
 /// 
 /// ## 响应
 /// - 200 OK: 返回模板列表
+/// - 401 Unauthorized: 带了token但token无效
 /// - 500 Internal Server Error: 服务器错误
 /// 
 /// ## 响应示例
@@ -42,7 +42,9 @@ use uuid::Uuid;
 /// ## 业务逻辑
 /// 1. 提取分页参数（默认第1页，每页20条）
 /// 2. 从数据库查询模板列表
-/// 3. 按创建时间倒序排列
+/// 3. 排序：
+///    - 匿名访问或未设置常驻城市：按创建时间倒序
+///    - 已设置常驻城市：本城市 → 所属省份 → 全国（CN）→ 其他地区，同一档内官方模板优先
 /// 4. 返回指定页的模板
 /// 
 /// ## 使用场景
@@ -55,14 +57,17 @@ use uuid::Uuid;
     params(TemplateSearchQuery),
     responses(
         (status = 200, description = "查询成功", body = ApiResponse<Vec<Template>>),
-        (status = 500, description = "服务器错误")
+        (status = 401, description = "Token无效", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
     ),
+    security((), ("bearer_auth" = [])),
     tag = "模板"
 )]
 pub async fn list_templates(
     State(state): State<AppState>,
+    viewer: OptionalCurrentUser,  // 可选认证，用于按常驻城市排序
     Query(params): Query<TemplateSearchQuery>,  // 从URL查询字符串提取参数
-) -> Result<Json<Vec<Template>>, (StatusCode, String)> {
+) -> ApiResult<Vec<Template>> {
     // 从依赖注入容器获取模板服务
     let template_service = &state.module.template_service;
     
@@ -72,11 +77,10 @@ pub async fn list_templates(
     
     // 查询模板列表
     let templates = template_service
-        .list_templates(page, page_size)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .list_templates(page, page_size, viewer.user_id())
+        .await?;
 
-    Ok(Json(templates))
+    Ok(ApiResponse::success(templates, "查询成功"))
 }
 
 /// 搜索模板
@@ -86,24 +90,30 @@ pub async fn list_templates(
 /// 
 /// ## 查询参数
 /// - `keyword`: 搜索关键词（可选）- 在标题和描述中搜索
-/// - `location_tag`: 地理标签（可选）- 如"CN"、"CN-BJ"、"CN-SH"
+/// - `location_tag`: 地区代码（可选）- 如"CN"、"CN-GD"、"CN-GZ"，可选值见`GET /api/locations`
 /// - `page`: 页码（可选，默认1）
 /// - `page_size`: 每页数量（可选，默认20）
 /// 
 /// ## 认证
-/// 无需认证（公开接口）
+/// 无需认证（公开接口）；带上token时按用户的常驻城市排序
 /// 
 /// ## 响应
 /// - 200 OK: 返回匹配的模板列表
+/// - 400 Bad Request: 地区代码不存在
+/// - 401 Unauthorized: 带了token但token无效
 /// - 500 Internal Server Error: 服务器错误
 /// 
 /// ## 搜索逻辑
-/// 1. **关键词搜索**：在标题和描述中模糊匹配（ILIKE）
-/// 2. **地理标签过滤**：精确匹配location_tag，同时包含通用模板（CN）
-/// 3. **组合搜索**：可以同时使用关键词和地理标签
+/// 1. **关键词搜索**：全文检索标题、描述和步骤内容；中文通过子串匹配兜底
+/// 2. **多关键词**：用空格分隔（最多5个），每个关键词都必须命中
+/// 3. **地理标签过滤**：匹配该地区及其所有上级地区（省、全国）的模板
+/// 4. **组合搜索**：可以同时使用关键词和地理标签
+/// 5. **排序**：按地区分档（城市 → 省 → 全国 → 其他），同一档内按相关度（`score`）、官方模板、创建时间排序；
+///    指定了`location_tag`时以它为准，否则使用登录用户的常驻城市
+/// 6. **高亮**：有关键词时返回`highlight`，命中部分用`<mark>`包裹，其余内容已做HTML转义
 /// 
 /// ## 示例
-/// ```
+/// ```text
 /// # 搜索所有租房相关模板
 /// GET /api/templates/search?keyword=租房
 /// 
@@ -112,13 +122,16 @@ pub async fn list_templates(
 /// 
 /// # 搜索北京的租房模板
 /// GET /api/templates/search?keyword=租房&location_tag=CN-BJ
+/// 
+/// # 同时包含"租房"和"押金"的模板
+/// GET /api/templates/search?keyword=租房%20押金
 /// ```
 /// 
 /// ## 地理标签说明
 /// - `CN`: 全国通用模板
-/// - `CN-BJ`: 北京专属模板
-/// - `CN-SH`: 上海专属模板
-/// - 搜索某城市时，会同时返回该城市和通用模板
+/// - `CN-GD`: 广东省内通用模板
+/// - `CN-GZ`: 广州专属模板
+/// - 搜索广州（CN-GZ）时，会同时返回 CN-GZ、CN-GD 和 CN 的模板
 /// 
 /// ## 使用场景
 /// - 用户搜索特定主题的模板
@@ -129,25 +142,28 @@ pub async fn list_templates(
     path = "/api/templates/search",
     params(TemplateSearchQuery),
     responses(
-        (status = 200, description = "搜索成功", body = ApiResponse<Vec<Template>>),
-        (status = 500, description = "服务器错误")
+        (status = 200, description = "搜索成功", body = ApiResponse<Vec<TemplateSearchResult>>),
+        (status = 400, description = "地区代码不存在", body = ErrorResponse),
+        (status = 401, description = "Token无效", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
     ),
+    security((), ("bearer_auth" = [])),
     tag = "模板"
 )]
 pub async fn search_templates(
     State(state): State<AppState>,
+    viewer: OptionalCurrentUser,  // 可选认证，用于按常驻城市排序
     Query(query): Query<TemplateSearchQuery>,
-) -> Result<Json<Vec<Template>>, (StatusCode, String)> {
+) -> ApiResult<Vec<TemplateSearchResult>> {
     // 从依赖注入容器获取模板服务
     let template_service = &state.module.template_service;
     
     // 执行搜索
     let templates = template_service
-        .search_templates(query)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .search_templates(query, viewer.user_id())
+        .await?;
 
-    Ok(Json(templates))
+    Ok(ApiResponse::success(templates, "搜索成功"))
 }
 
 /// 获取单个模板详情
@@ -208,25 +224,24 @@ pub async fn search_templates(
     ),
     responses(
         (status = 200, description = "获取成功", body = ApiResponse<Template>),
-        (status = 404, description = "模板不存在"),
-        (status = 500, description = "服务器错误")
+        (status = 404, description = "模板不存在", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
     ),
     tag = "模板"
 )]
 pub async fn get_template(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,  // 从URL路径提取模板ID
-) -> Result<Json<Template>, (StatusCode, String)> {
+) -> ApiResult<Template> {
     // 从依赖注入容器获取模板服务
     let template_service = &state.module.template_service;
     
     // 查询模板详情
     let template = template_service
         .get_template(id)
-        .await
-        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+        .await?;
 
-    Ok(Json(template))
+    Ok(ApiResponse::success(template, "获取成功"))
 }
 
 /// 创建新模板
@@ -235,7 +250,7 @@ pub async fn get_template(
 /// POST /api/templates
 /// 
 /// ## 认证
-/// 需要JWT token
+/// 需要JWT token，角色为`contributor`、`city_curator`或`admin`
 /// 
 /// ## 请求体
 /// ```json
@@ -263,22 +278,31 @@ pub async fn get_template(
 /// - 200 OK: 创建成功，返回新模板
 /// - 400 Bad Request: 验证失败
 /// - 401 Unauthorized: 未登录
+/// - 403 Forbidden: 普通用户（`user`）不能创建模板
+/// - 404 Not Found: 父模板不存在或已删除
 /// 
 /// ## 验证规则
 /// - `title`: 1-200字符
 /// - `description`: 1-2000字符
-/// - `location_tag`: 有效的地理标签
-/// - `steps`: 至少1个步骤
+/// - `location_tag`: `locations`表中存在的地区代码
+/// - `steps`: 至少1个步骤，步骤`id`不重复
+/// - `steps[].depends_on`: 只能引用本模板中的步骤，且依赖关系不能成环
+/// 
+/// ## 步骤依赖（Flow）
+/// 步骤可以通过`depends_on`声明前置步骤，此时需要为步骤指定`id`：
+/// ```json
+/// { "id": "uuid-3", "title": "签合同", "order": 2, "depends_on": ["uuid-1", "uuid-2"] }
+/// ```
 /// 
 /// ## 业务逻辑
 /// 1. 验证输入数据
 /// 2. 记录创建者ID（从JWT token获取）
-/// 3. 设置is_official=false（非官方模板）
+/// 3. 设置is_official=false（官方标记由管理员通过`PUT /api/admin/templates/:id/official`设置）
 /// 4. 保存到数据库
 /// 5. 返回创建的模板
 /// 
 /// ## 权限说明
-/// - V0.0.1版本：功能已实现但建议仅内部使用
+/// - V0.0.1版本：仅内部人员（贡献者、城市主编、管理员）可以创建
 /// - V0.1+版本：开放给所有用户创建模板
 /// 
 /// ## 使用场景
@@ -290,15 +314,26 @@ pub async fn get_template(
 /// 通过`parent_id`可以实现模板继承：
 /// - 通用模板（CN）作为父模板
 /// - 城市模板（CN-BJ）继承并扩展通用模板
-/// - V0.0.1暂不实现继承逻辑，仅保留字段
+/// 
+/// 子模板的`steps`只描述与父模板的差异：
+/// ```json
+/// { "overrides": "uuid-2", "title": "在链家/自如找房", "order": 0 }       // 覆盖父步骤的内容
+/// { "overrides": "uuid-4", "title": "找中介", "order": 0, "removed": true } // 移除父步骤
+/// { "title": "办理居住登记", "order": 2 }                                  // 新增步骤
+/// ```
+/// 覆盖和移除沿用父步骤的位置，它们的`order`会被忽略。
+/// 合并后的有效步骤通过`GET /api/templates/:id/resolved`查看；
+/// 继承链不能成环，深度不超过8层。
 #[utoipa::path(
     post,
     path = "/api/templates",
     request_body = CreateTemplateDto,
     responses(
         (status = 200, description = "创建成功", body = ApiResponse<Template>),
-        (status = 400, description = "验证失败"),
-        (status = 401, description = "未认证")
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "角色无权创建模板", body = ErrorResponse),
+        (status = 404, description = "父模板不存在", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "模板"
@@ -307,16 +342,293 @@ pub async fn create_template(
     State(state): State<AppState>,
     current_user: CurrentUser,  // JWT认证自动注入创建者ID
     Json(dto): Json<CreateTemplateDto>,
-) -> Result<Json<Template>, (StatusCode, String)> {
+) -> ApiResult<Template> {
+    // 从依赖注入容器获取模板服务
+    let template_service = &state.module.template_service;
+    
+    // 创建模板，记录创建者ID（服务层负责校验角色）
+    let template = template_service
+        .create_template(dto, &current_user.actor())
+        .await?;
+
+    Ok(ApiResponse::success(template, "创建成功"))
+}
+
+
+/// 更新模板
+/// 
+/// ## 端点
+/// PUT /api/templates/:id
+/// PATCH /api/templates/:id
+/// 
+/// 两种方法语义相同：只更新请求体中提供的字段。
+/// 
+/// ## 认证
+/// 需要JWT token。可以更新的用户：
+/// - 模板创建者
+/// - 负责该地区（或其上级地区）的城市主编
+/// - 管理员
+/// 
+/// 城市主编修改别人的模板时，新的`location_tag`也必须在自己负责的地区内。
+/// 
+/// ## 请求体
+/// ```json
+/// {
+///   "title": "第一次在上海找工作（2024版）",  // 可选
+///   "steps": [                                // 可选，整体替换
+///     { "title": "准备简历", "description": null, "order": 0 }
+///   ]
+/// }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 更新成功，返回更新后的模板
+/// - 400 Bad Request: 验证失败（规则与创建模板相同）
+/// - 401 Unauthorized: 未登录
+/// - 403 Forbidden: 无权修改该模板
+/// - 404 Not Found: 模板不存在或已删除
+/// 
+/// ## 版本
+/// 修改`title`、`description`或`steps`会发布新版本（版本号+1），
+/// 旧版本可通过`GET /api/templates/:id/versions/:version`查看
+/// 
+/// ## 注意事项
+/// - 已Fork的清单不受模板更新影响（Fork的是快照）
+#[utoipa::path(
+    put,
+    path = "/api/templates/{id}",
+    params(
+        ("id" = Uuid, Path, description = "模板UUID")
+    ),
+    request_body = UpdateTemplateDto,
+    responses(
+        (status = 200, description = "更新成功", body = ApiResponse<Template>),
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限修改该模板", body = ErrorResponse),
+        (status = 404, description = "模板不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "模板"
+)]
+pub async fn update_template(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<Uuid>,
+    Json(dto): Json<UpdateTemplateDto>,
+) -> ApiResult<Template> {
     // 从依赖注入容器获取模板服务
     let template_service = &state.module.template_service;
     
-    // 创建模板，记录创建者ID
+    // 更新模板（服务层负责校验创建者身份和角色）
     let template = template_service
-        .create_template(dto, current_user.user_id)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .update_template(id, dto, &current_user.actor())
+        .await?;
+
+    Ok(ApiResponse::success(template, "更新成功"))
+}
+
+/// 删除模板
+/// 
+/// ## 端点
+/// DELETE /api/templates/:id
+/// 
+/// ## 认证
+/// 需要JWT token，权限与更新模板相同（创建者、对应地区的城市主编、管理员）
+/// 
+/// ## 响应
+/// - 204 No Content: 删除成功
+/// - 401 Unauthorized: 未登录
+/// - 403 Forbidden: 无权删除该模板
+/// - 404 Not Found: 模板不存在或已删除
+/// 
+/// ## 删除策略（软删除）
+/// 
+/// 模板不会被物理删除，而是标记`deleted_at`：
+/// - 不再出现在列表、搜索和城市推荐中
+/// - 不能再被Fork
+/// - 已经Fork该模板的用户清单**完整保留**，进度不受影响
+/// - 通过`GET /api/templates/:id`仍可读取（供已有清单展示步骤）
+#[utoipa::path(
+    delete,
+    path = "/api/templates/{id}",
+    params(
+        ("id" = Uuid, Path, description = "模板UUID")
+    ),
+    responses(
+        (status = 204, description = "删除成功"),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 403, description = "无权限删除该模板", body = ErrorResponse),
+        (status = 404, description = "模板不存在", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "模板"
+)]
+pub async fn delete_template(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    // 从依赖注入容器获取模板服务
+    let template_service = &state.module.template_service;
+    
+    // 软删除模板（服务层负责校验创建者身份和角色）
+    template_service
+        .delete_template(id, &current_user.actor())
+        .await?;
 
-    Ok(Json(template))
+    Ok(StatusCode::NO_CONTENT)
 }
 
+/// 列出模板的所有历史版本
+/// 
+/// ## 端点
+/// GET /api/templates/:id/versions
+/// 
+/// ## 认证
+/// 无需认证（公开接口）
+/// 
+/// ## 响应
+/// - 200 OK: 返回版本快照列表（最新版本在前）
+/// - 404 Not Found: 模板不存在
+/// 
+/// ## 响应示例
+/// ```json
+/// [
+///   {
+///     "id": "uuid",
+///     "template_id": "uuid",
+///     "version": 2,
+///     "title": "第一次在北京租房整租指南",
+///     "description": "...",
+///     "steps": [...],
+///     "created_by": "uuid",
+///     "created_at": "2024-11-04T12:00:00Z"
+///   },
+///   { "version": 1, ... }
+/// ]
+/// ```
+/// 
+/// ## 使用场景
+/// - 查看模板的修改历史
+/// - 对比清单Fork时的版本与最新版本
+#[utoipa::path(
+    get,
+    path = "/api/templates/{id}/versions",
+    params(
+        ("id" = Uuid, Path, description = "模板UUID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<Vec<TemplateVersion>>),
+        (status = 404, description = "模板不存在", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
+    ),
+    tag = "模板"
+)]
+pub async fn list_template_versions(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Vec<TemplateVersion>> {
+    // 从依赖注入容器获取模板服务
+    let template_service = &state.module.template_service;
+    
+    // 查询版本列表
+    let versions = template_service
+        .list_versions(id)
+        .await?;
+
+    Ok(ApiResponse::success(versions, "获取成功"))
+}
+
+/// 获取模板的指定历史版本
+/// 
+/// ## 端点
+/// GET /api/templates/:id/versions/:version
+/// 
+/// ## 路径参数
+/// - `id`: 模板UUID
+/// - `version`: 版本号（从1开始）
+/// 
+/// ## 认证
+/// 无需认证（公开接口）
+/// 
+/// ## 响应
+/// - 200 OK: 返回该版本的完整快照（标题、描述、步骤）
+/// - 404 Not Found: 模板或版本不存在
+#[utoipa::path(
+    get,
+    path = "/api/templates/{id}/versions/{version}",
+    params(
+        ("id" = Uuid, Path, description = "模板UUID"),
+        ("version" = i32, Path, description = "版本号")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<TemplateVersion>),
+        (status = 404, description = "版本不存在", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
+    ),
+    tag = "模板"
+)]
+pub async fn get_template_version(
+    State(state): State<AppState>,
+    Path((id, version)): Path<(Uuid, i32)>,
+) -> ApiResult<TemplateVersion> {
+    // 从依赖注入容器获取模板服务
+    let template_service = &state.module.template_service;
+    
+    // 查询指定版本
+    let version = template_service
+        .get_version(id, version)
+        .await?;
+
+    Ok(ApiResponse::success(version, "获取成功"))
+}
+
+/// 获取解析继承后的模板
+/// 
+/// ## 端点
+/// GET /api/templates/:id/resolved
+/// 
+/// ## 认证
+/// 无需认证（公开接口）
+/// 
+/// ## 响应
+/// - 200 OK: 返回模板本身、祖先模板ID和合并后的有效步骤
+/// - 400 Bad Request: 继承链无效（成环、过深或覆盖了不存在的父步骤）
+/// - 404 Not Found: 模板或某个祖先模板不存在
+/// 
+/// ## 合并规则
+/// 从根模板开始逐层叠加：子模板可以覆盖、移除父模板的步骤，也可以新增步骤。
+/// 覆盖后的步骤沿用父步骤的ID，因此Fork出的清单在父模板更新后仍能正确同步。
+/// 
+/// ## 使用场景
+/// - Fork前预览城市模板实际包含的步骤
+/// - 模板作者检查覆盖是否生效
+#[utoipa::path(
+    get,
+    path = "/api/templates/{id}/resolved",
+    params(
+        ("id" = Uuid, Path, description = "模板UUID")
+    ),
+    responses(
+        (status = 200, description = "获取成功", body = ApiResponse<ResolvedTemplate>),
+        (status = 400, description = "继承链无效", body = ErrorResponse),
+        (status = 404, description = "模板不存在", body = ErrorResponse),
+        (status = 500, description = "服务器错误", body = ErrorResponse)
+    ),
+    tag = "模板"
+)]
+pub async fn get_resolved_template(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<ResolvedTemplate> {
+    // 从依赖注入容器获取模板服务
+    let template_service = &state.module.template_service;
+    
+    // 沿父模板链合并步骤
+    let resolved = template_service
+        .get_resolved_template(id)
+        .await?;
+
+    Ok(ApiResponse::success(resolved, "获取成功"))
+}
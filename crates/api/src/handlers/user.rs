@@ -1,7 +1,21 @@
-use axum::{extract::State, http::StatusCode, Json};
-use models::{UserProfile, UpdateProfileDto};
-use common::ApiResponse;
-use crate::{middleware::CurrentUser, state::AppState};
+use axum::{
+    extract::{multipart::MultipartRejection, ConnectInfo, Multipart, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use std::net::SocketAddr;
+use models::{
+    UserProfile, UpdateProfileDto, ChangePasswordDto, AuthResponse,
+    AccountExport, ExportFormat, ExportQuery, DeleteAccountDto, AccountDeletionResponse,
+    AvatarUploadForm, AvatarUploadResponse, AVATAR_MAX_BYTES,
+};
+use common::{ApiResponse, ApiResult, AppError, ErrorResponse};
+use crate::{
+    extract::{Json, Query},
+    handlers::auth::{client_ip, user_agent},
+    middleware::CurrentUser,
+    state::AppState,
+};
 
 /// 获取当前登录用户信息
 /// 
@@ -45,8 +59,8 @@ use crate::{middleware::CurrentUser, state::AppState};
     path = "/api/users/me",
     responses(
         (status = 200, description = "获取成功", body = ApiResponse<UserProfile>),
-        (status = 401, description = "未认证"),
-        (status = 404, description = "用户不存在")
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 404, description = "用户不存在", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "用户"
@@ -54,17 +68,16 @@ use crate::{middleware::CurrentUser, state::AppState};
 pub async fn get_current_user(
     State(state): State<AppState>,
     current_user: CurrentUser,  // JWT认证中间件自动注入
-) -> Result<Json<UserProfile>, (StatusCode, String)> {
+) -> ApiResult<UserProfile> {
     // 从依赖注入容器获取用户服务
     let user_service = &state.module.user_service;
     
     // 查询用户信息
     let profile = user_service
         .get_user(current_user.user_id)
-        .await
-        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))?;
+        .await?;
 
-    Ok(Json(profile))
+    Ok(ApiResponse::success(profile, "获取成功"))
 }
 
 /// 更新当前用户资料
@@ -88,7 +101,7 @@ pub async fn get_current_user(
 /// 
 /// ## 响应
 /// - 200 OK: 更新成功，返回更新后的用户资料
-/// - 400 Bad Request: 验证失败（如昵称过长）
+/// - 400 Bad Request: 验证失败（如昵称过长、城市代码不存在）
 /// - 401 Unauthorized: Token无效
 /// 
 /// ## 业务逻辑
@@ -98,8 +111,8 @@ pub async fn get_current_user(
 /// 
 /// ## 验证规则
 /// - `nickname`: 1-50字符
-/// - `avatar_url`: 任意URL字符串
-/// - `home_city`: 城市代码（如"CN-BJ"、"CN-SH"等）
+/// - `avatar_url`: 任意URL字符串（推荐通过`POST /api/users/me/avatar`上传，由服务器校验和处理）
+/// - `home_city`: 层级为`city`的地区代码（如"CN-BJ"、"CN-GZ"等，见`GET /api/locations`）
 /// 
 /// ## 使用场景
 /// - 用户修改昵称
@@ -115,8 +128,8 @@ pub async fn get_current_user(
     request_body = UpdateProfileDto,
     responses(
         (status = 200, description = "更新成功", body = ApiResponse<UserProfile>),
-        (status = 400, description = "验证失败"),
-        (status = 401, description = "未认证")
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse)
     ),
     security(("bearer_auth" = [])),
     tag = "用户"
@@ -125,16 +138,277 @@ pub async fn update_profile(
     State(state): State<AppState>,
     current_user: CurrentUser,
     Json(dto): Json<UpdateProfileDto>,
-) -> Result<Json<UserProfile>, (StatusCode, String)> {
+) -> ApiResult<UserProfile> {
     // 从依赖注入容器获取用户服务
     let user_service = &state.module.user_service;
     
     // 更新用户资料
     let profile = user_service
         .update_profile(current_user.user_id, dto)
-        .await
-        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        .await?;
 
-    Ok(Json(profile))
+    Ok(ApiResponse::success(profile, "更新成功"))
 }
 
+/// 上传头像
+/// 
+/// ## 端点
+/// POST /api/users/me/avatar
+/// 
+/// ## 认证
+/// 需要JWT token
+/// 
+/// ## 请求体
+/// `multipart/form-data`，图片放在`file`字段中：
+/// ```bash
+/// curl -X POST http://localhost:8080/api/users/me/avatar \
+///   -H "Authorization: Bearer <token>" \
+///   -F "file=@avatar.jpg"
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 上传成功，返回各尺寸的地址，最大的尺寸已保存为用户的`avatar_url`
+/// - 400 Bad Request: 不是JPEG、PNG或WebP图片，图片已损坏，或宽高超过8192像素
+/// - 401 Unauthorized: Token无效
+/// - 413 Payload Too Large: 文件超过5 MiB
+/// 
+/// ## 处理流程
+/// 1. 按文件内容识别图片类型（声明的`Content-Type`也必须是允许的类型）
+/// 2. 按EXIF方向旋转后重新编码，去除EXIF（拍摄地点、设备信息等）和其他元数据
+/// 3. 居中裁剪为正方形，生成512、256、64像素三个尺寸的JPEG（不放大小图）
+/// 4. 保存到对象存储（本地目录或S3兼容存储），更新用户的`avatar_url`
+/// 5. 删除之前上传的头像文件
+#[utoipa::path(
+    post,
+    path = "/api/users/me/avatar",
+    request_body(content = AvatarUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "上传成功", body = ApiResponse<AvatarUploadResponse>),
+        (status = 400, description = "图片类型不支持或图片无效", body = ErrorResponse),
+        (status = 401, description = "未认证", body = ErrorResponse),
+        (status = 413, description = "文件过大", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn upload_avatar(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> ApiResult<AvatarUploadResponse> {
+    // 从依赖注入容器获取头像服务
+    let avatar_service = &state.module.avatar_service;
+    
+    // 读取file字段，超过大小上限时立即停止读取
+    let mut multipart = multipart?;
+    let mut upload = None;
+    while let Some(mut field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        
+        let content_type = field.content_type().map(str::to_string);
+        let mut data = Vec::new();
+        while let Some(chunk) = field.chunk().await? {
+            if data.len() + chunk.len() > AVATAR_MAX_BYTES {
+                return Err(AppError::PayloadTooLarge(format!(
+                    "Avatar must be at most {} MiB",
+                    AVATAR_MAX_BYTES / 1024 / 1024
+                )));
+            }
+            data.extend_from_slice(&chunk);
+        }
+        
+        upload = Some((data, content_type));
+        break;
+    }
+    
+    let (data, content_type) = upload
+        .ok_or_else(|| AppError::ValidationError("Missing file field".to_string()))?;
+    
+    let response = avatar_service
+        .upload(current_user.user_id, data, content_type)
+        .await?;
+
+    Ok(ApiResponse::success(response, "上传成功"))
+}
+
+/// 修改密码
+/// 
+/// ## 端点
+/// PUT /api/users/me/password
+/// 
+/// ## 认证
+/// 需要JWT token
+/// 
+/// ## 请求体
+/// ```json
+/// { "current_password": "password123", "new_password": "newpassword123" }
+/// ```
+/// 
+/// ## 响应
+/// - 200 OK: 修改成功，返回当前设备新的访问令牌和刷新令牌
+/// - 400 Bad Request: 新密码不符合要求
+/// - 401 Unauthorized: 未登录，或当前密码错误
+/// - 429 Too Many Requests: 当前密码输错次数太多
+/// 
+/// ## 注意事项
+/// - 当前密码输错与登录失败共用同一个计数（退避和锁定规则相同），避免被盗的访问令牌用来猜密码
+/// - 修改后吊销该用户的所有刷新令牌，其他设备需要用新密码重新登录；当前设备改用返回的新令牌
+/// - 没有密码的账号（通过第三方登录或验证码登录自动注册）请使用找回密码设置密码
+#[utoipa::path(
+    put,
+    path = "/api/users/me/password",
+    request_body = ChangePasswordDto,
+    responses(
+        (status = 200, description = "修改成功", body = ApiResponse<AuthResponse>),
+        (status = 400, description = "验证失败", body = ErrorResponse),
+        (status = 401, description = "未认证或当前密码错误", body = ErrorResponse),
+        (status = 429, description = "当前密码输错次数太多", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn change_password(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    Json(dto): Json<ChangePasswordDto>,
+) -> ApiResult<AuthResponse> {
+    // 从依赖注入容器获取密码服务
+    let credential_service = &state.module.credential_service;
+    
+    let response = credential_service
+        .change_password(current_user.user_id, dto, user_agent(&headers), client_ip(&state, &headers, peer))
+        .await?;
+
+    Ok(ApiResponse::success(response, "修改成功"))
+}
+
+/// 导出个人数据
+/// 
+/// ## 端点
+/// GET /api/users/me/export
+/// 
+/// ## 认证
+/// 需要JWT token
+/// 
+/// ## 查询参数
+/// - `format`: `json`（默认）或`zip`
+/// 
+/// ## 响应
+/// - 200 OK: 返回导出的数据
+///   - `format=json`: 一个JSON文档
+///   - `format=zip`: ZIP压缩包（`application/zip`），包含`manifest.json`、`profile.json`、
+///     `identities.json`、`checklists.json`、`templates.json`、`template_versions.json`
+/// - 401 Unauthorized: Token无效
+/// 
+/// ## 导出内容
+/// - 完整的账号资料（含手机号和邮箱，不含密码哈希）
+/// - 绑定的第三方账号
+/// - 所有清单，含步骤和每个步骤的完成时间
+/// - 创建的模板（含已删除的）和发布过的模板版本
+#[utoipa::path(
+    get,
+    path = "/api/users/me/export",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "导出成功", body = AccountExport),
+        (status = 200, description = "导出成功（format=zip）", content_type = "application/zip"),
+        (status = 401, description = "未认证", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn export_data(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    // 从依赖注入容器获取账号服务
+    let account_service = &state.module.account_service;
+    
+    match query.format {
+        ExportFormat::Json => {
+            let export = account_service
+                .export(current_user.user_id)
+                .await?;
+            
+            Ok(axum::Json(export).into_response())
+        }
+        ExportFormat::Zip => {
+            let archive = account_service
+                .export_archive(current_user.user_id)
+                .await?;
+            
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "application/zip"),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"rookie-guide-export.zip\""),
+                ],
+                archive,
+            ).into_response())
+        }
+    }
+}
+
+/// 注销账号
+/// 
+/// ## 端点
+/// DELETE /api/users/me
+/// 
+/// ## 认证
+/// 需要JWT token
+/// 
+/// ## 请求体
+/// ```json
+/// { "password": "password123" }
+/// ```
+/// 
+/// 没有密码的账号（通过第三方登录或验证码登录自动注册）可以不传请求体。
+/// 
+/// ## 响应
+/// - 200 OK: 已申请注销，返回彻底删除的时间
+/// - 401 Unauthorized: 未登录，或密码错误
+/// - 429 Too Many Requests: 密码输错次数太多（与登录失败共用计数）
+/// 
+/// ## 注销流程
+/// 1. 申请后立即退出所有设备（吊销所有刷新令牌）
+/// 2. 宽限期（默认30天，`ACCOUNT_DELETION_GRACE_DAYS`）内用任何方式登录即撤销注销
+/// 3. 宽限期结束后由后台任务彻底删除：清单、第三方账号绑定、头像文件等一并删除；
+///    创建的模板保留并改为"已注销用户"创建，其他用户Fork的清单不受影响
+/// 
+/// ## 注意事项
+/// - 注销前可以通过`GET /api/users/me/export`导出个人数据
+/// - 重复申请不会推迟彻底删除的时间
+#[utoipa::path(
+    delete,
+    path = "/api/users/me",
+    request_body(content = DeleteAccountDto, description = "设置了密码的账号必须提供当前密码"),
+    responses(
+        (status = 200, description = "已申请注销", body = ApiResponse<AccountDeletionResponse>),
+        (status = 401, description = "未认证或密码错误", body = ErrorResponse),
+        (status = 429, description = "密码输错次数太多", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = [])),
+    tag = "用户"
+)]
+pub async fn delete_account(
+    State(state): State<AppState>,
+    current_user: CurrentUser,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    dto: Option<Json<DeleteAccountDto>>,
+) -> ApiResult<AccountDeletionResponse> {
+    // 从依赖注入容器获取账号服务
+    let account_service = &state.module.account_service;
+    
+    let dto = dto.map(|Json(dto)| dto).unwrap_or_default();
+    
+    let response = account_service
+        .request_deletion(current_user.user_id, dto, client_ip(&state, &headers, peer))
+        .await?;
+
+    Ok(ApiResponse::success(response, "已申请注销"))
+}
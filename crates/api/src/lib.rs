@@ -0,0 +1,41 @@
+//! HTTP API层
+//! 
+//! 路由、处理器、提取器和认证中间件。`main.rs`负责加载配置、连接数据库和启动服务器，
+//! 路由本身放在库中，`tests/`中的端到端测试可以直接构建同一个`Router`。
+//! 
+//! ## 模块结构
+//! 
+//! - `routes`: 路由表（`create_router`）
+//! - `state`: 应用状态（依赖注入容器）
+//! - `handlers`: HTTP处理器
+//! - `middleware`: JWT认证提取器
+//! - `extract`: 失败时返回统一错误响应的请求提取器
+//! - `docs`: OpenAPI文档路由
+//! 
+//! ## 使用示例
+//! 
+//! ```rust,no_run
+//! # async fn run(db: sea_orm::DatabaseConnection, config: common::AppConfig) {
+//! use api::{create_router, AppState};
+//! 
+//! let app_state = AppState::new(db, config);
+//! let app = create_router(app_state);
+//! # }
+//! ```
+//! 
+//! ## 测试
+//! 
+//! `tests/`中的测试针对真实的PostgreSQL：每个测试创建一个临时schema并运行全部迁移，
+//! 通过`tower::ServiceExt::oneshot`把请求直接交给路由。设置`TEST_DATABASE_URL`后才会运行，
+//! 未设置时跳过。
+
+pub mod routes;
+pub mod state;
+
+mod docs;
+mod extract;
+mod handlers;
+mod middleware;
+
+pub use routes::create_router;
+pub use state::AppState;
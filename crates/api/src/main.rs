@@ -1,43 +1,103 @@
-mod handlers;
-mod middleware;
-mod routes;
-mod state;
-mod docs;
-
-use common::AppConfig;
+use api::{create_router, AppState};
+use clap::Parser;
+use common::{AppConfig, ConfigOptions, Environment};
 use db::create_database_connection;
 use migration::{Migrator, MigratorTrait};
+use service_layer::AppModule;
 use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::CorsLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// 阅历进度条 API 服务器
+/// 
+/// 配置依次从默认值、配置文件、环境变量（和.env文件）、命令行参数中读取，后面的覆盖前面的。
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 运行环境：development（dev）、test、production（prod），默认读取APP_ENV
+    environment: Option<Environment>,
+    
+    /// 配置文件（TOML），指定后不再读取config/default.toml和config/{环境}.toml
+    #[arg(short, long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    
+    /// 监听地址（覆盖SERVER_HOST）
+    #[arg(long)]
+    host: Option<String>,
+    
+    /// 监听端口（覆盖SERVER_PORT）
+    #[arg(long)]
+    port: Option<u16>,
+    
+    /// 覆盖任意配置项，可以重复，如 --set database.max_connections=20
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_override)]
+    overrides: Vec<(String, String)>,
+    
+    /// 打印生效的配置（隐去密码和密钥）后退出
+    #[arg(long)]
+    print_config: bool,
+}
+
+impl Cli {
+    fn config_options(self) -> ConfigOptions {
+        let mut overrides = self.overrides;
+        overrides.extend(self.host.map(|host| ("SERVER_HOST".to_string(), host)));
+        overrides.extend(self.port.map(|port| ("SERVER_PORT".to_string(), port.to_string())));
+        
+        ConfigOptions {
+            environment: self.environment,
+            config_file: self.config,
+            overrides,
+        }
+    }
+}
+
+fn parse_override(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("应为KEY=VALUE: {}", value))
+}
+
 /// 应用程序主入口
 /// 
 /// 启动流程：
-/// 1. 初始化日志系统
-/// 2. 加载配置文件
+/// 1. 解析命令行参数，初始化日志系统
+/// 2. 加载并校验配置（`--print-config`时打印后退出）
 /// 3. 创建数据库连接（SeaORM）
 /// 4. **强制运行数据库迁移（SeaORM Migration，确保数据库结构最新）**
 /// 5. 初始化依赖注入容器
-/// 6. 构建路由和中间件
-/// 7. 启动HTTP服务器
+/// 6. 启动后台任务（彻底删除注销宽限期已过的账号）
+/// 7. 构建路由和中间件
+/// 8. 启动HTTP服务器
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let print_config = cli.print_config;
+    
     // ==================== 1. 初始化日志系统 ====================
     // 设置日志级别，可通过环境变量RUST_LOG控制
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "api=debug,tower_http=debug,sea_orm=debug".into()),
+                .unwrap_or_else(|_| "api=debug,common=info,auth=info,service_layer=info,tower_http=debug,sea_orm=debug".into()),
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
 
     // ==================== 2. 加载配置 ====================
-    // 从环境变量或.env文件加载配置
-    let config = AppConfig::from_env()?;
+    // 合并配置文件、环境变量（和.env文件）、命令行参数，所有问题一起报告
+    let config = AppConfig::load(&cli.config_options())?;
+    
+    if print_config {
+        print!("{}", config.redacted().to_toml()?);
+        return Ok(());
+    }
     
-    tracing::info!("🚀 启动阅历进度条 API 服务器...");
+    tracing::info!("🚀 启动阅历进度条 API 服务器（{}环境）...", config.environment);
     tracing::info!("📊 数据库连接: {}@{}:{}/{}", 
         config.database.user,
         config.database.host,
@@ -73,17 +133,24 @@ async fn main() -> anyhow::Result<()> {
 
     // ==================== 5. 初始化依赖注入容器 ====================
     // 创建应用状态，包含所有服务的依赖注入容器
-    let app_state = state::AppState::new(db, config.clone());
+    let app_state = AppState::new(db, config.clone());
     tracing::info!("✅ 依赖注入容器初始化完成");
 
-    // ==================== 6. 构建路由和中间件 ====================
+    // ==================== 6. 启动后台任务 ====================
+    // 定期彻底删除注销宽限期已过的账号
+    spawn_account_purge(app_state.module.clone(), config.account.purge_interval_seconds);
+
+    // ==================== 7. 构建路由和中间件 ====================
     // 配置HTTP路由、CORS跨域、请求追踪等中间件
-    let app = routes::create_router(app_state)
+    let app = create_router(app_state)
         .layer(CorsLayer::permissive())  // 允许跨域请求
         .layer(tower_http::trace::TraceLayer::new_for_http());  // HTTP请求追踪
 
-    // ==================== 7. 启动HTTP服务器 ====================
-    let addr = SocketAddr::from(([127, 0, 0, 1], config.server.port));
+    // ==================== 8. 启动HTTP服务器 ====================
+    // 监听配置的地址（SERVER_HOST可以是IP地址或主机名）
+    let listener = tokio::net::TcpListener::bind((config.server.host.as_str(), config.server.port)).await
+        .map_err(|e| anyhow::anyhow!("❌ 无法监听{}:{}: {}", config.server.host, config.server.port, e))?;
+    let addr = listener.local_addr()?;
     tracing::info!("🌐 服务器监听地址: http://{}", addr);
     tracing::info!("📖 健康检查: http://{}/health", addr);
     tracing::info!("📚 API 文档: http://{}/docs", addr);
@@ -91,8 +158,28 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("📘 ReDoc: http://{}/docs/redoc", addr);
     tracing::info!("🎉 服务器启动成功！");
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // 记录连接的对端地址，用于按IP的登录失败限制
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
+
+/// 每隔`interval_seconds`秒彻底删除一次注销宽限期已过的账号（启动时先执行一次）
+/// 
+/// 单次失败只记录日志，下一轮重试
+fn spawn_account_purge(module: Arc<AppModule>, interval_seconds: u64) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds.max(1)));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        
+        loop {
+            interval.tick().await;
+            
+            match module.account_service.purge_due_accounts().await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("🗑️ 已彻底删除{}个注销的账号", purged),
+                Err(e) => tracing::error!("❌ 删除注销的账号失败: {}", e),
+            }
+        }
+    });
+}
@@ -1,18 +1,20 @@
 use axum::{
-    extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
 };
-use auth::{JwtService, JwtServiceImpl};
-use common::AppConfig;
+use common::AppError;
+use models::{Actor, UserRole};
 use uuid::Uuid;
 
+use crate::state::AppState;
+
 /// 当前登录用户信息
 /// 
-/// 该结构体通过JWT认证中间件自动提取，包含当前请求的用户ID。
+/// 该结构体通过JWT认证中间件自动提取，包含当前请求的用户ID和角色（来自token）。
 /// 在需要认证的handler中，可以直接将其作为参数使用。
 /// 
 /// ## 使用示例
-/// ```rust
+/// ```rust,ignore
 /// async fn protected_handler(
 ///     current_user: CurrentUser,  // 自动提取当前用户
 /// ) -> impl IntoResponse {
@@ -22,6 +24,37 @@ use uuid::Uuid;
 pub struct CurrentUser {
     /// 当前登录用户的UUID
     pub user_id: Uuid,
+    
+    /// 角色（签发token时的角色）
+    pub role: UserRole,
+    
+    /// 角色的地区范围（只有城市主编有）
+    pub role_scope: Option<String>,
+}
+
+impl CurrentUser {
+    /// 转换为服务层使用的`Actor`
+    pub fn actor(&self) -> Actor {
+        Actor {
+            user_id: self.user_id,
+            role: self.role,
+            role_scope: self.role_scope.clone(),
+        }
+    }
+    
+    /// 要求至少拥有`required`角色，否则返回403
+    /// 
+    /// ## 使用示例
+    /// ```rust,ignore
+    /// current_user.require_role(UserRole::Contributor)?;
+    /// ```
+    pub fn require_role(&self, required: UserRole) -> Result<(), AppError> {
+        if self.role.includes(required) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden("当前角色无权执行该操作".to_string()))
+        }
+    }
 }
 
 /// JWT认证中间件
@@ -31,75 +64,137 @@ pub struct CurrentUser {
 /// ## 认证流程：
 /// 1. 从请求头中提取 `Authorization: Bearer <token>`
 /// 2. 验证token格式是否正确
-/// 3. 使用JWT服务验证token签名和有效期
-/// 4. 从token的claims中提取用户ID
+/// 3. 使用`AppModule`中的JWT服务验证token签名（按`kid`选择密钥）和有效期
+/// 4. 从token的claims中提取用户ID和角色
 /// 5. 返回`CurrentUser`实例
 /// 
 /// ## 错误处理：
+/// 拒绝时返回`AppError`，客户端收到统一的失败响应
 /// - 401 Unauthorized: token缺失、格式错误、验证失败、已过期
-/// - 500 Internal Server Error: 配置加载失败
 #[async_trait::async_trait]
 impl<S> FromRequestParts<S> for CurrentUser
 where
+    AppState: FromRef<S>,
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = AppError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         // ==================== 1. 提取Authorization头 ====================
         let auth_header = parts
             .headers
             .get("Authorization")
             .and_then(|h| h.to_str().ok())
-            .ok_or((
-                StatusCode::UNAUTHORIZED, 
-                "缺少Authorization请求头".to_string()
-            ))?;
+            .ok_or_else(|| AppError::AuthError("缺少Authorization请求头".to_string()))?;
 
         // ==================== 2. 验证Bearer格式 ====================
         // 标准格式: "Authorization: Bearer <token>"
-        if !auth_header.starts_with("Bearer ") {
-            return Err((
-                StatusCode::UNAUTHORIZED, 
-                "Authorization头格式错误，应为: Bearer <token>".to_string()
-            ));
-        }
+        let token = auth_header
+            .strip_prefix("Bearer ")
+            .ok_or_else(|| AppError::AuthError("Authorization头格式错误，应为: Bearer <token>".to_string()))?;
 
-        // 移除"Bearer "前缀，提取token
-        let token = &auth_header[7..];
-
-        // ==================== 3. 加载配置并验证token ====================
-        // 加载JWT密钥配置
-        let config = AppConfig::from_env()
-            .map_err(|_| (
-                StatusCode::INTERNAL_SERVER_ERROR, 
-                "服务器配置加载失败".to_string()
-            ))?;
-        
-        // 创建JWT服务实例
-        let jwt_service = JwtServiceImpl::new(
-            config.jwt.secret, 
-            config.jwt.expiration
-        );
-        
-        // 验证token并提取claims
-        let claims = jwt_service
+        // ==================== 3. 验证token ====================
+        // 使用启动时创建的JWT服务（包含当前签名密钥和轮换下来的旧密钥）
+        let state = AppState::from_ref(state);
+        let claims = state.module.jwt_service
             .validate_token(token)
-            .map_err(|e| (
-                StatusCode::UNAUTHORIZED, 
-                format!("Token验证失败: {}", e)
-            ))?;
+            .map_err(|e| AppError::AuthError(format!("Token验证失败: {}", e)))?;
 
-        // ==================== 4. 解析用户ID ====================
+        // ==================== 4. 解析用户ID和角色 ====================
         // 从claims中提取用户ID（sub字段）
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| (
-                StatusCode::UNAUTHORIZED, 
-                "Token中的用户ID格式无效".to_string()
-            ))?;
+        let actor = claims
+            .actor()
+            .map_err(|_| AppError::AuthError("Token中的用户ID格式无效".to_string()))?;
 
         // 返回当前用户信息
-        Ok(CurrentUser { user_id })
+        Ok(CurrentUser {
+            user_id: actor.user_id,
+            role: actor.role,
+            role_scope: actor.role_scope,
+        })
     }
 }
 
+
+/// 可选的当前登录用户
+/// 
+/// 用于登录前后表现不同的公开接口：匿名访问时为`None`，带token时按登录用户处理（例如按常驻城市排序）。
+/// 
+/// ## 提取规则：
+/// - 没有`Authorization`头: `None`
+/// - 有`Authorization`头: 与`CurrentUser`相同的校验，token无效时返回401，
+///   而不是静默降级为匿名，方便客户端及时刷新token
+/// 
+/// ## 使用示例
+/// ```rust,ignore
+/// async fn public_handler(
+///     viewer: OptionalCurrentUser,
+/// ) -> impl IntoResponse {
+///     match viewer.0 {
+///         Some(user) => format!("Hello, user {}", user.user_id),
+///         None => "Hello, guest".to_string(),
+///     }
+/// }
+/// ```
+pub struct OptionalCurrentUser(pub Option<CurrentUser>);
+
+impl OptionalCurrentUser {
+    /// 当前登录用户的UUID（匿名访问时为None）
+    pub fn user_id(&self) -> Option<Uuid> {
+        self.0.as_ref().map(|user| user.user_id)
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> FromRequestParts<S> for OptionalCurrentUser
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if !parts.headers.contains_key("Authorization") {
+            return Ok(OptionalCurrentUser(None));
+        }
+
+        let current_user = CurrentUser::from_request_parts(parts, state).await?;
+
+        Ok(OptionalCurrentUser(Some(current_user)))
+    }
+}
+
+/// 管理员
+/// 
+/// 只用于管理接口：先按`CurrentUser`校验token，再要求角色为`admin`。
+/// 
+/// ## 错误处理：
+/// - 401 Unauthorized: 与`CurrentUser`相同
+/// - 403 Forbidden: 不是管理员
+/// 
+/// ## 使用示例
+/// ```rust,ignore
+/// async fn admin_handler(
+///     AdminUser(admin): AdminUser,
+/// ) -> impl IntoResponse {
+///     format!("Hello, admin {}", admin.user_id)
+/// }
+/// ```
+pub struct AdminUser(pub CurrentUser);
+
+#[async_trait::async_trait]
+impl<S> FromRequestParts<S> for AdminUser
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let current_user = CurrentUser::from_request_parts(parts, state).await?;
+
+        current_user.require_role(UserRole::Admin)?;
+
+        Ok(AdminUser(current_user))
+    }
+}
@@ -1,4 +1,4 @@
 pub mod auth;
 
-pub use auth::CurrentUser;
+pub use auth::{CurrentUser, OptionalCurrentUser, AdminUser};
 
@@ -1,24 +1,31 @@
 use crate::{handlers, state::AppState};
 use axum::{
-    routing::{get, post, put},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, patch, post, put},
     Router,
 };
+use models::AVATAR_MAX_BYTES;
+use tower_http::services::ServeDir;
 
 /// 创建应用程序路由
 /// 
 /// 该函数定义了所有HTTP端点的路由规则，包括：
 /// - 健康检查
-/// - 用户认证（注册/登录）
+/// - 用户认证（注册/登录/验证码登录/第三方登录/刷新令牌/退出登录）
 /// - 用户管理
 /// - 模板管理
 /// - 清单管理
+/// - 管理接口
 /// 
 /// ## 路由分组
 /// - `/health` - 健康检查，用于监控服务状态
-/// - `/api/auth/*` - 认证相关，无需token
+/// - `/api/auth/*` - 认证相关，无需token（退出所有设备除外）
 /// - `/api/users/*` - 用户管理，需要token
 /// - `/api/templates/*` - 模板管理，部分需要token
 /// - `/api/checklists/*` - 清单管理，需要token
+/// - `/api/locations` - 地区列表，无需token
+/// - `/api/admin/*` - 管理接口，需要管理员token
+/// - `/uploads/*` - 用户上传的文件（只在使用本地对象存储时提供）
 /// 
 /// ## 参数
 /// * `state` - 应用状态，包含依赖注入容器
@@ -26,6 +33,9 @@ use axum::{
 /// ## 返回
 /// 返回配置好的Axum路由器
 pub fn create_router(state: AppState) -> Router {
+    // 本地对象存储的目录，在业务路由注入状态之前取出
+    let uploads_dir = state.uploads_dir.clone();
+    
     // 先创建文档路由（无状态），然后再创建业务路由（有状态）
     let api_routes = Router::new()
         // ==================== 健康检查 ====================
@@ -37,12 +47,53 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/auth/register", post(handlers::auth::register))
         // POST /api/auth/login - 用户登录
         .route("/api/auth/login", post(handlers::auth::login))
+        // POST /api/auth/sms/code - 发送登录验证码
+        .route("/api/auth/sms/code", post(handlers::auth::send_login_code))
+        // POST /api/auth/sms/login - 验证码登录（未注册时自动注册）
+        .route("/api/auth/sms/login", post(handlers::auth::login_with_code))
+        // POST /api/auth/refresh - 用刷新令牌换取新的令牌对
+        .route("/api/auth/refresh", post(handlers::auth::refresh))
+        // POST /api/auth/logout - 退出当前设备（吊销刷新令牌所在会话）
+        .route("/api/auth/logout", post(handlers::auth::logout))
+        // POST /api/auth/logout-all - 退出所有设备（需要认证）
+        .route("/api/auth/logout-all", post(handlers::auth::logout_all))
+        // POST /api/auth/password/forgot - 发送找回密码邮件
+        .route("/api/auth/password/forgot", post(handlers::auth::forgot_password))
+        // POST /api/auth/password/reset - 用邮件中的令牌重置密码
+        .route("/api/auth/password/reset", post(handlers::auth::reset_password))
+        
+        // ==================== 第三方登录路由（公开） ====================
+        // GET /api/auth/oauth/providers - 已配置的第三方登录提供方
+        .route("/api/auth/oauth/providers", get(handlers::oauth::list_providers))
+        // POST /api/auth/oauth/:provider/authorize - 获取提供方的授权地址
+        .route("/api/auth/oauth/:provider/authorize", post(handlers::oauth::authorize))
+        // POST /api/auth/oauth/:provider/callback - 用授权码登录（首次登录自动注册）
+        .route("/api/auth/oauth/:provider/callback", post(handlers::oauth::login))
         
         // ==================== 用户路由（需要认证） ====================
         // GET /api/users/me - 获取当前登录用户信息
         .route("/api/users/me", get(handlers::user::get_current_user))
         // PUT /api/users/me - 更新当前用户资料
         .route("/api/users/me", put(handlers::user::update_profile))
+        // DELETE /api/users/me - 注销账号（宽限期后彻底删除）
+        .route("/api/users/me", delete(handlers::user::delete_account))
+        // POST /api/users/me/avatar - 上传头像（multipart，请求体上限为头像大小上限加表单开销）
+        .route(
+            "/api/users/me/avatar",
+            post(handlers::user::upload_avatar).layer(DefaultBodyLimit::max(AVATAR_MAX_BYTES + 64 * 1024)),
+        )
+        // GET /api/users/me/export - 导出个人数据（JSON或ZIP）
+        .route("/api/users/me/export", get(handlers::user::export_data))
+        // PUT /api/users/me/password - 修改密码（退出其他设备）
+        .route("/api/users/me/password", put(handlers::user::change_password))
+        // GET /api/users/me/identities - 列出绑定的第三方账号
+        .route("/api/users/me/identities", get(handlers::oauth::list_identities))
+        // POST /api/users/me/identities/:provider/authorize - 发起绑定第三方账号
+        .route("/api/users/me/identities/:provider/authorize", post(handlers::oauth::authorize_link))
+        // POST /api/users/me/identities/:provider/callback - 完成绑定
+        .route("/api/users/me/identities/:provider/callback", post(handlers::oauth::link))
+        // DELETE /api/users/me/identities/:provider - 解绑第三方账号
+        .route("/api/users/me/identities/:provider", delete(handlers::oauth::unlink))
         
         // ==================== 模板路由 ====================
         // GET /api/templates - 列出所有模板（分页）
@@ -53,6 +104,17 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/templates/:id", get(handlers::template::get_template))
         // POST /api/templates - 创建新模板（需要认证）
         .route("/api/templates", post(handlers::template::create_template))
+        // PUT/PATCH /api/templates/:id - 更新模板（仅创建者）
+        .route("/api/templates/:id", put(handlers::template::update_template))
+        .route("/api/templates/:id", patch(handlers::template::update_template))
+        // DELETE /api/templates/:id - 软删除模板（仅创建者）
+        .route("/api/templates/:id", delete(handlers::template::delete_template))
+        // GET /api/templates/:id/resolved - 获取解析继承后的有效步骤
+        .route("/api/templates/:id/resolved", get(handlers::template::get_resolved_template))
+        // GET /api/templates/:id/versions - 列出模板的历史版本
+        .route("/api/templates/:id/versions", get(handlers::template::list_template_versions))
+        // GET /api/templates/:id/versions/:version - 获取指定版本的快照
+        .route("/api/templates/:id/versions/:version", get(handlers::template::get_template_version))
         
         // ==================== 清单路由（需要认证） ====================
         // GET /api/checklists - 获取当前用户的所有清单
@@ -63,11 +125,39 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/checklists/:id", get(handlers::checklist::get_checklist))
         // PUT /api/checklists/:id/steps - 更新清单中某个步骤的完成状态
         .route("/api/checklists/:id/steps", put(handlers::checklist::update_step))
+        // POST /api/checklists/:id/steps - 添加自定义步骤
+        .route("/api/checklists/:id/steps", post(handlers::checklist::add_custom_step))
+        // PUT /api/checklists/:id/steps/order - 重新排列步骤
+        .route("/api/checklists/:id/steps/order", put(handlers::checklist::reorder_steps))
+        // PATCH /api/checklists/:id/steps/:step_id - 改写、隐藏或恢复步骤
+        .route("/api/checklists/:id/steps/:step_id", patch(handlers::checklist::edit_step))
+        // DELETE /api/checklists/:id/steps/:step_id - 删除步骤
+        .route("/api/checklists/:id/steps/:step_id", delete(handlers::checklist::remove_step))
+        // GET /api/checklists/:id/sync - 预览与来源模板的同步结果
+        .route("/api/checklists/:id/sync", get(handlers::checklist::preview_sync))
+        // POST /api/checklists/:id/sync - 同步到来源模板的最新版本
+        .route("/api/checklists/:id/sync", post(handlers::checklist::apply_sync))
+        
+        // ==================== 地区路由（公开） ====================
+        // GET /api/locations - 列出地区（国家 → 省 → 城市）
+        .route("/api/locations", get(handlers::location::list_locations))
+        
+        // ==================== 管理路由（仅管理员） ====================
+        // PUT /api/admin/templates/:id/official - 设置或取消官方模板
+        .route("/api/admin/templates/:id/official", put(handlers::admin::set_template_official))
+        // PUT /api/admin/users/:id/role - 授予用户角色
+        .route("/api/admin/users/:id/role", put(handlers::admin::grant_role))
         
         // 注入应用状态，使所有handler都能访问服务
         .with_state(state);
     
     // 合并文档路由和业务路由
-    crate::docs::docs_routes().merge(api_routes)
+    let router = crate::docs::docs_routes().merge(api_routes);
+    
+    // GET /uploads/* - 本地对象存储中的文件（头像），使用S3兼容存储时由存储服务直接提供
+    match uploads_dir {
+        Some(dir) => router.nest_service("/uploads", ServeDir::new(dir)),
+        None => router,
+    }
 }
 
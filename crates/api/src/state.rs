@@ -1,4 +1,4 @@
-use common::AppConfig;
+use common::{AppConfig, StorageBackend};
 use service_layer::AppModule;
 use sea_orm::DatabaseConnection;
 use std::sync::Arc;
@@ -16,6 +16,12 @@ pub struct AppState {
     /// 依赖注入容器，包含所有业务服务
     /// 使用Arc包装以实现跨请求共享和线程安全
     pub module: Arc<AppModule>,
+    
+    /// 是否信任`X-Forwarded-For`请求头（见`ServerConfig::trust_forwarded_for`）
+    pub trust_forwarded_for: bool,
+    
+    /// 本地对象存储的目录，由`/uploads`路由提供访问（使用S3兼容存储时为`None`）
+    pub uploads_dir: Option<String>,
 }
 
 impl AppState {
@@ -28,12 +34,20 @@ impl AppState {
     /// ## 返回
     /// 返回包含完整依赖注入容器的应用状态
     pub fn new(db: DatabaseConnection, config: AppConfig) -> Self {
+        let trust_forwarded_for = config.server.trust_forwarded_for;
+        let uploads_dir = match &config.storage.backend {
+            StorageBackend::Local { dir } => Some(dir.clone()),
+            StorageBackend::S3(_) => None,
+        };
+        
         // 初始化依赖注入容器
         let module = AppModule::new(db, config);
         
         Self {
             // 使用Arc包装，允许在多个请求之间共享
             module: Arc::new(module),
+            trust_forwarded_for,
+            uploads_dir,
         }
     }
 }
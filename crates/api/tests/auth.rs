@@ -0,0 +1,217 @@
+//! 认证路由
+//!
+//! 注册、密码登录（包括失败后的等待）、刷新令牌的轮换和重用检测、退出登录、
+//! 短信验证码、找回密码（从捕获的邮件中取出重置链接）以及未配置提供方时的第三方登录。
+
+mod support;
+
+use auth::hash_refresh_token;
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use models::{password_reset_token, LoginScope};
+use sea_orm::{ActiveModelTrait, Set};
+use serde_json::json;
+use support::{TestApp, PASSWORD};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn register_then_login() {
+    let Some(app) = TestApp::spawn().await else { return };
+
+    let data = app
+        .post(
+            "/api/auth/register",
+            None,
+            json!({ "email": "ming@example.com", "password": PASSWORD, "nickname": "小明" }),
+        )
+        .await
+        .ok();
+    assert_eq!(data["user"]["nickname"], "小明");
+    assert_eq!(data["user"]["role"], "user");
+    assert!(data["expires_in"].as_i64().unwrap() > 0);
+    assert!(data["refresh_token"].as_str().is_some_and(|t| !t.is_empty()));
+
+    // The access token works right away
+    let me = app.get("/api/users/me", data["token"].as_str()).await.ok();
+    assert_eq!(me["id"], data["user"]["id"]);
+
+    app.post(
+        "/api/auth/register",
+        None,
+        json!({ "email": "ming@example.com", "password": PASSWORD, "nickname": "另一个小明" }),
+    )
+    .await
+    .error(StatusCode::CONFLICT, "CONFLICT");
+
+    let login = app
+        .post("/api/auth/login", None, json!({ "email": "ming@example.com", "password": PASSWORD }))
+        .await
+        .ok();
+    assert_eq!(login["user"]["id"], data["user"]["id"]);
+
+    for (email, password) in [("ming@example.com", "wrong-password"), ("nobody@example.com", PASSWORD)] {
+        app.post("/api/auth/login", None, json!({ "email": email, "password": password }))
+            .await
+            .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    }
+}
+
+#[tokio::test]
+async fn repeated_login_failures_are_rate_limited() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+    let login = |password: &'static str| {
+        app.post("/api/auth/login", None, json!({ "email": user.email, "password": password }))
+    };
+
+    for _ in 0..=LoginScope::Account.free_failures() {
+        login("wrong-password").await.error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    }
+
+    // Even the right password has to wait now
+    login(PASSWORD).await.error(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED");
+}
+
+#[tokio::test]
+async fn refresh_tokens_rotate_and_reuse_revokes_the_session() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    let rotated = app.refresh(&user).await;
+    assert_ne!(rotated.refresh_token, user.refresh_token);
+    app.get("/api/users/me", rotated.auth()).await.ok();
+
+    // Replaying the old token fails and signs out the whole session
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": user.refresh_token }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": rotated.refresh_token }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": "unknown" }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+}
+
+#[tokio::test]
+async fn logout_revokes_refresh_tokens() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    app.post("/api/auth/logout", None, json!({ "refresh_token": user.refresh_token }))
+        .await
+        .empty(StatusCode::NO_CONTENT);
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": user.refresh_token }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+
+    // Two devices, both signed out at once
+    let phone = app.refresh(&app.register("hong").await).await;
+    let laptop = app
+        .post("/api/auth/login", None, json!({ "email": "hong@example.com", "password": PASSWORD }))
+        .await
+        .ok();
+    app.post("/api/auth/logout-all", phone.auth(), json!({}))
+        .await
+        .empty(StatusCode::NO_CONTENT);
+    for refresh_token in [phone.refresh_token.as_str(), laptop["refresh_token"].as_str().unwrap()] {
+        app.post("/api/auth/refresh", None, json!({ "refresh_token": refresh_token }))
+            .await
+            .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    }
+}
+
+#[tokio::test]
+async fn sms_codes_are_throttled_and_checked() {
+    let Some(app) = TestApp::spawn().await else { return };
+
+    let sent = app.post("/api/auth/sms/code", None, json!({ "phone": "13800138000" })).await.ok();
+    assert!(sent["expires_in"].as_i64().unwrap() > 0);
+    assert!(sent["resend_after"].as_i64().unwrap() > 0);
+
+    app.post("/api/auth/sms/code", None, json!({ "phone": "13800138000" }))
+        .await
+        .error(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED");
+
+    app.post("/api/auth/sms/login", None, json!({ "phone": "13800138000", "code": "000000" }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+
+    // Eleven characters, but multi-byte ones
+    for uri in ["/api/auth/sms/code", "/api/auth/sms/login"] {
+        app.post(uri, None, json!({ "phone": "1٣٨٠٠١٣٨٠٠٠", "code": "000000" }))
+            .await
+            .error(StatusCode::BAD_REQUEST, "VALIDATION_FAILED");
+    }
+}
+
+#[tokio::test]
+async fn password_reset_through_the_emailed_link() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    // Unknown addresses get the same answer
+    for email in ["nobody@example.com", user.email.as_str()] {
+        app.post("/api/auth/password/forgot", None, json!({ "email": email }))
+            .await
+            .empty(StatusCode::ACCEPTED);
+    }
+    let email = app.wait_for_email_to(&user.email).await.expect("no password reset email was captured");
+    assert!(app.last_email_to("nobody@example.com").is_none());
+    let token = email
+        .split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("the email has no reset link");
+
+    // A second link left behind by a concurrent request
+    password_reset_token::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user.id),
+        token_hash: Set(hash_refresh_token("leftover-token")),
+        expires_at: Set(Utc::now() + Duration::minutes(30)),
+        consumed_at: Set(None),
+        created_at: Set(Utc::now()),
+    }
+    .insert(&app.db)
+    .await
+    .unwrap();
+
+    let reset = json!({ "token": token, "new_password": "a-brand-new-password" });
+    app.post("/api/auth/password/reset", None, reset.clone())
+        .await
+        .empty(StatusCode::NO_CONTENT);
+    app.post("/api/auth/password/reset", None, reset)
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    app.post("/api/auth/password/reset", None, json!({ "token": "leftover-token", "new_password": "yet-another-password" }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+
+    // Resetting signs out existing sessions
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": user.refresh_token }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    app.post("/api/auth/login", None, json!({ "email": user.email, "password": PASSWORD }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    app.post("/api/auth/login", None, json!({ "email": user.email, "password": "a-brand-new-password" }))
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn oauth_without_configured_providers() {
+    let Some(app) = TestApp::spawn().await else { return };
+
+    let providers = app.get("/api/auth/oauth/providers", None).await.ok();
+    assert_eq!(providers, json!([]));
+
+    app.post("/api/auth/oauth/github/authorize", None, json!({}))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+    app.post("/api/auth/oauth/github/callback", None, json!({ "code": "code", "state": "state" }))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+}
@@ -0,0 +1,250 @@
+//! 清单路由
+//!
+//! Fork模板、列表和详情、勾选步骤（前置步骤检查）、自定义步骤的添加、改写、隐藏、删除和排序、
+//! 与来源模板的同步，以及清单只能由所有者访问。
+
+mod support;
+
+use axum::http::StatusCode;
+use models::UserRole;
+use serde_json::{json, Value};
+use support::{TestApp, TestUser};
+use uuid::Uuid;
+
+/// 贡献者创建的三个步骤的模板：签合同之前要先看房
+async fn renting_template(app: &TestApp) -> (TestUser, Value) {
+    let author = app.register_with_role("author", UserRole::Contributor).await;
+    let viewing = Uuid::new_v4();
+    let template = app
+        .post(
+            "/api/templates",
+            author.auth(),
+            json!({
+                "title": "第一次租房",
+                "description": "从看房到签合同",
+                "location_tag": "CN",
+                "steps": [
+                    { "title": "确定预算", "order": 0 },
+                    { "id": viewing, "title": "看房", "order": 1 },
+                    { "title": "签合同", "order": 2, "depends_on": [viewing] }
+                ]
+            }),
+        )
+        .await
+        .ok();
+
+    (author, template)
+}
+
+async fn fork(app: &TestApp, user: &TestUser, template: &Value) -> Value {
+    app.post("/api/checklists", user.auth(), json!({ "template_id": template["id"] }))
+        .await
+        .ok()
+}
+
+fn step_ids(response: &Value) -> Vec<Value> {
+    response["checklist"]["steps"].as_array().unwrap().iter().map(|s| s["id"].clone()).collect()
+}
+
+fn states(response: &Value) -> Vec<&str> {
+    response["progress"]["steps"].as_array().unwrap().iter().map(|s| s["state"].as_str().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn forking_and_reading_checklists() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let (_, template) = renting_template(&app).await;
+    let user = app.register("ming").await;
+
+    let forked = fork(&app, &user, &template).await;
+    assert_eq!(forked["checklist"]["user_id"], user.id.to_string());
+    assert_eq!(forked["checklist"]["source_template_id"], template["id"]);
+    assert_eq!(forked["checklist"]["source_template_version"], 1);
+    assert_eq!(forked["progress"]["total_steps"], 3);
+    assert_eq!(forked["progress"]["completed_steps"], 0);
+    assert_eq!(states(&forked), vec!["available", "available", "locked"]);
+
+    let uri = format!("/api/checklists/{}", forked["checklist"]["id"].as_str().unwrap());
+    assert_eq!(app.get(&uri, user.auth()).await.ok(), forked);
+
+    let listed = app.get("/api/checklists", user.auth()).await.ok();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["checklist"]["id"], forked["checklist"]["id"]);
+
+    app.post("/api/checklists", user.auth(), json!({ "template_id": Uuid::new_v4() }))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+    app.get(&format!("/api/checklists/{}", Uuid::new_v4()), user.auth())
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn deleted_templates_cannot_be_forked() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let (author, template) = renting_template(&app).await;
+    let user = app.register("ming").await;
+    let forked = fork(&app, &user, &template).await;
+
+    app.delete(&format!("/api/templates/{}", template["id"].as_str().unwrap()), author.auth())
+        .await
+        .empty(StatusCode::NO_CONTENT);
+
+    app.post("/api/checklists", user.auth(), json!({ "template_id": template["id"] }))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+
+    // Existing checklists are not affected
+    app.get(&format!("/api/checklists/{}", forked["checklist"]["id"].as_str().unwrap()), user.auth())
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn completing_steps_respects_prerequisites() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let (_, template) = renting_template(&app).await;
+    let user = app.register("ming").await;
+    let forked = fork(&app, &user, &template).await;
+    let uri = format!("/api/checklists/{}/steps", forked["checklist"]["id"].as_str().unwrap());
+    let steps = step_ids(&forked);
+
+    app.put(&uri, user.auth(), json!({ "step_id": steps[2], "completed": true }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+
+    let viewed = app.put(&uri, user.auth(), json!({ "step_id": steps[1], "completed": true })).await.ok();
+    assert_eq!(viewed["progress"]["completed_steps"], 1);
+    assert_eq!(states(&viewed), vec!["available", "done", "available"]);
+
+    let forced = app
+        .put(&uri, user.auth(), json!({ "step_id": steps[0], "completed": true, "force": true }))
+        .await
+        .ok();
+    assert_eq!(forced["progress"]["completed_steps"], 2);
+
+    let unchecked = app.put(&uri, user.auth(), json!({ "step_id": steps[1], "completed": false })).await.ok();
+    assert_eq!(unchecked["progress"]["completed_steps"], 1);
+    assert!(unchecked["progress"]["steps"][1]["completed_at"].is_null());
+    assert_eq!(unchecked["progress"]["steps"][2]["blocked_by"], json!([steps[1]]));
+
+    app.put(&uri, user.auth(), json!({ "step_id": Uuid::new_v4(), "completed": true }))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn steps_can_be_added_edited_hidden_removed_and_reordered() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let (_, template) = renting_template(&app).await;
+    let user = app.register("ming").await;
+    let forked = fork(&app, &user, &template).await;
+    let uri = format!("/api/checklists/{}/steps", forked["checklist"]["id"].as_str().unwrap());
+
+    let added = app
+        .post(&uri, user.auth(), json!({ "title": "买家具", "position": 0 }))
+        .await
+        .ok();
+    assert_eq!(added["progress"]["total_steps"], 4);
+    let custom = &added["checklist"]["steps"][0];
+    assert_eq!(custom["title"], "买家具");
+    assert_eq!(custom["is_custom"], true);
+    app.post(&uri, user.auth(), json!({ "title": "" }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "VALIDATION_FAILED");
+
+    let budget = step_ids(&forked)[0].as_str().unwrap().to_string();
+    let edited = app
+        .patch(&format!("{}/{}", uri, budget), user.auth(), json!({ "title": "算好预算", "hidden": true }))
+        .await
+        .ok();
+    let budget_step = edited["checklist"]["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["id"] == budget)
+        .unwrap();
+    assert_eq!(budget_step["title"], "算好预算");
+    assert_eq!(budget_step["hidden"], true);
+    assert_eq!(edited["progress"]["total_steps"], 3);
+
+    let removed = app
+        .delete(&format!("{}/{}", uri, custom["id"].as_str().unwrap()), user.auth())
+        .await
+        .ok();
+    assert_eq!(step_ids(&removed), step_ids(&forked));
+    app.delete(&format!("{}/{}", uri, custom["id"].as_str().unwrap()), user.auth())
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+
+    let mut reversed = step_ids(&removed);
+    reversed.reverse();
+    let reordered = app
+        .put(&format!("{}/order", uri), user.auth(), json!({ "step_ids": reversed }))
+        .await
+        .ok();
+    assert_eq!(step_ids(&reordered), reversed);
+    app.put(&format!("{}/order", uri), user.auth(), json!({ "step_ids": &reversed[..2] }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+}
+
+#[tokio::test]
+async fn checklists_sync_with_new_template_versions() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let (author, template) = renting_template(&app).await;
+    let user = app.register("ming").await;
+    let forked = fork(&app, &user, &template).await;
+    let uri = format!("/api/checklists/{}/sync", forked["checklist"]["id"].as_str().unwrap());
+
+    let report = app.get(&uri, user.auth()).await.ok();
+    assert_eq!(report["up_to_date"], true);
+
+    let mut steps = template["steps"].as_array().unwrap().clone();
+    steps.push(json!({ "title": "办理入住", "order": 3 }));
+    app.patch(&format!("/api/templates/{}", template["id"].as_str().unwrap()), author.auth(), json!({ "steps": steps }))
+        .await
+        .ok();
+
+    let report = app.get(&uri, user.auth()).await.ok();
+    assert_eq!(report["up_to_date"], false);
+    assert_eq!(report["from_version"], 1);
+    assert_eq!(report["to_version"], 2);
+    assert_eq!(report["added"][0]["title"], "办理入住");
+    assert_eq!(report["kept"].as_array().unwrap().len(), 3);
+
+    let synced = app.post(&uri, user.auth(), json!({})).await.ok();
+    assert_eq!(synced["checklist"]["checklist"]["source_template_version"], 2);
+    assert_eq!(synced["checklist"]["progress"]["total_steps"], 4);
+    assert_eq!(app.get(&uri, user.auth()).await.ok()["up_to_date"], true);
+}
+
+#[tokio::test]
+async fn only_the_owner_can_use_a_checklist() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let (_, template) = renting_template(&app).await;
+    let owner = app.register("ming").await;
+    let other = app.register("hong").await;
+    let forked = fork(&app, &owner, &template).await;
+    let id = forked["checklist"]["id"].as_str().unwrap();
+    let step = step_ids(&forked)[0].clone();
+    let step_uri = format!("/api/checklists/{}/steps/{}", id, step.as_str().unwrap());
+
+    let forbidden = [
+        app.get(&format!("/api/checklists/{}", id), other.auth()).await,
+        app.put(&format!("/api/checklists/{}/steps", id), other.auth(), json!({ "step_id": step, "completed": true })).await,
+        app.post(&format!("/api/checklists/{}/steps", id), other.auth(), json!({ "title": "别人的步骤" })).await,
+        app.put(&format!("/api/checklists/{}/steps/order", id), other.auth(), json!({ "step_ids": step_ids(&forked) })).await,
+        app.patch(&step_uri, other.auth(), json!({ "hidden": true })).await,
+        app.delete(&step_uri, other.auth()).await,
+        app.get(&format!("/api/checklists/{}/sync", id), other.auth()).await,
+        app.post(&format!("/api/checklists/{}/sync", id), other.auth(), json!({})).await,
+    ];
+    for response in forbidden {
+        response.error(StatusCode::FORBIDDEN, "FORBIDDEN");
+    }
+
+    assert_eq!(app.get("/api/checklists", other.auth()).await.ok(), json!([]));
+    // Nothing was changed by the rejected requests
+    assert_eq!(app.get(&format!("/api/checklists/{}", id), owner.auth()).await.ok(), forked);
+}
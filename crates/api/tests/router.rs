@@ -0,0 +1,111 @@
+//! 路由层面的通用行为
+//!
+//! 健康检查和文档路由、所有需要认证的路由在没有token或token无效时返回统一的401响应，
+//! 以及请求体、路径参数、查询参数格式错误时的统一失败响应。
+
+mod support;
+
+use axum::body::Body;
+use axum::http::{header, Method, Request, StatusCode};
+use serde_json::json;
+use support::TestApp;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn health_and_docs_are_public() {
+    let Some(app) = TestApp::spawn().await else { return };
+
+    let health = app.get("/health", None).await;
+    assert_eq!(health.status, StatusCode::OK);
+    assert_eq!(health.json()["status"], "ok");
+
+    let docs = app.get("/docs", None).await;
+    assert_eq!(docs.status, StatusCode::OK);
+    assert!(docs.headers[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/html"));
+
+    let openapi = app.get("/api-docs/openapi.json", None).await.json();
+    let paths = openapi["paths"].as_object().unwrap();
+    for path in ["/api/auth/register", "/api/templates/{id}", "/api/checklists/{id}/sync", "/api/admin/users/{id}/role"] {
+        assert!(paths.contains_key(path), "{} is missing from the OpenAPI document", path);
+    }
+
+    assert_eq!(app.get("/api/nothing-here", None).await.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn protected_routes_require_a_valid_token() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let id = Uuid::new_v4();
+    let routes = [
+        (Method::POST, "/api/auth/logout-all".to_string()),
+        (Method::GET, "/api/users/me".to_string()),
+        (Method::PUT, "/api/users/me".to_string()),
+        (Method::DELETE, "/api/users/me".to_string()),
+        (Method::POST, "/api/users/me/avatar".to_string()),
+        (Method::GET, "/api/users/me/export".to_string()),
+        (Method::PUT, "/api/users/me/password".to_string()),
+        (Method::GET, "/api/users/me/identities".to_string()),
+        (Method::POST, "/api/users/me/identities/github/authorize".to_string()),
+        (Method::POST, "/api/users/me/identities/github/callback".to_string()),
+        (Method::DELETE, "/api/users/me/identities/github".to_string()),
+        (Method::POST, "/api/templates".to_string()),
+        (Method::PUT, format!("/api/templates/{}", id)),
+        (Method::PATCH, format!("/api/templates/{}", id)),
+        (Method::DELETE, format!("/api/templates/{}", id)),
+        (Method::GET, "/api/checklists".to_string()),
+        (Method::POST, "/api/checklists".to_string()),
+        (Method::GET, format!("/api/checklists/{}", id)),
+        (Method::PUT, format!("/api/checklists/{}/steps", id)),
+        (Method::POST, format!("/api/checklists/{}/steps", id)),
+        (Method::PUT, format!("/api/checklists/{}/steps/order", id)),
+        (Method::PATCH, format!("/api/checklists/{}/steps/{}", id, id)),
+        (Method::DELETE, format!("/api/checklists/{}/steps/{}", id, id)),
+        (Method::GET, format!("/api/checklists/{}/sync", id)),
+        (Method::POST, format!("/api/checklists/{}/sync", id)),
+        (Method::PUT, format!("/api/admin/templates/{}/official", id)),
+        (Method::PUT, format!("/api/admin/users/{}/role", id)),
+    ];
+
+    for (method, uri) in routes {
+        for token in [None, Some("not-a-jwt")] {
+            let response = app.call(method.clone(), &uri, token, Some(json!({}))).await;
+            assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{} {} with {:?}", method, uri, token);
+            response.error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+        }
+    }
+
+    // Optional authentication still rejects a bad token
+    app.get("/api/templates", Some("not-a-jwt")).await.error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+}
+
+#[tokio::test]
+async fn malformed_requests_get_the_error_envelope() {
+    let Some(app) = TestApp::spawn().await else { return };
+
+    let not_json = Request::builder()
+        .method(Method::POST)
+        .uri("/api/auth/login")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from("{ not json"))
+        .unwrap();
+    app.send(not_json).await.error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+
+    let wrong_type = app.post("/api/auth/register", None, json!({ "email": 42 })).await;
+    assert!(wrong_type.status.is_client_error());
+    assert_eq!(wrong_type.json()["success"], false);
+
+    app.get("/api/templates/not-a-uuid", None).await.error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+    app.get("/api/templates?page=first", None).await.error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+
+    let invalid = app
+        .post("/api/auth/register", None, json!({ "email": "not-an-email", "password": "123", "nickname": "x" }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "VALIDATION_FAILED");
+    let fields: Vec<&str> = invalid["error"]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["field"].as_str().unwrap())
+        .collect();
+    assert!(fields.contains(&"email") && fields.contains(&"password"), "{:?}", fields);
+}
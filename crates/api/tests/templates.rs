@@ -0,0 +1,331 @@
+//! 模板、地区和管理路由
+//!
+//! 模板的创建（角色检查）、列表、搜索、详情、修改（版本快照）、继承解析和软删除（删除后仍可按ID读取），
+//! 地区列表，以及管理员设置官方模板和授予角色。
+
+mod support;
+
+use axum::http::StatusCode;
+use models::UserRole;
+use serde_json::{json, Value};
+use support::{TestApp, TestUser};
+use uuid::Uuid;
+
+fn renting_template() -> Value {
+    json!({
+        "title": "第一次在广州租房",
+        "description": "从看房到签合同",
+        "location_tag": "CN-GZ",
+        "steps": [
+            { "title": "确定预算", "order": 0 },
+            { "title": "看房", "order": 1 },
+            { "title": "签合同", "order": 2 }
+        ]
+    })
+}
+
+async fn create(app: &TestApp, author: &TestUser, body: Value) -> Value {
+    app.post("/api/templates", author.auth(), body).await.ok()
+}
+
+#[tokio::test]
+async fn creating_templates_requires_the_contributor_role() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+    let contributor = app.register_with_role("hong", UserRole::Contributor).await;
+
+    app.post("/api/templates", user.auth(), renting_template())
+        .await
+        .error(StatusCode::FORBIDDEN, "FORBIDDEN");
+
+    let template = create(&app, &contributor, renting_template()).await;
+    assert_eq!(template["created_by"], contributor.id.to_string());
+    assert_eq!(template["version"], 1);
+    assert_eq!(template["is_official"], false);
+    assert_eq!(template["steps"].as_array().unwrap().len(), 3);
+
+    let invalid = app
+        .post("/api/templates", contributor.auth(), json!({ "title": "", "description": "d", "location_tag": "CN", "steps": [] }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "VALIDATION_FAILED");
+    assert!(!invalid["error"]["fields"].as_array().unwrap().is_empty());
+
+    let mut unknown_location = renting_template();
+    unknown_location["location_tag"] = json!("XX-NOWHERE");
+    app.post("/api/templates", contributor.auth(), unknown_location)
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+}
+
+#[tokio::test]
+async fn templates_can_be_listed_searched_and_read() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let contributor = app.register_with_role("hong", UserRole::Contributor).await;
+    let template = create(&app, &contributor, renting_template()).await;
+    let id = template["id"].as_str().unwrap();
+
+    let listed = app.get("/api/templates?page=1&page_size=10", None).await.ok();
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["id"], id);
+
+    assert_eq!(app.get(&format!("/api/templates/{}", id), None).await.ok(), template);
+    app.get(&format!("/api/templates/{}", Uuid::new_v4()), None)
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+
+    let found = app.get("/api/templates/search?keyword=%E7%A7%9F%E6%88%BF", None).await.ok();
+    assert_eq!(found.as_array().unwrap().len(), 1);
+    assert_eq!(found[0]["id"], id);
+    assert!(found[0]["score"].as_f64().unwrap() > 0.0);
+    assert!(found[0]["highlight"]["title"].as_str().unwrap().contains("<mark>"));
+
+    // Searching a city also matches its province and the whole country
+    let mut national = renting_template();
+    national["title"] = json!("第一次租房");
+    national["location_tag"] = json!("CN");
+    let national = create(&app, &contributor, national).await;
+    let in_guangzhou = app.get("/api/templates/search?location_tag=CN-GZ", None).await.ok();
+    assert_eq!(in_guangzhou.as_array().unwrap().len(), 2);
+    assert_eq!(in_guangzhou[0]["id"], id);
+    assert!(in_guangzhou[0]["highlight"].is_null());
+    let in_beijing = app.get("/api/templates/search?location_tag=CN-BJ", None).await.ok();
+    assert_eq!(in_beijing.as_array().unwrap().len(), 1);
+    assert_eq!(in_beijing[0]["id"], national["id"]);
+
+    app.get("/api/templates/search?location_tag=XX-NOWHERE", None)
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+}
+
+#[tokio::test]
+async fn updates_publish_versions() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let author = app.register_with_role("hong", UserRole::Contributor).await;
+    let other = app.register_with_role("gang", UserRole::Contributor).await;
+    let template = create(&app, &author, renting_template()).await;
+    let uri = format!("/api/templates/{}", template["id"].as_str().unwrap());
+
+    let updated = app
+        .patch(&uri, author.auth(), json!({ "title": "第一次在广州租房（2025版）" }))
+        .await
+        .ok();
+    assert_eq!(updated["version"], 2);
+    assert_eq!(updated["description"], template["description"]);
+
+    let replaced = app
+        .put(&uri, author.auth(), json!({ "steps": [{ "title": "找中介", "order": 0 }] }))
+        .await
+        .ok();
+    assert_eq!(replaced["version"], 3);
+    assert_eq!(replaced["steps"].as_array().unwrap().len(), 1);
+
+    app.patch(&uri, other.auth(), json!({ "title": "别人的修改" }))
+        .await
+        .error(StatusCode::FORBIDDEN, "FORBIDDEN");
+
+    let versions = app.get(&format!("{}/versions", uri), None).await.ok();
+    let numbers: Vec<i64> = versions.as_array().unwrap().iter().map(|v| v["version"].as_i64().unwrap()).collect();
+    assert_eq!(numbers.len(), 3);
+    assert!(numbers.contains(&1) && numbers.contains(&3));
+
+    let first = app.get(&format!("{}/versions/1", uri), None).await.ok();
+    assert_eq!(first["title"], "第一次在广州租房");
+    assert_eq!(first["steps"].as_array().unwrap().len(), 3);
+    app.get(&format!("{}/versions/9", uri), None)
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn child_templates_resolve_inherited_steps() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let author = app.register_with_role("hong", UserRole::Contributor).await;
+    let parent = create(&app, &author, renting_template()).await;
+    let child = create(
+        &app,
+        &author,
+        json!({
+            "title": "第一次在广州天河租房",
+            "description": "天河区的补充步骤",
+            "location_tag": "CN-GZ",
+            "steps": [{ "title": "办理居住证", "order": 3 }],
+            "parent_id": parent["id"]
+        }),
+    )
+    .await;
+
+    let resolved = app
+        .get(&format!("/api/templates/{}/resolved", child["id"].as_str().unwrap()), None)
+        .await
+        .ok();
+    assert_eq!(resolved["template"]["id"], child["id"]);
+    assert_eq!(resolved["ancestors"], json!([parent["id"]]));
+    let titles: Vec<&str> = resolved["steps"].as_array().unwrap().iter().map(|s| s["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, vec!["确定预算", "看房", "签合同", "办理居住证"]);
+
+    // Moving to another parent publishes a version; the snapshots keep each parent
+    let other_parent = create(&app, &author, renting_template()).await;
+    let child_uri = format!("/api/templates/{}", child["id"].as_str().unwrap());
+    let moved = app.patch(&child_uri, author.auth(), json!({ "parent_id": other_parent["id"] })).await.ok();
+    assert_eq!(moved["version"], 2);
+    let first = app.get(&format!("{}/versions/1", child_uri), None).await.ok();
+    let second = app.get(&format!("{}/versions/2", child_uri), None).await.ok();
+    assert_eq!(first["parent_id"], parent["id"]);
+    assert_eq!(second["parent_id"], other_parent["id"]);
+
+    let mut missing_parent = renting_template();
+    missing_parent["parent_id"] = json!(Uuid::new_v4());
+    app.post("/api/templates", author.auth(), missing_parent)
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn parent_edits_cannot_break_child_templates() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let author = app.register_with_role("hong", UserRole::Contributor).await;
+    let parent = create(&app, &author, renting_template()).await;
+    let parent_uri = format!("/api/templates/{}", parent["id"].as_str().unwrap());
+    let viewing = parent["steps"][1]["id"].clone();
+    let child = create(
+        &app,
+        &author,
+        json!({
+            "title": "第一次在广州天河租房",
+            "description": "天河区的补充步骤",
+            "location_tag": "CN-GZ",
+            "steps": [{ "title": "看房（天河）", "order": 1, "overrides": viewing }],
+            "parent_id": parent["id"]
+        }),
+    )
+    .await;
+    let child_uri = format!("/api/templates/{}/resolved", child["id"].as_str().unwrap());
+
+    // Dropping the step the child overrides is refused, and nothing changes
+    app.put(
+        &parent_uri,
+        author.auth(),
+        json!({ "steps": [{ "title": "确定预算", "order": 0 }, { "title": "签合同", "order": 1 }] }),
+    )
+    .await
+    .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+    assert_eq!(app.get(&parent_uri, None).await.ok()["version"], 1);
+
+    // Editing it under the same id keeps the child working
+    let mut steps = parent["steps"].clone();
+    steps[1]["title"] = json!("实地看房");
+    app.put(&parent_uri, author.auth(), json!({ "steps": steps })).await.ok();
+
+    let resolved = app.get(&child_uri, None).await.ok();
+    let titles: Vec<&str> = resolved["steps"].as_array().unwrap().iter().map(|s| s["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, vec!["确定预算", "看房（天河）", "签合同"]);
+}
+
+#[tokio::test]
+async fn only_the_author_can_delete_a_template() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let author = app.register_with_role("hong", UserRole::Contributor).await;
+    let other = app.register_with_role("gang", UserRole::Contributor).await;
+    let template = create(&app, &author, renting_template()).await;
+    let uri = format!("/api/templates/{}", template["id"].as_str().unwrap());
+
+    app.delete(&uri, other.auth()).await.error(StatusCode::FORBIDDEN, "FORBIDDEN");
+    app.delete(&uri, author.auth()).await.empty(StatusCode::NO_CONTENT);
+
+    // Forked checklists still point at it, so it stays readable by id
+    let deleted = app.get(&uri, None).await.ok();
+    assert!(deleted["deleted_at"].is_string());
+    assert_eq!(app.get("/api/templates", None).await.ok(), json!([]));
+    app.patch(&uri, author.auth(), json!({ "title": "改回来" }))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+    app.delete(&uri, author.auth()).await.error(StatusCode::NOT_FOUND, "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn locations_are_seeded_by_the_migrations() {
+    let Some(app) = TestApp::spawn().await else { return };
+
+    let locations = app.get("/api/locations", None).await.ok();
+    let guangzhou = locations
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|l| l["code"] == "CN-GZ")
+        .expect("CN-GZ is missing");
+    assert_eq!(guangzhou["parent_code"], "CN-GD");
+}
+
+#[tokio::test]
+async fn admins_mark_official_templates_and_grant_roles() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let admin = app.register_with_role("admin", UserRole::Admin).await;
+    let author = app.register_with_role("hong", UserRole::Contributor).await;
+    let user = app.register("ming").await;
+    let template = create(&app, &author, renting_template()).await;
+    let official_uri = format!("/api/admin/templates/{}/official", template["id"].as_str().unwrap());
+    let role_uri = format!("/api/admin/users/{}/role", user.id);
+
+    app.put(&official_uri, author.auth(), json!({ "is_official": true }))
+        .await
+        .error(StatusCode::FORBIDDEN, "FORBIDDEN");
+    let official = app.put(&official_uri, admin.auth(), json!({ "is_official": true })).await.ok();
+    assert_eq!(official["is_official"], true);
+
+    app.put(&role_uri, author.auth(), json!({ "role": "contributor" }))
+        .await
+        .error(StatusCode::FORBIDDEN, "FORBIDDEN");
+    app.put(&role_uri, admin.auth(), json!({ "role": "city_curator" }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+    app.put(&format!("/api/admin/users/{}/role", Uuid::new_v4()), admin.auth(), json!({ "role": "contributor" }))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+
+    // A city curator may edit other people's templates in their city
+    let profile = app
+        .put(&role_uri, admin.auth(), json!({ "role": "city_curator", "role_scope": "CN-GZ" }))
+        .await
+        .ok();
+    assert_eq!(profile["role"], "city_curator");
+    assert_eq!(profile["role_scope"], "CN-GZ");
+
+    let curator = app.refresh(&user).await;
+    let uri = format!("/api/templates/{}", template["id"].as_str().unwrap());
+    let edited = app.patch(&uri, curator.auth(), json!({ "description": "主编补充了说明" })).await.ok();
+    assert_eq!(edited["description"], "主编补充了说明");
+
+    // ...but can't move it out of their city
+    for location_tag in ["CN", "CN-BJ"] {
+        app.patch(&uri, curator.auth(), json!({ "location_tag": location_tag }))
+            .await
+            .error(StatusCode::FORBIDDEN, "FORBIDDEN");
+    }
+    assert_eq!(app.get(&uri, None).await.ok()["location_tag"], "CN-GZ");
+}
+
+#[tokio::test]
+async fn official_templates_come_first_without_location_preferences() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let admin = app.register_with_role("admin", UserRole::Admin).await;
+    let author = app.register_with_role("hong", UserRole::Contributor).await;
+
+    // The official template is the older one, so only the official flag can put it first
+    let official = create(&app, &author, renting_template()).await;
+    let newer = create(&app, &author, renting_template()).await;
+    app.put(
+        &format!("/api/admin/templates/{}/official", official["id"].as_str().unwrap()),
+        admin.auth(),
+        json!({ "is_official": true }),
+    )
+    .await
+    .ok();
+
+    let listed = app.get("/api/templates", None).await.ok();
+    assert_eq!(listed[0]["id"], official["id"]);
+    assert_eq!(listed[1]["id"], newer["id"]);
+
+    let found = app.get("/api/templates/search?keyword=%E7%A7%9F%E6%88%BF", None).await.ok();
+    assert_eq!(found[0]["id"], official["id"]);
+    assert_eq!(found[1]["id"], newer["id"]);
+}
@@ -0,0 +1,282 @@
+//! 用户路由
+//!
+//! 个人资料的读取和修改、头像上传（以及通过`/uploads`读取上传的文件）、个人数据导出、
+//! 修改密码、注销账号，以及未配置提供方时的第三方账号绑定。
+
+mod support;
+
+use std::io::Cursor;
+
+use axum::http::{header, Method, StatusCode};
+use image::{ImageFormat, Rgb, RgbImage};
+use models::{LoginScope, OrphanedFileEntity};
+use sea_orm::{ConnectionTrait, EntityTrait};
+use serde_json::json;
+use support::{TestApp, PASSWORD};
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let image = RgbImage::from_pixel(width, height, Rgb([200, 80, 40]));
+    let mut bytes = Cursor::new(Vec::new());
+    image.write_to(&mut bytes, ImageFormat::Png).unwrap();
+    bytes.into_inner()
+}
+
+#[tokio::test]
+async fn profile_can_be_read_and_updated() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    let me = app.get("/api/users/me", user.auth()).await.ok();
+    assert_eq!(me["id"], user.id.to_string());
+    assert_eq!(me["nickname"], "ming");
+    assert!(me.get("password_hash").is_none() && me.get("email").is_none());
+
+    let updated = app
+        .put("/api/users/me", user.auth(), json!({ "nickname": "小明", "home_city": "CN-GZ" }))
+        .await
+        .ok();
+    assert_eq!(updated["nickname"], "小明");
+    assert_eq!(updated["home_city"], "CN-GZ");
+    assert_eq!(app.get("/api/users/me", user.auth()).await.ok(), updated);
+
+    // Only city codes can be a home city
+    app.put("/api/users/me", user.auth(), json!({ "home_city": "CN-GD" }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+    app.put("/api/users/me", user.auth(), json!({ "nickname": "" }))
+        .await
+        .error(StatusCode::BAD_REQUEST, "VALIDATION_FAILED");
+}
+
+#[tokio::test]
+async fn avatars_are_processed_stored_and_served() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    let data = app.upload("/api/users/me/avatar", user.auth(), "image/png", &png(600, 400)).await.ok();
+    let avatar_url = data["avatar_url"].as_str().unwrap();
+    assert!(!data["variants"].as_array().unwrap().is_empty());
+    assert_eq!(app.get("/api/users/me", user.auth()).await.ok()["avatar_url"], avatar_url);
+
+    let path = avatar_url.strip_prefix("http://localhost:8080").unwrap();
+    let served = app.get(path, None).await;
+    assert_eq!(served.status, StatusCode::OK);
+    assert!(image::load_from_memory(&served.body).is_ok());
+
+    app.upload("/api/users/me/avatar", user.auth(), "image/png", b"not an image")
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+    app.upload("/api/users/me/avatar", user.auth(), "text/plain", &png(10, 10))
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+}
+
+#[tokio::test]
+async fn personal_data_can_be_exported() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    let export = app.get("/api/users/me/export", user.auth()).await;
+    assert_eq!(export.status, StatusCode::OK);
+    let export = export.json();
+    assert_eq!(export["profile"]["email"], user.email);
+    assert_eq!(export["profile"]["has_password"], true);
+    assert_eq!(export["checklists"], json!([]));
+
+    let archive = app.get("/api/users/me/export?format=zip", user.auth()).await;
+    assert_eq!(archive.status, StatusCode::OK);
+    assert_eq!(archive.headers[header::CONTENT_TYPE], "application/zip");
+    assert!(archive.body.starts_with(b"PK"));
+
+    app.get("/api/users/me/export?format=pdf", user.auth())
+        .await
+        .error(StatusCode::BAD_REQUEST, "BAD_REQUEST");
+}
+
+#[tokio::test]
+async fn changing_the_password_signs_out_other_sessions() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    app.put(
+        "/api/users/me/password",
+        user.auth(),
+        json!({ "current_password": "wrong-password", "new_password": "a-brand-new-password" }),
+    )
+    .await
+    .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+
+    let data = app
+        .put(
+            "/api/users/me/password",
+            user.auth(),
+            json!({ "current_password": PASSWORD, "new_password": "a-brand-new-password" }),
+        )
+        .await
+        .ok();
+    assert!(data["token"].is_string() && data["refresh_token"].is_string());
+
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": user.refresh_token }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": data["refresh_token"] }))
+        .await
+        .ok();
+    app.post("/api/auth/login", None, json!({ "email": user.email, "password": "a-brand-new-password" }))
+        .await
+        .ok();
+}
+
+#[tokio::test]
+async fn wrong_current_passwords_count_towards_the_login_limit() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+    let change = |current_password: &'static str| {
+        app.put(
+            "/api/users/me/password",
+            user.auth(),
+            json!({ "current_password": current_password, "new_password": "a-brand-new-password" }),
+        )
+    };
+
+    for _ in 0..=LoginScope::Account.free_failures() {
+        change("wrong-password").await.error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    }
+
+    // Both re-checks and sign-in have to wait now, even with the right password
+    change(PASSWORD).await.error(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED");
+    app.call(Method::DELETE, "/api/users/me", user.auth(), Some(json!({ "password": PASSWORD })))
+        .await
+        .error(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED");
+    app.post("/api/auth/login", None, json!({ "email": user.email, "password": PASSWORD }))
+        .await
+        .error(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED");
+}
+
+#[tokio::test]
+async fn wrong_passwords_when_deleting_the_account_are_limited() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    for _ in 0..=LoginScope::Account.free_failures() {
+        app.call(Method::DELETE, "/api/users/me", user.auth(), Some(json!({ "password": "wrong-password" })))
+            .await
+            .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+    }
+    app.call(Method::DELETE, "/api/users/me", user.auth(), Some(json!({ "password": PASSWORD })))
+        .await
+        .error(StatusCode::TOO_MANY_REQUESTS, "RATE_LIMITED");
+}
+
+#[tokio::test]
+async fn account_deletion_is_cancelled_by_signing_in() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    app.call(Method::DELETE, "/api/users/me", user.auth(), Some(json!({ "password": "wrong-password" })))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+
+    let data = app
+        .call(Method::DELETE, "/api/users/me", user.auth(), Some(json!({ "password": PASSWORD })))
+        .await
+        .ok();
+    assert!(data["deletion_requested_at"].is_string() && data["purge_after"].is_string());
+
+    // Requesting deletion signs out every device
+    app.post("/api/auth/refresh", None, json!({ "refresh_token": user.refresh_token }))
+        .await
+        .error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED");
+
+    let login = app
+        .post("/api/auth/login", None, json!({ "email": user.email, "password": PASSWORD }))
+        .await
+        .ok();
+    let export = app.get("/api/users/me/export", login["token"].as_str()).await.json();
+    assert!(export["profile"]["deletion_requested_at"].is_null());
+}
+
+#[tokio::test]
+async fn purging_an_account_deletes_its_avatar_files() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+    let other = app.register("hong").await;
+
+    let avatar_url = app.upload("/api/users/me/avatar", user.auth(), "image/png", &png(64, 64)).await.ok()["avatar_url"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    app.upload("/api/users/me/avatar", other.auth(), "image/png", &png(64, 64)).await.ok();
+    let avatar_path = avatar_url.strip_prefix("http://localhost:8080").unwrap().to_string();
+    assert_eq!(app.get(&avatar_path, None).await.status, StatusCode::OK);
+
+    app.call(Method::DELETE, "/api/users/me", user.auth(), Some(json!({ "password": PASSWORD })))
+        .await
+        .ok();
+    // Pretend the grace period is over
+    app.db
+        .execute_unprepared(&format!(
+            "UPDATE users SET deletion_requested_at = now() - interval '31 days' WHERE id = '{}'",
+            user.id
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(app.state.module.account_service.purge_due_accounts().await.unwrap(), 1);
+    assert_eq!(app.get(&avatar_path, None).await.status, StatusCode::NOT_FOUND);
+    assert!(!app.dir.join("uploads/avatars").join(user.id.to_string()).exists());
+    assert!(app.dir.join("uploads/avatars").join(other.id.to_string()).exists());
+}
+
+#[tokio::test]
+async fn avatar_files_that_fail_to_delete_are_retried_on_the_next_purge() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    app.call(Method::DELETE, "/api/users/me", user.auth(), Some(json!({ "password": PASSWORD })))
+        .await
+        .ok();
+    app.db
+        .execute_unprepared(&format!(
+            "UPDATE users SET deletion_requested_at = now() - interval '31 days' WHERE id = '{}'",
+            user.id
+        ))
+        .await
+        .unwrap();
+
+    // A plain file where the avatar directory should be cannot be removed as a directory
+    let avatars = app.dir.join("uploads/avatars");
+    std::fs::create_dir_all(&avatars).unwrap();
+    std::fs::write(avatars.join(user.id.to_string()), b"stuck").unwrap();
+
+    // The account is gone all the same; the files are left for the next run
+    assert_eq!(app.state.module.account_service.purge_due_accounts().await.unwrap(), 1);
+    let orphaned = OrphanedFileEntity::find().all(&app.db).await.unwrap();
+    assert_eq!(orphaned.len(), 1);
+    assert_eq!(orphaned[0].prefix, format!("avatars/{}", user.id));
+
+    std::fs::remove_file(avatars.join(user.id.to_string())).unwrap();
+    std::fs::create_dir_all(avatars.join(user.id.to_string()).join("old")).unwrap();
+
+    assert_eq!(app.state.module.account_service.purge_due_accounts().await.unwrap(), 0);
+    assert!(!avatars.join(user.id.to_string()).exists());
+    assert!(OrphanedFileEntity::find().all(&app.db).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn identities_without_configured_providers() {
+    let Some(app) = TestApp::spawn().await else { return };
+    let user = app.register("ming").await;
+
+    assert_eq!(app.get("/api/users/me/identities", user.auth()).await.ok(), json!([]));
+
+    app.post("/api/users/me/identities/github/authorize", user.auth(), json!({}))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+    app.post("/api/users/me/identities/github/callback", user.auth(), json!({ "code": "code", "state": "state" }))
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+    app.delete("/api/users/me/identities/github", user.auth())
+        .await
+        .error(StatusCode::NOT_FOUND, "NOT_FOUND");
+}
@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use common::{AppError, AppResult};
+use std::time::Duration;
+use url::Url;
+
+/// 后端HTTP请求
+/// 
+/// OAuth授权码换取令牌、读取OIDC发现文档和JWKS都通过它发出。
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// 请求方法（`GET`、`POST`）
+    pub method: &'static str,
+    
+    /// 完整URL（`http://`或`https://`）
+    pub url: String,
+    
+    /// 额外的请求头（`Host`、`Content-Length`和`User-Agent`自动设置）
+    pub headers: Vec<(String, String)>,
+    
+    /// 请求体
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// GET请求
+    pub fn get(url: &str) -> Self {
+        Self {
+            method: "GET",
+            url: url.to_string(),
+            headers: vec![("Accept".to_string(), "application/json".to_string())],
+            body: Vec::new(),
+        }
+    }
+
+    /// 表单POST请求（`application/x-www-form-urlencoded`）
+    pub fn post_form(url: &str, form: &[(&str, &str)]) -> Self {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(form)
+            .finish();
+
+        Self {
+            method: "POST",
+            url: url.to_string(),
+            headers: vec![
+                ("Accept".to_string(), "application/json".to_string()),
+                ("Content-Type".to_string(), "application/x-www-form-urlencoded".to_string()),
+            ],
+            body: body.into_bytes(),
+        }
+    }
+
+    /// 添加请求头
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// 后端HTTP响应
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// 状态码
+    pub status: u16,
+    
+    /// 响应体
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// 状态码是否为2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// 后端HTTP客户端接口
+/// 
+/// 默认实现是`DefaultHttpClient`；需要代理、连接池或测试替身时实现该trait。
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    /// 发送请求并读取完整响应
+    /// 
+    /// ## 错误
+    /// 连接失败、超时或响应无法解析时返回`InternalError`（非2xx状态码不算错误）
+    async fn send(&self, request: HttpRequest) -> AppResult<HttpResponse>;
+}
+
+/// 建立连接的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 整个请求（连接、发送和读取响应）的超时
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 响应体大小上限（发现文档、JWKS和令牌响应都很小）
+const MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// 默认的HTTP客户端（reqwest）
+/// 
+/// `https`使用rustls和内置的webpki根证书，不跟随重定向（令牌端点返回重定向视为失败）。
+/// 内部有连接池，克隆后共享同一个池。
+#[derive(Debug, Clone)]
+pub struct DefaultHttpClient {
+    client: reqwest::Client,
+}
+
+impl DefaultHttpClient {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent("rookie-guide")
+            .build()
+            .expect("the rustls HTTP client builds with static settings");
+
+        Self { client }
+    }
+}
+
+impl Default for DefaultHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl HttpClient for DefaultHttpClient {
+    async fn send(&self, request: HttpRequest) -> AppResult<HttpResponse> {
+        let HttpRequest { method, url, headers, body } = request;
+        let http_error = |e: reqwest::Error| AppError::InternalError(format!("HTTP request to {} failed: {}", url, e));
+
+        let parsed = Url::parse(&url).map_err(|e| AppError::InternalError(format!("Invalid URL {}: {}", url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(AppError::InternalError(format!("Unsupported URL scheme {}", parsed.scheme())));
+        }
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|e| AppError::InternalError(format!("Invalid HTTP method {}: {}", method, e)))?;
+
+        let mut builder = self.client.request(method, parsed);
+        for (name, value) in &headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        let mut response = builder.body(body).send().await.map_err(http_error)?;
+        let status = response.status().as_u16();
+
+        // Read chunk by chunk so that an oversized body is cut off instead of buffered whole
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(http_error)? {
+            body.extend_from_slice(&chunk);
+            if body.len() > MAX_RESPONSE_BYTES {
+                return Err(AppError::InternalError(format!("HTTP response from {} is too large", url)));
+            }
+        }
+
+        Ok(HttpResponse { status, body })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::Redirect;
+    use axum::routing::get;
+    use axum::Router;
+
+    #[tokio::test]
+    async fn does_not_follow_redirects_or_read_huge_bodies() {
+        let app = Router::new()
+            .route("/ok", get(|| async { "{}" }))
+            .route("/redirect", get(|| async { Redirect::temporary("/ok") }))
+            .route("/huge", get(|| async { vec![b'x'; MAX_RESPONSE_BYTES + 1] }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = DefaultHttpClient::new();
+        let ok = client.send(HttpRequest::get(&format!("{}/ok", base))).await.unwrap();
+        assert_eq!((ok.status, ok.body.as_slice()), (200, b"{}".as_slice()));
+
+        let redirect = client.send(HttpRequest::get(&format!("{}/redirect", base))).await.unwrap();
+        assert_eq!(redirect.status, 307);
+
+        assert!(client.send(HttpRequest::get(&format!("{}/huge", base))).await.is_err());
+        assert!(client.send(HttpRequest::get("ftp://127.0.0.1/file")).await.is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use chrono::{Duration, Utc};
-use common::AppResult;
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use common::{AppError, AppResult, JwtConfig, JwtKey};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use std::collections::HashMap;
+use models::{Actor, User, UserRole};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -15,6 +17,31 @@ pub struct Claims {
     pub exp: i64,
     /// Issued At - Token签发时间（Unix时间戳）
     pub iat: i64,
+    /// 用户角色（签发时的角色，角色变更后需要刷新令牌或重新登录）
+    /// 
+    /// 没有该字段的旧token按普通用户处理
+    #[serde(default)]
+    pub role: UserRole,
+    /// 角色的地区范围（只有城市主编有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role_scope: Option<String>,
+}
+
+impl Claims {
+    /// 转换为服务层使用的`Actor`
+    /// 
+    /// ## 错误
+    /// `sub`不是合法的UUID
+    pub fn actor(&self) -> AppResult<Actor> {
+        let user_id = Uuid::parse_str(&self.sub)
+            .map_err(|_| AppError::AuthError("Invalid user id in token".to_string()))?;
+
+        Ok(Actor {
+            user_id,
+            role: self.role,
+            role_scope: self.role_scope.clone(),
+        })
+    }
 }
 
 /// JWT服务接口
@@ -24,11 +51,11 @@ pub trait JwtService: Send + Sync {
     /// 为用户生成JWT token
     /// 
     /// ## 参数
-    /// - `user_id`: 用户UUID
+    /// - `user`: 用户（用到ID、角色和角色的地区范围）
     /// 
     /// ## 返回值
     /// 签名后的JWT token字符串
-    fn generate_token(&self, user_id: Uuid) -> AppResult<String>;
+    fn generate_token(&self, user: &User) -> AppResult<String>;
     
     /// 验证并解析JWT token
     /// 
@@ -43,58 +70,296 @@ pub trait JwtService: Send + Sync {
     /// - Token已过期
     /// - 签名验证失败
     fn validate_token(&self, token: &str) -> AppResult<Claims>;
+    
+    /// 访问令牌的有效期（秒）
+    fn expiration(&self) -> i64;
+}
+
+/// 验证token用的密钥
+#[derive(Clone)]
+struct VerificationKey {
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+impl VerificationKey {
+    fn from_config(key: &JwtKey) -> AppResult<Self> {
+        let invalid = |e: jsonwebtoken::errors::Error| AppError::InternalError(format!("Invalid JWT public key: {}", e));
+
+        Ok(match key {
+            JwtKey::Hs256 { secret } => Self {
+                algorithm: Algorithm::HS256,
+                key: DecodingKey::from_secret(secret.as_bytes()),
+            },
+            JwtKey::Rs256 { public_key_pem, .. } => Self {
+                algorithm: Algorithm::RS256,
+                key: DecodingKey::from_rsa_pem(public_key_pem.as_bytes()).map_err(invalid)?,
+            },
+            JwtKey::EdDsa { public_key_pem, .. } => Self {
+                algorithm: Algorithm::EdDSA,
+                key: DecodingKey::from_ed_pem(public_key_pem.as_bytes()).map_err(invalid)?,
+            },
+        })
+    }
 }
 
 /// JWT服务的实现
+/// 
+/// 用当前的签名密钥签发token，并把密钥ID写入token头的`kid`；
+/// 验证时按`kid`选择密钥，轮换密钥后用旧密钥签发、尚未过期的token仍然有效。
+/// 不带`kid`的token只用签名密钥验证。
 #[derive(Clone)]
 pub struct JwtServiceImpl {
-    /// JWT签名密钥
-    secret: String,
+    /// 签名密钥的ID
+    kid: Option<String>,
+    /// 签名算法
+    algorithm: Algorithm,
+    /// 签名密钥
+    encoding_key: EncodingKey,
+    /// 签名密钥对应的验证密钥
+    current_key: VerificationKey,
+    /// 按`kid`查找的验证密钥（包括有ID的签名密钥）
+    keys_by_id: HashMap<String, VerificationKey>,
     /// Token有效期（秒）
     expiration: i64,
 }
 
 impl JwtServiceImpl {
-    /// 创建JWT服务实例
+    /// 创建使用HS256共享密钥、没有密钥轮换的JWT服务
     /// 
     /// ## 参数
     /// - `secret`: 签名密钥（生产环境使用强随机密钥）
-    /// - `expiration`: Token有效期（秒，如86400=24小时）
+    /// - `expiration`: Token有效期（秒，如900=15分钟）
     pub fn new(secret: String, expiration: i64) -> Self {
-        Self { secret, expiration }
+        Self {
+            kid: None,
+            algorithm: Algorithm::HS256,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            current_key: VerificationKey {
+                algorithm: Algorithm::HS256,
+                key: DecodingKey::from_secret(secret.as_bytes()),
+            },
+            keys_by_id: HashMap::new(),
+            expiration,
+        }
+    }
+
+    /// 按配置创建JWT服务（签名密钥、轮换下来的旧密钥和有效期）
+    /// 
+    /// ## 错误
+    /// - 密钥不是合法的PEM，或私钥和公钥不是一对
+    /// - 非对称签名密钥没有私钥
+    /// - 旧密钥没有ID，或密钥ID重复
+    pub fn from_config(config: &JwtConfig) -> AppResult<Self> {
+        let invalid = |e: jsonwebtoken::errors::Error| AppError::InternalError(format!("Invalid JWT private key: {}", e));
+
+        let encoding_key = match &config.signing_key.key {
+            JwtKey::Hs256 { secret } => EncodingKey::from_secret(secret.as_bytes()),
+            JwtKey::Rs256 { private_key_pem: Some(pem), .. } => EncodingKey::from_rsa_pem(pem.as_bytes()).map_err(invalid)?,
+            JwtKey::EdDsa { private_key_pem: Some(pem), .. } => EncodingKey::from_ed_pem(pem.as_bytes()).map_err(invalid)?,
+            _ => return Err(AppError::InternalError("The JWT signing key has no private key".to_string())),
+        };
+        let current_key = VerificationKey::from_config(&config.signing_key.key)?;
+
+        // A mismatched key pair would otherwise only show up as every new token being rejected
+        let signature = jsonwebtoken::crypto::sign(b"key check", &encoding_key, current_key.algorithm).map_err(invalid)?;
+        if !jsonwebtoken::crypto::verify(&signature, b"key check", &current_key.key, current_key.algorithm).unwrap_or(false) {
+            return Err(AppError::InternalError("The JWT private and public keys do not match".to_string()));
+        }
+
+        let mut keys_by_id = HashMap::new();
+        if let Some(kid) = &config.signing_key.kid {
+            keys_by_id.insert(kid.clone(), current_key.clone());
+        }
+        for key in &config.verification_keys {
+            let kid = key.kid.clone()
+                .ok_or_else(|| AppError::InternalError("JWT verification keys need a key id".to_string()))?;
+            if keys_by_id.insert(kid.clone(), VerificationKey::from_config(&key.key)?).is_some() {
+                return Err(AppError::InternalError(format!("Duplicate JWT key id {}", kid)));
+            }
+        }
+
+        Ok(Self {
+            kid: config.signing_key.kid.clone(),
+            algorithm: current_key.algorithm,
+            encoding_key,
+            current_key,
+            keys_by_id,
+            expiration: config.expiration,
+        })
     }
 }
 
 impl JwtService for JwtServiceImpl {
-    fn generate_token(&self, user_id: Uuid) -> AppResult<String> {
+    fn generate_token(&self, user: &User) -> AppResult<String> {
         let now = Utc::now();
         let exp = (now + Duration::seconds(self.expiration)).timestamp();
 
         let claims = Claims {
-            sub: user_id.to_string(),
+            sub: user.id.to_string(),
             exp,
             iat: now.timestamp(),
+            role: user.role,
+            role_scope: user.role_scope.clone(),
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(self.secret.as_bytes()),
-        )
-        .map_err(|e| common::AppError::AuthError(format!("Failed to generate token: {}", e)))?;
+        let header = Header {
+            kid: self.kid.clone(),
+            ..Header::new(self.algorithm)
+        };
+
+        let token = encode(&header, &claims, &self.encoding_key)
+            .map_err(|e| AppError::AuthError(format!("Failed to generate token: {}", e)))?;
 
         Ok(token)
     }
 
     fn validate_token(&self, token: &str) -> AppResult<Claims> {
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.secret.as_bytes()),
-            &Validation::default(),
-        )
-        .map_err(|e| common::AppError::AuthError(format!("Invalid token: {}", e)))?;
+        let invalid = |e: jsonwebtoken::errors::Error| AppError::AuthError(format!("Invalid token: {}", e));
+
+        let header = decode_header(token).map_err(invalid)?;
+        let key = match &header.kid {
+            Some(kid) => self.keys_by_id
+                .get(kid)
+                .ok_or_else(|| AppError::AuthError(format!("Invalid token: unknown key id {}", kid)))?,
+            None => &self.current_key,
+        };
+
+        // Only the key's own algorithm is accepted, so an RS256 public key can never be used as an HS256 secret
+        let token_data = decode::<Claims>(token, &key.key, &Validation::new(key.algorithm)).map_err(invalid)?;
 
         Ok(token_data.claims)
     }
+
+    fn expiration(&self) -> i64 {
+        self.expiration
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::JwtKeyConfig;
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            phone: None,
+            email: Some("a@example.com".to_string()),
+            password_hash: String::new(),
+            nickname: "a".to_string(),
+            avatar_url: None,
+            home_city: None,
+            role: UserRole::CityCurator,
+            role_scope: Some("CN-GD".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deletion_requested_at: None,
+        }
+    }
+
+    fn hs256(kid: Option<&str>, secret: &str) -> JwtKeyConfig {
+        JwtKeyConfig {
+            kid: kid.map(str::to_string),
+            key: JwtKey::Hs256 { secret: secret.to_string() },
+        }
+    }
+
+    fn config(signing_key: JwtKeyConfig, verification_keys: Vec<JwtKeyConfig>) -> JwtConfig {
+        JwtConfig {
+            signing_key,
+            verification_keys,
+            expiration: 900,
+            refresh_expiration: 3600,
+        }
+    }
+
+    #[test]
+    fn accepts_tokens_from_rotated_keys_by_kid() {
+        let user = user();
+        let old = JwtServiceImpl::from_config(&config(hs256(Some("2025-06"), "old secret"), vec![])).unwrap();
+        let old_token = old.generate_token(&user).unwrap();
+        assert_eq!(decode_header(&old_token).unwrap().kid.as_deref(), Some("2025-06"));
+
+        let rotated = JwtServiceImpl::from_config(&config(
+            hs256(Some("2025-09"), "new secret"),
+            vec![hs256(Some("2025-06"), "old secret")],
+        ))
+        .unwrap();
+        let claims = rotated.validate_token(&old_token).unwrap();
+        assert_eq!(claims.actor().unwrap().user_id, user.id);
+        assert_eq!(claims.role_scope.as_deref(), Some("CN-GD"));
+        assert!(rotated.validate_token(&rotated.generate_token(&user).unwrap()).is_ok());
+
+        // Once the old key is retired its tokens are rejected
+        let retired = JwtServiceImpl::from_config(&config(hs256(Some("2025-09"), "new secret"), vec![])).unwrap();
+        assert!(retired.validate_token(&old_token).is_err());
+
+        // Tokens without a kid are checked against the signing key only
+        let legacy_token = JwtServiceImpl::new("new secret".to_string(), 900).generate_token(&user).unwrap();
+        assert!(retired.validate_token(&legacy_token).is_ok());
+        let legacy_token = JwtServiceImpl::new("old secret".to_string(), 900).generate_token(&user).unwrap();
+        assert!(rotated.validate_token(&legacy_token).is_err());
+    }
+
+    #[test]
+    fn signs_with_asymmetric_keys() {
+        let user = user();
+        let rs256 = JwtKey::Rs256 {
+            private_key_pem: Some(include_str!("../tests/fixtures/jwt_rsa.pem").to_string()),
+            public_key_pem: include_str!("../tests/fixtures/jwt_rsa.pub.pem").to_string(),
+        };
+        let ed25519 = JwtKey::EdDsa {
+            private_key_pem: Some(include_str!("../tests/fixtures/jwt_ed25519.pem").to_string()),
+            public_key_pem: include_str!("../tests/fixtures/jwt_ed25519.pub.pem").to_string(),
+        };
+
+        for (key, algorithm) in [(rs256.clone(), Algorithm::RS256), (ed25519.clone(), Algorithm::EdDSA)] {
+            let service = JwtServiceImpl::from_config(&config(JwtKeyConfig { kid: Some("k1".to_string()), key }, vec![])).unwrap();
+            let token = service.generate_token(&user).unwrap();
+            assert_eq!(decode_header(&token).unwrap().alg, algorithm);
+            assert_eq!(service.validate_token(&token).unwrap().sub, user.id.to_string());
+        }
+
+        // A verifier holding only the public key accepts the token
+        let signer = JwtServiceImpl::from_config(&config(JwtKeyConfig { kid: Some("rsa".to_string()), key: rs256.clone() }, vec![])).unwrap();
+        let JwtKey::Rs256 { public_key_pem, .. } = rs256 else { unreachable!() };
+        let verifier = JwtServiceImpl::from_config(&config(
+            JwtKeyConfig { kid: Some("ed".to_string()), key: ed25519.clone() },
+            vec![JwtKeyConfig { kid: Some("rsa".to_string()), key: JwtKey::Rs256 { private_key_pem: None, public_key_pem } }],
+        ))
+        .unwrap();
+        assert!(verifier.validate_token(&signer.generate_token(&user).unwrap()).is_ok());
+
+        // The public key must not be accepted as an HMAC secret
+        let JwtKey::EdDsa { public_key_pem, .. } = ed25519 else { unreachable!() };
+        let forged = encode(
+            &Header { kid: Some("ed".to_string()), ..Header::new(Algorithm::HS256) },
+            &Claims { sub: user.id.to_string(), exp: Utc::now().timestamp() + 60, iat: 0, role: UserRole::Admin, role_scope: None },
+            &EncodingKey::from_secret(public_key_pem.as_bytes()),
+        )
+        .unwrap();
+        assert!(verifier.validate_token(&forged).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_key_configuration() {
+        // Mismatched key pair, missing private key, missing kid on an old key, duplicate kid
+        let mismatched = JwtKey::Rs256 {
+            private_key_pem: Some(include_str!("../tests/fixtures/provider_key.pem").to_string()),
+            public_key_pem: include_str!("../tests/fixtures/jwt_rsa.pub.pem").to_string(),
+        };
+        let public_only = JwtKey::EdDsa {
+            private_key_pem: None,
+            public_key_pem: include_str!("../tests/fixtures/jwt_ed25519.pub.pem").to_string(),
+        };
+        for config in [
+            config(JwtKeyConfig { kid: None, key: mismatched }, vec![]),
+            config(JwtKeyConfig { kid: None, key: public_only }, vec![]),
+            config(hs256(None, "secret"), vec![hs256(None, "old")]),
+            config(hs256(Some("a"), "secret"), vec![hs256(Some("a"), "old")]),
+        ] {
+            assert!(JwtServiceImpl::from_config(&config).is_err());
+        }
+    }
+}
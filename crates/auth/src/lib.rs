@@ -4,30 +4,65 @@
 /// 
 /// ## 模块结构
 /// 
-/// - `jwt`: JWT token的生成和验证
-/// - `password`: 密码的bcrypt加密和验证
+/// - `jwt`: JWT token的生成和验证（HS256/RS256/EdDSA，按`kid`轮换密钥）
+/// - `password`: 密码的加密和验证（Argon2id，兼容验证bcrypt）
+/// - `refresh`: 刷新令牌的生成和哈希
+/// - `otp`: 短信验证码的生成和哈希
+/// - `sms`: 短信发送接口（默认只写日志）
+/// - `pkce`: OAuth2 PKCE校验码、`state`和`nonce`的生成
+/// - `oidc`: 第三方登录提供方（OAuth2授权码 + PKCE，OpenID Connect）
+/// - `http`: 访问第三方提供方的后端HTTP客户端
+/// - `mail`: 邮件发送接口（默认只写日志）
+/// - `smtp`: 通过SMTP服务器发送邮件
 /// 
 /// ## 使用示例
 /// 
 /// ```rust
 /// // 密码加密
-/// let password_service = PasswordServiceImpl::new();
+/// let password_service = PasswordServiceImpl::new(&config.password)?;
 /// let hash = password_service.hash_password("password123")?;
 /// 
-/// // 密码验证
+/// // 密码验证（旧的bcrypt哈希验证成功后用needs_rehash判断是否需要升级）
 /// let is_valid = password_service.verify_password("password123", &hash)?;
+/// let upgrade = is_valid && password_service.needs_rehash(&hash);
 /// 
-/// // 生成JWT token
-/// let jwt_service = JwtServiceImpl::new(secret, expiration);
-/// let token = jwt_service.generate_token(user_id)?;
+/// // 生成JWT token（签名密钥和轮换下来的旧密钥来自配置）
+/// let jwt_service = JwtServiceImpl::from_config(&config.jwt)?;
+/// let token = jwt_service.generate_token(&user)?;
 /// 
-/// // 验证JWT token
+/// // 验证JWT token（按token头的kid选择密钥）
 /// let claims = jwt_service.validate_token(&token)?;
+/// 
+/// // 生成刷新令牌（数据库只保存哈希）
+/// let refresh_token = generate_refresh_token();
+/// let token_hash = hash_refresh_token(&refresh_token);
+/// 
+/// // 第三方登录：生成授权地址，回调时换取并校验ID令牌
+/// let provider = OidcProviderImpl::new(config, Arc::new(DefaultHttpClient::new()));
+/// let verifier = generate_pkce_verifier();
+/// let url = provider.authorization_url(&state, &nonce, &pkce_challenge(&verifier)).await?;
+/// let identity = provider.exchange_code(&code, &verifier, &nonce).await?;
 /// ```
 
 pub mod jwt;
 pub mod password;
+pub mod refresh;
+pub mod otp;
+pub mod sms;
+pub mod pkce;
+pub mod oidc;
+pub mod http;
+pub mod mail;
+pub mod smtp;
 
 pub use jwt::{JwtService, JwtServiceImpl, Claims};
-pub use password::{PasswordService, PasswordServiceImpl};
+pub use password::{PasswordService, PasswordServiceImpl, PasswordAlgorithm, Argon2idAlgorithm, BcryptAlgorithm};
+pub use refresh::{generate_refresh_token, hash_refresh_token};
+pub use otp::{generate_verification_code, hash_verification_code};
+pub use sms::{SmsSender, LogSmsSender, DisabledSmsSender};
+pub use pkce::{generate_pkce_verifier, pkce_challenge, generate_oauth_state};
+pub use oidc::{OidcProvider, OidcProviderImpl, ExternalIdentity};
+pub use http::{HttpClient, DefaultHttpClient, HttpRequest, HttpResponse};
+pub use mail::{Mailer, Email, CaptureMailer};
+pub use smtp::SmtpMailer;
 
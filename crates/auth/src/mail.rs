@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use common::{AppError, AppResult};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// 一封纯文本邮件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Email {
+    /// 收件人邮箱
+    pub to: String,
+    
+    /// 主题
+    pub subject: String,
+    
+    /// 正文（纯文本）
+    pub body: String,
+}
+
+impl Email {
+    /// 生成完整的邮件内容（RFC 5322），换行为`\r\n`
+    /// 
+    /// 主题和发件人名称按RFC 2047编码，正文使用base64编码，支持中文。
+    /// 
+    /// ## 错误
+    /// 收件人、发件人或主题中含有换行（邮件头注入）时返回`ValidationError`
+    pub fn to_message(&self, from: &str) -> AppResult<String> {
+        for value in [from, self.to.as_str(), self.subject.as_str()] {
+            if value.contains(['\r', '\n']) {
+                return Err(AppError::ValidationError("Email header contains a line break".to_string()));
+            }
+        }
+
+        let body = STANDARD.encode(self.body.replace("\r\n", "\n").replace('\n', "\r\n"));
+        let body_lines: Vec<&str> = body
+            .as_bytes()
+            .chunks(76)
+            .map(|line| std::str::from_utf8(line).expect("base64 is ASCII"))
+            .collect();
+
+        Ok(format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@{}>\r\nMIME-Version: 1.0\r\n\
+             Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+            encode_mailbox(from),
+            self.to,
+            encode_word(&self.subject),
+            chrono::Utc::now().to_rfc2822(),
+            uuid::Uuid::new_v4(),
+            domain_of(mailbox_address(from)),
+            body_lines.join("\r\n"),
+        ))
+    }
+}
+
+/// 取出`名称 <地址>`格式中的地址
+pub(crate) fn mailbox_address(mailbox: &str) -> &str {
+    match (mailbox.rfind('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox.trim(),
+    }
+}
+
+fn domain_of(address: &str) -> &str {
+    address.rsplit_once('@').map_or("localhost", |(_, domain)| domain)
+}
+
+/// 发件人名称不是ASCII时按RFC 2047编码
+fn encode_mailbox(mailbox: &str) -> String {
+    match mailbox.rfind('<') {
+        Some(start) if start > 0 => {
+            format!("{} {}", encode_word(mailbox[..start].trim()), &mailbox[start..])
+        }
+        _ => mailbox.to_string(),
+    }
+}
+
+fn encode_word(text: &str) -> String {
+    if text.is_ascii() {
+        text.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", STANDARD.encode(text))
+    }
+}
+
+/// 邮件发送接口
+/// 
+/// 默认实现：配置了SMTP服务器时为`SmtpMailer`，否则为`CaptureMailer`。
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// 发送邮件
+    /// 
+    /// ## 错误
+    /// 邮件服务器连接失败或拒收时返回`InternalError`
+    async fn send(&self, email: Email) -> AppResult<()>;
+}
+
+/// 不真正发送邮件的实现（开发和测试环境）
+/// 
+/// 邮件写到日志（`auth`目标，info级别）并保存在内存中；
+/// 设置了目录时，同时把每封邮件保存为一个`.eml`文件，可以用邮件客户端打开。
+/// 
+/// **注意**：生产环境必须配置SMTP服务器。
+#[derive(Default)]
+pub struct CaptureMailer {
+    /// 保存`.eml`文件的目录和文件中的发件人
+    dir: Option<(PathBuf, String)>,
+    
+    /// 已发送的邮件
+    sent: Mutex<Vec<Email>>,
+}
+
+impl CaptureMailer {
+    /// 只保存在内存中
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 同时把邮件保存到`dir`目录（不存在时自动创建），`from`是文件中的发件人
+    pub fn with_dir(dir: impl Into<PathBuf>, from: &str) -> Self {
+        Self {
+            dir: Some((dir.into(), from.to_string())),
+            sent: Mutex::default(),
+        }
+    }
+
+    /// 所有已发送的邮件（按发送顺序）
+    pub fn sent(&self) -> Vec<Email> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// 最近一封发给该邮箱的邮件
+    pub fn last_to(&self, to: &str) -> Option<Email> {
+        self.sent.lock().unwrap().iter().rev().find(|e| e.to == to).cloned()
+    }
+}
+
+#[async_trait]
+impl Mailer for CaptureMailer {
+    async fn send(&self, email: Email) -> AppResult<()> {
+        tracing::info!(to = %email.to, subject = %email.subject, body = %email.body, "SMTP not configured, email captured instead");
+
+        if let Some((dir, from)) = &self.dir {
+            let message = email.to_message(from)?;
+            let path = dir.join(format!("{}-{}.eml", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"), uuid::Uuid::new_v4()));
+            let write = async {
+                tokio::fs::create_dir_all(dir).await?;
+                tokio::fs::write(&path, message).await
+            };
+            write
+                .await
+                .map_err(|e| AppError::InternalError(format!("Failed to save email to {}: {}", path.display(), e)))?;
+        }
+
+        self.sent.lock().unwrap().push(email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_headers_and_body() {
+        let email = Email {
+            to: "user@example.com".to_string(),
+            subject: "重置密码".to_string(),
+            body: "点击链接\n.\n".to_string(),
+        };
+
+        let message = email.to_message("阅历进度条 <no-reply@example.com>").unwrap();
+
+        assert!(message.contains("From: =?UTF-8?B?6ZiF5Y6G6L+b5bqm5p2h?= <no-reply@example.com>\r\n"));
+        assert!(message.contains("Subject: =?UTF-8?B?6YeN572u5a+G56CB?=\r\n"));
+        assert!(message.contains("@example.com>\r\n"));
+        assert!(message.ends_with(&format!("\r\n\r\n{}\r\n", STANDARD.encode("点击链接\r\n.\r\n"))));
+
+        let injected = Email { subject: "hi\r\nBcc: x@example.com".to_string(), ..email };
+        assert!(injected.to_message("no-reply@example.com").is_err());
+    }
+}
@@ -0,0 +1,365 @@
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use common::{AppError, AppResult, OidcProviderConfig};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::{OnceCell, RwLock};
+use url::Url;
+
+use crate::http::{HttpClient, HttpRequest};
+
+/// 接受的ID令牌签名算法
+/// 
+/// 只接受非对称算法：HMAC要用客户端密钥验证，`none`没有签名。
+const ALLOWED_ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::EdDSA,
+];
+
+/// 校验`exp`、`iat`时允许的时钟偏差（秒）
+const CLOCK_SKEW_SECONDS: u64 = 60;
+
+/// 第三方账号信息（来自通过校验的ID令牌）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalIdentity {
+    /// 提供方内的用户唯一标识（`sub`），与提供方名称一起唯一确定一个外部账号
+    pub subject: String,
+    
+    /// 邮箱（需要`email`权限范围）
+    pub email: Option<String>,
+    
+    /// 提供方是否验证过该邮箱
+    pub email_verified: bool,
+    
+    /// 显示名称
+    pub name: Option<String>,
+    
+    /// 头像URL
+    pub picture: Option<String>,
+}
+
+/// 第三方登录提供方接口（OAuth2授权码 + PKCE，OpenID Connect）
+/// 
+/// ## 登录流程
+/// 
+/// 1. 服务端生成`state`、`nonce`和PKCE校验码，调用`authorization_url`得到授权地址
+/// 2. 用户在提供方登录并同意授权，提供方带着`code`和`state`跳转到`redirect_uri`
+/// 3. 服务端调用`exchange_code`用授权码和PKCE校验码换取ID令牌，校验后得到`ExternalIdentity`
+#[async_trait]
+pub trait OidcProvider: Send + Sync {
+    /// 提供方名称（与配置中的`name`一致）
+    fn name(&self) -> &str;
+
+    /// 构造授权地址
+    /// 
+    /// ## 参数
+    /// - `state`: 回调时原样返回，用来找回这次授权请求
+    /// - `nonce`: 写入ID令牌，换取令牌时校验
+    /// - `code_challenge`: PKCE质询码（`S256`）
+    async fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> AppResult<String>;
+
+    /// 用授权码换取并校验ID令牌
+    /// 
+    /// ## 校验内容
+    /// - 签名（提供方JWKS中的公钥，只接受非对称算法）
+    /// - `iss`是配置的签发者，`aud`包含客户端ID，`exp`未过期
+    /// - `nonce`与授权请求一致
+    /// 
+    /// ## 错误
+    /// - `AuthError`: 授权码无效或ID令牌校验失败
+    /// - `InternalError`: 无法访问提供方或提供方返回的数据格式错误
+    async fn exchange_code(&self, code: &str, code_verifier: &str, nonce: &str) -> AppResult<ExternalIdentity>;
+}
+
+/// OIDC发现文档中用到的字段
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    #[serde(default)]
+    token_endpoint_auth_methods_supported: Option<Vec<String>>,
+    #[serde(default)]
+    code_challenge_methods_supported: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    azp: Option<String>,
+    email: Option<String>,
+    /// 有的提供方返回字符串`"true"`
+    email_verified: Option<serde_json::Value>,
+    name: Option<String>,
+    picture: Option<String>,
+}
+
+/// 通用的OIDC提供方实现
+/// 
+/// 首次使用时读取发现文档并缓存；JWKS也会缓存，遇到未知的`kid`时重新读取一次（提供方轮换密钥）。
+pub struct OidcProviderImpl {
+    config: OidcProviderConfig,
+    http: Arc<dyn HttpClient>,
+    metadata: OnceCell<ProviderMetadata>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+impl OidcProviderImpl {
+    pub fn new(config: OidcProviderConfig, http: Arc<dyn HttpClient>) -> Self {
+        Self {
+            config,
+            http,
+            metadata: OnceCell::new(),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    async fn metadata(&self) -> AppResult<&ProviderMetadata> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!("{}/.well-known/openid-configuration", self.config.issuer.trim_end_matches('/'));
+                let metadata: ProviderMetadata = self.get_json(&url).await?;
+
+                // OIDC Discovery 1.0 §4.3: the document must be about the configured issuer
+                if metadata.issuer != self.config.issuer {
+                    return Err(AppError::InternalError(format!(
+                        "OIDC provider {} reports issuer {}, expected {}",
+                        self.config.name, metadata.issuer, self.config.issuer
+                    )));
+                }
+
+                Ok(metadata)
+            })
+            .await
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> AppResult<T> {
+        let response = self.http.send(HttpRequest::get(url)).await?;
+        if !response.is_success() {
+            return Err(AppError::InternalError(format!(
+                "OIDC provider {} returned {} for {}",
+                self.config.name, response.status, url
+            )));
+        }
+
+        serde_json::from_slice(&response.body).map_err(|e| {
+            AppError::InternalError(format!("OIDC provider {} returned invalid JSON from {}: {}", self.config.name, url, e))
+        })
+    }
+
+    /// 查找ID令牌的签名公钥
+    async fn signing_key(&self, kid: Option<&str>, alg: Algorithm) -> AppResult<DecodingKey> {
+        let metadata = self.metadata().await?;
+
+        for refresh in [false, true] {
+            if refresh || self.jwks.read().await.is_none() {
+                let jwks: JwkSet = self.get_json(&metadata.jwks_uri).await?;
+                *self.jwks.write().await = Some(jwks);
+            }
+
+            let jwks = self.jwks.read().await;
+            let keys = &jwks.as_ref().expect("JWKS loaded above").keys;
+            let jwk = match kid {
+                Some(kid) => keys.iter().find(|k| k.common.key_id.as_deref() == Some(kid)),
+                // Without a kid the set must be unambiguous
+                None if keys.len() == 1 => keys.first(),
+                None => None,
+            };
+
+            if let Some(jwk) = jwk {
+                // A key published for one algorithm must not verify another
+                if let Some(key_alg) = &jwk.common.key_algorithm {
+                    if Algorithm::from_str(&key_alg.to_string()).ok() != Some(alg) {
+                        return Err(AppError::AuthError("id_token algorithm does not match its key".to_string()));
+                    }
+                }
+
+                return DecodingKey::from_jwk(jwk)
+                    .map_err(|e| AppError::InternalError(format!("Unusable JWK from {}: {}", self.config.name, e)));
+            }
+        }
+
+        Err(AppError::AuthError("Unknown id_token signing key".to_string()))
+    }
+
+    async fn validate_id_token(&self, id_token: &str, nonce: &str) -> AppResult<IdTokenClaims> {
+        let invalid = |reason: String| AppError::AuthError(format!("Invalid id_token: {}", reason));
+
+        let header = decode_header(id_token).map_err(|e| invalid(e.to_string()))?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            return Err(invalid(format!("algorithm {:?} not allowed", header.alg)));
+        }
+
+        let key = self.signing_key(header.kid.as_deref(), header.alg).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = CLOCK_SKEW_SECONDS;
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(|e| invalid(e.to_string()))?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid("nonce mismatch".to_string()));
+        }
+
+        if claims.azp.as_deref().is_some_and(|azp| azp != self.config.client_id) {
+            return Err(invalid("issued to another client".to_string()));
+        }
+
+        Ok(claims)
+    }
+
+    /// 构造令牌请求，按发现文档选择客户端认证方式（默认`client_secret_basic`）
+    fn token_request(&self, metadata: &ProviderMetadata, mut form: Vec<(&'static str, String)>) -> HttpRequest {
+        let mut basic_credentials = None;
+
+        match &self.config.client_secret {
+            // Public client: PKCE is the only proof
+            None => form.push(("client_id", self.config.client_id.clone())),
+            Some(secret) if supports_only_post(metadata) => {
+                form.push(("client_id", self.config.client_id.clone()));
+                form.push(("client_secret", secret.clone()));
+            }
+            Some(secret) => {
+                // RFC 6749 §2.3.1: both parts are form-encoded before base64
+                let encode = |s: &str| url::form_urlencoded::byte_serialize(s.as_bytes()).collect::<String>();
+                basic_credentials = Some(STANDARD.encode(format!("{}:{}", encode(&self.config.client_id), encode(secret))));
+            }
+        }
+
+        let pairs: Vec<(&str, &str)> = form.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let request = HttpRequest::post_form(&metadata.token_endpoint, &pairs);
+
+        match basic_credentials {
+            Some(credentials) => request.header("Authorization", &format!("Basic {}", credentials)),
+            None => request,
+        }
+    }
+}
+
+fn supports_only_post(metadata: &ProviderMetadata) -> bool {
+    metadata
+        .token_endpoint_auth_methods_supported
+        .as_ref()
+        .is_some_and(|methods| {
+            !methods.iter().any(|m| m == "client_secret_basic") && methods.iter().any(|m| m == "client_secret_post")
+        })
+}
+
+fn is_true(value: Option<&serde_json::Value>) -> bool {
+    match value {
+        Some(serde_json::Value::Bool(b)) => *b,
+        Some(serde_json::Value::String(s)) => s == "true",
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl OidcProvider for OidcProviderImpl {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> AppResult<String> {
+        let metadata = self.metadata().await?;
+
+        // Providers that advertise PKCE methods must support S256
+        if metadata
+            .code_challenge_methods_supported
+            .as_ref()
+            .is_some_and(|methods| !methods.iter().any(|m| m == "S256"))
+        {
+            return Err(AppError::InternalError(format!(
+                "OIDC provider {} does not support PKCE S256",
+                self.config.name
+            )));
+        }
+
+        let mut url = Url::parse(&metadata.authorization_endpoint).map_err(|e| {
+            AppError::InternalError(format!("Invalid authorization endpoint from {}: {}", self.config.name, e))
+        })?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    async fn exchange_code(&self, code: &str, code_verifier: &str, nonce: &str) -> AppResult<ExternalIdentity> {
+        let metadata = self.metadata().await?;
+
+        let request = self.token_request(metadata, vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code.to_string()),
+            ("redirect_uri", self.config.redirect_uri.clone()),
+            ("code_verifier", code_verifier.to_string()),
+        ]);
+
+        let response = self.http.send(request).await?;
+        if !response.is_success() {
+            // invalid_grant and friends: the code, verifier or redirect_uri was rejected
+            return Err(match serde_json::from_slice::<TokenErrorResponse>(&response.body) {
+                Ok(error) if response.status == 400 || response.status == 401 => AppError::AuthError(format!(
+                    "Authorization code rejected: {}{}",
+                    error.error,
+                    error.error_description.map(|d| format!(" ({})", d)).unwrap_or_default()
+                )),
+                _ => AppError::InternalError(format!(
+                    "OIDC provider {} token endpoint returned {}",
+                    self.config.name, response.status
+                )),
+            });
+        }
+
+        let tokens: TokenResponse = serde_json::from_slice(&response.body).map_err(|e| {
+            AppError::InternalError(format!("OIDC provider {} returned an invalid token response: {}", self.config.name, e))
+        })?;
+        let id_token = tokens
+            .id_token
+            .ok_or_else(|| AppError::AuthError("Token response has no id_token".to_string()))?;
+
+        let claims = self.validate_id_token(&id_token, nonce).await?;
+
+        Ok(ExternalIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: is_true(claims.email_verified.as_ref()),
+            name: claims.name,
+            picture: claims.picture,
+        })
+    }
+}
@@ -0,0 +1,56 @@
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::refresh::to_hex;
+
+/// 验证码位数
+const CODE_DIGITS: usize = 6;
+
+/// 生成短信验证码
+/// 
+/// ## 返回值
+/// 6位数字字符串（可能以0开头）
+pub fn generate_verification_code() -> String {
+    let mut rng = rand::thread_rng();
+
+    (0..CODE_DIGITS)
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect()
+}
+
+/// 计算验证码的哈希
+/// 
+/// 把手机号一起计算，同一个验证码发给不同手机号时哈希也不同。
+/// 验证码只有6位，真正的防护来自有效期和输错次数限制，哈希只是避免数据库中出现明文。
+/// 
+/// ## 返回值
+/// SHA-256哈希的十六进制字符串（64个字符）
+pub fn hash_verification_code(phone: &str, code: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(phone.as_bytes());
+    hasher.update(b":");
+    hasher.update(code.trim().as_bytes());
+
+    to_hex(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_six_digits() {
+        let code = generate_verification_code();
+
+        assert_eq!(code.len(), CODE_DIGITS);
+        assert!(code.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn hash_is_bound_to_the_phone_number() {
+        let hash = hash_verification_code("13800138000", "123456");
+
+        assert_eq!(hash, hash_verification_code("13800138000", " 123456 "));
+        assert_ne!(hash, hash_verification_code("13900139000", "123456"));
+    }
+}
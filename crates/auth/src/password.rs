@@ -1,13 +1,16 @@
-use common::AppResult;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use common::{AppError, AppResult, PasswordConfig};
+use std::sync::OnceLock;
 
 /// 密码服务接口
 /// 
-/// 提供密码的加密和验证功能，使用bcrypt算法。
+/// 提供密码的加密和验证功能，支持多种哈希算法（按哈希值的前缀区分）。
 /// 
 /// ## 安全性
 /// 
-/// - 使用bcrypt算法（自带salt）
-/// - 成本因子：DEFAULT_COST（当前为12）
+/// - 新密码使用Argon2id（内存困难，参数可配置），自带随机盐值
+/// - 兼容验证旧的bcrypt哈希，登录成功后升级为Argon2id（见`needs_rehash`）
 /// - 不可逆加密，无法从哈希值还原密码
 /// - 每次加密同一密码会产生不同的哈希值（盐值随机）
 pub trait PasswordService: Send + Sync {
@@ -17,58 +20,274 @@ pub trait PasswordService: Send + Sync {
     /// - `password`: 明文密码
     /// 
     /// ## 返回值
-    /// bcrypt哈希字符串（包含算法、成本因子、盐值和哈希值）
+    /// PHC格式的哈希字符串（包含算法、参数、盐值和哈希值）
     /// 
     /// ## 示例
     /// ```
     /// 输入：    "password123"
-    /// 输出：    "$2b$12$KIXxLx.../hash..."
+    /// 输出：    "$argon2id$v=19$m=19456,t=2,p=1$salt...$hash..."
     /// ```
     fn hash_password(&self, password: &str) -> AppResult<String>;
-    
+
     /// 验证密码
     /// 
     /// ## 参数
     /// - `password`: 用户输入的明文密码
-    /// - `hash`: 存储的bcrypt哈希值
+    /// - `hash`: 存储的哈希值（任何支持的算法）
     /// 
     /// ## 返回值
     /// - `true`: 密码正确
-    /// - `false`: 密码错误
+    /// - `false`: 密码错误，或哈希值不属于任何支持的算法（如没有密码的账号）
     fn verify_password(&self, password: &str, hash: &str) -> AppResult<bool>;
+
+    /// 哈希值是否需要用当前的默认算法和参数重新生成
+    /// 
+    /// 旧算法（bcrypt）或比当前配置弱的Argon2id参数返回`true`；
+    /// 调用方应在密码验证成功后（此时有明文密码）重新加密并保存
+    fn needs_rehash(&self, hash: &str) -> bool;
+
+    /// 用一个固定的哈希值做一次验证，结果丢弃
+    /// 
+    /// 账号不存在（或没有密码）时调用，让登录耗时和密码错误时一样，
+    /// 避免通过响应时间判断账号是否存在
+    fn dummy_verify(&self, password: &str);
 }
 
-/// 密码服务的实现
-#[derive(Clone)]
-pub struct PasswordServiceImpl;
+/// 一种密码哈希算法
+/// 
+/// 实现该trait并传给`PasswordServiceImpl::with_algorithms`即可接入新算法
+pub trait PasswordAlgorithm: Send + Sync {
+    /// 哈希值是否由该算法生成（按前缀判断，如`$argon2id$`、`$2b$`）
+    fn recognizes(&self, hash: &str) -> bool;
 
-impl PasswordServiceImpl {
+    /// 用该算法和当前参数加密密码
+    fn hash(&self, password: &str) -> AppResult<String>;
+
+    /// 验证密码（`hash`一定被`recognizes`识别过）
+    fn verify(&self, password: &str, hash: &str) -> AppResult<bool>;
+
+    /// 哈希值的参数是否与当前参数不同（需要重新生成）
+    fn is_outdated(&self, _hash: &str) -> bool {
+        false
+    }
+}
+
+/// Argon2id（RFC 9106），新密码的默认算法
+pub struct Argon2idAlgorithm {
+    params: Params,
+}
+
+impl Argon2idAlgorithm {
+    /// ## 错误
+    /// 参数超出Argon2允许的范围时返回`InternalError`
+    pub fn new(config: &PasswordConfig) -> AppResult<Self> {
+        let params = Params::new(
+            config.argon2_memory_kib,
+            config.argon2_iterations,
+            config.argon2_parallelism,
+            None,
+        )
+        .map_err(|e| AppError::InternalError(format!("Argon2id参数无效: {}", e)))?;
+
+        Ok(Self { params })
+    }
+
+    fn hasher(&self) -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, self.params.clone())
+    }
+}
+
+impl PasswordAlgorithm for Argon2idAlgorithm {
+    fn recognizes(&self, hash: &str) -> bool {
+        hash.starts_with("$argon2id$")
+    }
+
+    fn hash(&self, password: &str) -> AppResult<String> {
+        let salt = SaltString::generate(&mut rand::rngs::OsRng);
+
+        self.hasher()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| AppError::InternalError(format!("密码加密失败: {}", e)))
+    }
+
+    /// 使用哈希值中记录的参数验证，参数变化后旧哈希仍然可以验证
+    fn verify(&self, password: &str, hash: &str) -> AppResult<bool> {
+        let parsed = PasswordHash::new(hash)
+            .map_err(|e| AppError::AuthError(format!("密码验证失败: {}", e)))?;
+
+        match self.hasher().verify_password(password.as_bytes(), &parsed) {
+            Ok(()) => Ok(true),
+            Err(argon2::password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(AppError::AuthError(format!("密码验证失败: {}", e))),
+        }
+    }
+
+    fn is_outdated(&self, hash: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hash) else {
+            return true;
+        };
+
+        let current = parsed.version == Some(Version::V0x13.into())
+            && Params::try_from(&parsed).is_ok_and(|p| {
+                p.m_cost() == self.params.m_cost()
+                    && p.t_cost() == self.params.t_cost()
+                    && p.p_cost() == self.params.p_cost()
+            });
+
+        !current
+    }
+}
+
+/// bcrypt，早期版本使用的算法
+/// 
+/// 只用于验证已有的哈希（登录成功后升级为Argon2id），也可以作为默认算法
+pub struct BcryptAlgorithm {
+    cost: u32,
+}
+
+impl BcryptAlgorithm {
+    /// 成本因子默认使用bcrypt::DEFAULT_COST（当前为12）
     pub fn new() -> Self {
-        Self
+        Self { cost: bcrypt::DEFAULT_COST }
     }
 }
 
-impl Default for PasswordServiceImpl {
+impl Default for BcryptAlgorithm {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl PasswordService for PasswordServiceImpl {
-    /// 使用bcrypt加密密码
+impl PasswordAlgorithm for BcryptAlgorithm {
+    fn recognizes(&self, hash: &str) -> bool {
+        ["$2a$", "$2b$", "$2x$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix))
+    }
+
+    fn hash(&self, password: &str) -> AppResult<String> {
+        bcrypt::hash(password, self.cost)
+            .map_err(|e| AppError::InternalError(format!("密码加密失败: {}", e)))
+    }
+
+    /// bcrypt会自动从哈希值中提取盐值进行验证
+    fn verify(&self, password: &str, hash: &str) -> AppResult<bool> {
+        bcrypt::verify(password, hash)
+            .map_err(|e| AppError::AuthError(format!("密码验证失败: {}", e)))
+    }
+}
+
+/// 密码服务的实现
+/// 
+/// 新密码使用默认算法；验证时按哈希值的前缀在默认算法和旧算法中选择
+pub struct PasswordServiceImpl {
+    /// 加密新密码使用的算法
+    default: Box<dyn PasswordAlgorithm>,
+    
+    /// 只用于验证已有哈希的旧算法
+    legacy: Vec<Box<dyn PasswordAlgorithm>>,
+    
+    /// `dummy_verify`使用的固定哈希，第一次调用时用默认算法生成
+    dummy_hash: OnceLock<String>,
+}
+
+impl PasswordServiceImpl {
+    /// 默认算法为Argon2id（使用配置的参数），兼容验证bcrypt
     /// 
-    /// 成本因子使用bcrypt::DEFAULT_COST（当前为12）
-    fn hash_password(&self, password: &str) -> AppResult<String> {
-        bcrypt::hash(password, bcrypt::DEFAULT_COST)
-            .map_err(|e| common::AppError::InternalError(format!("密码加密失败: {}", e)))
+    /// ## 错误
+    /// Argon2id参数无效时返回`InternalError`
+    pub fn new(config: &PasswordConfig) -> AppResult<Self> {
+        Ok(Self::with_algorithms(
+            Box::new(Argon2idAlgorithm::new(config)?),
+            vec![Box::new(BcryptAlgorithm::new())],
+        ))
     }
 
-    /// 验证密码是否匹配
+    /// 自定义算法
     /// 
-    /// bcrypt会自动从哈希值中提取盐值进行验证
+    /// ## 参数
+    /// - `default`: 加密新密码使用的算法
+    /// - `legacy`: 只用于验证已有哈希的算法，这些哈希在登录成功后升级为`default`
+    pub fn with_algorithms(default: Box<dyn PasswordAlgorithm>, legacy: Vec<Box<dyn PasswordAlgorithm>>) -> Self {
+        Self {
+            default,
+            legacy,
+            dummy_hash: OnceLock::new(),
+        }
+    }
+
+    fn algorithm_for(&self, hash: &str) -> Option<&dyn PasswordAlgorithm> {
+        std::iter::once(&self.default)
+            .chain(&self.legacy)
+            .find(|algorithm| algorithm.recognizes(hash))
+            .map(|algorithm| algorithm.as_ref())
+    }
+}
+
+impl PasswordService for PasswordServiceImpl {
+    fn hash_password(&self, password: &str) -> AppResult<String> {
+        self.default.hash(password)
+    }
+
     fn verify_password(&self, password: &str, hash: &str) -> AppResult<bool> {
-        bcrypt::verify(password, hash)
-            .map_err(|e| common::AppError::AuthError(format!("密码验证失败: {}", e)))
+        match self.algorithm_for(hash) {
+            Some(algorithm) => algorithm.verify(password, hash),
+            None => Ok(false),
+        }
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        if self.default.recognizes(hash) {
+            self.default.is_outdated(hash)
+        } else {
+            // Unusable hashes (accounts without a password) have nothing to upgrade
+            self.algorithm_for(hash).is_some()
+        }
+    }
+
+    fn dummy_verify(&self, password: &str) {
+        let hash = self.dummy_hash.get_or_init(|| {
+            self.default
+                .hash("rookie-guide-dummy-password")
+                .unwrap_or_default()
+        });
+        let _ = self.default.verify(password, hash);
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small parameters keep the test fast in debug builds
+    fn config(memory_kib: u32) -> PasswordConfig {
+        PasswordConfig {
+            argon2_memory_kib: memory_kib,
+            argon2_iterations: 1,
+            argon2_parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn verifies_every_algorithm_and_flags_old_hashes() {
+        let service = PasswordServiceImpl::new(&config(64)).unwrap();
+
+        let hash = service.hash_password("password123").unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+        assert!(service.verify_password("password123", &hash).unwrap());
+        assert!(!service.verify_password("password124", &hash).unwrap());
+        assert!(!service.needs_rehash(&hash));
+
+        // Raising the parameters leaves existing hashes verifiable but outdated
+        let stronger = PasswordServiceImpl::new(&config(128)).unwrap();
+        assert!(stronger.verify_password("password123", &hash).unwrap());
+        assert!(stronger.needs_rehash(&hash));
+
+        let bcrypt_hash = bcrypt::hash("password123", 4).unwrap();
+        assert!(service.verify_password("password123", &bcrypt_hash).unwrap());
+        assert!(service.needs_rehash(&bcrypt_hash));
+
+        // Accounts without a password never match and have nothing to upgrade
+        assert!(!service.verify_password("", "!").unwrap());
+        assert!(!service.needs_rehash("!"));
+    }
+}
@@ -0,0 +1,54 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// PKCE校验码和`state`/`nonce`的随机字节数（256位）
+const RANDOM_BYTES: usize = 32;
+
+/// 生成PKCE校验码（code_verifier）
+/// 
+/// 校验码只保存在服务端，授权请求里只发送它的哈希（`pkce_challenge`），
+/// 用授权码换取令牌时再发送原文。截获授权码的人没有校验码，无法换取令牌。
+/// 
+/// ## 返回值
+/// 43个字符的base64url字符串（RFC 7636要求43-128个字符）
+pub fn generate_pkce_verifier() -> String {
+    random_url_safe()
+}
+
+/// 计算PKCE质询码（`S256`方法）
+/// 
+/// `BASE64URL(SHA256(code_verifier))`，不带填充
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// 生成授权请求的`state`或`nonce`
+/// 
+/// - `state`: 回调时用来找回授权请求，防止CSRF
+/// - `nonce`: 写入ID令牌，防止ID令牌被重放
+pub fn generate_oauth_state() -> String {
+    random_url_safe()
+}
+
+fn random_url_safe() -> String {
+    let mut bytes = [0u8; RANDOM_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenge_matches_rfc7636_example() {
+        // RFC 7636 Appendix B
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+
+        assert_eq!(pkce_challenge(verifier), "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+        assert_eq!(generate_pkce_verifier().len(), 43);
+    }
+}
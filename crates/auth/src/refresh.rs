@@ -0,0 +1,33 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// 刷新令牌的随机字节数（256位）
+const REFRESH_TOKEN_BYTES: usize = 32;
+
+/// 生成新的刷新令牌
+/// 
+/// 刷新令牌是不透明的随机字符串（不是JWT），只能通过数据库中的哈希校验。
+/// 
+/// ## 返回值
+/// 64个字符的十六进制字符串，只在签发时返回给客户端一次
+pub fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; REFRESH_TOKEN_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    to_hex(&bytes)
+}
+
+/// 计算刷新令牌的哈希
+/// 
+/// 数据库只保存哈希：即使数据库泄露，也无法用其中的记录换取访问令牌。
+/// 令牌本身是高熵随机数，不需要加盐或慢哈希。
+/// 
+/// ## 返回值
+/// SHA-256哈希的十六进制字符串（64个字符）
+pub fn hash_refresh_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use common::{AppError, AppResult};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 短信发送接口
+/// 
+/// 对接短信网关时实现该trait，并在`AppModule`中替换默认的`LogSmsSender`。
+#[async_trait]
+pub trait SmsSender: Send + Sync {
+    /// 发送登录验证码
+    /// 
+    /// ## 参数
+    /// - `phone`: 手机号
+    /// - `code`: 验证码明文
+    /// 
+    /// ## 错误
+    /// 网关调用失败时返回`InternalError`
+    async fn send_code(&self, phone: &str, code: &str) -> AppResult<()>;
+    
+    /// 是否启用了验证码登录（默认启用）
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
+
+/// 不真正发送短信的默认实现
+/// 
+/// 把验证码写到日志（`auth`目标，info级别），并记录每个手机号最近一次的验证码，
+/// 方便本地开发和测试在没有短信网关时完成登录。
+/// 
+/// **注意**：生产环境不能使用（`SMS_BACKEND=log`在生产环境会被配置校验拒绝）。
+#[derive(Default)]
+pub struct LogSmsSender {
+    /// 手机号 → 最近一次发送的验证码
+    sent: Mutex<HashMap<String, String>>,
+}
+
+impl LogSmsSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最近一次发给该手机号的验证码
+    pub fn last_code(&self, phone: &str) -> Option<String> {
+        self.sent.lock().unwrap().get(phone).cloned()
+    }
+}
+
+#[async_trait]
+impl SmsSender for LogSmsSender {
+    async fn send_code(&self, phone: &str, code: &str) -> AppResult<()> {
+        tracing::info!(phone, code, "SMS gateway not configured, verification code logged instead");
+
+        self.sent.lock().unwrap().insert(phone.to_string(), code.to_string());
+
+        Ok(())
+    }
+}
+
+/// 关闭验证码登录（`SMS_BACKEND=disabled`）
+/// 
+/// 没有接入短信网关的生产环境使用，发送验证码的接口返回404。
+pub struct DisabledSmsSender;
+
+#[async_trait]
+impl SmsSender for DisabledSmsSender {
+    async fn send_code(&self, _phone: &str, _code: &str) -> AppResult<()> {
+        Err(AppError::NotFound("SMS login is not enabled".to_string()))
+    }
+
+    fn is_enabled(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use common::{AppError, AppResult, SmtpConfig, SmtpSecurity};
+use lettre::address::Envelope;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::extension::ClientId;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use std::time::Duration;
+
+use crate::mail::{mailbox_address, Email, Mailer};
+
+/// 连接和每条命令的超时
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 通过SMTP服务器发送邮件（lettre）
+/// 
+/// 每封邮件新建一个连接，支持隐式TLS、`STARTTLS`（rustls和内置的webpki根证书）和登录。
+/// 邮件内容由`Email::to_message`生成，与`CaptureMailer`保存的`.eml`文件一致。
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: String,
+}
+
+impl SmtpMailer {
+    /// ## 参数
+    /// - `config`: SMTP服务器配置
+    /// - `from`: 发件人（`地址`或`名称 <地址>`）
+    /// 
+    /// ## 错误
+    /// - `InternalError`: 服务器地址不能用于TLS连接
+    pub fn new(config: SmtpConfig, from: String) -> AppResult<Self> {
+        let tls_error = |e: lettre::transport::smtp::Error| {
+            AppError::InternalError(format!("Invalid SMTP host {}: {}", config.host, e))
+        };
+
+        let mut builder = match config.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host).map_err(tls_error)?,
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host).map_err(tls_error)?
+            }
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+        };
+        builder = builder
+            .port(config.port)
+            .timeout(Some(TIMEOUT))
+            .hello_name(ClientId::Domain("rookie-guide".to_string()));
+
+        if let Some(username) = &config.username {
+            let password = config.password.clone().unwrap_or_default();
+            builder = builder.credentials(Credentials::new(username.clone(), password));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> AppResult<()> {
+        let message = email.to_message(&self.from)?;
+
+        let address = |value: &str| {
+            value
+                .parse::<Address>()
+                .map_err(|e| AppError::InternalError(format!("Invalid email address {}: {}", value, e)))
+        };
+        let envelope = Envelope::new(Some(address(mailbox_address(&self.from))?), vec![address(&email.to)?])
+            .map_err(|e| AppError::InternalError(format!("Invalid email envelope: {}", e)))?;
+
+        self.transport
+            .send_raw(&envelope, message.as_bytes())
+            .await
+            .map_err(|e| AppError::InternalError(format!("SMTP delivery to {} failed: {}", email.to, e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[tokio::test]
+    async fn delivers_message_with_auth_plain() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Scripted server: replies to each command and records the transcript
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut transcript = Vec::new();
+
+            writer.write_all(b"220 mail.test ESMTP\r\n").unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-mail.test\r\n250 AUTH PLAIN\r\n"
+                } else if line.starts_with("AUTH") {
+                    b"235 ok\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    b"221 bye\r\n"
+                } else {
+                    b"250 ok\r\n"
+                };
+                transcript.push(line);
+                if writer.write_all(reply).is_err() {
+                    break;
+                }
+            }
+
+            transcript
+        });
+
+        let config = SmtpConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::None,
+            username: Some("mailer".to_string()),
+            password: Some("secret".to_string()),
+        };
+        let mailer = SmtpMailer::new(config, "Rookie <no-reply@example.com>".to_string()).unwrap();
+        let email = Email {
+            to: "user@example.com".to_string(),
+            subject: "hello".to_string(),
+            body: "body".to_string(),
+        };
+
+        mailer.send(email).await.unwrap();
+
+        let transcript = server.join().unwrap();
+        assert_eq!(
+            transcript[..6],
+            [
+                "EHLO rookie-guide".to_string(),
+                format!("AUTH PLAIN {}", STANDARD.encode("\0mailer\0secret")),
+                "MAIL FROM:<no-reply@example.com>".to_string(),
+                "RCPT TO:<user@example.com>".to_string(),
+                "DATA".to_string(),
+                ".".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! OIDC授权码 + PKCE的一致性测试
+//!
+//! 在本机端口上启动一个模拟的OIDC提供方（发现文档、JWKS、令牌端点），
+//! 用`OidcProviderImpl`和默认的HTTP客户端走完整的授权流程，
+//! 并验证各种被篡改的授权码和ID令牌都会被拒绝。
+//!
+//! 测试密钥在`tests/fixtures`中：`provider_key`是提供方的签名密钥，
+//! `rotated_key`是轮换后的新密钥，`foreign_key`不在提供方的JWKS中。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use auth::{
+    generate_oauth_state, generate_pkce_verifier, pkce_challenge, DefaultHttpClient, ExternalIdentity,
+    OidcProvider, OidcProviderImpl,
+};
+use axum::extract::{Form, State};
+use axum::http::{header::AUTHORIZATION, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use common::{AppError, AppResult, OidcProviderConfig};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde_json::{json, Value};
+use url::Url;
+
+const CLIENT_ID: &str = "rookie-guide";
+const CLIENT_SECRET: &str = "s3cret:with/special chars";
+const REDIRECT_URI: &str = "https://app.example.com/oauth/mock/callback";
+
+const PROVIDER_KEY: &str = include_str!("fixtures/provider_key.pem");
+const PROVIDER_KEY_N: &str = include_str!("fixtures/provider_key.n");
+const ROTATED_KEY: &str = include_str!("fixtures/rotated_key.pem");
+const ROTATED_KEY_N: &str = include_str!("fixtures/rotated_key.n");
+const FOREIGN_KEY: &str = include_str!("fixtures/foreign_key.pem");
+
+/// 令牌端点对ID令牌做的篡改
+#[derive(Clone, Copy, PartialEq)]
+enum Tamper {
+    None,
+    WrongAudience,
+    WrongIssuer,
+    WrongNonce,
+    Expired,
+    /// 用不在JWKS中的密钥签名，但`kid`写成提供方的
+    ForeignSignature,
+    UnknownKid,
+    AlgNone,
+    /// 用公钥模数当作HMAC密钥（算法混淆攻击）
+    Hs256,
+}
+
+/// 用户在授权页同意授权后，提供方记下的授权请求
+struct Grant {
+    code_challenge: String,
+    nonce: String,
+    redirect_uri: String,
+}
+
+struct MockState {
+    issuer: String,
+    grants: HashMap<String, Grant>,
+    tamper: Tamper,
+    /// 当前签名密钥（kid, PEM, 模数）
+    signing_key: (&'static str, &'static str, &'static str),
+    /// 令牌端点收到的Authorization头
+    token_auth_headers: Vec<Option<String>>,
+    jwks_requests: usize,
+}
+
+#[derive(Clone)]
+struct MockProvider(Arc<Mutex<MockState>>);
+
+impl MockProvider {
+    /// 启动模拟提供方，返回它和签发者URL
+    async fn start() -> (Self, String) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+        let mock = MockProvider(Arc::new(Mutex::new(MockState {
+            issuer: issuer.clone(),
+            grants: HashMap::new(),
+            tamper: Tamper::None,
+            signing_key: ("key-1", PROVIDER_KEY, PROVIDER_KEY_N),
+            token_auth_headers: Vec::new(),
+            jwks_requests: 0,
+        })));
+
+        let app = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(mock.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (mock, issuer)
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.0.lock().unwrap()
+    }
+
+    /// 模拟用户打开授权地址并同意授权，返回回调地址中的`code`
+    fn approve(&self, authorization_url: &str) -> String {
+        let url = Url::parse(authorization_url).unwrap();
+        let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let code = generate_oauth_state();
+
+        self.state().grants.insert(
+            code.clone(),
+            Grant {
+                code_challenge: params["code_challenge"].clone(),
+                nonce: params["nonce"].clone(),
+                redirect_uri: params["redirect_uri"].clone(),
+            },
+        );
+
+        code
+    }
+
+    fn set_tamper(&self, tamper: Tamper) {
+        self.state().tamper = tamper;
+    }
+}
+
+async fn discovery(State(mock): State<MockProvider>) -> Json<Value> {
+    let issuer = mock.state().issuer.clone();
+
+    Json(json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{}/authorize", issuer),
+        "token_endpoint": format!("{}/token", issuer),
+        "jwks_uri": format!("{}/jwks", issuer),
+        "response_types_supported": ["code"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["RS256"],
+        "code_challenge_methods_supported": ["S256"],
+        "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post"],
+    }))
+}
+
+async fn jwks(State(mock): State<MockProvider>) -> Json<Value> {
+    let mut state = mock.state();
+    state.jwks_requests += 1;
+    let (kid, _, n) = state.signing_key;
+
+    Json(json!({
+        "keys": [{ "kty": "RSA", "use": "sig", "alg": "RS256", "kid": kid, "n": n.trim(), "e": "AQAB" }]
+    }))
+}
+
+fn token_error(error: &str) -> Response {
+    (StatusCode::BAD_REQUEST, Json(json!({ "error": error }))).into_response()
+}
+
+async fn token(
+    State(mock): State<MockProvider>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let mut state = mock.state();
+    state
+        .token_auth_headers
+        .push(headers.get(AUTHORIZATION).map(|h| h.to_str().unwrap().to_string()));
+
+    if form.get("grant_type").map(String::as_str) != Some("authorization_code") {
+        return token_error("unsupported_grant_type");
+    }
+
+    // Codes are single use, whatever happens next
+    let Some(grant) = form.get("code").and_then(|code| state.grants.remove(code)) else {
+        return token_error("invalid_grant");
+    };
+
+    let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+    if pkce_challenge(&verifier) != grant.code_challenge || form.get("redirect_uri") != Some(&grant.redirect_uri) {
+        return token_error("invalid_grant");
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut claims = json!({
+        "iss": state.issuer,
+        "aud": CLIENT_ID,
+        "sub": "mock-user-42",
+        "iat": now,
+        "exp": now + 300,
+        "nonce": grant.nonce,
+        "email": "rookie@example.com",
+        "email_verified": true,
+        "name": "新手小王",
+    });
+
+    let (kid, pem, n) = state.signing_key;
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(kid.to_string());
+    let mut key = EncodingKey::from_rsa_pem(pem.as_bytes()).unwrap();
+
+    match state.tamper {
+        Tamper::None => {}
+        Tamper::WrongAudience => claims["aud"] = json!("someone-else"),
+        Tamper::WrongIssuer => claims["iss"] = json!("https://evil.example.com"),
+        Tamper::WrongNonce => claims["nonce"] = json!("replayed-nonce"),
+        Tamper::Expired => claims["exp"] = json!(now - 3600),
+        Tamper::ForeignSignature => key = EncodingKey::from_rsa_pem(FOREIGN_KEY.as_bytes()).unwrap(),
+        Tamper::UnknownKid => header.kid = Some("key-unknown".to_string()),
+        Tamper::AlgNone => {
+            let unsigned = format!(
+                "{}.{}.",
+                URL_SAFE_NO_PAD.encode(json!({ "alg": "none", "typ": "JWT" }).to_string()),
+                URL_SAFE_NO_PAD.encode(claims.to_string()),
+            );
+            return Json(json!({ "access_token": "at", "token_type": "Bearer", "id_token": unsigned })).into_response();
+        }
+        Tamper::Hs256 => {
+            header.alg = Algorithm::HS256;
+            key = EncodingKey::from_secret(n.trim().as_bytes());
+        }
+    }
+
+    let id_token = encode(&header, &claims, &key).unwrap();
+
+    Json(json!({ "access_token": "at", "token_type": "Bearer", "expires_in": 300, "id_token": id_token })).into_response()
+}
+
+fn provider_config(issuer: &str) -> OidcProviderConfig {
+    OidcProviderConfig {
+        name: "mock".to_string(),
+        issuer: issuer.to_string(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: Some(CLIENT_SECRET.to_string()),
+        redirect_uri: REDIRECT_URI.to_string(),
+        scopes: "openid email profile".to_string(),
+    }
+}
+
+struct Fixture {
+    mock: MockProvider,
+    provider: OidcProviderImpl,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        let (mock, issuer) = MockProvider::start().await;
+        let provider = OidcProviderImpl::new(provider_config(&issuer), Arc::new(DefaultHttpClient::new()));
+
+        Self { mock, provider }
+    }
+
+    /// 走一遍完整的授权流程，返回换取结果
+    async fn sign_in(&self) -> AppResult<ExternalIdentity> {
+        let (code, verifier, nonce) = self.authorize().await;
+
+        self.provider.exchange_code(&code, &verifier, &nonce).await
+    }
+
+    /// 发起授权并同意，返回（授权码, PKCE校验码, nonce）
+    async fn authorize(&self) -> (String, String, String) {
+        let verifier = generate_pkce_verifier();
+        let nonce = generate_oauth_state();
+        let url = self
+            .provider
+            .authorization_url(&generate_oauth_state(), &nonce, &pkce_challenge(&verifier))
+            .await
+            .unwrap();
+
+        (self.mock.approve(&url), verifier, nonce)
+    }
+}
+
+fn assert_auth_error(result: AppResult<ExternalIdentity>) {
+    assert!(matches!(result, Err(AppError::AuthError(_))), "expected AuthError, got {:?}", result);
+}
+
+#[tokio::test]
+async fn authorization_url_carries_pkce_and_client_parameters() {
+    let f = Fixture::new().await;
+    let verifier = generate_pkce_verifier();
+
+    let url = f
+        .provider
+        .authorization_url("the-state", "the-nonce", &pkce_challenge(&verifier))
+        .await
+        .unwrap();
+
+    let url = Url::parse(&url).unwrap();
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    assert_eq!(url.path(), "/authorize");
+    assert_eq!(params["response_type"], "code");
+    assert_eq!(params["client_id"], CLIENT_ID);
+    assert_eq!(params["redirect_uri"], REDIRECT_URI);
+    assert_eq!(params["scope"], "openid email profile");
+    assert_eq!(params["state"], "the-state");
+    assert_eq!(params["nonce"], "the-nonce");
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert_eq!(params["code_challenge"], pkce_challenge(&verifier));
+}
+
+#[tokio::test]
+async fn exchanges_code_for_verified_identity() {
+    let f = Fixture::new().await;
+
+    let identity = f.sign_in().await.unwrap();
+
+    assert_eq!(identity.subject, "mock-user-42");
+    assert_eq!(identity.email.as_deref(), Some("rookie@example.com"));
+    assert!(identity.email_verified);
+    assert_eq!(identity.name.as_deref(), Some("新手小王"));
+
+    // client_secret_basic, both parts form-encoded (RFC 6749 §2.3.1)
+    let expected = format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode("rookie-guide:s3cret%3Awith%2Fspecial+chars")
+    );
+    assert_eq!(f.mock.state().token_auth_headers, vec![Some(expected)]);
+}
+
+#[tokio::test]
+async fn rejects_wrong_code_verifier() {
+    let f = Fixture::new().await;
+    let (code, _, nonce) = f.authorize().await;
+
+    let result = f.provider.exchange_code(&code, &generate_pkce_verifier(), &nonce).await;
+
+    assert_auth_error(result);
+}
+
+#[tokio::test]
+async fn authorization_code_is_single_use() {
+    let f = Fixture::new().await;
+    let (code, verifier, nonce) = f.authorize().await;
+
+    f.provider.exchange_code(&code, &verifier, &nonce).await.unwrap();
+    let replayed = f.provider.exchange_code(&code, &verifier, &nonce).await;
+
+    assert_auth_error(replayed);
+}
+
+#[tokio::test]
+async fn rejects_tampered_id_tokens() {
+    let cases = [
+        Tamper::WrongAudience,
+        Tamper::WrongIssuer,
+        Tamper::WrongNonce,
+        Tamper::Expired,
+        Tamper::ForeignSignature,
+        Tamper::UnknownKid,
+        Tamper::AlgNone,
+        Tamper::Hs256,
+    ];
+
+    let f = Fixture::new().await;
+    for tamper in cases {
+        f.mock.set_tamper(tamper);
+
+        let result = f.sign_in().await;
+
+        assert!(matches!(result, Err(AppError::AuthError(_))), "tamper case accepted: {:?}", result);
+    }
+}
+
+#[tokio::test]
+async fn refetches_jwks_after_key_rotation() {
+    let f = Fixture::new().await;
+    f.sign_in().await.unwrap();
+
+    f.mock.state().signing_key = ("key-2", ROTATED_KEY, ROTATED_KEY_N);
+    let identity = f.sign_in().await.unwrap();
+
+    assert_eq!(identity.subject, "mock-user-42");
+    assert_eq!(f.mock.state().jwks_requests, 2);
+}
+
+#[tokio::test]
+async fn rejects_discovery_document_for_another_issuer() {
+    let (mock, issuer) = MockProvider::start().await;
+    mock.state().issuer = "https://impostor.example.com".to_string();
+    let provider = OidcProviderImpl::new(provider_config(&issuer), Arc::new(DefaultHttpClient::new()));
+
+    let result = provider.authorization_url("state", "nonce", "challenge").await;
+
+    assert!(matches!(result, Err(AppError::InternalError(_))));
+}
@@ -1,21 +1,56 @@
 /// OpenAPI 统一响应结构
 /// 
-/// 为所有API返回统一的JSON格式，包含成功状态、消息、数据和时间戳
+/// 为所有API返回统一的JSON格式，包含成功状态、消息、数据和时间戳；
+/// `AppError`实现了`IntoResponse`，失败时返回同样结构的响应，并带上机器可读的错误码
 
 use serde::Serialize;
 use utoipa::ToSchema;
+use uuid::Uuid;
 use axum::{
     Json,
+    extract::{multipart::{MultipartError, MultipartRejection}, rejection::{JsonRejection, PathRejection, QueryRejection}},
     response::{IntoResponse, Response},
     http::StatusCode,
 };
 
+use crate::error::{AppError, ErrorCode, FieldError};
+
+/// Handler的统一返回类型
+/// 
+/// 成功时返回`ApiResponse<T>`，失败时由`AppError`转换为带错误码的失败响应
+/// 
+/// ## 示例
+/// ```rust
+/// # use axum::extract::Path;
+/// # use common::{ApiResponse, ApiResult, AppResult};
+/// # use uuid::Uuid;
+/// # #[derive(serde::Serialize)]
+/// # struct Template { id: Uuid }
+/// # struct TemplateService;
+/// # impl TemplateService {
+/// #     async fn get_template(&self, id: Uuid) -> AppResult<Template> { Ok(Template { id }) }
+/// # }
+/// # #[allow(non_upper_case_globals)]
+/// # const template_service: TemplateService = TemplateService;
+/// pub async fn get_template(Path(id): Path<Uuid>) -> ApiResult<Template> {
+///     let template = template_service.get_template(id).await?;
+///     Ok(ApiResponse::success(template, "获取成功"))
+/// }
+/// ```
+pub type ApiResult<T> = Result<Json<ApiResponse<T>>, AppError>;
+
+/// 失败响应（`data`为空，错误详情在`error`中）
+/// 
+/// 用于在OpenAPI文档中描述错误响应的结构
+pub type ErrorResponse = ApiResponse<serde_json::Value>;
+
 /// 统一 API 响应结构
 /// 
 /// ## 字段说明
 /// - `success`: 请求是否成功
 /// - `message`: 提示信息（成功消息或错误描述）
-/// - `data`: 响应数据（成功时包含，失败时为null）
+/// - `data`: 响应数据（成功时包含，失败时省略）
+/// - `error`: 错误详情（失败时包含）
 /// - `timestamp`: 响应时间戳（毫秒）
 /// 
 /// ## 成功响应示例
@@ -32,8 +67,23 @@ use axum::{
 /// ```json
 /// {
 ///   "success": false,
-///   "message": "用户不存在",
-///   "data": null,
+///   "message": "请求参数验证失败",
+///   "error": {
+///     "code": "VALIDATION_FAILED",
+///     "fields": [
+///       { "field": "title", "code": "length", "message": "Length must be between 1 and 200" }
+///     ]
+///   },
+///   "timestamp": 1730000000000
+/// }
+/// ```
+/// 
+/// 服务器内部错误不返回具体原因，只返回关联ID（原因记录在日志中）：
+/// ```json
+/// {
+///   "success": false,
+///   "message": "服务器内部错误，请稍后重试",
+///   "error": { "code": "INTERNAL_ERROR", "correlation_id": "0b6f..." },
 ///   "timestamp": 1730000000000
 /// }
 /// ```
@@ -49,6 +99,10 @@ pub struct ApiResponse<T> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
     
+    /// 错误详情
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+    
     /// 响应时间戳（毫秒）
     pub timestamp: i64,
 }
@@ -70,6 +124,7 @@ impl<T: Serialize> ApiResponse<T> {
             success: true,
             message: message.into(),
             data: Some(data),
+            error: None,
             timestamp: chrono::Utc::now().timestamp_millis(),
         })
     }
@@ -85,6 +140,7 @@ impl<T: Serialize> ApiResponse<T> {
             success: true,
             message: message.into(),
             data: None,
+            error: None,
             timestamp: chrono::Utc::now().timestamp_millis(),
         })
     }
@@ -95,68 +151,189 @@ impl<T: Serialize> ApiResponse<T> {
             success: false,
             message: message.into(),
             data: None,
+            error: None,
             timestamp: chrono::Utc::now().timestamp_millis(),
         })
     }
 }
 
-/// API错误类型（可转换为HTTP响应）
-/// 
-/// 自动转换为带有适当HTTP状态码的ApiResponse
-#[derive(Debug, thiserror::Error)]
-pub enum ApiError {
-    /// 400 - 请求参数错误
-    #[error("请求参数错误: {0}")]
-    BadRequest(String),
-    
-    /// 401 - 未认证
-    #[error("未认证: {0}")]
-    Unauthorized(String),
-    
-    /// 403 - 无权限
-    #[error("无权限: {0}")]
-    Forbidden(String),
+/// 失败响应中的错误详情
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiError {
+    /// 机器可读的错误码
+    pub code: ErrorCode,
     
-    /// 404 - 资源不存在
-    #[error("资源不存在: {0}")]
-    NotFound(String),
+    /// 逐个字段的校验错误（只有`VALIDATION_FAILED`有）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
     
-    /// 500 - 服务器内部错误
-    #[error("服务器错误: {0}")]
-    InternalError(String),
+    /// 关联ID（只有`INTERNAL_ERROR`有），与服务器日志中的`correlation_id`对应
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<Uuid>,
 }
 
-impl IntoResponse for ApiError {
+impl ErrorCode {
+    /// 错误码对应的HTTP状态码
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::BadRequest | ErrorCode::ValidationFailed => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// 将AppError转换为失败响应
+/// 
+/// 数据库错误和内部错误的原因可能包含SQL、配置等敏感信息，只写入日志；
+/// 客户端收到通用的提示和关联ID，排查问题时用关联ID在日志中查找
+impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
-            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        let code = self.code();
+        
+        let (message, fields, correlation_id) = match self {
+            AppError::DatabaseError(_) | AppError::InternalError(_) => {
+                let correlation_id = Uuid::new_v4();
+                tracing::error!(correlation_id = %correlation_id, error = %self, "Request failed with an internal error");
+                ("服务器内部错误，请稍后重试".to_string(), Vec::new(), Some(correlation_id))
+            }
+            AppError::InvalidFields(fields) => ("请求参数验证失败".to_string(), fields, None),
+            AppError::NotFound(msg)
+            | AppError::ValidationError(msg)
+            | AppError::AuthError(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Conflict(msg)
+            | AppError::PayloadTooLarge(msg)
+            | AppError::TooManyRequests(msg) => (msg, Vec::new(), None),
         };
         
-        let response: ApiResponse<()> = ApiResponse {
+        let response: ErrorResponse = ApiResponse {
             success: false,
             message,
             data: None,
+            error: Some(ApiError { code, fields, correlation_id }),
             timestamp: chrono::Utc::now().timestamp_millis(),
         };
         
-        (status, Json(response)).into_response()
+        (code.status(), Json(response)).into_response()
     }
 }
 
-/// 将AppError转换为ApiError
-impl From<crate::AppError> for ApiError {
-    fn from(err: crate::AppError) -> Self {
-        match err {
-            crate::AppError::NotFound(msg) => ApiError::NotFound(msg),
-            crate::AppError::ValidationError(msg) => ApiError::BadRequest(msg),
-            crate::AppError::AuthError(msg) => ApiError::Unauthorized(msg),
-            crate::AppError::DatabaseError(msg) => ApiError::InternalError(format!("数据库错误: {}", msg)),
-            crate::AppError::InternalError(msg) => ApiError::InternalError(msg),
-        }
+/// 提取器拒绝请求时的错误（请求体不是合法JSON、路径参数格式错误等）
+fn rejection_error(status: StatusCode, message: String) -> AppError {
+    if status == StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::PayloadTooLarge(message)
+    } else if status.is_server_error() {
+        AppError::InternalError(message)
+    } else {
+        AppError::ValidationError(message)
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        rejection_error(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        rejection_error(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        rejection_error(rejection.status(), rejection.body_text())
     }
 }
 
+impl From<MultipartError> for AppError {
+    fn from(error: MultipartError) -> Self {
+        rejection_error(error.status(), error.body_text())
+    }
+}
+
+impl From<MultipartRejection> for AppError {
+    fn from(rejection: MultipartRejection) -> Self {
+        rejection_error(rejection.status(), rejection.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Step {
+        #[validate(length(min = 1, max = 500))]
+        title: String,
+    }
+
+    #[derive(Validate)]
+    struct Template {
+        #[validate(length(min = 1, max = 200))]
+        title: String,
+        #[validate(email)]
+        contact: String,
+        #[validate(nested)]
+        steps: Vec<Step>,
+    }
+
+    async fn body_of(error: AppError) -> (StatusCode, Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn validation_errors_list_every_field() {
+        let template = Template {
+            title: String::new(),
+            contact: "not-an-email".to_string(),
+            steps: vec![Step { title: "ok".to_string() }, Step { title: String::new() }],
+        };
+        let error = AppError::from(template.validate().unwrap_err());
+
+        let (status, body) = body_of(error).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error"]["code"], "VALIDATION_FAILED");
+        assert_eq!(
+            body["error"]["fields"],
+            json!([
+                { "field": "contact", "code": "email", "message": "Must be a valid email address" },
+                { "field": "steps[1].title", "code": "length", "message": "Length must be between 1 and 500" },
+                { "field": "title", "code": "length", "message": "Length must be between 1 and 200" },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn internal_errors_are_not_leaked() {
+        let error = AppError::DatabaseError("connection to 10.0.0.5:5432 refused".to_string());
+
+        let (status, body) = body_of(error).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(body["error"]["code"], "INTERNAL_ERROR");
+        assert!(!body.to_string().contains("10.0.0.5"), "{}", body);
+        assert!(body["error"]["correlation_id"].as_str().unwrap().parse::<Uuid>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn business_errors_keep_their_message() {
+        let (status, body) = body_of(AppError::Conflict("Email already registered".to_string())).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["message"], "Email already registered");
+        assert_eq!(body["error"], json!({ "code": "CONFLICT" }));
+        assert!(body.get("data").is_none());
+    }
+}
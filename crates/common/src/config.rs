@@ -1,16 +1,26 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+mod source;
+
+pub use source::{ConfigError, ConfigOptions, ConfigOrigin, ConfigProblem, Environment};
+use source::{normalize_key, Source};
 
 /// 应用程序总配置
 /// 
-/// 包含服务器、数据库、JWT等所有配置项。
-/// 配置从环境变量或.env文件加载。
+/// 包含服务器、数据库、JWT等所有配置项，由`AppConfig::load`加载和校验。
 /// 
 /// ## 配置来源优先级
-/// 1. 系统环境变量（最高优先级）
-/// 2. .env文件中的配置
-/// 3. 代码中的默认值（最低优先级）
-#[derive(Debug, Clone, Deserialize)]
+/// 1. 命令行参数（最高优先级）
+/// 2. 系统环境变量
+/// 3. .env文件中的配置
+/// 4. 配置文件（`config/default.toml`和`config/{环境}.toml`）
+/// 5. 代码中的默认值（最低优先级）
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    /// 运行环境（决定读取的配置文件和是否做生产环境的检查）
+    pub environment: Environment,
+    
     /// 服务器配置（监听地址、端口）
     pub server: ServerConfig,
     
@@ -19,12 +29,30 @@ pub struct AppConfig {
     
     /// JWT配置（密钥、过期时间）
     pub jwt: JwtConfig,
+    
+    /// 第三方登录（OAuth2/OIDC）提供方，未配置时为空
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    
+    /// 邮件配置（发件人、SMTP服务器、找回密码链接）
+    pub mail: MailConfig,
+    
+    /// 短信配置（登录验证码的发送方式）
+    pub sms: SmsConfig,
+    
+    /// 密码哈希配置（Argon2id参数）
+    pub password: PasswordConfig,
+    
+    /// 账号注销配置（宽限期、清理间隔）
+    pub account: AccountConfig,
+    
+    /// 对象存储配置（用户上传的头像等文件）
+    pub storage: StorageConfig,
 }
 
 /// 服务器配置
 /// 
 /// 控制HTTP服务器的监听地址和端口
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     /// 监听主机地址（默认: 127.0.0.1）
     /// 
@@ -34,12 +62,19 @@ pub struct ServerConfig {
     
     /// 监听端口（默认: 8080）
     pub port: u16,
+    
+    /// 是否信任`X-Forwarded-For`请求头（默认: false）
+    /// 
+    /// 部署在反向代理（Nginx等）后面时开启，客户端IP取该请求头的最后一个地址
+    /// （由最近一层代理追加）；直接对外提供服务时必须关闭，否则客户端可以伪造IP，
+    /// 绕过按IP的登录失败限制
+    pub trust_forwarded_for: bool,
 }
 
 /// 数据库配置
 /// 
 /// 配置PostgreSQL连接参数
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseConfig {
     /// 数据库主机地址（默认: localhost）
     /// 
@@ -76,29 +111,296 @@ pub struct DatabaseConfig {
 /// JWT配置
 /// 
 /// 配置JSON Web Token的生成和验证参数
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JwtConfig {
-    /// JWT签名密钥
+    /// 当前的签名密钥，新签发的token都使用它
+    /// 
+    /// 默认使用HS256和`JWT_SECRET`；多实例部署或需要把公钥交给其他服务验证token时，
+    /// 可以改用RS256或EdDSA（Ed25519）非对称密钥。
+    /// 
+    /// **必需**：HS256需要`JWT_SECRET`，RS256/EdDSA需要私钥和公钥文件，否则应用启动失败
+    pub signing_key: JwtKeyConfig,
+    
+    /// 轮换下来的旧密钥，只用于验证
+    /// 
+    /// 轮换密钥时给新密钥设置新的`kid`，把旧密钥保留在这里，
+    /// 直到用旧密钥签发的访问令牌全部过期（`expiration`秒后）再删除。
+    pub verification_keys: Vec<JwtKeyConfig>,
+    
+    /// 访问令牌（JWT）过期时间（秒）
+    /// 
+    /// 默认: 900秒（15分钟）
+    /// 
+    /// 访问令牌无法提前吊销，有效期应尽量短，过期后客户端用刷新令牌换取新令牌。
+    /// 
+    /// 常用值：
+    /// - 300: 5分钟
+    /// - 900: 15分钟
+    /// - 3600: 1小时
+    pub expiration: i64,
+    
+    /// 刷新令牌过期时间（秒）
+    /// 
+    /// 默认: 2592000秒（30天）
+    /// 
+    /// 每次刷新都会签发新的刷新令牌并重新计时，超过该时间未使用需要重新登录。
+    pub refresh_expiration: i64,
+}
+
+/// JWT密钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtKeyConfig {
+    /// 密钥ID，签发时写入token头的`kid`，验证时按它选择密钥
     /// 
-    /// **重要**: 生产环境必须使用强随机密钥！
+    /// 签名密钥可以不设置，此时签发的token不带`kid`；
+    /// 不带`kid`的token（包括启用轮换之前签发的）只用签名密钥验证。
+    pub kid: Option<String>,
+    
+    /// 签名算法和密钥
+    pub key: JwtKey,
+}
+
+/// JWT签名算法和密钥
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "algorithm")]
+pub enum JwtKey {
+    /// HMAC-SHA256，签名和验证使用同一个密钥
     /// 
     /// 生成密钥示例:
     /// ```bash
     /// openssl rand -base64 64
     /// ```
+    #[serde(rename = "HS256")]
+    Hs256 {
+        /// 共享密钥（生产环境必须使用强随机密钥！）
+        secret: String,
+    },
+    
+    /// RSA签名（RSASSA-PKCS1-v1_5 + SHA-256）
     /// 
-    /// **必需**：此配置项必须提供，否则应用启动失败
-    pub secret: String,
+    /// 生成密钥示例:
+    /// ```bash
+    /// openssl genpkey -algorithm RSA -pkeyopt rsa_keygen_bits:2048 -out jwt.pem
+    /// openssl pkey -in jwt.pem -pubout -out jwt.pub.pem
+    /// ```
+    #[serde(rename = "RS256")]
+    Rs256 {
+        /// PEM格式的私钥（只用于验证的旧密钥没有私钥）
+        private_key_pem: Option<String>,
+        
+        /// PEM格式的公钥
+        public_key_pem: String,
+    },
     
-    /// Token过期时间（秒）
+    /// Ed25519签名
     /// 
-    /// 默认: 86400秒（24小时）
+    /// 生成密钥示例:
+    /// ```bash
+    /// openssl genpkey -algorithm ed25519 -out jwt.pem
+    /// openssl pkey -in jwt.pem -pubout -out jwt.pub.pem
+    /// ```
+    #[serde(rename = "EdDSA")]
+    EdDsa {
+        /// PEM格式的私钥（PKCS#8，只用于验证的旧密钥没有私钥）
+        private_key_pem: Option<String>,
+        
+        /// PEM格式的公钥
+        public_key_pem: String,
+    },
+}
+
+/// 第三方登录提供方配置（OpenID Connect）
+/// 
+/// 任何支持OIDC发现（`{issuer}/.well-known/openid-configuration`）和PKCE的提供方都可以接入，
+/// 在提供方后台登记`redirect_uri`后，把客户端ID和密钥填入环境变量。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcProviderConfig {
+    /// 提供方名称（小写字母、数字、`-`、`_`），用在URL中，如`/api/auth/oauth/google/authorize`
+    pub name: String,
+    
+    /// 签发者URL（如`https://accounts.google.com`），必须与发现文档和ID令牌中的`iss`一致
+    pub issuer: String,
+    
+    /// 在提供方登记的客户端ID
+    pub client_id: String,
+    
+    /// 客户端密钥（公开客户端没有密钥，只依赖PKCE）
+    pub client_secret: Option<String>,
+    
+    /// 授权回调地址，必须与在提供方登记的完全一致
     /// 
-    /// 常用值：
-    /// - 3600: 1小时
-    /// - 86400: 24小时
-    /// - 604800: 7天
-    pub expiration: i64,
+    /// 通常是前端页面，前端拿到`code`和`state`后调用回调接口
+    pub redirect_uri: String,
+    
+    /// 请求的权限范围（默认: `openid email profile`）
+    pub scopes: String,
+}
+
+/// 邮件配置
+/// 
+/// 找回密码等邮件通过`Mailer`发送：配置了SMTP服务器时使用SMTP，
+/// 否则邮件只写入日志和`capture_dir`（开发和测试环境）。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailConfig {
+    /// 发件人（如`阅历进度条 <no-reply@example.com>`，默认: `no-reply@localhost`）
+    pub from: String,
+    
+    /// SMTP服务器，未配置时不真正发送邮件
+    pub smtp: Option<SmtpConfig>,
+    
+    /// 未配置SMTP时，把邮件保存为`.eml`文件的目录（可选）
+    pub capture_dir: Option<String>,
+    
+    /// 找回密码页面地址，邮件中的链接为`{password_reset_url}?token=...`
+    /// 
+    /// 默认: `http://localhost:3000/reset-password`
+    pub password_reset_url: String,
+}
+
+/// 短信配置
+/// 
+/// 登录验证码通过`SmsSender`发送。目前还没有接入短信网关：`log`只把验证码写入日志，
+/// 用于开发和测试环境；生产环境在接入网关之前必须设置为`disabled`，关闭验证码登录。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsConfig {
+    /// 发送方式（默认: `log`）
+    pub backend: SmsBackend,
+}
+
+/// 短信发送方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmsBackend {
+    /// 不真正发送，验证码只写入日志（生产环境不可用）
+    Log,
+    
+    /// 关闭验证码登录，发送验证码的接口返回404
+    Disabled,
+}
+
+/// 密码哈希配置
+/// 
+/// 新密码使用Argon2id哈希，默认参数是OWASP推荐的最低配置（19 MiB内存、2次迭代、1个线程）。
+/// 提高参数后，旧参数的哈希在用户下次登录成功时自动升级，不需要用户重置密码。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordConfig {
+    /// Argon2id内存开销（KiB，默认: 19456）
+    pub argon2_memory_kib: u32,
+    
+    /// Argon2id迭代次数（默认: 2）
+    pub argon2_iterations: u32,
+    
+    /// Argon2id并行度（默认: 1）
+    pub argon2_parallelism: u32,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self {
+            argon2_memory_kib: 19456,
+            argon2_iterations: 2,
+            argon2_parallelism: 1,
+        }
+    }
+}
+
+/// 账号注销配置
+/// 
+/// 用户申请注销后，账号在宽限期内保留（登录即撤销注销），
+/// 宽限期结束后由后台任务彻底删除。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountConfig {
+    /// 注销宽限期（天，默认: 30）
+    pub deletion_grace_days: i64,
+    
+    /// 后台任务检查并删除到期账号的间隔（秒，默认: 3600）
+    pub purge_interval_seconds: u64,
+}
+
+/// 对象存储配置
+/// 
+/// 用户上传的文件（头像）保存在本地目录或S3兼容的对象存储（AWS S3、MinIO、阿里云OSS等）中，
+/// 客户端通过`public_base_url`下的地址访问。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// 存储后端
+    pub backend: StorageBackend,
+    
+    /// 对象的公开访问地址前缀，对象的URL为`{public_base_url}/{key}`
+    /// 
+    /// 默认: 本地存储为`http://localhost:{SERVER_PORT}/uploads`（由API服务器提供访问），
+    /// S3为`{endpoint}/{bucket}`（路径风格）或`{scheme}://{bucket}.{host}`（虚拟主机风格）
+    pub public_base_url: String,
+}
+
+/// 对象存储后端
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// 本地目录（单机部署、开发环境）
+    Local {
+        /// 保存文件的目录
+        dir: String,
+    },
+    
+    /// S3兼容的对象存储
+    S3(S3Config),
+}
+
+/// S3兼容对象存储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    /// 服务地址（如`https://s3.us-east-1.amazonaws.com`、`http://localhost:9000`）
+    pub endpoint: String,
+    
+    /// 存储桶名称
+    pub bucket: String,
+    
+    /// 区域（默认: `us-east-1`，用于请求签名）
+    pub region: String,
+    
+    /// 访问密钥ID
+    pub access_key_id: String,
+    
+    /// 访问密钥
+    pub secret_access_key: String,
+    
+    /// 是否使用路径风格的地址`{endpoint}/{bucket}/{key}`（默认: true，MinIO需要）；
+    /// 关闭时使用虚拟主机风格`{bucket}.{endpoint的主机}/{key}`
+    pub path_style: bool,
+}
+
+/// SMTP服务器配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmtpConfig {
+    /// 服务器地址
+    pub host: String,
+    
+    /// 端口（默认: `tls`为465，`starttls`为587，`none`为25）
+    pub port: u16,
+    
+    /// 连接加密方式（默认: `starttls`）
+    pub security: SmtpSecurity,
+    
+    /// 登录用户名（不需要登录时不配置）
+    pub username: Option<String>,
+    
+    /// 登录密码
+    pub password: Option<String>,
+}
+
+/// SMTP连接加密方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// 连接后立即使用TLS（通常是465端口）
+    Tls,
+    
+    /// 先明文连接，再用`STARTTLS`升级（通常是587端口）
+    StartTls,
+    
+    /// 不加密，只用于本机或内网的邮件中继
+    None,
 }
 
 impl DatabaseConfig {
@@ -128,19 +430,27 @@ impl DatabaseConfig {
 }
 
 impl AppConfig {
-    /// 从环境变量加载配置
+    /// 从分层的配置来源加载并校验配置
+    /// 
+    /// ## 配置来源（后面的覆盖前面的）
+    /// 1. 代码中的默认值
+    /// 2. 配置文件：`config/default.toml`和`config/{环境}.toml`（都是可选的），
+    ///    或`options.config_file`指定的文件（必须存在）
+    /// 3. 环境变量（包括当前目录的.env文件，.env不会覆盖已有的环境变量）
+    /// 4. 命令行参数（`options.overrides`）
+    /// 
+    /// 运行环境由`options.environment`或`APP_ENV`环境变量指定，默认`development`。
     /// 
-    /// ## 加载流程
-    /// 1. 尝试加载当前目录的.env文件（如果存在）
-    /// 2. 从环境变量读取各配置项
-    /// 3. 对于可选配置，使用默认值
-    /// 4. 对于必需配置（DATABASE_PASSWORD, JWT_SECRET），如果缺失则panic
+    /// 配置文件中的键换算成环境变量的名称：`[database] max_connections = 20`
+    /// 等同于`DATABASE_MAX_CONNECTIONS=20`，`[oidc.google] client_id`等同于`OIDC_GOOGLE_CLIENT_ID`，
+    /// 列表（如`[oidc] providers = ["google"]`）等同于逗号分隔的值。
     /// 
     /// ## 环境变量列表
     /// 
     /// ### 服务器配置
     /// - `SERVER_HOST`: 监听地址（默认: 127.0.0.1）
     /// - `SERVER_PORT`: 监听端口（默认: 8080）
+    /// - `SERVER_TRUST_FORWARDED_FOR`: 是否信任`X-Forwarded-For`请求头（默认: false）
     /// 
     /// ### 数据库配置
     /// - `DATABASE_HOST`: 数据库主机（默认: localhost）
@@ -151,81 +461,641 @@ impl AppConfig {
     /// - `DATABASE_MAX_CONNECTIONS`: 最大连接数（默认: 5）
     /// 
     /// ### JWT配置
-    /// - `JWT_SECRET`: JWT签名密钥（**必需**）
-    /// - `JWT_EXPIRATION`: Token过期时间/秒（默认: 86400）
+    /// - `JWT_ALGORITHM`: 签名算法 `HS256` | `RS256` | `EdDSA`（默认: `HS256`）
+    /// - `JWT_SECRET`: HS256签名密钥（使用HS256时**必需**，生产环境至少32个字节）
+    /// - `JWT_PRIVATE_KEY_FILE` / `JWT_PUBLIC_KEY_FILE`: PEM格式的私钥和公钥文件（使用RS256/EdDSA时**必需**）
+    /// - `JWT_KEY_ID`: 签名密钥的ID，写入token头的`kid`（可选，轮换密钥时必需）
+    /// - `JWT_VERIFICATION_KEYS`: 只用于验证的旧密钥ID，逗号分隔（默认为空）
+    /// - `JWT_KEY_<KID>_ALGORITHM`: 旧密钥的算法（默认: `HS256`）
+    /// - `JWT_KEY_<KID>_SECRET` / `JWT_KEY_<KID>_PUBLIC_KEY_FILE`: 旧密钥的HS256密钥或PEM格式的公钥文件
+    /// - `JWT_EXPIRATION`: 访问令牌过期时间/秒（默认: 900）
+    /// - `JWT_REFRESH_EXPIRATION`: 刷新令牌过期时间/秒（默认: 2592000）
+    /// 
+    /// ### 第三方登录配置
+    /// - `OIDC_PROVIDERS`: 启用的提供方名称，逗号分隔（默认为空，不启用第三方登录）
+    /// - `OIDC_<NAME>_ISSUER`: 签发者URL（启用时**必需**）
+    /// - `OIDC_<NAME>_CLIENT_ID`: 客户端ID（启用时**必需**）
+    /// - `OIDC_<NAME>_CLIENT_SECRET`: 客户端密钥（可选）
+    /// - `OIDC_<NAME>_REDIRECT_URI`: 授权回调地址（启用时**必需**）
+    /// - `OIDC_<NAME>_SCOPES`: 权限范围（默认: `openid email profile`）
+    /// 
+    /// `<NAME>`是大写的提供方名称，`-`换成`_`（如`OIDC_GOOGLE_CLIENT_ID`）
+    /// 
+    /// ### 邮件配置
+    /// - `MAIL_FROM`: 发件人（默认: `no-reply@localhost`）
+    /// - `SMTP_HOST`: SMTP服务器地址（默认为空，不真正发送邮件）
+    /// - `SMTP_PORT`: SMTP端口（默认按加密方式: 465 / 587 / 25）
+    /// - `SMTP_SECURITY`: 加密方式 `tls` | `starttls` | `none`（默认: `starttls`）
+    /// - `SMTP_USERNAME` / `SMTP_PASSWORD`: 登录用户名和密码（可选）
+    /// - `MAIL_CAPTURE_DIR`: 未配置SMTP时保存邮件的目录（可选）
+    /// - `PASSWORD_RESET_URL`: 找回密码页面地址（默认: `http://localhost:3000/reset-password`）
+    /// 
+    /// ### 密码哈希配置
+    /// - `PASSWORD_ARGON2_MEMORY_KIB`: Argon2id内存开销/KiB（默认: 19456）
+    /// - `PASSWORD_ARGON2_ITERATIONS`: Argon2id迭代次数（默认: 2）
+    /// - `PASSWORD_ARGON2_PARALLELISM`: Argon2id并行度（默认: 1）
+    /// 
+    /// ### 账号注销配置
+    /// - `ACCOUNT_DELETION_GRACE_DAYS`: 注销宽限期/天（默认: 30）
+    /// - `ACCOUNT_PURGE_INTERVAL_SECONDS`: 彻底删除到期账号的检查间隔/秒（默认: 3600）
+    /// 
+    /// ### 对象存储配置
+    /// - `STORAGE_BACKEND`: `local` | `s3`（默认: `local`）
+    /// - `STORAGE_LOCAL_DIR`: 本地存储的目录（默认: `./uploads`）
+    /// - `STORAGE_PUBLIC_BASE_URL`: 对象的公开访问地址前缀（默认见`StorageConfig::public_base_url`）
+    /// - `S3_ENDPOINT`、`S3_BUCKET`、`S3_ACCESS_KEY_ID`、`S3_SECRET_ACCESS_KEY`: 使用S3时**必需**
+    /// - `S3_REGION`: 区域（默认: `us-east-1`）
+    /// - `S3_PATH_STYLE`: 是否使用路径风格的地址（默认: true）
     /// 
     /// ## 错误处理
-    /// 如果必需的配置项缺失，应用会panic并显示清晰的错误信息
+    /// 不会panic：缺少必需的配置项、值的格式错误、配置文件中的未知配置项、
+    /// 密钥文件无法读取、生产环境的JWT密钥太弱等问题全部收集起来，在`ConfigError`中一起返回
     /// 
     /// ## 示例
     /// ```rust
-    /// // 从环境变量加载配置
-    /// let config = AppConfig::from_env()?;
+    /// // 读取config/production.toml、环境变量，再用命令行参数覆盖端口
+    /// let options = ConfigOptions {
+    ///     environment: Some(Environment::Production),
+    ///     overrides: vec![("server.port".to_string(), "9000".to_string())],
+    ///     ..Default::default()
+    /// };
+    /// let config = AppConfig::load(&options)?;
     /// 
-    /// // 使用配置
     /// println!("Server: {}:{}", config.server.host, config.server.port);
     /// println!("Database: {}", config.database.connection_url());
     /// ```
-    pub fn from_env() -> anyhow::Result<Self> {
-        // 尝试加载.env文件（如果存在）
-        // ok()表示即使文件不存在也不报错
+    pub fn load(options: &ConfigOptions) -> Result<Self, ConfigError> {
+        // 尝试加载.env文件（如果存在），不存在也不报错
         dotenvy::dotenv().ok();
         
-        Ok(AppConfig {
-            server: ServerConfig {
-                // SERVER_HOST环境变量，默认127.0.0.1（仅本机访问）
-                host: std::env::var("SERVER_HOST")
-                    .unwrap_or_else(|_| "127.0.0.1".to_string()),
-                
-                // SERVER_PORT环境变量，默认8080
-                port: std::env::var("SERVER_PORT")
-                    .unwrap_or_else(|_| "8080".to_string())
-                    .parse()
-                    .unwrap_or(8080),
-            },
-            database: DatabaseConfig {
-                // DATABASE_HOST环境变量，默认localhost
-                host: std::env::var("DATABASE_HOST")
-                    .unwrap_or_else(|_| "localhost".to_string()),
-                
-                // DATABASE_PORT环境变量，默认5432
-                port: std::env::var("DATABASE_PORT")
-                    .unwrap_or_else(|_| "5432".to_string())
-                    .parse()
-                    .unwrap_or(5432),
-                
-                // DATABASE_USER环境变量，默认postgres
-                user: std::env::var("DATABASE_USER")
-                    .unwrap_or_else(|_| "postgres".to_string()),
-                
-                // DATABASE_PASSWORD环境变量（必需）
-                // 如果未设置，应用将panic
-                password: std::env::var("DATABASE_PASSWORD")
-                    .expect("❌ DATABASE_PASSWORD环境变量未设置！请在.env文件中配置数据库密码"),
-                
-                // DATABASE_NAME环境变量，默认rookie_guide
-                database_name: std::env::var("DATABASE_NAME")
-                    .unwrap_or_else(|_| "rookie_guide".to_string()),
-                
-                // DATABASE_MAX_CONNECTIONS环境变量，默认5
-                max_connections: std::env::var("DATABASE_MAX_CONNECTIONS")
-                    .unwrap_or_else(|_| "5".to_string())
-                    .parse()
-                    .unwrap_or(5),
-            },
-            jwt: JwtConfig {
-                // JWT_SECRET环境变量（必需）
-                // 如果未设置，应用将panic
-                secret: std::env::var("JWT_SECRET")
-                    .expect("❌ JWT_SECRET环境变量未设置！请在.env文件中配置JWT密钥"),
-                
-                // JWT_EXPIRATION环境变量，默认86400秒（24小时）
-                expiration: std::env::var("JWT_EXPIRATION")
-                    .unwrap_or_else(|_| "86400".to_string())
-                    .parse()
-                    .unwrap_or(86400),
-            },
+        let app_env = std::env::var("APP_ENV").ok().filter(|v| !v.is_empty());
+        let environment = options.environment
+            .or_else(|| app_env.as_deref().and_then(|v| v.parse().ok()))
+            .unwrap_or_default();
+        
+        let source = Source::load(options, environment);
+        if options.environment.is_none() {
+            if let Err(message) = source.string("APP_ENV").map(|v| v.parse::<Environment>()).transpose() {
+                source.problem("APP_ENV", message);
+            }
+        }
+        
+        let config = Self::from_source(&source, environment);
+        source.finish()?;
+        
+        Ok(config)
+    }
+    
+    /// 从合并后的配置来源读取各配置项，问题记录在`source`中
+    fn from_source(source: &Source, environment: Environment) -> Self {
+        // SERVER_PORT，默认8080（本地对象存储的默认访问地址也用到）
+        let server = ServerConfig {
+            host: source.string_or("SERVER_HOST", "127.0.0.1"),
+            port: source.parse("SERVER_PORT", 8080),
+            trust_forwarded_for: source.bool("SERVER_TRUST_FORWARDED_FOR", false),
+        };
+        if server.host.parse::<IpAddr>().is_err() && !is_hostname(&server.host) {
+            source.problem("SERVER_HOST", format!("无效的监听地址: {}（需要IP地址或主机名）", server.host));
+        }
+        
+        let database = DatabaseConfig {
+            host: source.string_or("DATABASE_HOST", "localhost"),
+            port: source.parse("DATABASE_PORT", 5432),
+            user: source.string_or("DATABASE_USER", "postgres"),
+            password: source.required("DATABASE_PASSWORD", "数据库密码"),
+            database_name: source.string_or("DATABASE_NAME", "rookie_guide"),
+            max_connections: source.parse("DATABASE_MAX_CONNECTIONS", 5),
+        };
+        if database.max_connections == 0 {
+            source.problem("DATABASE_MAX_CONNECTIONS", "至少为1");
+        }
+        
+        let jwt = JwtConfig {
+            signing_key: JwtKeyConfig::signing_from_source(source, environment),
+            verification_keys: source
+                .list("JWT_VERIFICATION_KEYS")
+                .iter()
+                .filter_map(|kid| JwtKeyConfig::verification_from_source(source, kid, environment))
+                .collect(),
+            expiration: source.parse("JWT_EXPIRATION", 900),
+            refresh_expiration: source.parse("JWT_REFRESH_EXPIRATION", 2592000),
+        };
+        for (key, seconds) in [("JWT_EXPIRATION", jwt.expiration), ("JWT_REFRESH_EXPIRATION", jwt.refresh_expiration)] {
+            if seconds <= 0 {
+                source.problem(key, "必须大于0");
+            }
+        }
+        
+        let account = AccountConfig {
+            deletion_grace_days: source.parse("ACCOUNT_DELETION_GRACE_DAYS", 30),
+            purge_interval_seconds: source.parse("ACCOUNT_PURGE_INTERVAL_SECONDS", 3600),
+        };
+        if account.deletion_grace_days < 0 {
+            source.problem("ACCOUNT_DELETION_GRACE_DAYS", "不能为负数");
+        }
+        if account.purge_interval_seconds == 0 {
+            source.problem("ACCOUNT_PURGE_INTERVAL_SECONDS", "至少为1");
+        }
+        
+        let mail = MailConfig {
+            from: source.string_or("MAIL_FROM", "no-reply@localhost"),
+            smtp: SmtpConfig::from_source(source),
+            capture_dir: source.string("MAIL_CAPTURE_DIR"),
+            password_reset_url: source.string_or("PASSWORD_RESET_URL", "http://localhost:3000/reset-password"),
+        };
+        // Without SMTP, reset links only end up in the log and never reach the user
+        if mail.smtp.is_none() && environment == Environment::Production {
+            source.problem("SMTP_HOST", "生产环境必须配置SMTP服务器，否则找回密码邮件不会发出（只写入日志）");
+        }
+        
+        AppConfig {
+            environment,
+            database,
+            jwt,
+            oidc_providers: source
+                .list("OIDC_PROVIDERS")
+                .iter()
+                .filter_map(|name| OidcProviderConfig::from_source(source, name))
+                .collect(),
+            mail,
+            sms: SmsConfig::from_source(source, environment),
+            password: PasswordConfig::from_source(source),
+            account,
+            storage: StorageConfig::from_source(source, server.port),
+            server,
+        }
+    }
+    
+    /// 隐去密码、密钥等敏感信息的副本，用于打印配置
+    pub fn redacted(&self) -> Self {
+        let redact = |value: &mut String| {
+            if !value.is_empty() {
+                *value = REDACTED.to_string();
+            }
+        };
+        let redact_key = |key: &mut JwtKeyConfig| match &mut key.key {
+            JwtKey::Hs256 { secret } => redact(secret),
+            JwtKey::Rs256 { private_key_pem, .. } | JwtKey::EdDsa { private_key_pem, .. } => {
+                private_key_pem.iter_mut().for_each(redact)
+            }
+        };
+        
+        let mut config = self.clone();
+        redact(&mut config.database.password);
+        redact_key(&mut config.jwt.signing_key);
+        config.jwt.verification_keys.iter_mut().for_each(redact_key);
+        for provider in &mut config.oidc_providers {
+            provider.client_secret.iter_mut().for_each(redact);
+        }
+        if let Some(smtp) = &mut config.mail.smtp {
+            smtp.password.iter_mut().for_each(redact);
+        }
+        if let StorageBackend::S3(s3) = &mut config.storage.backend {
+            redact(&mut s3.secret_access_key);
+        }
+        config
+    }
+    
+    /// 以TOML格式输出配置（打印前先调用`redacted`）
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+}
+
+/// 打印配置时代替敏感信息
+const REDACTED: &str = "********";
+
+/// 生产环境HS256密钥的最小长度（字节）
+const MIN_JWT_SECRET_BYTES: usize = 32;
+
+fn is_hostname(host: &str) -> bool {
+    !host.is_empty()
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+impl StorageConfig {
+    /// 读取`STORAGE_*`和`S3_*`配置项
+    /// 
+    /// `server_port`用于本地存储的默认访问地址
+    fn from_source(source: &Source, server_port: u16) -> Self {
+        let required = |key: &str| source.required(key, "使用S3存储时必须配置");
+        
+        let (backend, default_public_base_url) = match source.string("STORAGE_BACKEND").as_deref() {
+            Some("s3") => {
+                let config = S3Config {
+                    endpoint: required("S3_ENDPOINT").trim_end_matches('/').to_string(),
+                    bucket: required("S3_BUCKET"),
+                    region: source.string_or("S3_REGION", "us-east-1"),
+                    access_key_id: required("S3_ACCESS_KEY_ID"),
+                    secret_access_key: required("S3_SECRET_ACCESS_KEY"),
+                    path_style: source.bool("S3_PATH_STYLE", true),
+                };
+                let default_url = match config.endpoint.split_once("://") {
+                    Some((scheme, authority)) if !config.path_style => {
+                        format!("{}://{}.{}", scheme, config.bucket, authority)
+                    }
+                    Some(_) => format!("{}/{}", config.endpoint, config.bucket),
+                    None => {
+                        if !config.endpoint.is_empty() {
+                            source.problem("S3_ENDPOINT", format!("无效的地址: {}（需要http://或https://开头）", config.endpoint));
+                        }
+                        String::new()
+                    }
+                };
+                (StorageBackend::S3(config), default_url)
+            }
+            other => {
+                if let Some(other) = other.filter(|v| *v != "local") {
+                    source.problem("STORAGE_BACKEND", format!("无效的值: {}（可选值: local, s3）", other));
+                }
+                let dir = source.string_or("STORAGE_LOCAL_DIR", "./uploads");
+                (StorageBackend::Local { dir }, format!("http://localhost:{}/uploads", server_port))
+            }
+        };
+        
+        StorageConfig {
+            backend,
+            public_base_url: source
+                .string("STORAGE_PUBLIC_BASE_URL")
+                .map(|url| url.trim_end_matches('/').to_string())
+                .unwrap_or(default_public_base_url),
+        }
+    }
+}
+
+impl PasswordConfig {
+    /// 读取`PASSWORD_ARGON2_*`配置项，未设置的参数使用默认值
+    fn from_source(source: &Source) -> Self {
+        let defaults = Self::default();
+        let config = PasswordConfig {
+            argon2_memory_kib: source.parse("PASSWORD_ARGON2_MEMORY_KIB", defaults.argon2_memory_kib),
+            argon2_iterations: source.parse("PASSWORD_ARGON2_ITERATIONS", defaults.argon2_iterations),
+            argon2_parallelism: source.parse("PASSWORD_ARGON2_PARALLELISM", defaults.argon2_parallelism),
+        };
+        
+        // Same limits as argon2::Params::new, checked here so a bad value fails at startup
+        if config.argon2_iterations < 1 {
+            source.problem("PASSWORD_ARGON2_ITERATIONS", "至少为1");
+        }
+        if !(1..=0xFFFFFF).contains(&config.argon2_parallelism) {
+            source.problem("PASSWORD_ARGON2_PARALLELISM", "至少为1");
+        }
+        if config.argon2_memory_kib < 8 * config.argon2_parallelism {
+            source.problem("PASSWORD_ARGON2_MEMORY_KIB", "至少为8 × PASSWORD_ARGON2_PARALLELISM");
+        }
+        
+        config
+    }
+}
+
+impl SmsConfig {
+    /// 读取`SMS_BACKEND`配置项，生产环境拒绝`log`
+    fn from_source(source: &Source, environment: Environment) -> Self {
+        let backend = match source.string("SMS_BACKEND").as_deref() {
+            None | Some("log") => SmsBackend::Log,
+            Some("disabled") => SmsBackend::Disabled,
+            Some(other) => {
+                source.problem("SMS_BACKEND", format!("无效的值: {}（可选值: log, disabled）", other));
+                return SmsConfig { backend: SmsBackend::Disabled };
+            }
+        };
+        
+        if backend == SmsBackend::Log && environment == Environment::Production {
+            source.problem(
+                "SMS_BACKEND",
+                "生产环境不能使用log（验证码只写入日志，不会发出）；接入短信网关之前设置为disabled，关闭验证码登录",
+            );
+        }
+        
+        SmsConfig { backend }
+    }
+}
+
+impl SmtpConfig {
+    /// 读取`SMTP_*`配置项，没有`SMTP_HOST`时返回`None`
+    fn from_source(source: &Source) -> Option<Self> {
+        let host = source.string("SMTP_HOST")?;
+        
+        let security = match source.string("SMTP_SECURITY").as_deref() {
+            None | Some("starttls") => SmtpSecurity::StartTls,
+            Some("tls") => SmtpSecurity::Tls,
+            Some("none") => SmtpSecurity::None,
+            Some(other) => {
+                source.problem("SMTP_SECURITY", format!("无效的值: {}（可选值: tls, starttls, none）", other));
+                SmtpSecurity::StartTls
+            }
+        };
+        
+        let default_port = match security {
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::None => 25,
+        };
+        
+        Some(SmtpConfig {
+            host,
+            port: source.parse("SMTP_PORT", default_port),
+            security,
+            username: source.string("SMTP_USERNAME"),
+            password: source.string("SMTP_PASSWORD"),
+        })
+    }
+}
+
+impl JwtKeyConfig {
+    /// 读取签名密钥：`JWT_ALGORITHM`、`JWT_SECRET`、`JWT_PRIVATE_KEY_FILE`、`JWT_PUBLIC_KEY_FILE`和`JWT_KEY_ID`
+    fn signing_from_source(source: &Source, environment: Environment) -> Self {
+        let kid = source.string("JWT_KEY_ID");
+        if let Some(kid) = &kid {
+            check_kid(source, "JWT_KEY_ID", kid);
+        }
+        
+        let key = match JwtAlgorithm::from_source(source, "JWT_ALGORITHM") {
+            JwtAlgorithm::Hs256 => {
+                let secret = source.required("JWT_SECRET", "使用HS256签名时必须配置");
+                check_secret_strength(source, "JWT_SECRET", &secret, environment);
+                JwtKey::Hs256 { secret }
+            }
+            algorithm => {
+                let hint = format!("使用{}签名时必须配置", algorithm.name());
+                let private_key_pem = read_pem(source, "JWT_PRIVATE_KEY_FILE", &hint);
+                algorithm.key(Some(private_key_pem), read_pem(source, "JWT_PUBLIC_KEY_FILE", &hint))
+            }
+        };
+        
+        JwtKeyConfig { kid, key }
+    }
+    
+    /// 读取`JWT_KEY_<KID>_*`中一个只用于验证的旧密钥，ID无效时返回`None`
+    fn verification_from_source(source: &Source, kid: &str, environment: Environment) -> Option<Self> {
+        if !check_kid(source, "JWT_VERIFICATION_KEYS", kid) {
+            return None;
+        }
+        
+        let prefix = format!("JWT_KEY_{}_", normalize_key(kid));
+        let hint = format!("JWT旧密钥{}需要", kid);
+        
+        let key = match JwtAlgorithm::from_source(source, &format!("{}ALGORITHM", prefix)) {
+            JwtAlgorithm::Hs256 => {
+                let key = format!("{}SECRET", prefix);
+                let secret = source.required(&key, &hint);
+                check_secret_strength(source, &key, &secret, environment);
+                JwtKey::Hs256 { secret }
+            }
+            algorithm => algorithm.key(None, read_pem(source, &format!("{}PUBLIC_KEY_FILE", prefix), &hint)),
+        };
+        
+        Some(JwtKeyConfig { kid: Some(kid.to_string()), key })
+    }
+}
+
+/// 配置中的JWT签名算法
+#[derive(Clone, Copy)]
+enum JwtAlgorithm {
+    Hs256,
+    Rs256,
+    EdDsa,
+}
+
+impl JwtAlgorithm {
+    fn from_source(source: &Source, key: &str) -> Self {
+        match source.string(key).as_deref() {
+            None | Some("HS256") => JwtAlgorithm::Hs256,
+            Some("RS256") => JwtAlgorithm::Rs256,
+            Some("EdDSA") => JwtAlgorithm::EdDsa,
+            Some(other) => {
+                source.problem(key, format!("无效的值: {}（可选值: HS256, RS256, EdDSA）", other));
+                JwtAlgorithm::Hs256
+            }
+        }
+    }
+    
+    fn name(self) -> &'static str {
+        match self {
+            JwtAlgorithm::Hs256 => "HS256",
+            JwtAlgorithm::Rs256 => "RS256",
+            JwtAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+    
+    /// 非对称算法的密钥
+    fn key(self, private_key_pem: Option<String>, public_key_pem: String) -> JwtKey {
+        match self {
+            JwtAlgorithm::Rs256 => JwtKey::Rs256 { private_key_pem, public_key_pem },
+            _ => JwtKey::EdDsa { private_key_pem, public_key_pem },
+        }
+    }
+}
+
+/// 密钥ID同时用在配置项的名称中，只允许字母、数字、`-`和`_`
+fn check_kid(source: &Source, key: &str, kid: &str) -> bool {
+    let valid = kid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        source.problem(key, format!("无效的密钥ID: {}（只允许字母、数字、-和_）", kid));
+    }
+    valid
+}
+
+/// 生产环境拒绝太短或明显是示例的HS256密钥
+fn check_secret_strength(source: &Source, key: &str, secret: &str, environment: Environment) {
+    if environment != Environment::Production || secret.is_empty() {
+        return;
+    }
+    
+    let distinct = secret.chars().collect::<std::collections::HashSet<_>>().len();
+    if secret.len() < MIN_JWT_SECRET_BYTES {
+        source.problem(key, format!(
+            "生产环境的JWT密钥至少需要{}个字节（可以用`openssl rand -base64 64`生成）",
+            MIN_JWT_SECRET_BYTES
+        ));
+    } else if distinct < 10 || secret.contains("change-this") {
+        source.problem(key, "生产环境不能使用示例密钥或重复字符组成的密钥（可以用`openssl rand -base64 64`生成）");
+    }
+}
+
+/// 读取配置项`key`指定的PEM文件
+fn read_pem(source: &Source, key: &str, hint: &str) -> String {
+    let path = source.required(key, hint);
+    if path.is_empty() {
+        return String::new();
+    }
+    
+    std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        source.problem(key, format!("无法读取密钥文件{}: {}", path, e));
+        String::new()
+    })
+}
+
+impl OidcProviderConfig {
+    /// 读取一个提供方的`OIDC_<NAME>_*`配置项，名称无效时返回`None`
+    fn from_source(source: &Source, name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            source.problem("OIDC_PROVIDERS", format!("无效的提供方名称: {}", name));
+            return None;
+        }
+        
+        let prefix = format!("OIDC_{}_", normalize_key(&name));
+        let hint = format!("第三方登录提供方{}需要", name);
+        let required = |key: &str| source.required(&format!("{}{}", prefix, key), &hint);
+        
+        Some(OidcProviderConfig {
+            issuer: required("ISSUER"),
+            client_id: required("CLIENT_ID"),
+            client_secret: source.string(&format!("{}CLIENT_SECRET", prefix)),
+            redirect_uri: required("REDIRECT_URI"),
+            scopes: source.string_or(&format!("{}SCOPES", prefix), "openid email profile"),
+            name,
         })
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(values: &[(&str, &str)]) -> Source {
+        let mut source = Source::empty();
+        for (key, value) in values {
+            source.insert(normalize_key(key), value.to_string(), ConfigOrigin::Cli);
+        }
+        source
+    }
+
+    fn problems(source: Source) -> Vec<String> {
+        source.finish().err().map(|e| e.problems.into_iter().map(|p| p.key).collect()).unwrap_or_default()
+    }
+
+    #[test]
+    fn reads_defaults_and_overrides() {
+        let source = source(&[
+            ("database.password", "x"),
+            ("jwt.secret", "short"),
+            ("server.host", "0.0.0.0"),
+            ("SERVER_PORT", "9000"),
+            ("server.trust_forwarded_for", "yes"),
+            ("oidc.providers", "google"),
+            ("oidc.google.issuer", "https://accounts.google.com"),
+            ("oidc.google.client_id", "id"),
+            ("oidc.google.redirect_uri", "https://app.example.com/callback"),
+        ]);
+        let config = AppConfig::from_source(&source, Environment::Development);
+        assert_eq!(problems(source), Vec::<String>::new());
+
+        assert_eq!((config.server.host.as_str(), config.server.port), ("0.0.0.0", 9000));
+        assert!(config.server.trust_forwarded_for);
+        assert_eq!(config.database.max_connections, 5);
+        assert_eq!(config.jwt.expiration, 900);
+        assert_eq!(config.oidc_providers[0].scopes, "openid email profile");
+        assert_eq!(config.storage.public_base_url, "http://localhost:9000/uploads");
+    }
+
+    #[test]
+    fn reports_every_problem_at_once() {
+        let source = source(&[
+            ("SERVER_PORT", "http"),
+            ("SERVER_HOST", "not a host"),
+            ("DATABASE_MAX_CONNECTIONS", "0"),
+            ("JWT_ALGORITHM", "RS256"),
+            ("STORAGE_BACKEND", "ftp"),
+            ("SERVER_PROT", "9000"),
+        ]);
+        let _ = AppConfig::from_source(&source, Environment::Development);
+
+        assert_eq!(
+            problems(source),
+            [
+                "SERVER_PORT",
+                "SERVER_HOST",
+                "DATABASE_PASSWORD",
+                "DATABASE_MAX_CONNECTIONS",
+                "JWT_PRIVATE_KEY_FILE",
+                "JWT_PUBLIC_KEY_FILE",
+                "STORAGE_BACKEND",
+                "SERVER_PROT",
+            ]
+        );
+    }
+
+    #[test]
+    fn production_requires_real_mail_and_sms_delivery() {
+        let strong = "3q2+7wAAAAC6bPz0l8x5V1uYk2T9rJmHcQeXgWfDsNaRiLoEpGt";
+        let base = [("DATABASE_PASSWORD", "x"), ("JWT_SECRET", strong)];
+
+        let development = source(&base);
+        let config = AppConfig::from_source(&development, Environment::Development);
+        assert_eq!(problems(development), Vec::<String>::new());
+        assert_eq!(config.sms.backend, SmsBackend::Log);
+
+        let production = source(&base);
+        let _ = AppConfig::from_source(&production, Environment::Production);
+        assert_eq!(problems(production), ["SMTP_HOST", "SMS_BACKEND"]);
+
+        let configured = source(&[base[0], base[1], ("SMTP_HOST", "smtp.example.com"), ("SMS_BACKEND", "disabled")]);
+        let config = AppConfig::from_source(&configured, Environment::Production);
+        assert_eq!(problems(configured), Vec::<String>::new());
+        assert_eq!(config.sms.backend, SmsBackend::Disabled);
+    }
+
+    #[test]
+    fn requires_a_strong_jwt_secret_in_production() {
+        let strong = "3q2+7wAAAAC6bPz0l8x5V1uYk2T9rJmHcQeXgWfDsNaRiLoEpGt";
+        for (secret, environment, ok) in [
+            ("short", Environment::Development, true),
+            ("short", Environment::Production, false),
+            ("your-super-secret-jwt-key-change-this-in-production", Environment::Production, false),
+            (&"a".repeat(64), Environment::Production, false),
+            (strong, Environment::Production, true),
+        ] {
+            let source = source(&[
+                ("DATABASE_PASSWORD", "x"),
+                ("JWT_SECRET", secret),
+                ("SMTP_HOST", "smtp.example.com"),
+                ("SMS_BACKEND", "disabled"),
+            ]);
+            let _ = AppConfig::from_source(&source, environment);
+            assert_eq!(problems(source).is_empty(), ok, "{} in {}", secret, environment);
+        }
+    }
+
+    #[test]
+    fn flattens_config_files_and_redacts_secrets() {
+        let path = std::env::temp_dir().join(format!("rookie-guide-config-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"
+            [database]
+            password = "db-password"
+            max_connections = 20
+
+            [jwt]
+            secret = "jwt-secret"
+            verification_keys = ["2026-07"]
+
+            [jwt.key.2026-07]
+            secret = "old-secret"
+            "#,
+        )
+        .unwrap();
+
+        let mut source = Source::empty();
+        source.add_file(&path, true);
+        source.insert("DATABASE_MAX_CONNECTIONS".to_string(), "30".to_string(), ConfigOrigin::Cli);
+        let config = AppConfig::from_source(&source, Environment::Test);
+        assert_eq!(problems(source), Vec::<String>::new());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.database.max_connections, 30);
+        assert_eq!(config.jwt.verification_keys[0].kid.as_deref(), Some("2026-07"));
+
+        let dump = config.redacted().to_toml().unwrap();
+        assert!(dump.contains("environment = \"test\""), "{}", dump);
+        for secret in ["db-password", "jwt-secret", "old-secret"] {
+            assert!(!dump.contains(secret), "{}", dump);
+        }
+    }
+}
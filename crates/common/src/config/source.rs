@@ -0,0 +1,336 @@
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 运行环境
+/// 
+/// 决定读取哪个配置文件（`config/{环境}.toml`），生产环境还会做更严格的检查（如JWT密钥强度）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    /// 开发环境（默认）
+    #[default]
+    Development,
+    
+    /// 测试环境
+    Test,
+    
+    /// 生产环境
+    Production,
+}
+
+impl Environment {
+    /// 配置文件名中使用的名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Environment::Development => "development",
+            Environment::Test => "test",
+            Environment::Production => "production",
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    /// 接受全称和`cargo dev`/`cargo prod`别名使用的简称
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "development" | "dev" => Ok(Environment::Development),
+            "test" => Ok(Environment::Test),
+            "production" | "prod" => Ok(Environment::Production),
+            _ => Err(format!("无效的运行环境: {}（可选值: development, test, production）", value)),
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 加载配置的选项（通常来自命令行参数）
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOptions {
+    /// 运行环境，未指定时读取`APP_ENV`环境变量，默认`development`
+    pub environment: Option<Environment>,
+    
+    /// 配置文件，指定后不再读取`config/`目录下的默认文件，文件必须存在
+    pub config_file: Option<PathBuf>,
+    
+    /// 命令行覆盖的配置项（键可以写成`server.port`或`SERVER_PORT`），优先级最高
+    pub overrides: Vec<(String, String)>,
+}
+
+/// 配置项的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    /// 配置文件
+    File(PathBuf),
+    
+    /// 环境变量（包括.env文件）
+    Env,
+    
+    /// 命令行参数
+    Cli,
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigOrigin::File(path) => write!(f, "{}", path.display()),
+            ConfigOrigin::Env => f.write_str("环境变量"),
+            ConfigOrigin::Cli => f.write_str("命令行"),
+        }
+    }
+}
+
+/// 一个配置问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// 配置项（环境变量形式的键，如`SERVER_PORT`）
+    pub key: String,
+    
+    /// 配置项的来源（未设置的必需项没有来源）
+    pub origin: Option<ConfigOrigin>,
+    
+    /// 问题描述
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.origin {
+            Some(origin) => write!(f, "{}（来自{}）: {}", self.key, origin, self.message),
+            None => write!(f, "{}: {}", self.key, self.message),
+        }
+    }
+}
+
+/// 配置无效，包含发现的所有问题
+#[derive(Debug, Clone, thiserror::Error)]
+pub struct ConfigError {
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "❌ 配置无效（{}个问题）:", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+struct Value {
+    raw: String,
+    origin: ConfigOrigin,
+}
+
+/// 合并后的配置来源：配置文件 < 环境变量 < 命令行，后面的覆盖前面的
+/// 
+/// 所有来源都换算成环境变量形式的键：配置文件中的`[database] max_connections`
+/// 和命令行的`--set database.max_connections=20`都对应`DATABASE_MAX_CONNECTIONS`。
+/// 读取时出现的问题先记录下来，全部读完后一起报告。
+pub(crate) struct Source {
+    values: HashMap<String, Value>,
+    used: RefCell<BTreeSet<String>>,
+    problems: RefCell<Vec<ConfigProblem>>,
+}
+
+impl Source {
+    /// 按选项读取配置文件、环境变量和命令行覆盖的配置项
+    pub(crate) fn load(options: &ConfigOptions, environment: Environment) -> Self {
+        let mut source = Source::empty();
+
+        let files = match &options.config_file {
+            Some(path) => vec![(path.clone(), true)],
+            None => vec![
+                (PathBuf::from("config/default.toml"), false),
+                (PathBuf::from(format!("config/{}.toml", environment)), false),
+            ],
+        };
+        for (path, required) in files {
+            source.add_file(&path, required);
+        }
+
+        for (key, raw) in std::env::vars() {
+            source.insert(key, raw, ConfigOrigin::Env);
+        }
+
+        for (key, raw) in &options.overrides {
+            source.insert(normalize_key(key), raw.clone(), ConfigOrigin::Cli);
+        }
+
+        source
+    }
+
+    pub(crate) fn empty() -> Self {
+        Source {
+            values: HashMap::new(),
+            used: RefCell::new(BTreeSet::new()),
+            problems: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// 空值等同于未设置，不会覆盖前面来源中的值
+    pub(crate) fn insert(&mut self, key: String, raw: String, origin: ConfigOrigin) {
+        if !raw.is_empty() {
+            self.values.insert(key, Value { raw, origin });
+        }
+    }
+
+    pub(crate) fn add_file(&mut self, path: &Path, required: bool) {
+        let origin = ConfigOrigin::File(path.to_path_buf());
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if !required && e.kind() == std::io::ErrorKind::NotFound => return,
+            Err(e) => return self.problem_at(&path.display().to_string(), None, format!("无法读取配置文件: {}", e)),
+        };
+
+        match content.parse::<toml::Table>() {
+            Ok(table) => self.add_table(String::new(), table, &origin),
+            Err(e) => self.problem_at(&path.display().to_string(), None, format!("配置文件格式错误: {}", e)),
+        }
+    }
+
+    fn add_table(&mut self, prefix: String, table: toml::Table, origin: &ConfigOrigin) {
+        for (name, value) in table {
+            let key = normalize_key(&if prefix.is_empty() { name } else { format!("{}_{}", prefix, name) });
+            let raw = match value {
+                toml::Value::Table(table) => {
+                    self.add_table(key, table, origin);
+                    continue;
+                }
+                toml::Value::String(s) => s,
+                toml::Value::Array(items) => {
+                    // Lists are written like the comma-separated environment variables
+                    let items: Option<Vec<String>> = items.into_iter().map(scalar_to_string).collect();
+                    match items {
+                        Some(items) => items.join(","),
+                        None => {
+                            self.problem_at(&key, Some(origin.clone()), "列表中只能包含字符串、数字或布尔值");
+                            continue;
+                        }
+                    }
+                }
+                other => scalar_to_string(other).unwrap_or_default(),
+            };
+            self.insert(key, raw, origin.clone());
+        }
+    }
+
+    fn problem_at(&self, key: &str, origin: Option<ConfigOrigin>, message: impl Into<String>) {
+        self.problems.borrow_mut().push(ConfigProblem {
+            key: key.to_string(),
+            origin,
+            message: message.into(),
+        });
+    }
+
+    /// 记录`key`的问题（带上它的来源）
+    pub(crate) fn problem(&self, key: &str, message: impl Into<String>) {
+        self.problem_at(key, self.values.get(key).map(|v| v.origin.clone()), message);
+    }
+
+    /// 读取字符串，未设置时为`None`
+    pub(crate) fn string(&self, key: &str) -> Option<String> {
+        self.used.borrow_mut().insert(key.to_string());
+        self.values.get(key).map(|v| v.raw.clone())
+    }
+
+    pub(crate) fn string_or(&self, key: &str, default: &str) -> String {
+        self.string(key).unwrap_or_else(|| default.to_string())
+    }
+
+    /// 读取必需的字符串，未设置时记录问题（`hint`说明为什么需要）
+    pub(crate) fn required(&self, key: &str, hint: &str) -> String {
+        self.string(key).unwrap_or_else(|| {
+            self.problem(key, format!("未设置（{}）", hint));
+            String::new()
+        })
+    }
+
+    /// 解析数字等值，未设置时使用默认值，格式错误时记录问题
+    pub(crate) fn parse<T: FromStr>(&self, key: &str, default: T) -> T {
+        match self.string(key) {
+            None => default,
+            Some(raw) => raw.trim().parse().unwrap_or_else(|_| {
+                self.problem(key, format!("无效的值: {}", raw));
+                default
+            }),
+        }
+    }
+
+    /// 读取布尔值（`true`/`false`、`1`/`0`、`yes`/`no`）
+    pub(crate) fn bool(&self, key: &str, default: bool) -> bool {
+        match self.string(key).map(|raw| raw.trim().to_ascii_lowercase()) {
+            None => default,
+            Some(raw) => match raw.as_str() {
+                "1" | "true" | "yes" | "on" => true,
+                "0" | "false" | "no" | "off" => false,
+                _ => {
+                    self.problem(key, format!("无效的布尔值: {}（可选值: true, false）", raw));
+                    default
+                }
+            },
+        }
+    }
+
+    /// 读取逗号分隔的列表
+    pub(crate) fn list(&self, key: &str) -> Vec<String> {
+        self.string(key)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 结束读取：配置文件和命令行中没有被读取的键视为拼写错误；没有问题时返回`Ok`
+    /// 
+    /// 环境变量中有大量与本应用无关的变量，不做检查
+    pub(crate) fn finish(self) -> Result<(), ConfigError> {
+        let used = self.used.into_inner();
+        let mut problems = self.problems.into_inner();
+
+        let mut unknown: Vec<_> = self.values
+            .into_iter()
+            .filter(|(key, value)| value.origin != ConfigOrigin::Env && !used.contains(key))
+            .collect();
+        unknown.sort_by(|a, b| a.0.cmp(&b.0));
+        problems.extend(unknown.into_iter().map(|(key, value)| ConfigProblem {
+            key,
+            origin: Some(value.origin),
+            message: "未知的配置项（拼写错误，或所属的功能未启用？）".to_string(),
+        }));
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError { problems })
+        }
+    }
+}
+
+/// `server.port`、`server-port`和`SERVER_PORT`都换算成`SERVER_PORT`
+pub(crate) fn normalize_key(key: &str) -> String {
+    key.trim().replace(['.', '-'], "_").to_ascii_uppercase()
+}
+
+fn scalar_to_string(value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s),
+        toml::Value::Integer(i) => Some(i.to_string()),
+        toml::Value::Float(f) => Some(f.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        toml::Value::Datetime(d) => Some(d.to_string()),
+        toml::Value::Array(_) | toml::Value::Table(_) => None,
+    }
+}
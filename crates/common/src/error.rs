@@ -1,4 +1,7 @@
+use serde::Serialize;
 use std::fmt;
+use utoipa::ToSchema;
+use validator::{ValidationErrors, ValidationErrorsKind};
 
 /// 应用程序统一结果类型
 /// 
@@ -36,10 +39,26 @@ pub type AppResult<T> = Result<T, AppError>;
 /// - 业务规则违反
 /// - 参数缺失
 /// 
-/// ### AuthError - 认证/授权失败
+/// ### InvalidFields - 字段校验失败
+/// - DTO上`validator`规则不满足（长度、邮箱格式等），逐个字段给出原因
+/// 
+/// ### AuthError - 认证失败
 /// - 密码错误
 /// - Token无效/过期
-/// - 权限不足
+/// 
+/// ### Forbidden - 无权限
+/// - 修改/删除他人创建的模板
+/// - 访问不属于自己的资源
+/// 
+/// ### Conflict - 资源冲突
+/// - 手机号/邮箱已注册
+/// - 第三方账号已被绑定
+/// 
+/// ### PayloadTooLarge - 请求体过大
+/// - 上传的文件超过大小上限
+/// 
+/// ### TooManyRequests - 请求过于频繁
+/// - 验证码发送太频繁
 /// 
 /// ### InternalError - 内部错误
 /// - 未预期的错误
@@ -62,11 +81,36 @@ pub enum AppError {
     /// 应返回HTTP 400，用于客户端提交的数据不符合要求
     ValidationError(String),
     
-    /// 认证/授权错误
+    /// 字段校验错误
+    /// 
+    /// 应返回HTTP 400，响应中逐个列出不满足规则的字段；由`validator::ValidationErrors`转换而来
+    InvalidFields(Vec<FieldError>),
+    
+    /// 认证错误
     /// 
-    /// 应返回HTTP 401（未认证）或403（无权限）
+    /// 应返回HTTP 401，用于未登录或凭证无效
     AuthError(String),
     
+    /// 无权限错误
+    /// 
+    /// 应返回HTTP 403，用于已登录但无权操作目标资源
+    Forbidden(String),
+    
+    /// 资源冲突错误
+    /// 
+    /// 应返回HTTP 409，用于要创建或绑定的资源已经存在
+    Conflict(String),
+    
+    /// 请求体过大错误
+    /// 
+    /// 应返回HTTP 413，用于上传的内容超过大小上限
+    PayloadTooLarge(String),
+    
+    /// 请求频率超限错误
+    /// 
+    /// 应返回HTTP 429，用于需要限制调用频率的操作
+    TooManyRequests(String),
+    
     /// 内部服务器错误
     /// 
     /// 应返回HTTP 500，用于未预期的错误
@@ -79,7 +123,21 @@ impl fmt::Display for AppError {
             AppError::DatabaseError(msg) => write!(f, "数据库错误: {}", msg),
             AppError::NotFound(msg) => write!(f, "未找到: {}", msg),
             AppError::ValidationError(msg) => write!(f, "验证错误: {}", msg),
+            AppError::InvalidFields(fields) => {
+                write!(f, "验证错误: ")?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}: {}", field.field, field.message)?;
+                }
+                Ok(())
+            }
             AppError::AuthError(msg) => write!(f, "认证错误: {}", msg),
+            AppError::Forbidden(msg) => write!(f, "无权限: {}", msg),
+            AppError::Conflict(msg) => write!(f, "冲突: {}", msg),
+            AppError::PayloadTooLarge(msg) => write!(f, "请求体过大: {}", msg),
+            AppError::TooManyRequests(msg) => write!(f, "请求过于频繁: {}", msg),
             AppError::InternalError(msg) => write!(f, "内部错误: {}", msg),
         }
     }
@@ -87,6 +145,131 @@ impl fmt::Display for AppError {
 
 impl std::error::Error for AppError {}
 
+impl AppError {
+    /// 机器可读的错误码
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::DatabaseError(_) | AppError::InternalError(_) => ErrorCode::InternalError,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::ValidationError(_) => ErrorCode::BadRequest,
+            AppError::InvalidFields(_) => ErrorCode::ValidationFailed,
+            AppError::AuthError(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            AppError::TooManyRequests(_) => ErrorCode::RateLimited,
+        }
+    }
+}
+
+/// 机器可读的错误码
+/// 
+/// 出现在失败响应的`error.code`中，客户端应根据错误码（而不是`message`）判断错误类型。
+/// 每个错误码对应固定的HTTP状态码。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// 400 - 请求不符合业务规则（如前置步骤未完成）
+    BadRequest,
+    
+    /// 400 - 字段校验失败，`error.fields`中列出每个字段的原因
+    ValidationFailed,
+    
+    /// 401 - 未登录或凭证无效
+    Unauthorized,
+    
+    /// 403 - 无权操作目标资源
+    Forbidden,
+    
+    /// 404 - 资源不存在
+    NotFound,
+    
+    /// 409 - 资源已存在
+    Conflict,
+    
+    /// 413 - 请求体过大
+    PayloadTooLarge,
+    
+    /// 429 - 请求过于频繁
+    RateLimited,
+    
+    /// 500 - 服务器内部错误，`error.correlation_id`可用于在日志中查找原因
+    InternalError,
+}
+
+/// 字段校验错误
+/// 
+/// ## 示例
+/// ```json
+/// { "field": "steps[0].title", "code": "length", "message": "Length must be between 1 and 500" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// 字段路径（嵌套字段用`.`连接，列表元素带下标）
+    pub field: String,
+    
+    /// 不满足的规则（`length`、`email`或自定义规则名）
+    pub code: String,
+    
+    /// 错误描述
+    pub message: String,
+}
+
+/// 将`validator`的校验结果转换为字段级别的错误
+/// 
+/// 这允许在Service层直接写`dto.validate()?`
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields = Vec::new();
+        collect_field_errors(&errors, "", &mut fields);
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        AppError::InvalidFields(fields)
+    }
+}
+
+fn collect_field_errors(errors: &ValidationErrors, prefix: &str, fields: &mut Vec<FieldError>) {
+    for (name, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                fields.extend(errors.iter().map(|error| FieldError {
+                    field: path.clone(),
+                    code: error.code.to_string(),
+                    message: describe_validation_error(error),
+                }));
+            }
+            ValidationErrorsKind::Struct(errors) => collect_field_errors(errors, &path, fields),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_field_errors(errors, &format!("{}[{}]", path, index), fields);
+                }
+            }
+        }
+    }
+}
+
+/// 规则自带的描述，没有时按常用规则的参数生成
+fn describe_validation_error(error: &validator::ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("length", Some(min), Some(max)) if min == max => format!("Length must be {}", min),
+        ("length", Some(min), Some(max)) => format!("Length must be between {} and {}", min, max),
+        ("length", Some(min), None) => format!("Length must be at least {}", min),
+        ("length", None, Some(max)) => format!("Length must be at most {}", max),
+        ("email", ..) => "Must be a valid email address".to_string(),
+        (code, ..) => format!("Failed the {} check", code),
+    }
+}
+
 /// 自动将anyhow错误转换为AppError
 /// 
 /// 用于处理通用错误场景
@@ -98,10 +281,16 @@ impl From<anyhow::Error> for AppError {
 
 /// 自动将SeaORM DbErr转换为AppError
 /// 
-/// 这允许在Repository层使用`?`操作符直接传播SeaORM错误
+/// 这允许在Repository层使用`?`操作符直接传播SeaORM错误。
+/// 违反唯一约束（并发注册同一个邮箱等）转换为`Conflict`，其余为`DatabaseError`
 impl From<sea_orm::DbErr> for AppError {
     fn from(err: sea_orm::DbErr) -> Self {
-        AppError::DatabaseError(err.to_string())
+        match err.sql_err() {
+            Some(sea_orm::SqlErr::UniqueConstraintViolation(_)) => {
+                AppError::Conflict("Resource already exists".to_string())
+            }
+            _ => AppError::DatabaseError(err.to_string()),
+        }
     }
 }
 
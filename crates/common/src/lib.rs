@@ -2,7 +2,12 @@ pub mod config;
 pub mod error;
 pub mod api_response;
 
-pub use config::AppConfig;
-pub use error::{AppError, AppResult};
-pub use api_response::{ApiResponse, ApiError};
+pub use config::{
+    AppConfig, ConfigOptions, ConfigError, ConfigProblem, ConfigOrigin, Environment,
+    ServerConfig, DatabaseConfig,
+    JwtConfig, JwtKeyConfig, JwtKey, OidcProviderConfig, MailConfig, SmtpConfig, SmtpSecurity, SmsConfig, SmsBackend,
+    PasswordConfig, AccountConfig, StorageConfig, StorageBackend, S3Config,
+};
+pub use error::{AppError, AppResult, ErrorCode, FieldError};
+pub use api_response::{ApiResponse, ApiResult, ApiError, ErrorResponse};
 
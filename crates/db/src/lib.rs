@@ -13,8 +13,17 @@
 /// │   └── create_pool()                # 创建PgPool
 /// └── repositories/                    # Repository层
 ///     ├── template_repository.rs       # 模板数据访问
+///     ├── location_repository.rs       # 地区数据访问
 ///     ├── user_repository.rs           # 用户数据访问
-///     └── user_checklist_repository.rs # 清单数据访问
+///     ├── refresh_token_repository.rs  # 刷新令牌数据访问
+///     ├── verification_code_repository.rs # 短信验证码数据访问
+///     ├── password_reset_token_repository.rs # 找回密码令牌数据访问
+///     ├── login_attempt_repository.rs  # 登录失败计数和锁定审计数据访问
+///     ├── account_repository.rs        # 账号级数据访问（导出、彻底删除）
+///     ├── user_identity_repository.rs  # 第三方账号绑定数据访问
+///     ├── oauth_state_repository.rs    # 第三方授权请求数据访问
+///     ├── user_checklist_repository.rs # 清单数据访问
+///     └── memory/                      # 内存实现（启用`testing`特性时可用）
 /// ```
 /// 
 /// ## Repository模式
@@ -58,16 +67,36 @@
 pub mod pool;
 pub mod repositories;
 
+// 内存中的Repository实现，只用于测试（`db = { features = ["testing"] }`）
+#[cfg(feature = "testing")]
+pub use repositories::memory;
+
 // 从pool模块导出创建连接池的函数
 pub use pool::create_database_connection;
 
 // 从repositories模块导出所有Repository接口和实现
 // - TemplateRepository/TemplateRepositoryImpl: 模板数据访问
+// - LocationRepository/LocationRepositoryImpl: 地区数据访问
 // - UserRepository/UserRepositoryImpl: 用户数据访问
+// - RefreshTokenRepository/RefreshTokenRepositoryImpl: 刷新令牌数据访问
+// - VerificationCodeRepository/VerificationCodeRepositoryImpl: 短信验证码数据访问
+// - PasswordResetTokenRepository/PasswordResetTokenRepositoryImpl: 找回密码令牌数据访问
+// - LoginAttemptRepository/LoginAttemptRepositoryImpl: 登录失败计数和锁定审计数据访问
+// - AccountRepository/AccountRepositoryImpl: 账号级数据访问（导出、彻底删除）
+// - UserIdentityRepository/UserIdentityRepositoryImpl: 第三方账号绑定数据访问
+// - OAuthStateRepository/OAuthStateRepositoryImpl: 第三方授权请求数据访问
 // - UserChecklistRepository/UserChecklistRepositoryImpl: 清单数据访问
 pub use repositories::{
     TemplateRepository, TemplateRepositoryImpl,
+    LocationRepository, LocationRepositoryImpl,
     UserRepository, UserRepositoryImpl,
+    RefreshTokenRepository, RefreshTokenRepositoryImpl,
+    VerificationCodeRepository, VerificationCodeRepositoryImpl,
+    PasswordResetTokenRepository, PasswordResetTokenRepositoryImpl,
+    LoginAttemptRepository, LoginAttemptRepositoryImpl,
+    AccountRepository, AccountRepositoryImpl,
+    UserIdentityRepository, UserIdentityRepositoryImpl,
+    OAuthStateRepository, OAuthStateRepositoryImpl,
     UserChecklistRepository, UserChecklistRepositoryImpl,
 };
 
@@ -0,0 +1,209 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::AppResult;
+use models::{
+    User, Template, TemplateVersion, LoginScope, DELETED_USER_ID,
+    UserEntity, UserColumn, TemplateEntity, TemplateColumn, TemplateVersionEntity, TemplateVersionColumn,
+    LoginAttemptEntity, LoginAttemptColumn, LoginLockoutEntity, LoginLockoutColumn,
+    VerificationCodeEntity, VerificationCodeColumn, OrphanedFile, OrphanedFileEntity, OrphanedFileColumn,
+};
+use sea_orm::{
+    DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, ColumnTrait, TransactionTrait, Set,
+    sea_query::{Expr, OnConflict},
+};
+use uuid::Uuid;
+
+/// 账号数据Repository接口
+/// 
+/// 跨多张表的账号级操作：导出用户创建的内容、查找和彻底删除注销宽限期已过的账号。
+/// 
+/// ## 彻底删除
+/// 
+/// - 用户创建的模板和发布的模板版本转给占位用户（`DELETED_USER_ID`），
+///   其他用户基于这些模板的清单不受影响
+/// - 清单、刷新令牌、第三方账号绑定等随用户级联删除
+/// - 登录失败计数、锁定记录和短信验证码按账号（邮箱、手机号）一并删除
+/// - 对象存储中的头像文件不在事务内，删除失败时记为待清理文件（`orphaned_files`），之后重试
+#[async_trait]
+pub trait AccountRepository: Send + Sync {
+    /// 用户创建的所有模板（含已删除的），按创建时间排序
+    async fn find_authored_templates(&self, user_id: Uuid) -> AppResult<Vec<Template>>;
+
+    /// 用户发布的所有模板版本（含修改他人模板时发布的），按发布时间排序
+    async fn find_authored_versions(&self, user_id: Uuid) -> AppResult<Vec<TemplateVersion>>;
+
+    /// 在`requested_before`之前申请注销的账号，最早申请的在前，最多`limit`个
+    async fn find_due_for_purge(&self, requested_before: DateTime<Utc>, limit: u64) -> AppResult<Vec<User>>;
+
+    /// 彻底删除账号（在一个事务中）
+    /// 
+    /// ## 返回值
+    /// - `true`: 已删除
+    /// - `false`: 账号不存在，或已撤销注销（申请时间不早于`requested_before`），没有删除任何数据
+    async fn purge(&self, user_id: Uuid, requested_before: DateTime<Utc>) -> AppResult<bool>;
+
+    /// 记录未能删除的对象存储文件（对象键前缀），已记录过的前缀忽略
+    async fn record_orphaned_files(&self, prefix: &str) -> AppResult<()>;
+
+    /// 待清理的文件，最早记录的在前，最多`limit`个
+    async fn find_orphaned_files(&self, limit: u64) -> AppResult<Vec<OrphanedFile>>;
+
+    /// 文件已删除，移除记录
+    async fn delete_orphaned_files(&self, prefix: &str) -> AppResult<()>;
+}
+
+/// 账号数据Repository的SeaORM实现
+#[derive(Clone)]
+pub struct AccountRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl AccountRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl AccountRepository for AccountRepositoryImpl {
+    async fn find_authored_templates(&self, user_id: Uuid) -> AppResult<Vec<Template>> {
+        let templates = TemplateEntity::find()
+            .filter(TemplateColumn::CreatedBy.eq(user_id))
+            .order_by_asc(TemplateColumn::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(templates)
+    }
+
+    async fn find_authored_versions(&self, user_id: Uuid) -> AppResult<Vec<TemplateVersion>> {
+        let versions = TemplateVersionEntity::find()
+            .filter(TemplateVersionColumn::CreatedBy.eq(user_id))
+            .order_by_asc(TemplateVersionColumn::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(versions)
+    }
+
+    async fn find_due_for_purge(&self, requested_before: DateTime<Utc>, limit: u64) -> AppResult<Vec<User>> {
+        let users = UserEntity::find()
+            .filter(UserColumn::DeletionRequestedAt.lte(requested_before))
+            .filter(UserColumn::Id.ne(DELETED_USER_ID))
+            .order_by_asc(UserColumn::DeletionRequestedAt)
+            .limit(limit)
+            .all(&self.db)
+            .await?;
+
+        Ok(users)
+    }
+
+    /// ### SQL示例
+    /// ```sql
+    /// BEGIN;
+    /// SELECT * FROM users WHERE id = $1 AND deletion_requested_at <= $2 FOR UPDATE;
+    /// UPDATE templates SET created_by = '00000000-...' WHERE created_by = $1;
+    /// UPDATE template_versions SET created_by = '00000000-...' WHERE created_by = $1;
+    /// DELETE FROM login_lockouts WHERE user_id = $1;
+    /// DELETE FROM login_attempts WHERE scope = 'account' AND subject IN ($email, $phone);
+    /// DELETE FROM verification_codes WHERE phone = $phone;
+    /// DELETE FROM users WHERE id = $1;  -- 清单、刷新令牌、第三方账号绑定级联删除
+    /// COMMIT;
+    /// ```
+    async fn purge(&self, user_id: Uuid, requested_before: DateTime<Utc>) -> AppResult<bool> {
+        if user_id == DELETED_USER_ID {
+            return Ok(false);
+        }
+
+        let txn = self.db.begin().await?;
+
+        // Row lock: a login that cancels the deletion waits for (or wins against) the purge
+        let Some(user) = UserEntity::find_by_id(user_id)
+            .filter(UserColumn::DeletionRequestedAt.lte(requested_before))
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        TemplateEntity::update_many()
+            .col_expr(TemplateColumn::CreatedBy, Expr::value(DELETED_USER_ID))
+            .filter(TemplateColumn::CreatedBy.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        TemplateVersionEntity::update_many()
+            .col_expr(TemplateVersionColumn::CreatedBy, Expr::value(DELETED_USER_ID))
+            .filter(TemplateVersionColumn::CreatedBy.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        LoginLockoutEntity::delete_many()
+            .filter(LoginLockoutColumn::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        // Throttle counters are keyed like LoginThrottleService: lowercase email or phone
+        let accounts: Vec<String> = user.email.iter()
+            .map(|email| email.trim().to_lowercase())
+            .chain(user.phone.clone())
+            .collect();
+        if !accounts.is_empty() {
+            LoginAttemptEntity::delete_many()
+                .filter(LoginAttemptColumn::Scope.eq(LoginScope::Account))
+                .filter(LoginAttemptColumn::Subject.is_in(accounts))
+                .exec(&txn)
+                .await?;
+        }
+
+        if let Some(phone) = &user.phone {
+            VerificationCodeEntity::delete_many()
+                .filter(VerificationCodeColumn::Phone.eq(phone.as_str()))
+                .exec(&txn)
+                .await?;
+        }
+
+        UserEntity::delete_by_id(user_id).exec(&txn).await?;
+
+        txn.commit().await?;
+        Ok(true)
+    }
+    /// ### SQL示例
+    /// ```sql
+    /// INSERT INTO orphaned_files (prefix, created_at) VALUES ($1, now())
+    /// ON CONFLICT (prefix) DO NOTHING;
+    /// ```
+    async fn record_orphaned_files(&self, prefix: &str) -> AppResult<()> {
+        use models::orphaned_file::ActiveModel;
+
+        OrphanedFileEntity::insert(ActiveModel {
+            prefix: Set(prefix.to_string()),
+            created_at: Set(Utc::now()),
+        })
+        .on_conflict(OnConflict::column(OrphanedFileColumn::Prefix).do_nothing().to_owned())
+        .do_nothing()
+        .exec(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_orphaned_files(&self, limit: u64) -> AppResult<Vec<OrphanedFile>> {
+        let files = OrphanedFileEntity::find()
+            .order_by_asc(OrphanedFileColumn::CreatedAt)
+            .limit(limit)
+            .all(&self.db)
+            .await?;
+
+        Ok(files)
+    }
+
+    async fn delete_orphaned_files(&self, prefix: &str) -> AppResult<()> {
+        OrphanedFileEntity::delete_by_id(prefix.to_string())
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use common::AppResult;
+use models::{Location, LocationQuery, LocationEntity, LocationColumn};
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, ColumnTrait};
+
+/// 地区Repository接口
+/// 
+/// 地区数据由迁移写入，运行时只读。
+/// 
+/// ## 职责
+/// 
+/// - 按代码查找地区（校验`location_tag`、`home_city`）
+/// - 按上级或关键词列出地区
+/// - 查找地区的所有上级（按地区搜索模板时使用）
+#[async_trait]
+pub trait LocationRepository: Send + Sync {
+    /// 根据地区代码查找地区
+    async fn find_by_code(&self, code: &str) -> AppResult<Option<Location>>;
+    
+    /// 列出地区
+    /// 
+    /// ## 参数
+    /// - `query`: 按上级（`parent`）和/或关键词（`keyword`）过滤，都为空时返回全部
+    /// 
+    /// ## 返回值
+    /// 地区列表，按代码排序
+    async fn list(&self, query: LocationQuery) -> AppResult<Vec<Location>>;
+    
+    /// 查找地区及其所有上级
+    /// 
+    /// ## 返回值
+    /// 从该地区本身到全国（例如 CN-GZ → CN-GD → CN）；地区不存在时为空列表
+    async fn find_with_ancestors(&self, code: &str) -> AppResult<Vec<Location>>;
+}
+
+/// 地区Repository的SeaORM实现
+#[derive(Clone)]
+pub struct LocationRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl LocationRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl LocationRepository for LocationRepositoryImpl {
+    async fn find_by_code(&self, code: &str) -> AppResult<Option<Location>> {
+        let location = LocationEntity::find_by_id(code.to_string())
+            .one(&self.db)
+            .await?;
+
+        Ok(location)
+    }
+
+    /// 列出地区
+    /// 
+    /// 上级过滤在数据库中完成；关键词要匹配JSONB中的别名，
+    /// 而地区表很小，所以在内存中用`Location::matches`过滤。
+    async fn list(&self, query: LocationQuery) -> AppResult<Vec<Location>> {
+        let mut query_builder = LocationEntity::find();
+        
+        if let Some(parent) = query.parent {
+            query_builder = query_builder.filter(LocationColumn::ParentCode.eq(parent));
+        }
+        
+        let locations = query_builder
+            .order_by_asc(LocationColumn::Code)
+            .all(&self.db)
+            .await?;
+
+        Ok(match query.keyword {
+            Some(keyword) => locations.into_iter().filter(|l| l.matches(&keyword)).collect(),
+            None => locations,
+        })
+    }
+
+    /// 查找地区及其所有上级
+    /// 
+    /// 层级最多三层（国家 → 省 → 城市），逐级向上查询即可
+    async fn find_with_ancestors(&self, code: &str) -> AppResult<Vec<Location>> {
+        let mut chain: Vec<Location> = Vec::new();
+        let mut next = Some(code.to_string());
+        
+        while let Some(code) = next {
+            // 防御异常数据中的环
+            if chain.iter().any(|l| l.code == code) {
+                break;
+            }
+            
+            let Some(location) = self.find_by_code(&code).await? else {
+                break;
+            };
+            
+            next = location.parent_code.clone();
+            chain.push(location);
+        }
+
+        Ok(chain)
+    }
+}
@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use common::AppResult;
+use models::{
+    LoginAttempt, LoginAttemptEntity, LoginAttemptColumn, LoginLockout, LoginScope,
+    LOGIN_FAILURE_WINDOW_SECONDS,
+};
+use sea_orm::{
+    DatabaseConnection, EntityTrait, QueryFilter, Set, ColumnTrait, ActiveModelTrait, TransactionTrait,
+    sea_query::{Expr, OnConflict},
+};
+use uuid::Uuid;
+
+/// 登录失败计数Repository接口
+/// 
+/// ## 职责
+/// 
+/// - 查询账号/IP的失败计数和锁定状态
+/// - 原子地累加失败次数（并发的失败请求不会丢失计数）
+/// - 锁定账号/IP，同时写入锁定审计记录
+/// - 登录成功后清零
+#[async_trait]
+pub trait LoginAttemptRepository: Send + Sync {
+    /// 查找账号/IP的失败计数
+    async fn find(&self, scope: LoginScope, subject: &str) -> AppResult<Option<LoginAttempt>>;
+
+    /// 记录一次失败，返回累加后的计数
+    /// 
+    /// 距离上次失败超过统计窗口时从1重新计数（同时清除已过期的锁定）
+    async fn record_failure(&self, scope: LoginScope, subject: &str) -> AppResult<LoginAttempt>;
+
+    /// 锁定到`locked_until`，并写入锁定审计记录
+    /// 
+    /// ## 参数
+    /// - `attempt`: 刚累加过的失败计数
+    /// - `user_id`: 账号对应的用户（按IP锁定、或账号不存在时为`None`）
+    /// - `ip`: 触发锁定的请求的客户端IP
+    /// 
+    /// ## 返回值
+    /// - `Some`: 锁定成功，返回审计记录
+    /// - `None`: 已经被并发的请求锁定，不重复记录
+    async fn lock(
+        &self,
+        attempt: &LoginAttempt,
+        locked_until: DateTime<Utc>,
+        user_id: Option<Uuid>,
+        ip: Option<&str>,
+    ) -> AppResult<Option<LoginLockout>>;
+
+    /// 清零账号/IP的失败计数
+    async fn reset(&self, scope: LoginScope, subject: &str) -> AppResult<()>;
+}
+
+/// 登录失败计数Repository的SeaORM实现
+#[derive(Clone)]
+pub struct LoginAttemptRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl LoginAttemptRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for LoginAttemptRepositoryImpl {
+    async fn find(&self, scope: LoginScope, subject: &str) -> AppResult<Option<LoginAttempt>> {
+        let attempt = LoginAttemptEntity::find()
+            .filter(LoginAttemptColumn::Scope.eq(scope))
+            .filter(LoginAttemptColumn::Subject.eq(subject))
+            .one(&self.db)
+            .await?;
+
+        Ok(attempt)
+    }
+
+    /// ### SQL示例
+    /// ```sql
+    /// INSERT INTO login_attempts (id, scope, subject, failures, last_failed_at)
+    /// VALUES ($1, $2, $3, 1, $4)
+    /// ON CONFLICT (scope, subject) DO UPDATE SET
+    ///     failures = CASE WHEN login_attempts.last_failed_at < $5 THEN 1 ELSE login_attempts.failures + 1 END,
+    ///     locked_until = CASE WHEN login_attempts.last_failed_at < $5 THEN NULL ELSE login_attempts.locked_until END,
+    ///     last_failed_at = excluded.last_failed_at
+    /// RETURNING *;
+    /// ```
+    async fn record_failure(&self, scope: LoginScope, subject: &str) -> AppResult<LoginAttempt> {
+        use models::login_attempt::ActiveModel;
+
+        let now = Utc::now();
+        let stale = Expr::col((LoginAttemptEntity, LoginAttemptColumn::LastFailedAt))
+            .lt(now - Duration::seconds(LOGIN_FAILURE_WINDOW_SECONDS));
+
+        let on_conflict = OnConflict::columns([LoginAttemptColumn::Scope, LoginAttemptColumn::Subject])
+            .value(
+                LoginAttemptColumn::Failures,
+                Expr::case(stale.clone(), 1)
+                    .finally(Expr::col((LoginAttemptEntity, LoginAttemptColumn::Failures)).add(1)),
+            )
+            .value(
+                LoginAttemptColumn::LockedUntil,
+                Expr::case(stale, Expr::value(Option::<DateTime<Utc>>::None))
+                    .finally(Expr::col((LoginAttemptEntity, LoginAttemptColumn::LockedUntil))),
+            )
+            .update_column(LoginAttemptColumn::LastFailedAt)
+            .to_owned();
+
+        let attempt = LoginAttemptEntity::insert(ActiveModel {
+            id: Set(Uuid::new_v4()),
+            scope: Set(scope),
+            subject: Set(subject.to_string()),
+            failures: Set(1),
+            last_failed_at: Set(now),
+            locked_until: Set(None),
+        })
+        .on_conflict(on_conflict)
+        .exec_with_returning(&self.db)
+        .await?;
+
+        Ok(attempt)
+    }
+
+    async fn lock(
+        &self,
+        attempt: &LoginAttempt,
+        locked_until: DateTime<Utc>,
+        user_id: Option<Uuid>,
+        ip: Option<&str>,
+    ) -> AppResult<Option<LoginLockout>> {
+        use models::login_lockout::ActiveModel;
+
+        let now = Utc::now();
+        let txn = self.db.begin().await?;
+
+        // Only the request that actually locks writes the audit record
+        let result = LoginAttemptEntity::update_many()
+            .col_expr(LoginAttemptColumn::LockedUntil, Expr::value(locked_until))
+            .filter(LoginAttemptColumn::Id.eq(attempt.id))
+            .filter(
+                LoginAttemptColumn::LockedUntil.is_null()
+                    .or(LoginAttemptColumn::LockedUntil.lte(now)),
+            )
+            .exec(&txn)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Ok(None);
+        }
+
+        let lockout = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            scope: Set(attempt.scope),
+            subject: Set(attempt.subject.clone()),
+            user_id: Set(user_id),
+            ip: Set(ip.map(str::to_string)),
+            failures: Set(attempt.failures),
+            locked_until: Set(locked_until),
+            created_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+        Ok(Some(lockout))
+    }
+
+    async fn reset(&self, scope: LoginScope, subject: &str) -> AppResult<()> {
+        LoginAttemptEntity::delete_many()
+            .filter(LoginAttemptColumn::Scope.eq(scope))
+            .filter(LoginAttemptColumn::Subject.eq(subject))
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
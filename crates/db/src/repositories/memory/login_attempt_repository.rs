@@ -0,0 +1,112 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use common::AppResult;
+use models::{LoginAttempt, LoginLockout, LoginScope, LOGIN_FAILURE_WINDOW_SECONDS};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::repositories::LoginAttemptRepository;
+
+/// 内存中的登录失败计数表和锁定审计表
+#[derive(Default)]
+pub struct InMemoryLoginAttemptRepository {
+    attempts: Mutex<Vec<LoginAttempt>>,
+    lockouts: Mutex<Vec<LoginLockout>>,
+}
+
+impl InMemoryLoginAttemptRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已写入的锁定审计记录
+    pub fn lockouts(&self) -> Vec<LoginLockout> {
+        self.lockouts.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for InMemoryLoginAttemptRepository {
+    async fn find(&self, scope: LoginScope, subject: &str) -> AppResult<Option<LoginAttempt>> {
+        Ok(self.attempts
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|a| a.scope == scope && a.subject == subject)
+            .cloned())
+    }
+
+    async fn record_failure(&self, scope: LoginScope, subject: &str) -> AppResult<LoginAttempt> {
+        let now = Utc::now();
+        let mut attempts = self.attempts.lock().unwrap();
+
+        let Some(attempt) = attempts.iter_mut().find(|a| a.scope == scope && a.subject == subject) else {
+            let attempt = LoginAttempt {
+                id: Uuid::new_v4(),
+                scope,
+                subject: subject.to_string(),
+                failures: 1,
+                last_failed_at: now,
+                locked_until: None,
+            };
+            attempts.push(attempt.clone());
+            return Ok(attempt);
+        };
+
+        // Outside the window the count starts over and an old lock no longer applies
+        if attempt.last_failed_at < now - Duration::seconds(LOGIN_FAILURE_WINDOW_SECONDS) {
+            attempt.failures = 1;
+            attempt.locked_until = None;
+        } else {
+            attempt.failures += 1;
+        }
+        attempt.last_failed_at = now;
+
+        Ok(attempt.clone())
+    }
+
+    async fn lock(
+        &self,
+        attempt: &LoginAttempt,
+        locked_until: DateTime<Utc>,
+        user_id: Option<Uuid>,
+        ip: Option<&str>,
+    ) -> AppResult<Option<LoginLockout>> {
+        let now = Utc::now();
+
+        {
+            let mut attempts = self.attempts.lock().unwrap();
+            let row = attempts
+                .iter_mut()
+                .find(|a| a.id == attempt.id)
+                .filter(|a| a.locked_until.is_none_or(|until| until <= now));
+            let Some(row) = row else {
+                return Ok(None);
+            };
+            row.locked_until = Some(locked_until);
+        }
+
+        let lockout = LoginLockout {
+            id: Uuid::new_v4(),
+            scope: attempt.scope,
+            subject: attempt.subject.clone(),
+            user_id,
+            ip: ip.map(str::to_string),
+            failures: attempt.failures,
+            locked_until,
+            created_at: now,
+        };
+        self.lockouts.lock().unwrap().push(lockout.clone());
+
+        Ok(Some(lockout))
+    }
+
+    async fn reset(&self, scope: LoginScope, subject: &str) -> AppResult<()> {
+        self.attempts
+            .lock()
+            .unwrap()
+            .retain(|a| !(a.scope == scope && a.subject == subject));
+
+        Ok(())
+    }
+}
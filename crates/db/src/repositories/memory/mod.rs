@@ -0,0 +1,44 @@
+/// 内存中的Repository实现（测试用）
+/// 
+/// 需要启用`testing`特性。数据保存在`Mutex`保护的`Vec`中，行为与SeaORM实现保持一致：
+/// 
+/// - 违反唯一约束（重复的手机号、邮箱）返回`Conflict`，和数据库错误的转换结果相同
+/// - 更新不存在的记录返回`NotFound`
+/// - 模板的版本号和版本快照、清单的Fork快照和步骤状态使用与SeaORM实现相同的规则
+/// 
+/// 没有数据库的全文索引：模板搜索按关键词子串匹配标题、描述和步骤，相关度只按命中的字段计算。
+/// 
+/// ## 使用示例
+/// 
+/// ```rust
+/// let users = Arc::new(InMemoryUserRepository::new());
+/// let app_module = AppModule::builder(config)
+///     .user_repository(users.clone())
+///     .build();
+/// ```
+/// 
+/// ## 模块结构
+/// 
+/// ```
+/// memory/
+/// ├── user_repository.rs           # InMemoryUserRepository
+/// ├── template_repository.rs       # InMemoryTemplateRepository
+/// ├── user_checklist_repository.rs # InMemoryUserChecklistRepository
+/// ├── refresh_token_repository.rs  # InMemoryRefreshTokenRepository（注册、登录签发令牌）
+/// ├── login_attempt_repository.rs  # InMemoryLoginAttemptRepository（登录失败计数）
+/// └── password_reset_token_repository.rs # InMemoryPasswordResetTokenRepository（找回密码令牌）
+/// ```
+
+mod user_repository;
+mod template_repository;
+mod user_checklist_repository;
+mod refresh_token_repository;
+mod login_attempt_repository;
+mod password_reset_token_repository;
+
+pub use user_repository::InMemoryUserRepository;
+pub use template_repository::InMemoryTemplateRepository;
+pub use user_checklist_repository::InMemoryUserChecklistRepository;
+pub use refresh_token_repository::InMemoryRefreshTokenRepository;
+pub use login_attempt_repository::InMemoryLoginAttemptRepository;
+pub use password_reset_token_repository::InMemoryPasswordResetTokenRepository;
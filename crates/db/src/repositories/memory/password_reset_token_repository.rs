@@ -0,0 +1,77 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::AppResult;
+use models::PasswordResetToken;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::repositories::PasswordResetTokenRepository;
+
+/// 内存中的找回密码令牌表
+#[derive(Default)]
+pub struct InMemoryPasswordResetTokenRepository {
+    tokens: Mutex<Vec<PasswordResetToken>>,
+}
+
+impl InMemoryPasswordResetTokenRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前所有令牌（包括已使用的）
+    pub fn all(&self) -> Vec<PasswordResetToken> {
+        self.tokens.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl PasswordResetTokenRepository for InMemoryPasswordResetTokenRepository {
+    async fn create(&self, user_id: Uuid, token_hash: String, expires_at: DateTime<Utc>) -> AppResult<PasswordResetToken> {
+        let now = Utc::now();
+        let mut tokens = self.tokens.lock().unwrap();
+
+        for token in tokens.iter_mut().filter(|t| t.user_id == user_id && t.consumed_at.is_none()) {
+            token.consumed_at = Some(now);
+        }
+
+        let token = PasswordResetToken {
+            id: Uuid::new_v4(),
+            user_id,
+            token_hash,
+            expires_at,
+            consumed_at: None,
+            created_at: now,
+        };
+        tokens.push(token.clone());
+
+        Ok(token)
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> AppResult<Option<PasswordResetToken>> {
+        Ok(self.tokens.lock().unwrap().iter().find(|t| t.token_hash == token_hash).cloned())
+    }
+
+    async fn find_latest(&self, user_id: Uuid) -> AppResult<Option<PasswordResetToken>> {
+        Ok(self.tokens
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.user_id == user_id)
+            .max_by_key(|t| t.created_at)
+            .cloned())
+    }
+
+    async fn consume(&self, id: Uuid, user_id: Uuid) -> AppResult<bool> {
+        let now = Utc::now();
+        let mut tokens = self.tokens.lock().unwrap();
+
+        if !tokens.iter().any(|t| t.id == id && t.consumed_at.is_none()) {
+            return Ok(false);
+        }
+        for token in tokens.iter_mut().filter(|t| t.user_id == user_id && t.consumed_at.is_none()) {
+            token.consumed_at = Some(now);
+        }
+
+        Ok(true)
+    }
+}
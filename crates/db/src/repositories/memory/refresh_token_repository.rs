@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::AppResult;
+use models::RefreshToken;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::repositories::RefreshTokenRepository;
+
+/// 内存中的刷新令牌表
+#[derive(Default)]
+pub struct InMemoryRefreshTokenRepository {
+    tokens: Mutex<Vec<RefreshToken>>,
+}
+
+impl InMemoryRefreshTokenRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前所有令牌（包括已吊销的）
+    pub fn all(&self) -> Vec<RefreshToken> {
+        self.tokens.lock().unwrap().clone()
+    }
+
+    /// 吊销满足条件且尚未吊销的令牌，返回吊销的数量
+    fn revoke_where(&self, matches: impl Fn(&RefreshToken) -> bool) -> u64 {
+        let now = Utc::now();
+        let mut revoked = 0;
+        for token in self.tokens.lock().unwrap().iter_mut() {
+            if token.revoked_at.is_none() && matches(token) {
+                token.revoked_at = Some(now);
+                revoked += 1;
+            }
+        }
+        revoked
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for InMemoryRefreshTokenRepository {
+    async fn create(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: String,
+        user_agent: Option<String>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<RefreshToken> {
+        let token = RefreshToken {
+            id: Uuid::new_v4(),
+            user_id,
+            family_id,
+            token_hash,
+            user_agent,
+            expires_at,
+            revoked_at: None,
+            replaced_by: None,
+            created_at: Utc::now(),
+        };
+        self.tokens.lock().unwrap().push(token.clone());
+
+        Ok(token)
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
+        Ok(self.tokens.lock().unwrap().iter().find(|t| t.token_hash == token_hash).cloned())
+    }
+
+    async fn rotate(
+        &self,
+        old_id: Uuid,
+        token_hash: String,
+        user_agent: Option<String>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<Option<RefreshToken>> {
+        let mut tokens = self.tokens.lock().unwrap();
+
+        let Some(old) = tokens.iter_mut().find(|t| t.id == old_id && t.revoked_at.is_none()) else {
+            return Ok(None);
+        };
+
+        let now = Utc::now();
+        let new_token = RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: old.user_id,
+            family_id: old.family_id,
+            token_hash,
+            user_agent,
+            expires_at,
+            revoked_at: None,
+            replaced_by: None,
+            created_at: now,
+        };
+        old.revoked_at = Some(now);
+        old.replaced_by = Some(new_token.id);
+        tokens.push(new_token.clone());
+
+        Ok(Some(new_token))
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> AppResult<u64> {
+        Ok(self.revoke_where(|t| t.family_id == family_id))
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> AppResult<u64> {
+        Ok(self.revoke_where(|t| t.user_id == user_id))
+    }
+}
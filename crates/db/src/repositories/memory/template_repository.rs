@@ -0,0 +1,254 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use common::{AppError, AppResult};
+use models::{parse_keywords, Template, CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery, TemplateVersion};
+use std::cmp::Reverse;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::repositories::TemplateRepository;
+
+/// 内存中的模板表和版本快照表
+/// 
+/// 创建和发布新版本时写入快照，规则与`TemplateRepositoryImpl`相同
+#[derive(Default)]
+pub struct InMemoryTemplateRepository {
+    templates: Mutex<Vec<Template>>,
+    versions: Mutex<Vec<TemplateVersion>>,
+}
+
+impl InMemoryTemplateRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 直接写入一个模板（准备测试数据，不写版本快照）
+    pub fn insert(&self, template: Template) {
+        self.templates.lock().unwrap().push(template);
+    }
+
+    fn snapshot(&self, template: &Template, created_by: Uuid) {
+        self.versions.lock().unwrap().push(TemplateVersion {
+            id: Uuid::new_v4(),
+            template_id: template.id,
+            version: template.version,
+            title: template.title.clone(),
+            description: template.description.clone(),
+            steps: template.steps.clone(),
+            parent_id: template.parent_id,
+            created_by,
+            created_at: template.updated_at,
+        });
+    }
+
+    /// 修改一个模板，返回修改后的副本
+    fn modify(&self, id: Uuid, change: impl FnOnce(&mut Template) -> AppResult<()>) -> AppResult<Template> {
+        let mut templates = self.templates.lock().unwrap();
+        let template = templates
+            .iter_mut()
+            .find(|t| t.id == id)
+            .ok_or_else(|| AppError::NotFound(format!("Template {} not found", id)))?;
+
+        change(template)?;
+        template.updated_at = Utc::now();
+
+        Ok(template.clone())
+    }
+
+    /// 未删除的模板，按`search`/`list_all`的规则排序
+    /// 
+    /// `filter`返回`None`的模板被排除，否则返回它的相关度
+    fn ranked(
+        &self,
+        filter: impl Fn(&Template) -> Option<f64>,
+        preferred_locations: &[String],
+    ) -> Vec<(Template, f64)> {
+        let mut rows: Vec<(Template, f64)> = self.templates
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| !t.is_deleted())
+            .filter_map(|t| filter(t).map(|score| (t.clone(), score)))
+            .collect();
+
+        let rank = |t: &Template| {
+            preferred_locations
+                .iter()
+                .position(|code| *code == t.location_tag)
+                .unwrap_or(preferred_locations.len())
+        };
+        rows.sort_by(|(a, a_score), (b, b_score)| {
+            rank(a)
+                .cmp(&rank(b))
+                .then(b_score.total_cmp(a_score))
+                .then(b.is_official.cmp(&a.is_official))
+                .then(b.created_at.cmp(&a.created_at))
+        });
+
+        rows
+    }
+}
+
+/// 分页（页码从1开始）
+fn page_of<T>(rows: Vec<T>, page: i32, page_size: i32) -> Vec<T> {
+    let offset = ((page - 1).max(0) * page_size.max(0)) as usize;
+    rows.into_iter().skip(offset).take(page_size.max(0) as usize).collect()
+}
+
+/// 所有关键词都命中时的相关度：每个关键词命中标题3分、描述2分、任意文本1分
+fn relevance(template: &Template, keywords: &[String]) -> Option<f64> {
+    let title = template.title.to_lowercase();
+    let description = template.description.to_lowercase();
+    let steps = template.steps.to_string().to_lowercase();
+
+    keywords.iter().try_fold(0.0, |score, keyword| {
+        let keyword = keyword.to_lowercase();
+        let in_title = title.contains(&keyword);
+        let in_description = description.contains(&keyword);
+        if !in_title && !in_description && !steps.contains(&keyword) {
+            return None;
+        }
+
+        Some(score + 1.0 + if in_title { 3.0 } else { 0.0 } + if in_description { 2.0 } else { 0.0 })
+    })
+}
+
+#[async_trait]
+impl TemplateRepository for InMemoryTemplateRepository {
+    async fn create(&self, dto: CreateTemplateDto, created_by: Uuid) -> AppResult<Template> {
+        let now = Utc::now();
+        let template = Template {
+            id: Uuid::new_v4(),
+            title: dto.title,
+            description: dto.description,
+            location_tag: dto.location_tag,
+            steps: serde_json::to_value(&dto.steps)?,
+            parent_id: dto.parent_id,
+            created_at: now,
+            updated_at: now,
+            created_by,
+            is_official: false,
+            version: 1,
+            deleted_at: None,
+        };
+
+        self.templates.lock().unwrap().push(template.clone());
+        self.snapshot(&template, created_by);
+
+        Ok(template)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Template>> {
+        Ok(self.templates.lock().unwrap().iter().find(|t| t.id == id).cloned())
+    }
+
+    async fn find_children(&self, parent_id: Uuid) -> AppResult<Vec<Template>> {
+        Ok(self.templates.lock().unwrap().iter().filter(|t| t.parent_id == Some(parent_id)).cloned().collect())
+    }
+
+    async fn update(&self, id: Uuid, dto: UpdateTemplateDto, updated_by: Uuid) -> AppResult<Template> {
+        let publishes_revision = dto.title.is_some()
+            || dto.description.is_some()
+            || dto.steps.is_some()
+            || dto.parent_id.is_some();
+
+        let template = self.modify(id, |template| {
+            if let Some(title) = dto.title {
+                template.title = title;
+            }
+            if let Some(description) = dto.description {
+                template.description = description;
+            }
+            if let Some(location_tag) = dto.location_tag {
+                template.location_tag = location_tag;
+            }
+            if let Some(steps) = dto.steps {
+                template.steps = serde_json::to_value(&steps)?;
+            }
+            if let Some(parent_id) = dto.parent_id {
+                template.parent_id = Some(parent_id);
+            }
+            if publishes_revision {
+                template.version += 1;
+            }
+            Ok(())
+        })?;
+
+        if publishes_revision {
+            self.snapshot(&template, updated_by);
+        }
+
+        Ok(template)
+    }
+
+    async fn soft_delete(&self, id: Uuid) -> AppResult<()> {
+        self.modify(id, |template| {
+            template.deleted_at = Some(Utc::now());
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    async fn set_official(&self, id: Uuid, is_official: bool) -> AppResult<Template> {
+        self.modify(id, |template| {
+            template.is_official = is_official;
+            Ok(())
+        })
+    }
+
+    async fn list_versions(&self, template_id: Uuid) -> AppResult<Vec<TemplateVersion>> {
+        let mut versions: Vec<TemplateVersion> = self.versions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|v| v.template_id == template_id)
+            .cloned()
+            .collect();
+        versions.sort_by_key(|v| Reverse(v.version));
+
+        Ok(versions)
+    }
+
+    async fn find_version(&self, template_id: Uuid, version: i32) -> AppResult<Option<TemplateVersion>> {
+        Ok(self.versions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|v| v.template_id == template_id && v.version == version)
+            .cloned())
+    }
+
+    async fn search(
+        &self,
+        query: TemplateSearchQuery,
+        location_tags: Vec<String>,
+        preferred_locations: Vec<String>,
+    ) -> AppResult<Vec<(Template, f64)>> {
+        let keywords = query.keyword.as_deref().map(parse_keywords).unwrap_or_default();
+
+        let rows = self.ranked(
+            |t| {
+                if !location_tags.is_empty() && !location_tags.contains(&t.location_tag) {
+                    return None;
+                }
+                relevance(t, &keywords)
+            },
+            &preferred_locations,
+        );
+
+        Ok(page_of(rows, query.page.unwrap_or(1), query.page_size.unwrap_or(20)))
+    }
+
+    async fn find_by_location(&self, location_tags: Vec<String>) -> AppResult<Vec<Template>> {
+        let rows = self.ranked(|t| location_tags.contains(&t.location_tag).then_some(0.0), &[]);
+
+        Ok(rows.into_iter().map(|(t, _)| t).collect())
+    }
+
+    async fn list_all(&self, page: i32, page_size: i32, preferred_locations: Vec<String>) -> AppResult<Vec<Template>> {
+        let rows = self.ranked(|_| Some(0.0), &preferred_locations);
+
+        Ok(page_of(rows, page, page_size).into_iter().map(|(t, _)| t).collect())
+    }
+}
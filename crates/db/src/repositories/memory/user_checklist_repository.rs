@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use common::{AppError, AppResult};
+use models::{UserChecklist, ChecklistStep, StepProgress, Template, TemplateStep};
+use std::cmp::Reverse;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::repositories::UserChecklistRepository;
+use crate::repositories::user_checklist_repository::{fork_steps, set_step_status};
+
+/// 内存中的用户清单表
+/// 
+/// Fork快照和步骤状态的更新与`UserChecklistRepositoryImpl`共用同一套规则
+#[derive(Default)]
+pub struct InMemoryUserChecklistRepository {
+    checklists: Mutex<Vec<UserChecklist>>,
+}
+
+impl InMemoryUserChecklistRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 直接写入一个清单（准备测试数据）
+    pub fn insert(&self, checklist: UserChecklist) {
+        self.checklists.lock().unwrap().push(checklist);
+    }
+
+    /// 修改一个清单，返回修改后的副本
+    fn modify(&self, checklist_id: Uuid, change: impl FnOnce(&mut UserChecklist) -> AppResult<()>) -> AppResult<UserChecklist> {
+        let mut checklists = self.checklists.lock().unwrap();
+        let checklist = checklists
+            .iter_mut()
+            .find(|c| c.id == checklist_id)
+            .ok_or_else(|| AppError::NotFound("Checklist not found".to_string()))?;
+
+        change(checklist)?;
+        checklist.updated_at = Utc::now();
+
+        Ok(checklist.clone())
+    }
+}
+
+#[async_trait]
+impl UserChecklistRepository for InMemoryUserChecklistRepository {
+    async fn create_from_template(&self, user_id: Uuid, template: &Template, template_steps: &[TemplateStep]) -> AppResult<UserChecklist> {
+        let (steps, progress) = fork_steps(template_steps);
+        let now = Utc::now();
+
+        let checklist = UserChecklist {
+            id: Uuid::new_v4(),
+            user_id,
+            source_template_id: template.id,
+            source_template_version: template.version,
+            title: template.title.clone(),
+            steps: serde_json::to_value(&steps)?,
+            base_steps: serde_json::to_value(template_steps)?,
+            progress_status: serde_json::to_value(&progress)?,
+            created_at: now,
+            updated_at: now,
+        };
+        self.checklists.lock().unwrap().push(checklist.clone());
+
+        Ok(checklist)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> AppResult<Option<UserChecklist>> {
+        Ok(self.checklists.lock().unwrap().iter().find(|c| c.id == id).cloned())
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> AppResult<Vec<UserChecklist>> {
+        let mut checklists: Vec<UserChecklist> = self.checklists
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|c| c.user_id == user_id)
+            .cloned()
+            .collect();
+        checklists.sort_by_key(|c| Reverse(c.created_at));
+
+        Ok(checklists)
+    }
+
+    async fn update_step_status(&self, checklist_id: Uuid, step_id: Uuid, completed: bool) -> AppResult<UserChecklist> {
+        self.modify(checklist_id, |checklist| {
+            let progress = set_step_status(checklist, step_id, completed)?;
+            checklist.set_progress(progress)?;
+            Ok(())
+        })
+    }
+
+    async fn apply_sync(
+        &self,
+        checklist_id: Uuid,
+        version: i32,
+        base_steps: Vec<TemplateStep>,
+        steps: Vec<ChecklistStep>,
+        progress: Vec<StepProgress>,
+    ) -> AppResult<UserChecklist> {
+        self.modify(checklist_id, |checklist| {
+            checklist.source_template_version = version;
+            checklist.base_steps = serde_json::to_value(&base_steps)?;
+            checklist.set_steps(steps)?;
+            checklist.set_progress(progress)?;
+            Ok(())
+        })
+    }
+
+    async fn update_steps(
+        &self,
+        checklist_id: Uuid,
+        steps: Vec<ChecklistStep>,
+        progress: Vec<StepProgress>,
+    ) -> AppResult<UserChecklist> {
+        self.modify(checklist_id, |checklist| {
+            checklist.set_steps(steps)?;
+            checklist.set_progress(progress)?;
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::{AppError, AppResult};
+use models::{User, UserRole, RegisterDto, UpdateProfileDto};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::repositories::UserRepository;
+
+/// 内存中的用户表
+/// 
+/// 手机号和邮箱唯一，与数据库的唯一约束一致
+#[derive(Default)]
+pub struct InMemoryUserRepository {
+    users: Mutex<Vec<User>>,
+}
+
+impl InMemoryUserRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 直接写入一个用户（准备测试数据，不检查唯一约束）
+    pub fn insert(&self, user: User) {
+        self.users.lock().unwrap().push(user);
+    }
+
+    /// 当前所有用户
+    pub fn all(&self) -> Vec<User> {
+        self.users.lock().unwrap().clone()
+    }
+
+    /// 修改一个用户，返回修改后的副本
+    fn modify(&self, user_id: Uuid, change: impl FnOnce(&mut User)) -> AppResult<User> {
+        let mut users = self.users.lock().unwrap();
+        let user = users
+            .iter_mut()
+            .find(|u| u.id == user_id)
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        change(user);
+        user.updated_at = Utc::now();
+
+        Ok(user.clone())
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn create(&self, dto: RegisterDto, password_hash: String) -> AppResult<User> {
+        let mut users = self.users.lock().unwrap();
+
+        let taken = users.iter().any(|u| {
+            (dto.phone.is_some() && u.phone == dto.phone) || (dto.email.is_some() && u.email == dto.email)
+        });
+        if taken {
+            return Err(AppError::Conflict("Resource already exists".to_string()));
+        }
+
+        let now = Utc::now();
+        let user = User {
+            id: Uuid::new_v4(),
+            phone: dto.phone,
+            email: dto.email,
+            password_hash,
+            nickname: dto.nickname,
+            avatar_url: None,
+            home_city: None,
+            role: UserRole::User,
+            role_scope: None,
+            created_at: now,
+            updated_at: now,
+            deletion_requested_at: None,
+        };
+        users.push(user.clone());
+
+        Ok(user)
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> AppResult<Option<User>> {
+        Ok(self.users.lock().unwrap().iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn find_by_phone(&self, phone: &str) -> AppResult<Option<User>> {
+        Ok(self.users.lock().unwrap().iter().find(|u| u.phone.as_deref() == Some(phone)).cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> AppResult<Option<User>> {
+        Ok(self.users.lock().unwrap().iter().find(|u| u.email.as_deref() == Some(email)).cloned())
+    }
+
+    async fn update_profile(&self, user_id: Uuid, dto: UpdateProfileDto) -> AppResult<User> {
+        self.modify(user_id, |user| {
+            if let Some(nickname) = dto.nickname {
+                user.nickname = nickname;
+            }
+            if let Some(avatar_url) = dto.avatar_url {
+                user.avatar_url = Some(avatar_url);
+            }
+            if let Some(home_city) = dto.home_city {
+                user.home_city = Some(home_city);
+            }
+        })
+    }
+
+    async fn set_role(&self, user_id: Uuid, role: UserRole, role_scope: Option<String>) -> AppResult<User> {
+        self.modify(user_id, |user| {
+            user.role = role;
+            user.role_scope = role_scope;
+        })
+    }
+
+    async fn update_password(&self, user_id: Uuid, password_hash: String) -> AppResult<User> {
+        self.modify(user_id, |user| user.password_hash = password_hash)
+    }
+
+    async fn set_deletion_requested_at(&self, user_id: Uuid, requested_at: Option<DateTime<Utc>>) -> AppResult<User> {
+        self.modify(user_id, |user| user.deletion_requested_at = requested_at)
+    }
+}
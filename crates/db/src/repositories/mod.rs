@@ -5,7 +5,7 @@
 /// ## Repository模式的优势
 /// 
 /// 1. **抽象数据访问**：Service层不需要知道数据来源（SQL、NoSQL、缓存等）
-/// 2. **易于测试**：可以轻松mock Repository进行单元测试
+/// 2. **易于测试**：可以轻松mock Repository进行单元测试（`memory`模块提供现成的内存实现）
 /// 3. **业务逻辑分离**：将SQL操作从业务逻辑中分离
 /// 4. **可替换实现**：未来可以切换数据库或添加缓存层
 /// 
@@ -16,12 +16,37 @@
 /// ├── user_repository.rs           # 用户数据访问
 /// │   ├── UserRepository trait     # 接口定义
 /// │   └── UserRepositoryImpl       # SQLx实现
+/// ├── refresh_token_repository.rs  # 刷新令牌数据访问
+/// │   ├── RefreshTokenRepository trait
+/// │   └── RefreshTokenRepositoryImpl
+/// ├── verification_code_repository.rs # 短信验证码数据访问
+/// │   ├── VerificationCodeRepository trait
+/// │   └── VerificationCodeRepositoryImpl
+/// ├── password_reset_token_repository.rs # 找回密码令牌数据访问
+/// │   ├── PasswordResetTokenRepository trait
+/// │   └── PasswordResetTokenRepositoryImpl
+/// ├── login_attempt_repository.rs  # 登录失败计数和锁定审计数据访问
+/// │   ├── LoginAttemptRepository trait
+/// │   └── LoginAttemptRepositoryImpl
+/// ├── account_repository.rs        # 账号级数据访问（导出创建的内容、彻底删除注销的账号）
+/// │   ├── AccountRepository trait
+/// │   └── AccountRepositoryImpl
+/// ├── user_identity_repository.rs  # 第三方账号绑定数据访问
+/// │   ├── UserIdentityRepository trait
+/// │   └── UserIdentityRepositoryImpl
+/// ├── oauth_state_repository.rs    # 第三方授权请求数据访问
+/// │   ├── OAuthStateRepository trait
+/// │   └── OAuthStateRepositoryImpl
 /// ├── template_repository.rs       # 模板数据访问
 /// │   ├── TemplateRepository trait
 /// │   └── TemplateRepositoryImpl
-/// └── user_checklist_repository.rs # 清单数据访问
-///     ├── UserChecklistRepository trait
-///     └── UserChecklistRepositoryImpl
+/// ├── location_repository.rs       # 地区数据访问（只读）
+/// │   ├── LocationRepository trait
+/// │   └── LocationRepositoryImpl
+/// ├── user_checklist_repository.rs # 清单数据访问
+/// │   ├── UserChecklistRepository trait
+/// │   └── UserChecklistRepositoryImpl
+/// └── memory/                      # 内存实现（`testing`特性，用于Service层测试）
 /// ```
 /// 
 /// ## 使用示例
@@ -45,11 +70,30 @@
 /// ```
 
 mod template_repository;
+mod location_repository;
 mod user_repository;
+mod refresh_token_repository;
+mod verification_code_repository;
+mod password_reset_token_repository;
+mod login_attempt_repository;
+mod account_repository;
+mod user_identity_repository;
+mod oauth_state_repository;
 mod user_checklist_repository;
 
+#[cfg(feature = "testing")]
+pub mod memory;
+
 // 导出所有Repository接口和实现
 pub use template_repository::{TemplateRepository, TemplateRepositoryImpl};
+pub use location_repository::{LocationRepository, LocationRepositoryImpl};
 pub use user_repository::{UserRepository, UserRepositoryImpl};
+pub use refresh_token_repository::{RefreshTokenRepository, RefreshTokenRepositoryImpl};
+pub use verification_code_repository::{VerificationCodeRepository, VerificationCodeRepositoryImpl};
+pub use password_reset_token_repository::{PasswordResetTokenRepository, PasswordResetTokenRepositoryImpl};
+pub use login_attempt_repository::{LoginAttemptRepository, LoginAttemptRepositoryImpl};
+pub use account_repository::{AccountRepository, AccountRepositoryImpl};
+pub use user_identity_repository::{UserIdentityRepository, UserIdentityRepositoryImpl};
+pub use oauth_state_repository::{OAuthStateRepository, OAuthStateRepositoryImpl};
 pub use user_checklist_repository::{UserChecklistRepository, UserChecklistRepositoryImpl};
 
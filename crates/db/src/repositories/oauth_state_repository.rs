@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::AppResult;
+use models::{OAuthState, OAuthStateEntity, OAuthStateColumn};
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, Set, ColumnTrait, ActiveModelTrait};
+use uuid::Uuid;
+
+/// 第三方授权请求Repository接口
+/// 
+/// ## 职责
+/// 
+/// - 保存发起的授权请求（state、PKCE校验码、nonce）
+/// - 回调时取出并删除授权请求，保证每个`state`只能使用一次
+#[async_trait]
+pub trait OAuthStateRepository: Send + Sync {
+    /// 保存授权请求，顺便清理已过期的请求
+    async fn create(
+        &self,
+        state: &str,
+        provider: &str,
+        code_verifier: &str,
+        nonce: &str,
+        user_id: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<OAuthState>;
+    
+    /// 取出并删除授权请求
+    /// 
+    /// ## 返回值
+    /// - `Some`: 授权请求（可能已过期，由业务层检查）
+    /// - `None`: 不存在或已经被使用
+    async fn take(&self, state: &str) -> AppResult<Option<OAuthState>>;
+}
+
+/// 第三方授权请求Repository的SeaORM实现
+#[derive(Clone)]
+pub struct OAuthStateRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl OAuthStateRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl OAuthStateRepository for OAuthStateRepositoryImpl {
+    async fn create(
+        &self,
+        state: &str,
+        provider: &str,
+        code_verifier: &str,
+        nonce: &str,
+        user_id: Option<Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<OAuthState> {
+        use models::oauth_state::ActiveModel;
+        
+        let now = Utc::now();
+        
+        // 放弃的授权请求不会被取出，在这里顺便清理
+        OAuthStateEntity::delete_many()
+            .filter(OAuthStateColumn::ExpiresAt.lt(now))
+            .exec(&self.db)
+            .await?;
+        
+        let oauth_state = ActiveModel {
+            state: Set(state.to_string()),
+            provider: Set(provider.to_string()),
+            code_verifier: Set(code_verifier.to_string()),
+            nonce: Set(nonce.to_string()),
+            user_id: Set(user_id),
+            expires_at: Set(expires_at),
+            created_at: Set(now),
+        }
+        .insert(&self.db)
+        .await?;
+
+        Ok(oauth_state)
+    }
+
+    /// ### SQL示例
+    /// ```sql
+    /// DELETE FROM oauth_states WHERE state = $1 RETURNING *;
+    /// ```
+    async fn take(&self, state: &str) -> AppResult<Option<OAuthState>> {
+        // 删除和读取是同一条语句，并发的回调只有一个能拿到
+        let taken = OAuthStateEntity::delete_many()
+            .filter(OAuthStateColumn::State.eq(state))
+            .exec_with_returning(&self.db)
+            .await?;
+
+        Ok(taken.into_iter().next())
+    }
+}
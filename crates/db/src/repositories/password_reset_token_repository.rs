@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::AppResult;
+use models::{PasswordResetToken, PasswordResetTokenEntity, PasswordResetTokenColumn};
+use sea_orm::{
+    DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, ColumnTrait,
+    ActiveModelTrait, TransactionTrait, sea_query::Expr,
+};
+use uuid::Uuid;
+
+/// 找回密码令牌Repository接口
+/// 
+/// ## 职责
+/// 
+/// - 保存新令牌（只保存哈希），同时作废该用户之前的令牌
+/// - 按哈希查找令牌、查找用户最新的令牌（发送频率限制）
+/// - 标记令牌已使用（密码重置后该用户的所有令牌都失效）
+#[async_trait]
+pub trait PasswordResetTokenRepository: Send + Sync {
+    /// 保存新令牌，并作废该用户之前尚未使用的令牌
+    async fn create(&self, user_id: Uuid, token_hash: String, expires_at: DateTime<Utc>) -> AppResult<PasswordResetToken>;
+    
+    /// 根据令牌哈希查找（包括已使用、已过期的）
+    async fn find_by_hash(&self, token_hash: &str) -> AppResult<Option<PasswordResetToken>>;
+    
+    /// 查找用户最新的令牌
+    async fn find_latest(&self, user_id: Uuid) -> AppResult<Option<PasswordResetToken>>;
+    
+    /// 标记令牌已使用，同时作废该用户其他尚未使用的令牌
+    /// 
+    /// ## 返回值
+    /// - `true`: 标记成功
+    /// - `false`: 令牌已经被使用（并发的重置请求抢先使用了它），其他令牌保持不变
+    async fn consume(&self, id: Uuid, user_id: Uuid) -> AppResult<bool>;
+}
+
+/// 找回密码令牌Repository的SeaORM实现
+#[derive(Clone)]
+pub struct PasswordResetTokenRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl PasswordResetTokenRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl PasswordResetTokenRepository for PasswordResetTokenRepositoryImpl {
+    async fn create(&self, user_id: Uuid, token_hash: String, expires_at: DateTime<Utc>) -> AppResult<PasswordResetToken> {
+        use models::password_reset_token::ActiveModel;
+        
+        let now = Utc::now();
+        let txn = self.db.begin().await?;
+        
+        // 每个用户只有最新的令牌有效
+        PasswordResetTokenEntity::update_many()
+            .col_expr(PasswordResetTokenColumn::ConsumedAt, Expr::value(now))
+            .filter(PasswordResetTokenColumn::UserId.eq(user_id))
+            .filter(PasswordResetTokenColumn::ConsumedAt.is_null())
+            .exec(&txn)
+            .await?;
+        
+        let token = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            token_hash: Set(token_hash),
+            expires_at: Set(expires_at),
+            consumed_at: Set(None),
+            created_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+        
+        txn.commit().await?;
+        Ok(token)
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> AppResult<Option<PasswordResetToken>> {
+        let token = PasswordResetTokenEntity::find()
+            .filter(PasswordResetTokenColumn::TokenHash.eq(token_hash))
+            .one(&self.db)
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn find_latest(&self, user_id: Uuid) -> AppResult<Option<PasswordResetToken>> {
+        let token = PasswordResetTokenEntity::find()
+            .filter(PasswordResetTokenColumn::UserId.eq(user_id))
+            .order_by_desc(PasswordResetTokenColumn::CreatedAt)
+            .one(&self.db)
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn consume(&self, id: Uuid, user_id: Uuid) -> AppResult<bool> {
+        let now = Utc::now();
+        let txn = self.db.begin().await?;
+        
+        let result = PasswordResetTokenEntity::update_many()
+            .col_expr(PasswordResetTokenColumn::ConsumedAt, Expr::value(now))
+            .filter(PasswordResetTokenColumn::Id.eq(id))
+            .filter(PasswordResetTokenColumn::ConsumedAt.is_null())
+            .exec(&txn)
+            .await?;
+        
+        if result.rows_affected != 1 {
+            return Ok(false);
+        }
+        
+        // 其他还在邮箱里的链接（例如并发请求留下的）一并作废
+        PasswordResetTokenEntity::update_many()
+            .col_expr(PasswordResetTokenColumn::ConsumedAt, Expr::value(now))
+            .filter(PasswordResetTokenColumn::UserId.eq(user_id))
+            .filter(PasswordResetTokenColumn::ConsumedAt.is_null())
+            .exec(&txn)
+            .await?;
+        
+        txn.commit().await?;
+        Ok(true)
+    }
+}
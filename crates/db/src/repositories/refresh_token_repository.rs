@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::AppResult;
+use models::{RefreshToken, RefreshTokenEntity, RefreshTokenColumn};
+use sea_orm::{
+    DatabaseConnection, EntityTrait, QueryFilter, QuerySelect, Set, ColumnTrait, ActiveModelTrait,
+    IntoActiveModel, TransactionTrait, sea_query::Expr,
+};
+use uuid::Uuid;
+
+/// 刷新令牌Repository接口
+/// 
+/// 定义了刷新令牌的存储和吊销操作。
+/// 
+/// ## 职责
+/// 
+/// - 保存新签发的令牌（只保存哈希）
+/// - 按哈希查找令牌
+/// - 轮换：吊销旧令牌并签发同family的新令牌
+/// - 按family或按用户批量吊销
+#[async_trait]
+pub trait RefreshTokenRepository: Send + Sync {
+    /// 保存新签发的令牌
+    /// 
+    /// ## 参数
+    /// - `user_id`: 所属用户
+    /// - `family_id`: 令牌家族（登录时新建，刷新时沿用）
+    /// - `token_hash`: 令牌的SHA-256哈希
+    /// - `user_agent`: 客户端的User-Agent
+    /// - `expires_at`: 过期时间
+    async fn create(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: String,
+        user_agent: Option<String>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<RefreshToken>;
+    
+    /// 根据哈希查找令牌（包括已吊销、已过期的令牌，用于重用检测）
+    async fn find_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>>;
+    
+    /// 轮换令牌
+    /// 
+    /// 在同一事务中吊销`old_id`（`replaced_by`指向新令牌），并签发同family的新令牌。
+    /// 
+    /// ## 返回值
+    /// - `Some(新令牌)`: 轮换成功
+    /// - `None`: 旧令牌已被吊销（例如并发的刷新请求抢先使用了它）
+    async fn rotate(
+        &self,
+        old_id: Uuid,
+        token_hash: String,
+        user_agent: Option<String>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<Option<RefreshToken>>;
+    
+    /// 吊销整个family中尚未吊销的令牌
+    /// 
+    /// ## 返回值
+    /// 本次吊销的令牌数
+    async fn revoke_family(&self, family_id: Uuid) -> AppResult<u64>;
+    
+    /// 吊销用户所有尚未吊销的令牌（退出所有设备）
+    /// 
+    /// ## 返回值
+    /// 本次吊销的令牌数
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> AppResult<u64>;
+}
+
+/// 刷新令牌Repository的SeaORM实现
+#[derive(Clone)]
+pub struct RefreshTokenRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl RefreshTokenRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
+    async fn create(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: String,
+        user_agent: Option<String>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<RefreshToken> {
+        use models::refresh_token::ActiveModel;
+        
+        let active_model = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            family_id: Set(family_id),
+            token_hash: Set(token_hash),
+            user_agent: Set(user_agent),
+            expires_at: Set(expires_at),
+            revoked_at: Set(None),
+            replaced_by: Set(None),
+            created_at: Set(Utc::now()),
+        };
+
+        let token = active_model.insert(&self.db).await?;
+        Ok(token)
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
+        let token = RefreshTokenEntity::find()
+            .filter(RefreshTokenColumn::TokenHash.eq(token_hash))
+            .one(&self.db)
+            .await?;
+
+        Ok(token)
+    }
+
+    /// 轮换令牌
+    /// 
+    /// 使用`SELECT ... FOR UPDATE`锁定旧令牌，同一个令牌的并发刷新只有一个能成功，
+    /// 其余的会看到它已被吊销。
+    async fn rotate(
+        &self,
+        old_id: Uuid,
+        token_hash: String,
+        user_agent: Option<String>,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<Option<RefreshToken>> {
+        use models::refresh_token::ActiveModel;
+        
+        let txn = self.db.begin().await?;
+        
+        let old = RefreshTokenEntity::find_by_id(old_id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?;
+        
+        let Some(old) = old.filter(|t| t.revoked_at.is_none()) else {
+            return Ok(None);
+        };
+        
+        let now = Utc::now();
+        let new_token = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(old.user_id),
+            family_id: Set(old.family_id),
+            token_hash: Set(token_hash),
+            user_agent: Set(user_agent),
+            expires_at: Set(expires_at),
+            revoked_at: Set(None),
+            replaced_by: Set(None),
+            created_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+        
+        let mut old = old.into_active_model();
+        old.revoked_at = Set(Some(now));
+        old.replaced_by = Set(Some(new_token.id));
+        old.update(&txn).await?;
+        
+        txn.commit().await?;
+        Ok(Some(new_token))
+    }
+
+    async fn revoke_family(&self, family_id: Uuid) -> AppResult<u64> {
+        let result = RefreshTokenEntity::update_many()
+            .col_expr(RefreshTokenColumn::RevokedAt, Expr::value(Utc::now()))
+            .filter(RefreshTokenColumn::FamilyId.eq(family_id))
+            .filter(RefreshTokenColumn::RevokedAt.is_null())
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> AppResult<u64> {
+        let result = RefreshTokenEntity::update_many()
+            .col_expr(RefreshTokenColumn::RevokedAt, Expr::value(Utc::now()))
+            .filter(RefreshTokenColumn::UserId.eq(user_id))
+            .filter(RefreshTokenColumn::RevokedAt.is_null())
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
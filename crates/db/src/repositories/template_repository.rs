@@ -1,7 +1,15 @@
 use async_trait::async_trait;
 use common::AppResult;
-use models::{Template, CreateTemplateDto, TemplateSearchQuery, TemplateEntity, TemplateColumn};
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, ColumnTrait, ActiveModelTrait};
+use models::{
+    parse_keywords,
+    Template, CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery, TemplateEntity, TemplateColumn,
+    TemplateVersion, TemplateVersionEntity, TemplateVersionColumn,
+};
+use sea_orm::{
+    DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Select, Set,
+    ColumnTrait, ActiveModelTrait, IntoActiveModel, TransactionTrait, Order, FromQueryResult, QueryResult, DbErr,
+    sea_query::{CaseStatement, Expr, SimpleExpr},
+};
 use uuid::Uuid;
 
 /// 模板Repository接口
@@ -11,6 +19,8 @@ use uuid::Uuid;
 /// ## 职责
 /// 
 /// - 创建新模板
+/// - 更新、（软）删除模板
+/// - 维护模板的版本快照（`template_versions`）
 /// - 查询模板（按ID、地理位置、关键词搜索）
 /// - 分页列出模板
 /// 
@@ -31,7 +41,8 @@ pub trait TemplateRepository: Send + Sync {
     /// - `created_by`: 创建者用户ID
     /// 
     /// ## 返回值
-    /// 创建成功的模板实体（包含生成的UUID和时间戳）
+    /// 创建成功的模板实体（包含生成的UUID和时间戳），版本号为1，
+    /// 同时写入第1版快照
     async fn create(&self, dto: CreateTemplateDto, created_by: Uuid) -> AppResult<Template>;
     
     /// 根据ID查找模板
@@ -42,39 +53,100 @@ pub trait TemplateRepository: Send + Sync {
     /// ## 返回值
     /// - `Some(Template)`: 找到模板
     /// - `None`: 模板不存在
+    /// 
+    /// ## 注意
+    /// 已软删除的模板同样会被返回（调用方通过`deleted_at`判断），
+    /// 因为已Fork的清单仍然需要读取原模板。
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Template>>;
     
+    /// 查找直接继承`parent_id`的子模板
+    /// 
+    /// 同样包含已软删除的模板：它们的清单仍然沿父模板链解析步骤，
+    /// 它们的子模板也仍然经过它们继承。
+    async fn find_children(&self, parent_id: Uuid) -> AppResult<Vec<Template>>;
+    
+    /// 更新模板
+    /// 
+    /// 只更新DTO中提供的字段，并刷新`updated_at`。
+    /// 如果修改了标题、描述或步骤，版本号+1并写入新的版本快照。
+    /// 
+    /// ## 参数
+    /// - `id`: 模板UUID
+    /// - `dto`: 更新模板的数据传输对象
+    /// - `updated_by`: 发布新版本的用户ID
+    /// 
+    /// ## 返回值
+    /// 更新后的模板实体
+    async fn update(&self, id: Uuid, dto: UpdateTemplateDto, updated_by: Uuid) -> AppResult<Template>;
+    
+    /// 软删除模板
+    /// 
+    /// 设置`deleted_at`为当前时间，而不是物理删除记录，
+    /// 这样引用该模板的用户清单（`user_checklists`）不会被级联删除。
+    /// 
+    /// ## 参数
+    /// - `id`: 模板UUID
+    async fn soft_delete(&self, id: Uuid) -> AppResult<()>;
+    
+    /// 设置或取消官方模板标记
+    /// 
+    /// 官方标记不属于模板内容，不会发布新版本。
+    async fn set_official(&self, id: Uuid, is_official: bool) -> AppResult<Template>;
+    
+    /// 列出模板的所有历史版本
+    /// 
+    /// ## 返回值
+    /// 版本快照列表，按版本号倒序（最新版本在前）
+    async fn list_versions(&self, template_id: Uuid) -> AppResult<Vec<TemplateVersion>>;
+    
+    /// 查找模板的指定版本
+    /// 
+    /// ## 返回值
+    /// - `Some(TemplateVersion)`: 找到该版本
+    /// - `None`: 版本不存在
+    async fn find_version(&self, template_id: Uuid, version: i32) -> AppResult<Option<TemplateVersion>>;
+    
     /// 搜索模板
     /// 
-    /// 支持关键词搜索、地理位置过滤和分页。
+    /// 支持多关键词全文搜索、地理位置过滤和分页。
     /// 
     /// ## 参数
-    /// - `query`: 搜索查询对象（包含keyword、location_tag、page、page_size）
+    /// - `query`: 搜索查询对象（使用其中的keyword、page、page_size）
+    /// - `location_tags`: 要匹配的地区代码（`query.location_tag`及其所有上级），为空时不按地区过滤
+    /// - `preferred_locations`: 排序偏好，见`list_all`
     /// 
     /// ## 返回值
-    /// 匹配的模板列表，按创建时间倒序排列
-    async fn search(&self, query: TemplateSearchQuery) -> AppResult<Vec<Template>>;
+    /// 匹配的模板及其相关度（没有关键词时为0）。同一地区档内按相关度倒序。
+    async fn search(
+        &self,
+        query: TemplateSearchQuery,
+        location_tags: Vec<String>,
+        preferred_locations: Vec<String>,
+    ) -> AppResult<Vec<(Template, f64)>>;
     
     /// 根据地理位置查找模板
     /// 
-    /// 查找指定地理标签的模板，同时包含通用模板（CN）。
+    /// 查找标签属于`location_tags`的模板。
     /// 
     /// ## 参数
-    /// - `location_tag`: 地理位置标签（如"CN-BJ"）
+    /// - `location_tags`: 地区代码及其所有上级（如`["CN-GZ", "CN-GD", "CN"]`）
     /// 
     /// ## 返回值
-    /// 该地理位置的模板 + 通用模板，按创建时间倒序
-    async fn find_by_location(&self, location_tag: String) -> AppResult<Vec<Template>>;
+    /// 该地区及其上级地区的模板，按创建时间倒序
+    async fn find_by_location(&self, location_tags: Vec<String>) -> AppResult<Vec<Template>>;
     
     /// 分页列出所有模板
     /// 
     /// ## 参数
     /// - `page`: 页码（从1开始）
     /// - `page_size`: 每页数量
+    /// - `preferred_locations`: 排序偏好，从最具体的地区到全国（如`["CN-GZ", "CN-GD", "CN"]`）
     /// 
-    /// ## 返回值
-    /// 指定页的模板列表，按创建时间倒序排列
-    async fn list_all(&self, page: i32, page_size: i32) -> AppResult<Vec<Template>>;
+    /// ## 排序
+    /// - `preferred_locations`为空：按创建时间倒序
+    /// - 否则按标签在`preferred_locations`中的位置分档（城市 → 省 → 全国 → 其他地区），
+    ///   同一档内官方模板优先，再按创建时间倒序
+    async fn list_all(&self, page: i32, page_size: i32, preferred_locations: Vec<String>) -> AppResult<Vec<Template>>;
 }
 
 /// 模板Repository的SeaORM实现
@@ -94,6 +166,98 @@ impl TemplateRepositoryImpl {
     pub fn new(db: DatabaseConnection) -> Self {
         Self { db }
     }
+
+    /// 将模板当前内容写入版本快照表
+    /// 
+    /// 必须与模板本身的写入处于同一事务中，保证`templates.version`
+    /// 与`template_versions`始终一致。
+    async fn insert_version_snapshot(
+        txn: &DatabaseTransaction,
+        template: &Template,
+        created_by: Uuid,
+    ) -> AppResult<()> {
+        use models::template_version::ActiveModel;
+        
+        let snapshot = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            template_id: Set(template.id),
+            version: Set(template.version),
+            title: Set(template.title.clone()),
+            description: Set(template.description.clone()),
+            steps: Set(template.steps.clone()),
+            parent_id: Set(template.parent_id),
+            created_by: Set(created_by),
+            created_at: Set(template.updated_at),
+        };
+        
+        snapshot.insert(txn).await?;
+        Ok(())
+    }
+
+    /// 按地区偏好排序
+    /// 
+    /// ```sql
+    /// ORDER BY CASE
+    ///     WHEN location_tag = 'CN-GZ' THEN 0
+    ///     WHEN location_tag = 'CN-GD' THEN 1
+    ///     WHEN location_tag = 'CN' THEN 2
+    ///     ELSE 3
+    /// END, score DESC, is_official DESC, created_at DESC
+    /// ```
+    /// 
+    /// `by_relevance`为true时，查询必须选出`score`列（见`search`）。
+    fn order_by_preference(
+        mut select: Select<TemplateEntity>,
+        preferred_locations: Vec<String>,
+        by_relevance: bool,
+    ) -> Select<TemplateEntity> {
+        let has_preference = !preferred_locations.is_empty();
+        
+        if has_preference {
+            let others = preferred_locations.len() as i32;
+            let rank = preferred_locations
+                .into_iter()
+                .enumerate()
+                .fold(CaseStatement::new(), |case, (rank, code)| {
+                    case.case(TemplateColumn::LocationTag.eq(code), Expr::val(rank as i32))
+                })
+                .finally(Expr::val(others));
+            
+            select = select.order_by(SimpleExpr::Case(Box::new(rank)), Order::Asc);
+        }
+        
+        if by_relevance {
+            select = select.order_by(Expr::cust("score"), Order::Desc);
+        }
+        
+        // Official templates come first with or without location preferences
+        select
+            .order_by_desc(TemplateColumn::IsOfficial)
+            .order_by_desc(TemplateColumn::CreatedAt)
+    }
+}
+
+/// 搜索结果行：模板的所有列 + 额外选出的`score`列
+struct ScoredTemplate {
+    template: Template,
+    score: f64,
+}
+
+impl FromQueryResult for ScoredTemplate {
+    fn from_query_result(res: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            template: Template::from_query_result(res, pre)?,
+            score: res.try_get(pre, "score")?,
+        })
+    }
+}
+
+/// 转义LIKE模式中的通配符，让关键词按字面匹配
+fn escape_like(keyword: &str) -> String {
+    keyword
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
 #[async_trait]
@@ -110,9 +274,10 @@ impl TemplateRepository for TemplateRepositoryImpl {
     /// 
     /// ### 注意事项
     /// - `steps` 字段存储为 JSONB，需要先序列化为 JSON
-    /// - `is_official` 默认为 false（用户创建的模板）
+    /// - `is_official` 默认为 false，由管理员通过`set_official`设置
     /// - `id` 使用 UUID v4 自动生成
     /// - `created_at` 和 `updated_at` 都设置为当前时间
+    /// - `version` 从1开始，并在同一事务中写入第1版快照
     async fn create(&self, dto: CreateTemplateDto, created_by: Uuid) -> AppResult<Template> {
         use models::template::ActiveModel;
         
@@ -134,11 +299,17 @@ impl TemplateRepository for TemplateRepositoryImpl {
             created_at: Set(now),
             updated_at: Set(now),
             created_by: Set(created_by),
-            is_official: Set(false), // 默认非官方模板（用户创建）
+            is_official: Set(false), // 默认非官方模板，由管理员单独标记
+            version: Set(1),
+            deleted_at: Set(None),
         };
 
-        // 插入数据库并返回创建的模板
-        let template = active_model.insert(&self.db).await?;
+        // 在事务中插入模板和第1版快照
+        let txn = self.db.begin().await?;
+        let template = active_model.insert(&txn).await?;
+        Self::insert_version_snapshot(&txn, &template, created_by).await?;
+        txn.commit().await?;
+        
         Ok(template)
     }
 
@@ -150,97 +321,260 @@ impl TemplateRepository for TemplateRepositoryImpl {
         Ok(template)
     }
 
-    /// 搜索模板
+    async fn find_children(&self, parent_id: Uuid) -> AppResult<Vec<Template>> {
+        let children = TemplateEntity::find()
+            .filter(TemplateColumn::ParentId.eq(parent_id))
+            .order_by_asc(TemplateColumn::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(children)
+    }
+
+    /// 更新模板
+    /// 
+    /// ## SeaORM 动态更新
+    /// 
+    /// 与`UserRepository::update_profile`相同：先读取实体并转换为 ActiveModel，
+    /// 只对DTO中提供的字段调用`Set()`，未设置的字段不会出现在UPDATE语句中。
+    /// 
+    /// - `steps` 整体替换（重新序列化为 JSONB）
+    /// - `updated_at` 总是刷新为当前时间
     /// 
-    /// ## SeaORM 查询逻辑
+    /// ## 版本发布
     /// 
-    /// 动态构建查询条件，支持：
-    /// 1. **关键词搜索**：使用LIKE模糊匹配标题和描述
-    /// 2. **地理位置过滤**：查找指定地理位置 + 通用模板（CN）
-    /// 3. **分页**：使用offset和limit
+    /// 如果DTO包含`title`、`description`、`steps`或`parent_id`，则在同一事务中：
+    /// 1. `version` + 1
+    /// 2. 写入新版本快照到`template_versions`
     /// 
-    /// ### SQL示例（有关键词和位置）
+    /// 读取时使用`SELECT ... FOR UPDATE`锁定模板行，避免并发更新产生相同的版本号。
+    async fn update(&self, id: Uuid, dto: UpdateTemplateDto, updated_by: Uuid) -> AppResult<Template> {
+        let txn = self.db.begin().await?;
+        
+        let template = TemplateEntity::find_by_id(id)
+            .lock_exclusive()
+            .one(&txn)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound(format!("Template {} not found", id)))?;
+        
+        // 标题、描述、步骤属于版本快照的内容；更换父模板会改变有效步骤，同样发布新版本
+        let publishes_revision = dto.title.is_some()
+            || dto.description.is_some()
+            || dto.steps.is_some()
+            || dto.parent_id.is_some();
+        let next_version = template.version + 1;
+        
+        let mut active_model = template.into_active_model();
+        
+        if let Some(title) = dto.title {
+            active_model.title = Set(title);
+        }
+        
+        if let Some(description) = dto.description {
+            active_model.description = Set(description);
+        }
+        
+        if let Some(location_tag) = dto.location_tag {
+            active_model.location_tag = Set(location_tag);
+        }
+        
+        if let Some(steps) = dto.steps {
+            active_model.steps = Set(serde_json::to_value(&steps)?);
+        }
+        
+        if let Some(parent_id) = dto.parent_id {
+            active_model.parent_id = Set(Some(parent_id));
+        }
+        
+        if publishes_revision {
+            active_model.version = Set(next_version);
+        }
+        
+        active_model.updated_at = Set(chrono::Utc::now());
+        
+        let updated_template = active_model.update(&txn).await?;
+        
+        if publishes_revision {
+            Self::insert_version_snapshot(&txn, &updated_template, updated_by).await?;
+        }
+        
+        txn.commit().await?;
+        Ok(updated_template)
+    }
+
+    /// 软删除模板
+    /// 
+    /// ### SQL示例
     /// ```sql
-    /// SELECT * FROM templates
-    /// WHERE (title LIKE '%租房%' OR description LIKE '%租房%')
-    ///   AND (location_tag = 'CN-BJ' OR location_tag = 'CN')
-    /// ORDER BY created_at DESC
-    /// LIMIT 20 OFFSET 0;
+    /// UPDATE templates
+    /// SET deleted_at = NOW(), updated_at = NOW()
+    /// WHERE id = $1;
     /// ```
+    async fn soft_delete(&self, id: Uuid) -> AppResult<()> {
+        let template = TemplateEntity::find_by_id(id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound(format!("Template {} not found", id)))?;
+        
+        let now = chrono::Utc::now();
+        let mut active_model = template.into_active_model();
+        active_model.deleted_at = Set(Some(now));
+        active_model.updated_at = Set(now);
+        active_model.update(&self.db).await?;
+        
+        Ok(())
+    }
+
+    async fn set_official(&self, id: Uuid, is_official: bool) -> AppResult<Template> {
+        let template = TemplateEntity::find_by_id(id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound(format!("Template {} not found", id)))?;
+        
+        let mut active_model = template.into_active_model();
+        active_model.is_official = Set(is_official);
+        active_model.updated_at = Set(chrono::Utc::now());
+        
+        let template = active_model.update(&self.db).await?;
+        
+        Ok(template)
+    }
+
+    async fn list_versions(&self, template_id: Uuid) -> AppResult<Vec<TemplateVersion>> {
+        let versions = TemplateVersionEntity::find()
+            .filter(TemplateVersionColumn::TemplateId.eq(template_id))
+            .order_by_desc(TemplateVersionColumn::Version)
+            .all(&self.db)
+            .await?;
+
+        Ok(versions)
+    }
+
+    async fn find_version(&self, template_id: Uuid, version: i32) -> AppResult<Option<TemplateVersion>> {
+        let version = TemplateVersionEntity::find()
+            .filter(TemplateVersionColumn::TemplateId.eq(template_id))
+            .filter(TemplateVersionColumn::Version.eq(version))
+            .one(&self.db)
+            .await?;
+
+        Ok(version)
+    }
+
+    /// 搜索模板
+    /// 
+    /// ## 查询逻辑
+    /// 
+    /// 1. **关键词搜索**：输入按空白拆成多个关键词（见`parse_keywords`），每个关键词都要命中：
+    ///    - 全文索引`search_vector`（标题、描述、步骤，按字段加权），或
+    ///    - 三元组索引`search_text`上的`ILIKE`子串匹配（中文没有分词，主要靠这一条）
+    /// 2. **相关度**：每个关键词的`ts_rank` + 命中标题3分 + 命中描述2分 + 命中任意文本1分
+    /// 3. **地理位置过滤**：查找指定地区及其所有上级地区（省、全国）的模板
+    /// 4. **排序**：地区档（见`order_by_preference`）→ 相关度 → 官方优先 → 创建时间
+    /// 5. **分页**：使用offset和limit
+    /// 
+    /// `search_text`和`search_vector`由数据库触发器维护，实体中没有这两列。
     /// 
-    /// ### SQL示例（仅分页）
+    /// ### SQL示例（关键词"北京 租房"，地区CN-GZ）
     /// ```sql
-    /// SELECT * FROM templates
-    /// ORDER BY created_at DESC
-    /// LIMIT 20 OFFSET 20;  -- 第2页
+    /// SELECT templates.*,
+    ///        (ts_rank(search_vector, plainto_tsquery('simple', '北京')) + ...) AS score
+    /// FROM templates
+    /// WHERE deleted_at IS NULL
+    ///   AND (search_vector @@ plainto_tsquery('simple', '北京') OR search_text ILIKE '%北京%')
+    ///   AND (search_vector @@ plainto_tsquery('simple', '租房') OR search_text ILIKE '%租房%')
+    ///   AND location_tag IN ('CN-GZ', 'CN-GD', 'CN')
+    /// ORDER BY CASE location_tag WHEN 'CN-GZ' THEN 0 WHEN 'CN-GD' THEN 1 WHEN 'CN' THEN 2 ELSE 3 END,
+    ///          score DESC, is_official DESC, created_at DESC
+    /// LIMIT 20 OFFSET 0;
     /// ```
-    async fn search(&self, query: TemplateSearchQuery) -> AppResult<Vec<Template>> {
+    async fn search(
+        &self,
+        query: TemplateSearchQuery,
+        location_tags: Vec<String>,
+        preferred_locations: Vec<String>,
+    ) -> AppResult<Vec<(Template, f64)>> {
         // 分页参数（默认第1页，每页20条）
         let page = query.page.unwrap_or(1);
         let page_size = query.page_size.unwrap_or(20);
         let offset = ((page - 1) * page_size) as u64;
         
-        // 开始构建查询
-        let mut query_builder = TemplateEntity::find();
-        
-        // 关键词搜索（模糊匹配标题和描述）
-        // 使用 OR 条件：title LIKE '%keyword%' OR description LIKE '%keyword%'
-        if let Some(keyword) = query.keyword {
-            let pattern = format!("%{}%", keyword);
-            query_builder = query_builder.filter(
-                sea_orm::Condition::any()
-                    .add(TemplateColumn::Title.like(&pattern))
-                    .add(TemplateColumn::Description.like(&pattern))
+        // 开始构建查询（排除已删除的模板）
+        let mut query_builder = TemplateEntity::find()
+            .filter(TemplateColumn::DeletedAt.is_null());
+        
+        // 关键词搜索：多个关键词之间是 AND，相关度逐个累加
+        let keywords = query.keyword.as_deref().map(parse_keywords).unwrap_or_default();
+        let mut score: Option<SimpleExpr> = None;
+        
+        for keyword in &keywords {
+            let pattern = format!("%{}%", escape_like(keyword));
+            
+            query_builder = query_builder.filter(Expr::cust_with_values(
+                "(search_vector @@ plainto_tsquery('simple', $1) OR search_text ILIKE $2)",
+                [keyword.clone(), pattern.clone()],
+            ));
+            
+            let keyword_score = Expr::cust_with_values(
+                "(CAST(ts_rank(search_vector, plainto_tsquery('simple', $1)) AS DOUBLE PRECISION) \
+                 + CASE WHEN title ILIKE $2 THEN 3 ELSE 0 END \
+                 + CASE WHEN description ILIKE $2 THEN 2 ELSE 0 END \
+                 + CASE WHEN search_text ILIKE $2 THEN 1 ELSE 0 END)",
+                [keyword.clone(), pattern],
             );
+            
+            score = Some(match score {
+                Some(total) => total.add(keyword_score),
+                None => keyword_score,
+            });
         }
         
         // 地理位置过滤
-        // 查找该地理位置 OR 通用模板（CN）
-        // 例如：查询北京模板时，返回 CN-BJ 和 CN 的模板
-        if let Some(location_tag) = query.location_tag {
-            query_builder = query_builder.filter(
-                sea_orm::Condition::any()
-                    .add(TemplateColumn::LocationTag.eq(&location_tag))
-                    .add(TemplateColumn::LocationTag.eq("CN"))
-            );
+        // 例如：查询广州模板时，返回 CN-GZ、CN-GD 和 CN 的模板
+        if !location_tags.is_empty() {
+            query_builder = query_builder.filter(TemplateColumn::LocationTag.is_in(location_tags));
         }
         
-        // 按创建时间倒序排列，应用分页
-        let templates = query_builder
-            .order_by_desc(TemplateColumn::CreatedAt)
+        let by_relevance = score.is_some();
+        let query_builder = query_builder.column_as(
+            score.unwrap_or_else(|| Expr::cust("CAST(0 AS DOUBLE PRECISION)")),
+            "score",
+        );
+        
+        // 排序，应用分页
+        let rows = Self::order_by_preference(query_builder, preferred_locations, by_relevance)
             .offset(offset)
             .limit(page_size as u64)
+            .into_model::<ScoredTemplate>()
             .all(&self.db)
             .await?;
 
-        Ok(templates)
+        Ok(rows.into_iter().map(|row| (row.template, row.score)).collect())
     }
 
     /// 根据地理位置查找模板
     /// 
     /// ## 地理位置查询逻辑
     /// 
-    /// 查询指定地理位置的模板时，同时返回通用模板（CN）。
+    /// 查询指定地区的模板时，同时返回所属省份和通用（CN）的模板。
     /// 
     /// ### 设计理由
     /// 
-    /// 用户在北京查看"第一次租房"模板时，应该看到：
-    /// - CN-BJ（北京专属）的租房模板
+    /// 用户在广州查看"第一次租房"模板时，应该看到：
+    /// - CN-GZ（广州专属）的租房模板
+    /// - CN-GD（广东省内通用）的租房模板
     /// - CN（通用）的租房模板
     /// 
     /// ### SQL示例
     /// ```sql
     /// SELECT * FROM templates
-    /// WHERE location_tag = 'CN-BJ' OR location_tag = 'CN'
+    /// WHERE deleted_at IS NULL
+    ///   AND location_tag IN ('CN-GZ', 'CN-GD', 'CN')
     /// ORDER BY created_at DESC;
     /// ```
-    async fn find_by_location(&self, location_tag: String) -> AppResult<Vec<Template>> {
+    async fn find_by_location(&self, location_tags: Vec<String>) -> AppResult<Vec<Template>> {
         let templates = TemplateEntity::find()
-            .filter(
-                sea_orm::Condition::any()
-                    .add(TemplateColumn::LocationTag.eq(&location_tag))
-                    .add(TemplateColumn::LocationTag.eq("CN"))
-            )
+            .filter(TemplateColumn::DeletedAt.is_null())
+            .filter(TemplateColumn::LocationTag.is_in(location_tags))
             .order_by_desc(TemplateColumn::CreatedAt)
             .all(&self.db)
             .await?;
@@ -248,11 +582,13 @@ impl TemplateRepository for TemplateRepositoryImpl {
         Ok(templates)
     }
 
-    async fn list_all(&self, page: i32, page_size: i32) -> AppResult<Vec<Template>> {
+    async fn list_all(&self, page: i32, page_size: i32, preferred_locations: Vec<String>) -> AppResult<Vec<Template>> {
         let offset = ((page - 1) * page_size) as u64;
         
-        let templates = TemplateEntity::find()
-            .order_by_desc(TemplateColumn::CreatedAt)
+        let query_builder = TemplateEntity::find()
+            .filter(TemplateColumn::DeletedAt.is_null());
+        
+        let templates = Self::order_by_preference(query_builder, preferred_locations, false)
             .offset(offset)
             .limit(page_size as u64)
             .all(&self.db)
@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use common::AppResult;
-use models::{UserChecklist, StepProgress, Template, UserChecklistEntity, UserChecklistColumn};
+use models::{UserChecklist, ChecklistStep, StepProgress, Template, TemplateStep, UserChecklistEntity, UserChecklistColumn};
 use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set, ColumnTrait, IntoActiveModel, ActiveModelTrait};
 use uuid::Uuid;
 
@@ -13,13 +13,14 @@ use uuid::Uuid;
 /// - Fork模板创建清单
 /// - 查询用户的所有清单
 /// - 更新步骤完成状态
+/// - 保存用户对步骤列表的修改
 /// 
 /// ## Fork机制
 /// 
 /// 用户Fork模板时，会创建清单的"快照"：
-/// 1. 复制模板的标题和步骤
+/// 1. 复制模板的标题和有效步骤（沿继承链解析后；之后步骤归清单所有）
 /// 2. 初始化所有步骤为未完成状态
-/// 3. 记录来源模板ID（source_template_id）
+/// 3. 记录来源模板ID和版本号（source_template_id、source_template_version）
 /// 4. 后续模板修改不影响已创建的清单
 #[async_trait]
 pub trait UserChecklistRepository: Send + Sync {
@@ -28,10 +29,11 @@ pub trait UserChecklistRepository: Send + Sync {
     /// ## 参数
     /// - `user_id`: 用户ID
     /// - `template`: 要Fork的模板
+    /// - `steps`: 模板的有效步骤（已沿继承链解析），同时作为同步基准保存
     /// 
     /// ## 返回值
     /// 新创建的用户清单（所有步骤初始化为未完成）
-    async fn create_from_template(&self, user_id: Uuid, template: &Template) -> AppResult<UserChecklist>;
+    async fn create_from_template(&self, user_id: Uuid, template: &Template, steps: &[TemplateStep]) -> AppResult<UserChecklist>;
     
     /// 根据ID查找清单
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<UserChecklist>>;
@@ -45,7 +47,7 @@ pub trait UserChecklistRepository: Send + Sync {
     /// 
     /// ## 参数
     /// - `checklist_id`: 清单ID
-    /// - `step_index`: 步骤索引（从0开始）
+    /// - `step_id`: 步骤ID（模板步骤的`id`）
     /// - `completed`: 新的完成状态
     /// 
     /// ## 逻辑
@@ -53,7 +55,92 @@ pub trait UserChecklistRepository: Send + Sync {
     /// 2. 在内存中更新指定步骤的状态
     /// 3. 如果设为完成，记录当前时间
     /// 4. 保存整个progress_status到数据库
-    async fn update_step_status(&self, checklist_id: Uuid, step_index: i32, completed: bool) -> AppResult<UserChecklist>;
+    async fn update_step_status(&self, checklist_id: Uuid, step_id: Uuid, completed: bool) -> AppResult<UserChecklist>;
+    
+    /// 将清单同步到来源模板的新版本
+    /// 
+    /// ## 参数
+    /// - `checklist_id`: 清单ID
+    /// - `version`: 同步后的模板版本号
+    /// - `base_steps`: 上游当前的有效步骤，作为下一次同步的基准
+    /// - `steps` / `progress`: 合并后的步骤和进度（由`models::plan_sync`计算）
+    async fn apply_sync(
+        &self,
+        checklist_id: Uuid,
+        version: i32,
+        base_steps: Vec<TemplateStep>,
+        steps: Vec<ChecklistStep>,
+        progress: Vec<StepProgress>,
+    ) -> AppResult<UserChecklist>;
+    
+    /// 保存清单的步骤列表和进度
+    /// 
+    /// 用于添加、修改、隐藏、删除和重排步骤，两个字段整体替换
+    async fn update_steps(
+        &self,
+        checklist_id: Uuid,
+        steps: Vec<ChecklistStep>,
+        progress: Vec<StepProgress>,
+    ) -> AppResult<UserChecklist>;
+}
+
+/// Fork时的步骤快照和初始进度（所有步骤未完成）
+/// 
+/// 步骤按模板中的顺序重新编号`order`
+pub(crate) fn fork_steps(template_steps: &[TemplateStep]) -> (Vec<ChecklistStep>, Vec<StepProgress>) {
+    let steps = template_steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| ChecklistStep {
+            order: index as i32,
+            ..ChecklistStep::from_template(step)
+        })
+        .collect();
+    let progress = template_steps
+        .iter()
+        .map(|step| StepProgress {
+            step_id: step.id,
+            completed: false,
+            completed_at: None,
+        })
+        .collect();
+
+    (steps, progress)
+}
+
+/// 设置一个步骤的完成状态，返回更新后的整个进度列表
+/// 
+/// - 步骤必须属于这个清单，否则返回`NotFound`
+/// - 设为完成时记录当前时间，设为未完成时清空
+/// - 没有进度记录的步骤（如后来添加的步骤）新建一条
+pub(crate) fn set_step_status(checklist: &UserChecklist, step_id: Uuid, completed: bool) -> AppResult<Vec<StepProgress>> {
+    if !checklist.get_steps()?.iter().any(|s| s.id == step_id) {
+        return Err(common::AppError::NotFound(format!("Step {} not found", step_id)));
+    }
+    
+    let mut progress_status = checklist.get_progress()?;
+    
+    let index = match progress_status.iter().position(|s| s.step_id == step_id) {
+        Some(index) => index,
+        None => {
+            progress_status.push(StepProgress {
+                step_id,
+                completed: false,
+                completed_at: None,
+            });
+            progress_status.len() - 1
+        }
+    };
+    let step = &mut progress_status[index];
+    
+    step.completed = completed;
+    step.completed_at = if completed {
+        Some(chrono::Utc::now())
+    } else {
+        None
+    };
+    
+    Ok(progress_status)
 }
 
 /// 用户清单Repository的SeaORM实现
@@ -76,16 +163,17 @@ impl UserChecklistRepository for UserChecklistRepositoryImpl {
     /// 
     /// Fork时创建模板的"快照"，步骤存储在清单中：
     /// - ✅ 标题复制：清单独立存储标题
+    /// - ✅ 步骤复制：清单独立存储步骤，用户可以自行修改
     /// - ✅ 进度独立：每个清单有自己的进度状态
     /// - ✅ 不受影响：模板后续修改不影响已创建的清单
     /// 
     /// ### 初始化进度状态
     /// 
-    /// 为模板的每个步骤创建 StepProgress：
+    /// 为模板的每个步骤创建 StepProgress（通过步骤ID关联）：
     /// ```json
     /// [
-    ///   { "step_index": 0, "completed": false, "completed_at": null },
-    ///   { "step_index": 1, "completed": false, "completed_at": null },
+    ///   { "step_id": "uuid-1", "completed": false, "completed_at": null },
+    ///   { "step_id": "uuid-2", "completed": false, "completed_at": null },
     ///   ...
     /// ]
     /// ```
@@ -93,40 +181,37 @@ impl UserChecklistRepository for UserChecklistRepositoryImpl {
     /// ### SQL示例
     /// ```sql
     /// INSERT INTO user_checklists (
-    ///   id, user_id, source_template_id, title, 
-    ///   progress_status, created_at, updated_at
+    ///   id, user_id, source_template_id, source_template_version, title, 
+    ///   steps, progress_status, created_at, updated_at
     /// ) VALUES (
-    ///   $1, $2, $3, $4, 
-    ///   '[{"step_index": 0, "completed": false, ...}]'::jsonb,
-    ///   $5, $6
+    ///   $1, $2, $3, $4, $5, 
+    ///   '[{"id": "uuid-1", "title": "...", "is_custom": false, ...}]'::jsonb,
+    ///   '[{"step_id": "uuid-1", "completed": false, ...}]'::jsonb,
+    ///   $6, $7
     /// ) RETURNING *;
     /// ```
-    async fn create_from_template(&self, user_id: Uuid, template: &Template) -> AppResult<UserChecklist> {
+    async fn create_from_template(&self, user_id: Uuid, template: &Template, template_steps: &[TemplateStep]) -> AppResult<UserChecklist> {
         use models::user_checklist::ActiveModel;
         
         let id = Uuid::new_v4();
         let now = chrono::Utc::now();
         
-        // 获取模板步骤并初始化进度
-        let template_steps = template.get_steps()?;
-        let progress_status: Vec<StepProgress> = template_steps
-            .iter()
-            .enumerate()
-            .map(|(index, _step)| StepProgress {
-                step_index: index as i32,
-                completed: false,
-                completed_at: None,
-            })
-            .collect();
+        // 复制模板步骤并初始化进度
+        let (steps, progress_status) = fork_steps(template_steps);
         
-        // 序列化进度状态为 JSON
+        // 序列化步骤和进度状态为 JSON
+        let base_steps_json = serde_json::to_value(template_steps)?;
+        let steps_json = serde_json::to_value(&steps)?;
         let progress_json = serde_json::to_value(&progress_status)?;
         
         let active_model = ActiveModel {
             id: Set(id),
             user_id: Set(user_id),
             source_template_id: Set(template.id),
+            source_template_version: Set(template.version),
             title: Set(template.title.clone()),
+            steps: Set(steps_json),
+            base_steps: Set(base_steps_json),
             progress_status: Set(progress_json),
             created_at: Set(now),
             updated_at: Set(now),
@@ -175,35 +260,22 @@ impl UserChecklistRepository for UserChecklistRepositoryImpl {
     /// ```sql
     /// UPDATE user_checklists
     /// SET progress_status = '[
-    ///   {"step_index": 0, "completed": true, "completed_at": "2024-10-21T10:00:00Z"},
-    ///   {"step_index": 1, "completed": false, "completed_at": null}
+    ///   {"step_id": "uuid-1", "completed": true, "completed_at": "2024-10-21T10:00:00Z"},
+    ///   {"step_id": "uuid-2", "completed": false, "completed_at": null}
     /// ]'::jsonb,
     /// updated_at = NOW()
     /// WHERE id = $1
     /// RETURNING *;
     /// ```
-    async fn update_step_status(&self, checklist_id: Uuid, step_index: i32, completed: bool) -> AppResult<UserChecklist> {
+    async fn update_step_status(&self, checklist_id: Uuid, step_id: Uuid, completed: bool) -> AppResult<UserChecklist> {
         // 查找清单
         let checklist = UserChecklistEntity::find_by_id(checklist_id)
             .one(&self.db)
             .await?
             .ok_or_else(|| common::AppError::NotFound("Checklist not found".to_string()))?;
         
-        // 获取当前进度状态
-        let mut progress_status = checklist.get_progress()?;
-        
-        // 查找并更新指定步骤
-        let step = progress_status
-            .iter_mut()
-            .find(|s| s.step_index == step_index)
-            .ok_or_else(|| common::AppError::NotFound(format!("Step {} not found", step_index)))?;
-        
-        step.completed = completed;
-        step.completed_at = if completed {
-            Some(chrono::Utc::now())
-        } else {
-            None
-        };
+        // 在内存中更新指定步骤的进度
+        let progress_status = set_step_status(&checklist, step_id, completed)?;
         
         // 序列化更新后的进度
         let progress_json = serde_json::to_value(&progress_status)?;
@@ -217,4 +289,57 @@ impl UserChecklistRepository for UserChecklistRepositoryImpl {
         
         Ok(updated_checklist)
     }
+
+    async fn apply_sync(
+        &self,
+        checklist_id: Uuid,
+        version: i32,
+        base_steps: Vec<TemplateStep>,
+        steps: Vec<ChecklistStep>,
+        progress: Vec<StepProgress>,
+    ) -> AppResult<UserChecklist> {
+        let checklist = UserChecklistEntity::find_by_id(checklist_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound("Checklist not found".to_string()))?;
+        
+        let base_steps_json = serde_json::to_value(&base_steps)?;
+        let steps_json = serde_json::to_value(&steps)?;
+        let progress_json = serde_json::to_value(&progress)?;
+        
+        let mut active_model = checklist.into_active_model();
+        active_model.source_template_version = Set(version);
+        active_model.base_steps = Set(base_steps_json);
+        active_model.steps = Set(steps_json);
+        active_model.progress_status = Set(progress_json);
+        active_model.updated_at = Set(chrono::Utc::now());
+        
+        let updated_checklist = active_model.update(&self.db).await?;
+        
+        Ok(updated_checklist)
+    }
+
+    async fn update_steps(
+        &self,
+        checklist_id: Uuid,
+        steps: Vec<ChecklistStep>,
+        progress: Vec<StepProgress>,
+    ) -> AppResult<UserChecklist> {
+        let checklist = UserChecklistEntity::find_by_id(checklist_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound("Checklist not found".to_string()))?;
+        
+        let steps_json = serde_json::to_value(&steps)?;
+        let progress_json = serde_json::to_value(&progress)?;
+        
+        let mut active_model = checklist.into_active_model();
+        active_model.steps = Set(steps_json);
+        active_model.progress_status = Set(progress_json);
+        active_model.updated_at = Set(chrono::Utc::now());
+        
+        let updated_checklist = active_model.update(&self.db).await?;
+        
+        Ok(updated_checklist)
+    }
 }
@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use common::AppResult;
+use models::{User, UserIdentity, UserIdentityEntity, UserIdentityColumn, RegisterDto};
+use sea_orm::{
+    DatabaseConnection, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set, ColumnTrait,
+    ActiveModelTrait, TransactionTrait,
+};
+use uuid::Uuid;
+
+use super::user_repository::insert_user;
+
+/// 第三方账号绑定Repository接口
+/// 
+/// ## 职责
+/// 
+/// - 按`(provider, subject)`查找绑定的用户（第三方登录）
+/// - 绑定、解绑第三方账号
+/// - 首次第三方登录时在同一事务中创建用户和绑定
+#[async_trait]
+pub trait UserIdentityRepository: Send + Sync {
+    /// 绑定第三方账号到已有用户
+    async fn create(&self, user_id: Uuid, provider: &str, subject: &str, email: Option<String>) -> AppResult<UserIdentity>;
+    
+    /// 创建新用户并绑定第三方账号（同一事务）
+    async fn create_with_user(
+        &self,
+        dto: RegisterDto,
+        password_hash: String,
+        provider: &str,
+        subject: &str,
+    ) -> AppResult<(User, UserIdentity)>;
+    
+    /// 按提供方和提供方内的用户标识查找绑定
+    async fn find_by_subject(&self, provider: &str, subject: &str) -> AppResult<Option<UserIdentity>>;
+    
+    /// 列出用户绑定的所有第三方账号（按绑定时间排序）
+    async fn find_by_user(&self, user_id: Uuid) -> AppResult<Vec<UserIdentity>>;
+    
+    /// 解绑用户在某个提供方的账号
+    /// 
+    /// ## 返回值
+    /// - `true`: 解绑成功
+    /// - `false`: 用户没有绑定该提供方
+    async fn delete(&self, user_id: Uuid, provider: &str) -> AppResult<bool>;
+}
+
+/// 第三方账号绑定Repository的SeaORM实现
+#[derive(Clone)]
+pub struct UserIdentityRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl UserIdentityRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+async fn insert_identity<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    provider: &str,
+    subject: &str,
+    email: Option<String>,
+) -> AppResult<UserIdentity> {
+    use models::user_identity::ActiveModel;
+    
+    let identity = ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        provider: Set(provider.to_string()),
+        subject: Set(subject.to_string()),
+        email: Set(email),
+        created_at: Set(chrono::Utc::now()),
+    }
+    .insert(db)
+    .await?;
+
+    Ok(identity)
+}
+
+#[async_trait]
+impl UserIdentityRepository for UserIdentityRepositoryImpl {
+    async fn create(&self, user_id: Uuid, provider: &str, subject: &str, email: Option<String>) -> AppResult<UserIdentity> {
+        insert_identity(&self.db, user_id, provider, subject, email).await
+    }
+
+    async fn create_with_user(
+        &self,
+        dto: RegisterDto,
+        password_hash: String,
+        provider: &str,
+        subject: &str,
+    ) -> AppResult<(User, UserIdentity)> {
+        let txn = self.db.begin().await?;
+        
+        let email = dto.email.clone();
+        let user = insert_user(&txn, dto, password_hash).await?;
+        let identity = insert_identity(&txn, user.id, provider, subject, email).await?;
+        
+        txn.commit().await?;
+        Ok((user, identity))
+    }
+
+    async fn find_by_subject(&self, provider: &str, subject: &str) -> AppResult<Option<UserIdentity>> {
+        let identity = UserIdentityEntity::find()
+            .filter(UserIdentityColumn::Provider.eq(provider))
+            .filter(UserIdentityColumn::Subject.eq(subject))
+            .one(&self.db)
+            .await?;
+
+        Ok(identity)
+    }
+
+    async fn find_by_user(&self, user_id: Uuid) -> AppResult<Vec<UserIdentity>> {
+        let identities = UserIdentityEntity::find()
+            .filter(UserIdentityColumn::UserId.eq(user_id))
+            .order_by_asc(UserIdentityColumn::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(identities)
+    }
+
+    async fn delete(&self, user_id: Uuid, provider: &str) -> AppResult<bool> {
+        let result = UserIdentityEntity::delete_many()
+            .filter(UserIdentityColumn::UserId.eq(user_id))
+            .filter(UserIdentityColumn::Provider.eq(provider))
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+}
@@ -1,7 +1,8 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use common::AppResult;
-use models::{User, RegisterDto, UpdateProfileDto, UserEntity, UserColumn};
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, Set, ColumnTrait, ActiveModelTrait, IntoActiveModel};
+use models::{User, UserRole, RegisterDto, UpdateProfileDto, UserEntity, UserColumn};
+use sea_orm::{DatabaseConnection, ConnectionTrait, EntityTrait, QueryFilter, Set, ColumnTrait, ActiveModelTrait, IntoActiveModel};
 use uuid::Uuid;
 
 /// 用户Repository接口
@@ -12,11 +13,12 @@ use uuid::Uuid;
 /// 
 /// - 创建新用户（注册）
 /// - 查询用户（按ID、手机号、邮箱）
-/// - 更新用户资料
+/// - 更新用户资料、修改密码
+/// - 申请和撤销注销（彻底删除见`AccountRepository`）
 /// 
 /// ## 安全性
 /// 
-/// - 密码必须已经过bcrypt加密才能传入create方法
+/// - 密码必须已经过`PasswordService`加密才能传入create方法
 /// - 所有查询方法都返回完整的User对象（包含password_hash）
 /// - 业务层需要使用UserProfile过滤敏感信息
 #[async_trait]
@@ -25,13 +27,13 @@ pub trait UserRepository: Send + Sync {
     /// 
     /// ## 参数
     /// - `dto`: 注册数据（手机号/邮箱、明文密码、昵称）
-    /// - `password_hash`: 已加密的密码哈希（bcrypt）
+    /// - `password_hash`: 已加密的密码哈希
     /// 
     /// ## 返回值
     /// 创建成功的用户实体
     /// 
     /// ## 注意
-    /// 调用前必须先使用bcrypt加密密码！
+    /// 调用前必须先使用`PasswordService::hash_password`加密密码！
     async fn create(&self, dto: RegisterDto, password_hash: String) -> AppResult<User>;
     
     /// 根据ID查找用户
@@ -51,6 +53,49 @@ pub trait UserRepository: Send + Sync {
     /// 
     /// 动态更新：只更新DTO中提供的字段。
     async fn update_profile(&self, user_id: Uuid, dto: UpdateProfileDto) -> AppResult<User>;
+    
+    /// 设置用户角色
+    /// 
+    /// `role_scope`只用于城市主编，其他角色传`None`（由业务层校验）
+    async fn set_role(&self, user_id: Uuid, role: UserRole, role_scope: Option<String>) -> AppResult<User>;
+    
+    /// 修改密码
+    /// 
+    /// `password_hash`必须已经过`PasswordService::hash_password`加密
+    async fn update_password(&self, user_id: Uuid, password_hash: String) -> AppResult<User>;
+    
+    /// 设置或清除注销申请时间
+    /// 
+    /// - `Some(time)`: 申请注销（宽限期结束后彻底删除）
+    /// - `None`: 撤销注销
+    async fn set_deletion_requested_at(&self, user_id: Uuid, requested_at: Option<DateTime<Utc>>) -> AppResult<User>;
+}
+
+/// 插入新用户（普通角色）
+/// 
+/// 供需要在同一事务中创建用户和关联数据的Repository复用
+pub(crate) async fn insert_user<C: ConnectionTrait>(db: &C, dto: RegisterDto, password_hash: String) -> AppResult<User> {
+    use models::user::ActiveModel;
+    
+    let now = chrono::Utc::now();
+    
+    let active_model = ActiveModel {
+        id: Set(Uuid::new_v4()),
+        phone: Set(dto.phone),
+        email: Set(dto.email),
+        password_hash: Set(password_hash),
+        nickname: Set(dto.nickname),
+        avatar_url: Set(None),
+        home_city: Set(None),
+        role: Set(UserRole::User),
+        role_scope: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+        deletion_requested_at: Set(None),
+    };
+
+    let user = active_model.insert(db).await?;
+    Ok(user)
 }
 
 /// 用户Repository的SeaORM实现
@@ -68,25 +113,7 @@ impl UserRepositoryImpl {
 #[async_trait]
 impl UserRepository for UserRepositoryImpl {
     async fn create(&self, dto: RegisterDto, password_hash: String) -> AppResult<User> {
-        use models::user::ActiveModel;
-        
-        let id = Uuid::new_v4();
-        let now = chrono::Utc::now();
-        
-        let active_model = ActiveModel {
-            id: Set(id),
-            phone: Set(dto.phone),
-            email: Set(dto.email),
-            password_hash: Set(password_hash),
-            nickname: Set(dto.nickname),
-            avatar_url: Set(None),
-            home_city: Set(None),
-            created_at: Set(now),
-            updated_at: Set(now),
-        };
-
-        let user = active_model.insert(&self.db).await?;
-        Ok(user)
+        insert_user(&self.db, dto, password_hash).await
     }
 
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<User>> {
@@ -154,4 +181,50 @@ impl UserRepository for UserRepositoryImpl {
         
         Ok(updated_user)
     }
+
+    async fn set_role(&self, user_id: Uuid, role: UserRole, role_scope: Option<String>) -> AppResult<User> {
+        let user = UserEntity::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound("User not found".to_string()))?;
+        
+        let mut active_model = user.into_active_model();
+        active_model.role = Set(role);
+        active_model.role_scope = Set(role_scope);
+        active_model.updated_at = Set(chrono::Utc::now());
+        
+        let updated_user = active_model.update(&self.db).await?;
+        
+        Ok(updated_user)
+    }
+
+    async fn update_password(&self, user_id: Uuid, password_hash: String) -> AppResult<User> {
+        let user = UserEntity::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound("User not found".to_string()))?;
+        
+        let mut active_model = user.into_active_model();
+        active_model.password_hash = Set(password_hash);
+        active_model.updated_at = Set(chrono::Utc::now());
+        
+        let updated_user = active_model.update(&self.db).await?;
+        
+        Ok(updated_user)
+    }
+
+    async fn set_deletion_requested_at(&self, user_id: Uuid, requested_at: Option<DateTime<Utc>>) -> AppResult<User> {
+        let user = UserEntity::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| common::AppError::NotFound("User not found".to_string()))?;
+        
+        let mut active_model = user.into_active_model();
+        active_model.deletion_requested_at = Set(requested_at);
+        active_model.updated_at = Set(chrono::Utc::now());
+        
+        let updated_user = active_model.update(&self.db).await?;
+        
+        Ok(updated_user)
+    }
 }
@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use common::AppResult;
+use models::{VerificationCode, VerificationCodeEntity, VerificationCodeColumn};
+use sea_orm::{
+    DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, PaginatorTrait, Set, ColumnTrait,
+    ActiveModelTrait, TransactionTrait, sea_query::Expr,
+};
+use uuid::Uuid;
+
+/// 短信验证码Repository接口
+/// 
+/// ## 职责
+/// 
+/// - 保存新发送的验证码（只保存哈希），同时作废该手机号之前的验证码
+/// - 查找手机号最新的验证码
+/// - 统计发送次数（发送频率限制）
+/// - 记录输错次数、标记验证码已使用
+#[async_trait]
+pub trait VerificationCodeRepository: Send + Sync {
+    /// 保存新验证码，并作废该手机号之前尚未使用的验证码
+    async fn create(&self, phone: &str, code_hash: String, expires_at: DateTime<Utc>) -> AppResult<VerificationCode>;
+    
+    /// 查找手机号最新发送的验证码（包括已使用、已过期的）
+    async fn find_latest(&self, phone: &str) -> AppResult<Option<VerificationCode>>;
+    
+    /// 统计手机号在`since`之后发送的验证码数量
+    async fn count_since(&self, phone: &str, since: DateTime<Utc>) -> AppResult<u64>;
+    
+    /// 输错次数 + 1
+    async fn increment_attempts(&self, id: Uuid) -> AppResult<()>;
+    
+    /// 标记验证码已使用
+    /// 
+    /// ## 返回值
+    /// - `true`: 标记成功
+    /// - `false`: 验证码已经被使用（并发的登录请求抢先使用了它）
+    async fn consume(&self, id: Uuid) -> AppResult<bool>;
+}
+
+/// 短信验证码Repository的SeaORM实现
+#[derive(Clone)]
+pub struct VerificationCodeRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl VerificationCodeRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl VerificationCodeRepository for VerificationCodeRepositoryImpl {
+    async fn create(&self, phone: &str, code_hash: String, expires_at: DateTime<Utc>) -> AppResult<VerificationCode> {
+        use models::verification_code::ActiveModel;
+        
+        let now = Utc::now();
+        let txn = self.db.begin().await?;
+        
+        // 每个手机号只有最新的验证码有效
+        VerificationCodeEntity::update_many()
+            .col_expr(VerificationCodeColumn::ConsumedAt, Expr::value(now))
+            .filter(VerificationCodeColumn::Phone.eq(phone))
+            .filter(VerificationCodeColumn::ConsumedAt.is_null())
+            .exec(&txn)
+            .await?;
+        
+        let code = ActiveModel {
+            id: Set(Uuid::new_v4()),
+            phone: Set(phone.to_string()),
+            code_hash: Set(code_hash),
+            attempts: Set(0),
+            expires_at: Set(expires_at),
+            consumed_at: Set(None),
+            created_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+        
+        txn.commit().await?;
+        Ok(code)
+    }
+
+    async fn find_latest(&self, phone: &str) -> AppResult<Option<VerificationCode>> {
+        let code = VerificationCodeEntity::find()
+            .filter(VerificationCodeColumn::Phone.eq(phone))
+            .order_by_desc(VerificationCodeColumn::CreatedAt)
+            .one(&self.db)
+            .await?;
+
+        Ok(code)
+    }
+
+    async fn count_since(&self, phone: &str, since: DateTime<Utc>) -> AppResult<u64> {
+        let count = VerificationCodeEntity::find()
+            .filter(VerificationCodeColumn::Phone.eq(phone))
+            .filter(VerificationCodeColumn::CreatedAt.gte(since))
+            .count(&self.db)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// ### SQL示例
+    /// ```sql
+    /// UPDATE verification_codes SET attempts = attempts + 1 WHERE id = $1;
+    /// ```
+    async fn increment_attempts(&self, id: Uuid) -> AppResult<()> {
+        VerificationCodeEntity::update_many()
+            .col_expr(
+                VerificationCodeColumn::Attempts,
+                Expr::col(VerificationCodeColumn::Attempts).add(1),
+            )
+            .filter(VerificationCodeColumn::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn consume(&self, id: Uuid) -> AppResult<bool> {
+        let result = VerificationCodeEntity::update_many()
+            .col_expr(VerificationCodeColumn::ConsumedAt, Expr::value(Utc::now()))
+            .filter(VerificationCodeColumn::Id.eq(id))
+            .filter(VerificationCodeColumn::ConsumedAt.is_null())
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected == 1)
+    }
+}
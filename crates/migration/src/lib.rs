@@ -3,6 +3,22 @@ pub use sea_orm_migration::prelude::*;
 mod m20241021_000001_create_users;
 mod m20241021_000002_create_templates;
 mod m20241021_000003_create_user_checklists;
+mod m20241028_000004_soft_delete_templates;
+mod m20241104_000005_create_template_versions;
+mod m20241111_000006_backfill_step_ids;
+mod m20241118_000007_add_checklist_steps;
+mod m20241125_000008_add_checklist_base_steps;
+mod m20241202_000009_create_locations;
+mod m20241209_000010_add_template_search;
+mod m20241216_000011_add_user_roles;
+mod m20241223_000012_create_refresh_tokens;
+mod m20241230_000013_create_verification_codes;
+mod m20250106_000014_create_user_identities;
+mod m20250113_000015_create_password_reset_tokens;
+mod m20250120_000016_create_login_attempts;
+mod m20250127_000017_add_account_deletion;
+mod m20250203_000018_create_orphaned_files;
+mod m20250210_000019_add_template_version_parent;
 
 pub struct Migrator;
 
@@ -13,6 +29,22 @@ impl MigratorTrait for Migrator {
             Box::new(m20241021_000001_create_users::Migration),
             Box::new(m20241021_000002_create_templates::Migration),
             Box::new(m20241021_000003_create_user_checklists::Migration),
+            Box::new(m20241028_000004_soft_delete_templates::Migration),
+            Box::new(m20241104_000005_create_template_versions::Migration),
+            Box::new(m20241111_000006_backfill_step_ids::Migration),
+            Box::new(m20241118_000007_add_checklist_steps::Migration),
+            Box::new(m20241125_000008_add_checklist_base_steps::Migration),
+            Box::new(m20241202_000009_create_locations::Migration),
+            Box::new(m20241209_000010_add_template_search::Migration),
+            Box::new(m20241216_000011_add_user_roles::Migration),
+            Box::new(m20241223_000012_create_refresh_tokens::Migration),
+            Box::new(m20241230_000013_create_verification_codes::Migration),
+            Box::new(m20250106_000014_create_user_identities::Migration),
+            Box::new(m20250113_000015_create_password_reset_tokens::Migration),
+            Box::new(m20250120_000016_create_login_attempts::Migration),
+            Box::new(m20250127_000017_add_account_deletion::Migration),
+            Box::new(m20250203_000018_create_orphaned_files::Migration),
+            Box::new(m20250210_000019_add_template_version_parent::Migration),
         ]
     }
 }
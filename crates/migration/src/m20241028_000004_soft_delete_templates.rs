@@ -0,0 +1,106 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 为 templates 表添加软删除标记
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Templates::Table)
+                    .add_column(timestamp_with_time_zone_null(Templates::DeletedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_templates_deleted_at")
+                    .table(Templates::Table)
+                    .col(Templates::DeletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 删除模板时不再级联删除用户清单
+        // 模板改为软删除，外键改为 RESTRICT，防止物理删除时悄悄清空用户的进度
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_user_checklists_source_template_id")
+                    .table(UserChecklists::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_user_checklists_source_template_id")
+                    .from(UserChecklists::Table, UserChecklists::SourceTemplateId)
+                    .to(Templates::Table, Templates::Id)
+                    .on_delete(ForeignKeyAction::Restrict)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_user_checklists_source_template_id")
+                    .table(UserChecklists::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_user_checklists_source_template_id")
+                    .from(UserChecklists::Table, UserChecklists::SourceTemplateId)
+                    .to(Templates::Table, Templates::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_templates_deleted_at")
+                    .table(Templates::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Templates::Table)
+                    .drop_column(Templates::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Templates {
+    Table,
+    Id,
+    DeletedAt,
+}
+
+#[derive(DeriveIden)]
+enum UserChecklists {
+    Table,
+    SourceTemplateId,
+}
@@ -0,0 +1,142 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // templates 表添加当前版本号
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Templates::Table)
+                    .add_column(integer(Templates::Version).default(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 创建 template_versions 表（不可变的历史快照）
+        manager
+            .create_table(
+                Table::create()
+                    .table(TemplateVersions::Table)
+                    .if_not_exists()
+                    .col(uuid(TemplateVersions::Id).primary_key())
+                    .col(uuid(TemplateVersions::TemplateId))
+                    .col(integer(TemplateVersions::Version))
+                    .col(string_len(TemplateVersions::Title, 255))
+                    .col(text(TemplateVersions::Description))
+                    .col(json_binary(TemplateVersions::Steps)) // JSONB
+                    .col(uuid(TemplateVersions::CreatedBy))
+                    .col(timestamp_with_time_zone(TemplateVersions::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_template_versions_template_id")
+                            .from(TemplateVersions::Table, TemplateVersions::TemplateId)
+                            .to(Templates::Table, Templates::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_template_versions_created_by")
+                            .from(TemplateVersions::Table, TemplateVersions::CreatedBy)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 同一模板的版本号唯一
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_template_versions_template_version")
+                    .table(TemplateVersions::Table)
+                    .col(TemplateVersions::TemplateId)
+                    .col(TemplateVersions::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 为已有模板补齐第1版快照
+        let sql = r#"
+            INSERT INTO template_versions (id, template_id, version, title, description, steps, created_by, created_at)
+            SELECT gen_random_uuid(), id, 1, title, description, steps, created_by, updated_at
+            FROM templates
+        "#;
+
+        manager.get_connection().execute_unprepared(sql).await?;
+
+        // user_checklists 表记录Fork时的模板版本
+        // 已有清单都是基于第1版Fork的
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserChecklists::Table)
+                    .add_column(integer(UserChecklists::SourceTemplateVersion).default(1))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserChecklists::Table)
+                    .drop_column(UserChecklists::SourceTemplateVersion)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TemplateVersions::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Templates::Table)
+                    .drop_column(Templates::Version)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TemplateVersions {
+    Table,
+    Id,
+    TemplateId,
+    Version,
+    Title,
+    Description,
+    Steps,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Templates {
+    Table,
+    Id,
+    Version,
+}
+
+#[derive(DeriveIden)]
+enum UserChecklists {
+    Table,
+    SourceTemplateVersion,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // 1. 为模板的每个步骤生成稳定ID
+        let sql = r#"
+            UPDATE templates t
+            SET steps = COALESCE((
+                SELECT jsonb_agg(
+                    CASE WHEN s.elem ? 'id' THEN s.elem
+                         ELSE s.elem || jsonb_build_object('id', gen_random_uuid())
+                    END
+                    ORDER BY s.ord
+                )
+                FROM jsonb_array_elements(t.steps) WITH ORDINALITY AS s(elem, ord)
+            ), '[]'::jsonb)
+        "#;
+        db.execute_unprepared(sql).await?;
+
+        // 2. 历史版本沿用当前模板中同名步骤的ID（同名步骤按出现顺序一一对应）
+        //    当前模板中已不存在的步骤生成新ID
+        let sql = r#"
+            UPDATE template_versions v
+            SET steps = COALESCE((
+                SELECT jsonb_agg(
+                    CASE WHEN vs.elem ? 'id' THEN vs.elem
+                         ELSE vs.elem || jsonb_build_object('id', COALESCE(cs.id, gen_random_uuid()::text))
+                    END
+                    ORDER BY vs.ord
+                )
+                FROM (
+                    SELECT elem, ord,
+                           row_number() OVER (PARTITION BY elem->>'title' ORDER BY ord) AS rn
+                    FROM jsonb_array_elements(v.steps) WITH ORDINALITY AS s(elem, ord)
+                ) vs
+                LEFT JOIN (
+                    SELECT c.elem->>'id' AS id, c.elem->>'title' AS title,
+                           row_number() OVER (PARTITION BY c.elem->>'title' ORDER BY c.ord) AS rn
+                    FROM templates t
+                    CROSS JOIN LATERAL jsonb_array_elements(t.steps) WITH ORDINALITY AS c(elem, ord)
+                    WHERE t.id = v.template_id
+                ) cs ON cs.title = vs.elem->>'title' AND cs.rn = vs.rn
+            ), '[]'::jsonb)
+        "#;
+        db.execute_unprepared(sql).await?;
+
+        // 3. 清单进度：step_index 指向Fork版本中的位置，换成该位置步骤的ID
+        let sql = r#"
+            UPDATE user_checklists uc
+            SET progress_status = COALESCE((
+                SELECT jsonb_agg(
+                    (p.elem - 'step_index')
+                        || jsonb_build_object('step_id', COALESCE(vs.elem->>'id', gen_random_uuid()::text))
+                    ORDER BY p.ord
+                )
+                FROM jsonb_array_elements(uc.progress_status) WITH ORDINALITY AS p(elem, ord)
+                LEFT JOIN template_versions v
+                    ON v.template_id = uc.source_template_id
+                   AND v.version = uc.source_template_version
+                LEFT JOIN LATERAL jsonb_array_elements(v.steps) WITH ORDINALITY AS vs(elem, ord)
+                    ON vs.ord = (p.elem->>'step_index')::bigint + 1
+            ), '[]'::jsonb)
+            WHERE EXISTS (
+                SELECT 1 FROM jsonb_array_elements(uc.progress_status) AS e(elem)
+                WHERE e.elem ? 'step_index'
+            )
+        "#;
+        db.execute_unprepared(sql).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // 进度恢复为Fork版本中的位置索引（找不到步骤时退回进度数组中的位置）
+        let sql = r#"
+            UPDATE user_checklists uc
+            SET progress_status = COALESCE((
+                SELECT jsonb_agg(
+                    (p.elem - 'step_id')
+                        || jsonb_build_object('step_index', COALESCE(vs.ord, p.ord) - 1)
+                    ORDER BY p.ord
+                )
+                FROM jsonb_array_elements(uc.progress_status) WITH ORDINALITY AS p(elem, ord)
+                LEFT JOIN template_versions v
+                    ON v.template_id = uc.source_template_id
+                   AND v.version = uc.source_template_version
+                LEFT JOIN LATERAL jsonb_array_elements(v.steps) WITH ORDINALITY AS vs(elem, ord)
+                    ON vs.elem->>'id' = p.elem->>'step_id'
+            ), '[]'::jsonb)
+        "#;
+        db.execute_unprepared(sql).await?;
+
+        for table in ["templates", "template_versions"] {
+            let sql = format!(
+                r#"
+                UPDATE {table} t
+                SET steps = COALESCE((
+                    SELECT jsonb_agg(s.elem - 'id' ORDER BY s.ord)
+                    FROM jsonb_array_elements(t.steps) WITH ORDINALITY AS s(elem, ord)
+                ), '[]'::jsonb)
+                "#
+            );
+            db.execute_unprepared(&sql).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 清单拥有自己的步骤列表
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserChecklists::Table)
+                    .add_column(json_binary(UserChecklists::Steps).default(Expr::cust("'[]'::jsonb")))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已有清单从Fork时的模板版本复制步骤
+        let sql = r#"
+            UPDATE user_checklists uc
+            SET steps = COALESCE((
+                SELECT jsonb_agg(
+                    jsonb_build_object(
+                        'id', s.elem->'id',
+                        'title', s.elem->'title',
+                        'description', COALESCE(s.elem->'description', 'null'::jsonb),
+                        'order', s.ord - 1,
+                        'is_custom', false,
+                        'hidden', false
+                    )
+                    ORDER BY s.ord
+                )
+                FROM template_versions v
+                CROSS JOIN LATERAL jsonb_array_elements(v.steps) WITH ORDINALITY AS s(elem, ord)
+                WHERE v.template_id = uc.source_template_id
+                  AND v.version = uc.source_template_version
+            ), '[]'::jsonb)
+        "#;
+
+        manager.get_connection().execute_unprepared(sql).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserChecklists::Table)
+                    .drop_column(UserChecklists::Steps)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserChecklists {
+    Table,
+    Steps,
+}
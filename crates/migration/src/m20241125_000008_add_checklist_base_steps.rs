@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 清单记录Fork（或上次同步）时上游的有效步骤，作为同步的合并基准
+        // 模板继承后，有效步骤不再只由模板自身的版本决定
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserChecklists::Table)
+                    .add_column(json_binary(UserChecklists::BaseSteps).default(Expr::cust("'[]'::jsonb")))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已有清单的基准就是Fork时的版本快照（此前没有继承逻辑）
+        let sql = r#"
+            UPDATE user_checklists uc
+            SET base_steps = v.steps
+            FROM template_versions v
+            WHERE v.template_id = uc.source_template_id
+              AND v.version = uc.source_template_version
+        "#;
+
+        manager.get_connection().execute_unprepared(sql).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserChecklists::Table)
+                    .drop_column(UserChecklists::BaseSteps)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserChecklists {
+    Table,
+    BaseSteps,
+}
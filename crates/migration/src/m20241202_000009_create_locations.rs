@@ -0,0 +1,87 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 创建 locations 表（国家 → 省 → 城市）
+        manager
+            .create_table(
+                Table::create()
+                    .table(Locations::Table)
+                    .if_not_exists()
+                    .col(string_len(Locations::Code, 20).primary_key())
+                    .col(string_len_null(Locations::ParentCode, 20))
+                    .col(string_len(Locations::Level, 20))
+                    .col(string_len(Locations::Name, 50))
+                    .col(string_len(Locations::Pinyin, 100))
+                    .col(json_binary(Locations::Aliases).default(Expr::cust("'[]'::jsonb")))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_locations_parent_code")
+                            .from(Locations::Table, Locations::ParentCode)
+                            .to(Locations::Table, Locations::Code)
+                            .on_delete(ForeignKeyAction::Restrict)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 按上级查询下级地区
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_locations_parent_code")
+                    .table(Locations::Table)
+                    .col(Locations::ParentCode)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 初始数据：直辖市直接挂在全国下面，与省同级
+        // 原有的 CN / CN-BJ / CN-SH / CN-GZ / CN-SZ 代码保持不变
+        let sql = r#"
+            INSERT INTO locations (code, parent_code, level, name, pinyin, aliases) VALUES
+            ('CN',    NULL,    'country',  '中国', 'zhongguo',  '["全国"]'),
+            ('CN-BJ', 'CN',    'city',     '北京', 'beijing',   '["北京市", "京", "帝都"]'),
+            ('CN-SH', 'CN',    'city',     '上海', 'shanghai',  '["上海市", "沪", "魔都"]'),
+            ('CN-TJ', 'CN',    'city',     '天津', 'tianjin',   '["天津市", "津"]'),
+            ('CN-CQ', 'CN',    'city',     '重庆', 'chongqing', '["重庆市", "渝", "山城"]'),
+            ('CN-GD', 'CN',    'province', '广东', 'guangdong', '["广东省", "粤"]'),
+            ('CN-ZJ', 'CN',    'province', '浙江', 'zhejiang',  '["浙江省", "浙"]'),
+            ('CN-JS', 'CN',    'province', '江苏', 'jiangsu',   '["江苏省", "苏"]'),
+            ('CN-SC', 'CN',    'province', '四川', 'sichuan',   '["四川省", "川", "蜀"]'),
+            ('CN-HB', 'CN',    'province', '湖北', 'hubei',     '["湖北省", "鄂"]'),
+            ('CN-GZ', 'CN-GD', 'city',     '广州', 'guangzhou', '["广州市", "穗", "羊城"]'),
+            ('CN-SZ', 'CN-GD', 'city',     '深圳', 'shenzhen',  '["深圳市", "鹏城"]'),
+            ('CN-HZ', 'CN-ZJ', 'city',     '杭州', 'hangzhou',  '["杭州市"]'),
+            ('CN-NJ', 'CN-JS', 'city',     '南京', 'nanjing',   '["南京市", "金陵"]'),
+            ('CN-SU', 'CN-JS', 'city',     '苏州', 'suzhou',    '["苏州市", "姑苏"]'),
+            ('CN-CD', 'CN-SC', 'city',     '成都', 'chengdu',   '["成都市", "蓉城"]'),
+            ('CN-WH', 'CN-HB', 'city',     '武汉', 'wuhan',     '["武汉市", "江城"]')
+        "#;
+
+        manager.get_connection().execute_unprepared(sql).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Locations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Locations {
+    Table,
+    Code,
+    ParentCode,
+    Level,
+    Name,
+    Pinyin,
+    Aliases,
+}
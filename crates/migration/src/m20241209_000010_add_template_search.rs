@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // 三元组索引：中文没有分词器，按子串匹配时依赖它加速
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm").await?;
+
+        // search_text: 标题、描述、步骤标题和说明拼成的纯文本（三元组匹配和高亮用）
+        // search_vector: 按字段加权的全文索引（标题 A、描述 B、步骤 C）
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE templates
+                ADD COLUMN search_text TEXT NOT NULL DEFAULT '',
+                ADD COLUMN search_vector TSVECTOR NOT NULL DEFAULT ''::tsvector
+            "#,
+        )
+        .await?;
+
+        // 新建和修改模板时由触发器自动维护，应用代码不需要关心这两列
+        db.execute_unprepared(
+            r#"
+            CREATE OR REPLACE FUNCTION templates_search_refresh() RETURNS trigger AS $$
+            DECLARE
+                steps_text TEXT;
+            BEGIN
+                SELECT COALESCE(string_agg(concat_ws(' ', s->>'title', s->>'description'), ' '), '')
+                  INTO steps_text
+                  FROM jsonb_array_elements(NEW.steps) AS s;
+
+                NEW.search_text := concat_ws(' ', NEW.title, NEW.description, steps_text);
+                NEW.search_vector :=
+                    setweight(to_tsvector('simple', COALESCE(NEW.title, '')), 'A') ||
+                    setweight(to_tsvector('simple', COALESCE(NEW.description, '')), 'B') ||
+                    setweight(to_tsvector('simple', steps_text), 'C');
+                RETURN NEW;
+            END
+            $$ LANGUAGE plpgsql
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE TRIGGER templates_search_refresh
+                BEFORE INSERT OR UPDATE OF title, description, steps ON templates
+                FOR EACH ROW EXECUTE FUNCTION templates_search_refresh()
+            "#,
+        )
+        .await?;
+
+        // 回填已有模板（触发器会重新计算）
+        db.execute_unprepared("UPDATE templates SET title = title").await?;
+
+        db.execute_unprepared(
+            "CREATE INDEX idx_templates_search_vector ON templates USING GIN (search_vector)",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "CREATE INDEX idx_templates_search_text_trgm ON templates USING GIN (search_text gin_trgm_ops)",
+        )
+        .await?;
+
+        // 建表时的表达式索引（只覆盖标题和描述，查询从未用上）由 search_vector 取代
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_templates_search").await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DROP TRIGGER IF EXISTS templates_search_refresh ON templates").await?;
+        db.execute_unprepared("DROP FUNCTION IF EXISTS templates_search_refresh()").await?;
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_templates_search_text_trgm").await?;
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_templates_search_vector").await?;
+        db.execute_unprepared(
+            "ALTER TABLE templates DROP COLUMN IF EXISTS search_vector, DROP COLUMN IF EXISTS search_text",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "CREATE INDEX idx_templates_search ON templates USING gin(to_tsvector('simple', title || ' ' || description))",
+        )
+        .await?;
+
+        Ok(())
+    }
+}
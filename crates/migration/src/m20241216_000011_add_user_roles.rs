@@ -0,0 +1,71 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 用户角色：user / contributor / city_curator / admin
+        // city_curator 只负责一个地区（role_scope），其他角色没有地区范围
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(string_len(Users::Role, 20).default("user"))
+                    .add_column(string_len_null(Users::RoleScope, 20))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_users_role_scope")
+                            .from_tbl(Users::Table)
+                            .from_col(Users::RoleScope)
+                            .to_tbl(Locations::Table)
+                            .to_col(Locations::Code)
+                            .on_delete(ForeignKeyAction::Restrict)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 有且只有 city_curator 带地区范围
+        let sql = r#"
+            ALTER TABLE users ADD CONSTRAINT chk_users_role_scope
+            CHECK ((role = 'city_curator') = (role_scope IS NOT NULL))
+        "#;
+
+        manager.get_connection().execute_unprepared(sql).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE users DROP CONSTRAINT IF EXISTS chk_users_role_scope")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_foreign_key(Alias::new("fk_users_role_scope"))
+                    .drop_column(Users::RoleScope)
+                    .drop_column(Users::Role)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Role,
+    RoleScope,
+}
+
+#[derive(DeriveIden)]
+enum Locations {
+    Table,
+    Code,
+}
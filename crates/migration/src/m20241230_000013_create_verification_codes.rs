@@ -0,0 +1,56 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 创建 verification_codes 表（短信验证码，只保存哈希）
+        manager
+            .create_table(
+                Table::create()
+                    .table(VerificationCodes::Table)
+                    .if_not_exists()
+                    .col(uuid(VerificationCodes::Id).primary_key())
+                    .col(string_len(VerificationCodes::Phone, 20))
+                    .col(string_len(VerificationCodes::CodeHash, 64))
+                    .col(integer(VerificationCodes::Attempts).default(0))
+                    .col(timestamp_with_time_zone(VerificationCodes::ExpiresAt))
+                    .col(timestamp_with_time_zone_null(VerificationCodes::ConsumedAt))
+                    .col(timestamp_with_time_zone(VerificationCodes::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 按手机号查找最新的验证码、统计发送次数
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_verification_codes_phone_created_at")
+                    .table(VerificationCodes::Table)
+                    .col(VerificationCodes::Phone)
+                    .col(VerificationCodes::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(VerificationCodes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum VerificationCodes {
+    Table,
+    Id,
+    Phone,
+    CodeHash,
+    Attempts,
+    ExpiresAt,
+    ConsumedAt,
+    CreatedAt,
+}
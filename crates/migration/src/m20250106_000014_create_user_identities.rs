@@ -0,0 +1,121 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 创建 user_identities 表（第三方账号与用户的绑定关系）
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserIdentities::Table)
+                    .if_not_exists()
+                    .col(uuid(UserIdentities::Id).primary_key())
+                    .col(uuid(UserIdentities::UserId))
+                    .col(string_len(UserIdentities::Provider, 50))
+                    .col(string_len(UserIdentities::Subject, 255)) // 提供方的用户标识（ID令牌的sub）
+                    .col(string_len_null(UserIdentities::Email, 255))
+                    .col(timestamp_with_time_zone(UserIdentities::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_user_identities_user_id")
+                            .from(UserIdentities::Table, UserIdentities::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 一个第三方账号只能绑定一个用户
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_identities_provider_subject")
+                    .table(UserIdentities::Table)
+                    .col(UserIdentities::Provider)
+                    .col(UserIdentities::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 每个用户在每个提供方只能绑定一个账号
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_identities_user_id_provider")
+                    .table(UserIdentities::Table)
+                    .col(UserIdentities::UserId)
+                    .col(UserIdentities::Provider)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 创建 oauth_states 表（进行中的授权请求，回调时取出并删除）
+        manager
+            .create_table(
+                Table::create()
+                    .table(OauthStates::Table)
+                    .if_not_exists()
+                    .col(string_len(OauthStates::State, 64).primary_key())
+                    .col(string_len(OauthStates::Provider, 50))
+                    .col(string_len(OauthStates::CodeVerifier, 128))
+                    .col(string_len(OauthStates::Nonce, 64))
+                    .col(uuid_null(OauthStates::UserId)) // 绑定第三方账号时是发起绑定的用户，登录时为空
+                    .col(timestamp_with_time_zone(OauthStates::ExpiresAt))
+                    .col(timestamp_with_time_zone(OauthStates::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_oauth_states_user_id")
+                            .from(OauthStates::Table, OauthStates::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OauthStates::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(UserIdentities::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserIdentities {
+    Table,
+    Id,
+    UserId,
+    Provider,
+    Subject,
+    Email,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OauthStates {
+    Table,
+    State,
+    Provider,
+    CodeVerifier,
+    Nonce,
+    UserId,
+    ExpiresAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,114 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 创建 login_attempts 表（按账号和按IP统计的连续登录失败次数）
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempts::Table)
+                    .if_not_exists()
+                    .col(uuid(LoginAttempts::Id).primary_key())
+                    .col(string_len(LoginAttempts::Scope, 20))
+                    .col(string_len(LoginAttempts::Subject, 255))
+                    .col(integer(LoginAttempts::Failures).default(0))
+                    .col(timestamp_with_time_zone(LoginAttempts::LastFailedAt))
+                    .col(timestamp_with_time_zone_null(LoginAttempts::LockedUntil))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 每个账号/IP一条记录，失败计数用 ON CONFLICT 原子累加
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_attempts_scope_subject")
+                    .table(LoginAttempts::Table)
+                    .col(LoginAttempts::Scope)
+                    .col(LoginAttempts::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 创建 login_lockouts 表（锁定审计记录）
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginLockouts::Table)
+                    .if_not_exists()
+                    .col(uuid(LoginLockouts::Id).primary_key())
+                    .col(string_len(LoginLockouts::Scope, 20))
+                    .col(string_len(LoginLockouts::Subject, 255))
+                    .col(uuid_null(LoginLockouts::UserId))
+                    .col(string_len_null(LoginLockouts::Ip, 45))
+                    .col(integer(LoginLockouts::Failures))
+                    .col(timestamp_with_time_zone(LoginLockouts::LockedUntil))
+                    .col(timestamp_with_time_zone(LoginLockouts::CreatedAt).default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_login_lockouts_user_id")
+                            .from(LoginLockouts::Table, LoginLockouts::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 按时间倒序查看锁定记录
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_lockouts_created_at")
+                    .table(LoginLockouts::Table)
+                    .col(LoginLockouts::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginLockouts::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(LoginAttempts::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginAttempts {
+    Table,
+    Id,
+    Scope,
+    Subject,
+    Failures,
+    LastFailedAt,
+    LockedUntil,
+}
+
+#[derive(DeriveIden)]
+enum LoginLockouts {
+    Table,
+    Id,
+    Scope,
+    Subject,
+    UserId,
+    Ip,
+    Failures,
+    LockedUntil,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,127 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 为 users 表添加注销申请时间（宽限期结束后由后台任务彻底删除）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(timestamp_with_time_zone_null(Users::DeletionRequestedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_users_deletion_requested_at")
+                    .table(Users::Table)
+                    .col(Users::DeletionRequestedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 占位用户"已注销用户"（固定ID，全零UUID）
+        // 注销的用户创建的模板和版本转给它，不再随用户级联删除；它没有密码，无法登录
+        let sql = r#"
+            INSERT INTO users (id, email, password_hash, nickname)
+            VALUES ('00000000-0000-0000-0000-000000000000', 'deleted-user@invalid', '!', '已注销用户')
+            ON CONFLICT (id) DO NOTHING
+        "#;
+
+        manager.get_connection().execute_unprepared(sql).await?;
+
+        // 删除用户时不再级联删除模板和版本
+        // 外键改为 RESTRICT：先把作者改为占位用户才能删除，防止悄悄删掉别人Fork过的模板
+        replace_author_foreign_key(
+            manager, Templates::Table, Templates::CreatedBy, "fk_templates_created_by", ForeignKeyAction::Restrict,
+        ).await?;
+
+        replace_author_foreign_key(
+            manager, TemplateVersions::Table, TemplateVersions::CreatedBy, "fk_template_versions_created_by", ForeignKeyAction::Restrict,
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        replace_author_foreign_key(
+            manager, Templates::Table, Templates::CreatedBy, "fk_templates_created_by", ForeignKeyAction::Cascade,
+        ).await?;
+
+        replace_author_foreign_key(
+            manager, TemplateVersions::Table, TemplateVersions::CreatedBy, "fk_template_versions_created_by", ForeignKeyAction::Cascade,
+        ).await?;
+
+        // 占位用户保留：已匿名的模板仍然引用它，原作者无法恢复
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_users_deletion_requested_at")
+                    .table(Users::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::DeletionRequestedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+/// 重建引用作者（`users.id`）的外键，修改删除用户时的行为
+async fn replace_author_foreign_key<T: Iden + Copy + 'static>(
+    manager: &SchemaManager<'_>,
+    table: T,
+    column: T,
+    name: &str,
+    on_delete: ForeignKeyAction,
+) -> Result<(), DbErr> {
+    manager
+        .drop_foreign_key(
+            ForeignKey::drop()
+                .name(name)
+                .table(table)
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_foreign_key(
+            ForeignKey::create()
+                .name(name)
+                .from(table, column)
+                .to(Users::Table, Users::Id)
+                .on_delete(on_delete)
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+    DeletionRequestedAt,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum Templates {
+    Table,
+    CreatedBy,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum TemplateVersions {
+    Table,
+    CreatedBy,
+}
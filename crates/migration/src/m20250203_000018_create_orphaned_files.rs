@@ -0,0 +1,34 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 创建 orphaned_files 表（彻底删除账号后未能删除的头像文件，由后台任务重试）
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrphanedFiles::Table)
+                    .if_not_exists()
+                    .col(string_len(OrphanedFiles::Prefix, 255).primary_key())
+                    .col(timestamp_with_time_zone(OrphanedFiles::CreatedAt).default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrphanedFiles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrphanedFiles {
+    Table,
+    Prefix,
+    CreatedAt,
+}
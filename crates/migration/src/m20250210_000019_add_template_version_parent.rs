@@ -0,0 +1,49 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 版本快照记录父模板：更换父模板会发布新版本，快照需要能还原当时的继承关系
+        // 不加外键，快照写入后不再修改
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TemplateVersions::Table)
+                    .add_column(uuid_null(TemplateVersions::ParentId))
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已有快照补齐为模板当前的父模板（之前没有记录，这是能得到的最接近的值）
+        let sql = r#"
+            UPDATE template_versions v
+            SET parent_id = t.parent_id
+            FROM templates t
+            WHERE v.template_id = t.id AND t.parent_id IS NOT NULL
+        "#;
+
+        manager.get_connection().execute_unprepared(sql).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TemplateVersions::Table)
+                    .drop_column(TemplateVersions::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TemplateVersions {
+    Table,
+    ParentId,
+}
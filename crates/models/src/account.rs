@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use crate::template::Model as Template;
+use crate::template_version::Model as TemplateVersion;
+use crate::user::{Model as User, UserRole};
+use crate::user_checklist::Model as UserChecklist;
+use crate::user_identity::Model as UserIdentity;
+
+/// 导出文件的格式版本，导出结构有不兼容的修改时递增
+pub const ACCOUNT_EXPORT_VERSION: u32 = 1;
+
+/// 注销账号数据传输对象（DTO）
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "password": "password123" }
+/// ```
+/// 
+/// ## 规则
+/// - 设置了密码的账号必须提供当前密码
+/// - 没有密码的账号（通过第三方登录或验证码登录自动注册）不需要提供
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct DeleteAccountDto {
+    /// 当前密码
+    pub password: Option<String>,
+}
+
+/// 注销申请的响应
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// {
+///   "deletion_requested_at": "2025-01-27T10:00:00Z",
+///   "purge_after": "2025-02-26T10:00:00Z"
+/// }
+/// ```
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountDeletionResponse {
+    /// 申请注销的时间
+    pub deletion_requested_at: DateTime<Utc>,
+    
+    /// 账号将被彻底删除的时间，在此之前登录即可撤销注销
+    pub purge_after: DateTime<Utc>,
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// 一个JSON文档（默认）
+    #[default]
+    Json,
+    
+    /// ZIP压缩包，每部分数据一个JSON文件
+    Zip,
+}
+
+/// 导出个人数据的查询参数
+/// 
+/// ## 示例
+/// 
+/// ```text
+/// GET /api/users/me/export
+/// GET /api/users/me/export?format=zip
+/// ```
+#[derive(Debug, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct ExportQuery {
+    /// 导出格式：`json`（默认）或`zip`
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// 个人数据导出
+/// 
+/// 包含用户的完整资料、绑定的第三方账号、所有清单（含每个步骤的完成时间）
+/// 以及用户创建的模板（含已删除的）和发布过的模板版本。
+/// 
+/// ZIP格式中每个字段是一个JSON文件（`profile.json`、`checklists.json`等），
+/// 另有`manifest.json`记录`format_version`和`exported_at`。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountExport {
+    /// 导出文件的格式版本
+    pub format_version: u32,
+    
+    /// 导出时间
+    pub exported_at: DateTime<Utc>,
+    
+    /// 账号资料
+    pub profile: AccountExportProfile,
+    
+    /// 绑定的第三方账号
+    pub identities: Vec<UserIdentity>,
+    
+    /// 清单（含步骤和每个步骤的完成状态、完成时间）
+    pub checklists: Vec<UserChecklist>,
+    
+    /// 创建的模板（含已删除的）
+    pub templates: Vec<Template>,
+    
+    /// 发布过的模板版本（含修改他人模板时发布的版本）
+    pub template_versions: Vec<TemplateVersion>,
+}
+
+/// 导出的账号资料
+/// 
+/// 和`UserProfile`不同，包含手机号、邮箱等只有本人能看到的信息，但仍然不包含密码哈希
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountExportProfile {
+    /// 用户ID
+    pub id: Uuid,
+    
+    /// 手机号
+    pub phone: Option<String>,
+    
+    /// 邮箱
+    pub email: Option<String>,
+    
+    /// 是否设置了密码
+    pub has_password: bool,
+    
+    /// 昵称
+    pub nickname: String,
+    
+    /// 头像URL
+    pub avatar_url: Option<String>,
+    
+    /// 常驻城市
+    pub home_city: Option<String>,
+    
+    /// 角色
+    pub role: UserRole,
+    
+    /// 角色的地区范围（只有城市主编有）
+    pub role_scope: Option<String>,
+    
+    /// 注册时间
+    pub created_at: DateTime<Utc>,
+    
+    /// 资料最后更新时间
+    pub updated_at: DateTime<Utc>,
+    
+    /// 申请注销的时间
+    pub deletion_requested_at: Option<DateTime<Utc>>,
+}
+
+impl From<User> for AccountExportProfile {
+    fn from(user: User) -> Self {
+        AccountExportProfile {
+            id: user.id,
+            has_password: user.has_password(),
+            phone: user.phone,
+            email: user.email,
+            nickname: user.nickname,
+            avatar_url: user.avatar_url,
+            home_city: user.home_city,
+            role: user.role,
+            role_scope: user.role_scope,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            deletion_requested_at: user.deletion_requested_at,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// 上传的头像文件大小上限（5 MiB）
+pub const AVATAR_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+/// 上传的头像的最大宽度和高度（像素），防止解码超大图片耗尽内存
+pub const AVATAR_MAX_DIMENSION: u32 = 8192;
+
+/// 允许上传的图片类型（按文件内容识别，不只看`Content-Type`）
+pub const AVATAR_CONTENT_TYPES: [&str; 3] = ["image/jpeg", "image/png", "image/webp"];
+
+/// 生成的头像尺寸（正方形边长，像素），第一个保存为用户的`avatar_url`
+/// 
+/// 原图小于某个尺寸时该尺寸按原图的短边生成，不放大
+pub const AVATAR_SIZES: [u32; 3] = [512, 256, 64];
+
+/// 一个尺寸的头像
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AvatarVariant {
+    /// 尺寸名（`AVATAR_SIZES`中的值，如512）
+    pub size: u32,
+    
+    /// 访问地址
+    pub url: String,
+}
+
+/// 上传头像的响应
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// {
+///   "avatar_url": "https://cdn.example.com/avatars/{user_id}/{upload_id}/512.jpg",
+///   "variants": [
+///     { "size": 512, "url": "https://cdn.example.com/avatars/{user_id}/{upload_id}/512.jpg" },
+///     { "size": 256, "url": "https://cdn.example.com/avatars/{user_id}/{upload_id}/256.jpg" },
+///     { "size": 64, "url": "https://cdn.example.com/avatars/{user_id}/{upload_id}/64.jpg" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AvatarUploadResponse {
+    /// 保存到用户资料中的头像地址（最大的尺寸）
+    pub avatar_url: String,
+    
+    /// 所有尺寸，从大到小
+    pub variants: Vec<AvatarVariant>,
+}
+
+/// 上传头像的表单（`multipart/form-data`，只用于API文档）
+#[derive(Debug, ToSchema)]
+pub struct AvatarUploadForm {
+    /// 图片文件（JPEG、PNG或WebP，最大5 MiB）
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+}
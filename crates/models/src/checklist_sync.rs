@@ -0,0 +1,353 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::template::TemplateStep;
+use crate::user_checklist::{ChecklistStep, StepProgress, UserChecklistResponse};
+
+/// 同步报告中的单个步骤
+/// 
+/// ## 字段说明
+/// 
+/// - `step_id`: 步骤ID
+/// - `from_index`: 同步前步骤在清单中的位置（新增步骤为None）
+/// - `to_index`: 同步后步骤在清单中的位置（被移除的步骤为None）
+/// - `completed`: 用户在该步骤上的完成状态
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SyncedStep {
+    /// 步骤ID
+    pub step_id: Uuid,
+    
+    /// 步骤标题
+    pub title: String,
+    
+    /// 同步前在清单中的位置
+    pub from_index: Option<i32>,
+    
+    /// 同步后在清单中的位置
+    pub to_index: Option<i32>,
+    
+    /// 是否已完成
+    /// 
+    /// 对于被移除的步骤，`true`表示同步后这条完成记录会丢失
+    pub completed: bool,
+}
+
+/// 清单与来源模板的同步报告
+/// 
+/// 三方合并的结果：上次Fork/同步时的有效步骤（base）、模板当前的有效步骤（theirs）、
+/// 用户的清单步骤和进度（ours）。
+/// 
+/// ## 合并规则
+/// 
+/// ```text
+/// 模板改了内容、用户没改过  → updated，采用模板的新内容
+/// 其余仍在最新版本中的步骤  → kept，保留用户的内容
+/// 只在最新版本中的步骤      → added，追加到清单末尾，未完成
+/// 最新版本中已删除的步骤    → removed，从清单中移除并报告
+/// 用户自定义的步骤          → 原样保留，不出现在报告中
+/// 用户删除过的步骤          → 不会被重新加回
+/// ```
+/// 
+/// 所有保留下来的步骤（包括隐藏状态）都保留原有的完成状态。
+/// 
+/// ## 响应示例
+/// 
+/// ```json
+/// {
+///   "from_version": 1,
+///   "to_version": 3,
+///   "up_to_date": false,
+///   "kept": [{ "step_id": "uuid-1", "title": "确定预算", "from_index": 0, "to_index": 0, "completed": true }],
+///   "updated": [],
+///   "added": [{ "step_id": "uuid-3", "title": "办理居住证", "from_index": null, "to_index": 2, "completed": false }],
+///   "removed": []
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChecklistSyncReport {
+    /// 清单当前基于的模板版本
+    pub from_version: i32,
+    
+    /// 模板最新版本
+    pub to_version: i32,
+    
+    /// 是否已是最新（无需同步）
+    /// 
+    /// 版本号相同且继承来的步骤也没有变化时才为true
+    pub up_to_date: bool,
+    
+    /// 保留的步骤
+    pub kept: Vec<SyncedStep>,
+    
+    /// 采用了模板新内容的步骤
+    pub updated: Vec<SyncedStep>,
+    
+    /// 新增的步骤
+    pub added: Vec<SyncedStep>,
+    
+    /// 被移除的步骤
+    pub removed: Vec<SyncedStep>,
+}
+
+/// 应用同步后的响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChecklistSyncResponse {
+    /// 本次同步的报告
+    pub report: ChecklistSyncReport,
+    
+    /// 同步后的清单和进度
+    pub checklist: UserChecklistResponse,
+}
+
+/// 三方合并的计算结果
+/// 
+/// `steps` 和 `progress` 是合并后的清单内容，`base_steps` 是下一次同步的合并基准，
+/// 应用同步时三者一起写回清单。
+#[derive(Debug, Clone)]
+pub struct SyncPlan {
+    pub report: ChecklistSyncReport,
+    pub base_steps: Vec<TemplateStep>,
+    pub steps: Vec<ChecklistStep>,
+    pub progress: Vec<StepProgress>,
+}
+
+/// 计算清单与来源模板之间的三方合并
+/// 
+/// ## 参数
+/// - `base`: 上次Fork/同步时的有效步骤（已解析继承）
+/// - `current`: 模板当前的有效步骤（已解析继承）
+/// - `ours`: 清单当前的步骤（可能被用户修改过）
+/// - `progress`: 用户当前的进度（通过`step_id`关联步骤）
+/// - `from_version` / `to_version`: 两侧的版本号
+/// 
+/// ## 步骤匹配
+/// 
+/// 步骤按`id`匹配，标题修改或顺序调整都不影响完成状态。
+pub fn plan_sync(
+    base: &[TemplateStep],
+    current: &[TemplateStep],
+    ours: &[ChecklistStep],
+    progress: &[StepProgress],
+    from_version: i32,
+    to_version: i32,
+) -> SyncPlan {
+    let find_base = |id: Uuid| base.iter().find(|b| b.id == id);
+    let find_current = |id: Uuid| current.iter().find(|c| c.id == id);
+    let is_completed = |id: Uuid| progress.iter().any(|p| p.step_id == id && p.completed);
+
+    let mut ours = ours.to_vec();
+    ours.sort_by_key(|s| s.order);
+
+    let mut steps: Vec<ChecklistStep> = Vec::with_capacity(ours.len());
+    let mut kept = Vec::new();
+    let mut updated = Vec::new();
+    let mut removed = Vec::new();
+
+    for (from_index, step) in ours.iter().enumerate() {
+        let from_index = Some(from_index as i32);
+
+        // Custom steps belong to the user only
+        if step.is_custom {
+            steps.push(step.clone());
+            continue;
+        }
+
+        let Some(theirs) = find_current(step.id) else {
+            removed.push(SyncedStep {
+                step_id: step.id,
+                title: step.title.clone(),
+                from_index,
+                to_index: None,
+                completed: is_completed(step.id),
+            });
+            continue;
+        };
+
+        // The dependency graph always follows the template
+        let mut merged = step.clone();
+        merged.depends_on = theirs.depends_on.clone();
+        let same_content =
+            |title: &str, description: &Option<String>| step.title == title && &step.description == description;
+        let edited_by_user = find_base(step.id).is_some_and(|b| !same_content(&b.title, &b.description));
+        let changed_upstream = !same_content(&theirs.title, &theirs.description);
+
+        let report = if changed_upstream && !edited_by_user {
+            merged.title = theirs.title.clone();
+            merged.description = theirs.description.clone();
+            &mut updated
+        } else {
+            &mut kept
+        };
+
+        report.push(SyncedStep {
+            step_id: step.id,
+            title: merged.title.clone(),
+            from_index,
+            to_index: Some(steps.len() as i32),
+            completed: is_completed(step.id),
+        });
+        steps.push(merged);
+    }
+
+    // New upstream steps are appended; steps the user deleted stay deleted
+    let mut added = Vec::new();
+    for theirs in current {
+        if find_base(theirs.id).is_some() || ours.iter().any(|s| s.id == theirs.id) {
+            continue;
+        }
+
+        added.push(SyncedStep {
+            step_id: theirs.id,
+            title: theirs.title.clone(),
+            from_index: None,
+            to_index: Some(steps.len() as i32),
+            completed: false,
+        });
+        steps.push(ChecklistStep::from_template(theirs));
+    }
+
+    let ids: Vec<Uuid> = steps.iter().map(|s| s.id).collect();
+    for (order, step) in steps.iter_mut().enumerate() {
+        step.order = order as i32;
+        step.depends_on.retain(|dep| ids.contains(dep));
+    }
+
+    let progress = steps
+        .iter()
+        .map(|step| {
+            progress
+                .iter()
+                .find(|p| p.step_id == step.id)
+                .cloned()
+                .unwrap_or(StepProgress {
+                    step_id: step.id,
+                    completed: false,
+                    completed_at: None,
+                })
+        })
+        .collect();
+
+    SyncPlan {
+        report: ChecklistSyncReport {
+            from_version,
+            to_version,
+            up_to_date: from_version == to_version && base == current,
+            kept,
+            updated,
+            added,
+            removed,
+        },
+        base_steps: current.to_vec(),
+        steps,
+        progress,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn step(title: &str, order: i32) -> TemplateStep {
+        TemplateStep {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: None,
+            order,
+            depends_on: Vec::new(),
+            overrides: None,
+            removed: false,
+        }
+    }
+
+    fn fork(steps: &[TemplateStep]) -> Vec<ChecklistStep> {
+        steps.iter().map(ChecklistStep::from_template).collect()
+    }
+
+    fn done(id: Uuid) -> StepProgress {
+        StepProgress {
+            step_id: id,
+            completed: true,
+            completed_at: Some(Utc::now()),
+        }
+    }
+
+    fn todo(id: Uuid) -> StepProgress {
+        StepProgress {
+            step_id: id,
+            completed: false,
+            completed_at: None,
+        }
+    }
+
+    #[test]
+    fn keeps_completion_and_appends_new_steps() {
+        let base = vec![step("预算", 0), step("找房", 1)];
+        let mut current = base.clone();
+        current.push(step("签合同", 2));
+        let progress = vec![done(base[0].id), todo(base[1].id)];
+
+        let plan = plan_sync(&base, &current, &fork(&base), &progress, 1, 2);
+
+        assert_eq!(plan.report.kept.len(), 2);
+        assert_eq!(plan.report.added.len(), 1);
+        assert!(plan.report.removed.is_empty());
+        assert!(plan.progress[0].completed);
+        assert!(!plan.progress[2].completed);
+        assert_eq!(plan.steps[2].id, current[2].id);
+    }
+
+    #[test]
+    fn reports_removed_steps_with_their_completion() {
+        let base = vec![step("预算", 0), step("看房", 1), step("找房", 2)];
+        let current = vec![base[2].clone(), base[0].clone()];
+        let progress = vec![done(base[0].id), done(base[1].id), done(base[2].id)];
+
+        let plan = plan_sync(&base, &current, &fork(&base), &progress, 1, 2);
+
+        assert_eq!(plan.steps.len(), 2);
+        assert!(plan.progress.iter().all(|p| p.completed));
+        assert_eq!(plan.report.removed.len(), 1);
+        assert_eq!(plan.report.removed[0].title, "看房");
+        assert!(plan.report.removed[0].completed);
+    }
+
+    #[test]
+    fn user_edits_and_custom_steps_survive_upstream_changes() {
+        let base = vec![step("预算", 0), step("找房", 1), step("中介", 2)];
+        let mut current = base.clone();
+        current[0].title = "确定预算".to_string();
+        current[1].title = "寻找房源".to_string();
+
+        let mut ours = fork(&base);
+        ours[1].title = "自己找房".to_string();
+        ours.remove(2);
+        ours.push(ChecklistStep {
+            id: Uuid::new_v4(),
+            title: "问同事".to_string(),
+            description: None,
+            order: 3,
+            is_custom: true,
+            hidden: false,
+            depends_on: Vec::new(),
+        });
+
+        let plan = plan_sync(&base, &current, &ours, &[], 1, 2);
+
+        let titles: Vec<_> = plan.steps.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["确定预算", "自己找房", "问同事"]);
+        assert_eq!(plan.report.updated.len(), 1);
+        assert!(plan.report.added.is_empty());
+    }
+
+    #[test]
+    fn same_version_is_up_to_date() {
+        let base = vec![step("预算", 0)];
+        let plan = plan_sync(&base, &base, &fork(&base), &[todo(base[0].id)], 2, 2);
+
+        assert!(plan.report.up_to_date);
+        assert!(plan.report.added.is_empty());
+        assert!(plan.report.removed.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::template::TemplateStep;
+use crate::user_checklist::{ChecklistStep, StepProgress};
+
+/// 步骤在依赖图（Flow）中的状态
+/// 
+/// ## 状态说明
+/// 
+/// - `locked`: 还有未完成的前置步骤，暂时不能开始
+/// - `available`: 前置步骤都已完成（或没有前置步骤），可以开始
+/// - `done`: 已完成
+/// 
+/// ## 示例
+/// 
+/// ```text
+/// 确定预算 ──┐
+///            ├──> 签合同
+/// 寻找房源 ──┘
+/// 
+/// 确定预算已完成、寻找房源未完成 → 签合同为 locked
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StepState {
+    /// 前置步骤未完成
+    Locked,
+    
+    /// 可以开始
+    Available,
+    
+    /// 已完成
+    Done,
+}
+
+/// 校验模板步骤之间的依赖关系
+/// 
+/// ## 校验规则
+/// 
+/// - `depends_on` 只能引用同一模板中的步骤（不允许悬空引用）
+/// - 步骤不能依赖自己
+/// - 依赖关系不能成环
+/// 
+/// ## 返回值
+/// 
+/// 校验失败时返回可读的错误描述
+pub fn validate_dependencies(steps: &[TemplateStep]) -> Result<(), String> {
+    for step in steps {
+        for dep in &step.depends_on {
+            if *dep == step.id {
+                return Err(format!("Step '{}' depends on itself", step.title));
+            }
+            if !steps.iter().any(|s| s.id == *dep) {
+                return Err(format!("Step '{}' depends on unknown step {}", step.title, dep));
+            }
+        }
+    }
+
+    // Kahn's algorithm: whatever cannot be scheduled is part of (or behind) a cycle
+    let mut remaining: Vec<&TemplateStep> = steps.iter().collect();
+    let mut scheduled: Vec<Uuid> = Vec::with_capacity(steps.len());
+
+    loop {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|s| s.depends_on.iter().all(|dep| scheduled.contains(dep)));
+
+        if ready.is_empty() {
+            remaining = blocked;
+            break;
+        }
+
+        scheduled.extend(ready.iter().map(|s| s.id));
+        remaining = blocked;
+    }
+
+    if remaining.is_empty() {
+        Ok(())
+    } else {
+        let titles: Vec<&str> = remaining.iter().map(|s| s.title.as_str()).collect();
+        Err(format!("Step dependencies form a cycle: {}", titles.join(", ")))
+    }
+}
+
+/// 找出某个清单步骤尚未完成的前置步骤
+/// 
+/// 隐藏或已删除的前置步骤视为已满足，不会挡住后续步骤。
+pub fn unfinished_prerequisites(
+    steps: &[ChecklistStep],
+    progress: &[StepProgress],
+    step: &ChecklistStep,
+) -> Vec<Uuid> {
+    let is_completed = |id: &Uuid| progress.iter().any(|p| p.step_id == *id && p.completed);
+
+    step.depends_on
+        .iter()
+        .filter(|dep| steps.iter().any(|s| s.id == **dep && !s.hidden))
+        .filter(|dep| !is_completed(dep))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(title: &str, depends_on: Vec<Uuid>) -> TemplateStep {
+        TemplateStep {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: None,
+            order: 0,
+            depends_on,
+            overrides: None,
+            removed: false,
+        }
+    }
+
+    #[test]
+    fn accepts_acyclic_graph() {
+        let a = step("预算", vec![]);
+        let b = step("找房", vec![]);
+        let c = step("签合同", vec![a.id, b.id]);
+
+        assert!(validate_dependencies(&[c, a, b]).is_ok());
+    }
+
+    #[test]
+    fn rejects_dangling_reference() {
+        let a = step("预算", vec![Uuid::new_v4()]);
+
+        assert!(validate_dependencies(&[a]).unwrap_err().contains("unknown step"));
+    }
+
+    #[test]
+    fn rejects_cycle() {
+        let mut a = step("预算", vec![]);
+        let b = step("找房", vec![a.id]);
+        a.depends_on.push(b.id);
+        let c = step("签合同", vec![]);
+
+        let err = validate_dependencies(&[a, b, c]).unwrap_err();
+        assert!(err.contains("cycle"));
+        assert!(!err.contains("签合同"));
+    }
+}
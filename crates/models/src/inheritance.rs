@@ -0,0 +1,153 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::template::{Model as Template, TemplateStep};
+
+/// 模板继承链的最大深度
+/// 
+/// 正常的层级是 全国 → 省 → 城市，留出余量的同时防止异常数据导致无限遍历
+pub const MAX_INHERITANCE_DEPTH: usize = 8;
+
+/// 解析继承后的模板
+/// 
+/// `GET /api/templates/:id/resolved` 的响应，`steps` 是沿父模板链逐层合并后的有效步骤。
+/// 
+/// ## 响应示例
+/// 
+/// ```json
+/// {
+///   "template": { "id": "uuid-bj", "title": "第一次在北京租房", "parent_id": "uuid-cn", ... },
+///   "ancestors": ["uuid-cn"],
+///   "steps": [
+///     { "id": "uuid-1", "title": "确定预算", "order": 0, ... },
+///     { "id": "uuid-2", "title": "在链家/自如找房", "order": 1, ... },
+///     { "id": "uuid-9", "title": "办理居住登记", "order": 2, ... }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ResolvedTemplate {
+    /// 模板本身（`steps`字段为本层定义的原始步骤）
+    pub template: Template,
+    
+    /// 祖先模板ID，从直接父模板到根模板
+    pub ancestors: Vec<Uuid>,
+    
+    /// 合并后的有效步骤
+    pub steps: Vec<TemplateStep>,
+}
+
+/// 将子模板的一层步骤叠加到继承来的步骤上
+/// 
+/// ## 子模板步骤的三种写法
+/// 
+/// ```text
+/// { "overrides": "<父步骤ID>", "title": "...", "order": 0 }                  → 覆盖：原位置替换父步骤的内容，保留父步骤ID
+/// { "overrides": "<父步骤ID>", "title": "...", "order": 0, "removed": true }  → 移除：删掉父步骤
+/// { "title": "...", "order": 2 }                                              → 新增：插入到有效列表的第`order`位
+/// ```
+/// 
+/// 覆盖和移除沿用父步骤的位置，它们的`order`会被忽略。
+/// 
+/// 子模板的步骤可以依赖父模板的步骤；依赖被移除步骤的边会一并去掉。
+/// 
+/// ## 返回值
+/// 
+/// 覆盖或移除不存在的父步骤时返回错误描述
+pub fn apply_layer(inherited: &[TemplateStep], layer: &[TemplateStep]) -> Result<Vec<TemplateStep>, String> {
+    let mut steps = inherited.to_vec();
+    let mut removed: Vec<Uuid> = Vec::new();
+    // 覆盖项自身的ID → 被覆盖的父步骤ID
+    let mut aliases: Vec<(Uuid, Uuid)> = Vec::new();
+
+    for step in layer.iter().filter(|s| s.overrides.is_some()) {
+        let target = step.overrides.unwrap_or_default();
+        let index = steps
+            .iter()
+            .position(|s| s.id == target)
+            .ok_or_else(|| format!("Step '{}' overrides unknown parent step {}", step.title, target))?;
+
+        if step.removed {
+            steps.remove(index);
+            removed.push(target);
+        } else {
+            let parent = &steps[index];
+            steps[index] = TemplateStep {
+                id: parent.id,
+                order: parent.order,
+                overrides: None,
+                removed: false,
+                ..step.clone()
+            };
+            aliases.push((step.id, target));
+        }
+    }
+
+    let mut added: Vec<&TemplateStep> = layer
+        .iter()
+        .filter(|s| s.overrides.is_none() && !s.removed)
+        .collect();
+    added.sort_by_key(|s| s.order);
+
+    for step in added {
+        let position = (step.order.max(0) as usize).min(steps.len());
+        steps.insert(position, step.clone());
+    }
+
+    for (order, step) in steps.iter_mut().enumerate() {
+        step.order = order as i32;
+        for dep in step.depends_on.iter_mut() {
+            if let Some((_, target)) = aliases.iter().find(|(alias, _)| alias == dep) {
+                *dep = *target;
+            }
+        }
+        step.depends_on.retain(|dep| !removed.contains(dep));
+    }
+
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(title: &str, order: i32) -> TemplateStep {
+        TemplateStep {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: None,
+            order,
+            depends_on: Vec::new(),
+            overrides: None,
+            removed: false,
+        }
+    }
+
+    #[test]
+    fn child_adds_overrides_and_removes_steps() {
+        let parent = vec![step("预算", 0), step("找房", 1), step("找中介", 2), step("签合同", 3)];
+
+        let mut rename = step("在链家找房", 0);
+        rename.overrides = Some(parent[1].id);
+        let mut remove = step("", 0);
+        remove.overrides = Some(parent[2].id);
+        remove.removed = true;
+        let add = step("办居住登记", 3);
+
+        let resolved = apply_layer(&parent, &[rename, remove, add]).unwrap();
+
+        let titles: Vec<_> = resolved.iter().map(|s| s.title.as_str()).collect();
+        assert_eq!(titles, ["预算", "在链家找房", "签合同", "办居住登记"]);
+        assert_eq!(resolved[1].id, parent[1].id);
+        assert!(resolved.iter().enumerate().all(|(i, s)| s.order == i as i32));
+    }
+
+    #[test]
+    fn rejects_override_of_unknown_step() {
+        let mut orphan = step("找房", 0);
+        orphan.overrides = Some(Uuid::new_v4());
+
+        assert!(apply_layer(&[step("预算", 0)], &[orphan]).is_err());
+    }
+}
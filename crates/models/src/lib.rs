@@ -12,15 +12,64 @@
 /// ├── user.rs              # 用户相关模型
 /// │   ├── User             # 用户实体
 /// │   ├── UserProfile      # 用户公开资料
-/// │   └── RegisterDto、LoginDto等
+/// │   ├── UserRole         # 用户角色
+/// │   └── RegisterDto、LoginDto、ChangePasswordDto等
+/// ├── avatar.rs            # 头像上传
+/// │   ├── AvatarUploadResponse # 上传后各尺寸的地址
+/// │   ├── AvatarUploadForm # 上传表单（API文档）
+/// │   └── AVATAR_SIZES、AVATAR_MAX_BYTES等限制
+/// ├── account.rs           # 注销账号和个人数据导出
+/// │   ├── AccountExport    # 导出的个人数据（资料、清单、模板）
+/// │   └── DeleteAccountDto、AccountDeletionResponse
+/// ├── refresh_token.rs     # 刷新令牌
+/// │   ├── RefreshToken     # 刷新令牌实体（只存哈希）
+/// │   └── RefreshTokenDto  # 刷新/退出登录请求
+/// ├── verification_code.rs # 短信验证码
+/// │   ├── VerificationCode # 验证码实体（只存哈希）
+/// │   └── SendCodeDto、PhoneLoginDto
+/// ├── password_reset_token.rs # 找回密码令牌
+/// │   ├── PasswordResetToken # 令牌实体（只存哈希）
+/// │   └── ForgotPasswordDto、ResetPasswordDto
+/// ├── login_attempt.rs     # 登录失败计数
+/// │   ├── LoginAttempt     # 按账号/IP的连续失败次数和锁定时间
+/// │   └── LoginScope       # account / ip
+/// ├── login_lockout.rs     # 登录锁定审计记录
+/// │   └── LoginLockout
+/// ├── orphaned_file.rs     # 待清理的对象存储文件
+/// │   └── OrphanedFile
+/// ├── user_identity.rs     # 第三方账号绑定
+/// │   └── UserIdentity     # (provider, subject) → 用户
+/// ├── oauth_state.rs       # 进行中的第三方授权请求
+/// │   ├── OAuthState       # state、PKCE校验码、nonce
+/// │   └── OAuthCallbackDto、AuthorizationUrlResponse
 /// ├── template.rs          # 模板相关模型
 /// │   ├── Template         # 模板实体
 /// │   ├── TemplateStep     # 模板步骤
 /// │   └── CreateTemplateDto等
-/// └── user_checklist.rs    # 清单相关模型
-///     ├── UserChecklist    # 用户清单实体
-///     ├── StepProgress     # 步骤进度
-///     └── ForkTemplateDto等
+/// ├── search.rs            # 模板全文搜索
+/// │   ├── TemplateSearchResult # 带相关度和高亮的搜索结果
+/// │   └── highlight        # 生成关键词高亮
+/// ├── location.rs          # 地区（国家 → 省 → 城市）
+/// │   ├── Location         # 地区实体
+/// │   └── LocationQuery    # 地区查询参数
+/// ├── template_version.rs  # 模板版本快照
+/// │   └── TemplateVersion  # 不可变的历史版本
+/// ├── user_checklist.rs    # 清单相关模型
+/// │   ├── UserChecklist    # 用户清单实体
+/// │   ├── ChecklistStep    # 清单自己的步骤
+/// │   ├── StepProgress     # 步骤进度
+/// │   └── ForkTemplateDto等
+/// ├── inheritance.rs       # 模板继承（parent_id）
+/// │   ├── apply_layer      # 叠加子模板的步骤
+/// │   └── ResolvedTemplate
+/// ├── ownership.rs         # 资源归属
+/// │   └── Owned            # 有所有者的资源（清单、模板）
+/// ├── flow.rs              # 步骤依赖图
+/// │   ├── StepState        # locked / available / done
+/// │   └── validate_dependencies
+/// └── checklist_sync.rs    # 清单与上游模板的同步
+///     ├── plan_sync        # 三方合并
+///     └── ChecklistSyncReport
 /// ```
 /// 
 /// ## 设计原则
@@ -31,7 +80,7 @@
 /// 
 /// 2. **类型安全**：
 ///    - 使用UUID代替字符串ID
-///    - 使用枚举（LocationTag）代替魔术字符串
+///    - 地区代码统一由`locations`表校验，不使用魔术字符串
 ///    - 使用validator进行输入验证
 /// 
 /// 3. **自文档化**：
@@ -56,34 +105,90 @@
 /// println!("模板标题: {}", template.title);
 /// ```
 
+pub mod account;
+pub mod avatar;
+pub mod checklist_sync;
+pub mod flow;
+pub mod inheritance;
+pub mod location;
+pub mod ownership;
+pub mod refresh_token;
+pub mod search;
 pub mod template;
+pub mod template_version;
 pub mod user;
 pub mod user_checklist;
+pub mod verification_code;
+pub mod password_reset_token;
+pub mod login_attempt;
+pub mod login_lockout;
+pub mod orphaned_file;
+pub mod user_identity;
+pub mod oauth_state;
 
 // ==================== SeaORM 实体导出 ====================
 // SeaORM 生成的实体类型
 pub use user::Entity as UserEntity;
 pub use template::Entity as TemplateEntity;
+pub use template_version::Entity as TemplateVersionEntity;
+pub use location::Entity as LocationEntity;
 pub use user_checklist::Entity as UserChecklistEntity;
+pub use refresh_token::Entity as RefreshTokenEntity;
+pub use verification_code::Entity as VerificationCodeEntity;
+pub use password_reset_token::Entity as PasswordResetTokenEntity;
+pub use login_attempt::Entity as LoginAttemptEntity;
+pub use login_lockout::Entity as LoginLockoutEntity;
+pub use orphaned_file::Entity as OrphanedFileEntity;
+pub use user_identity::Entity as UserIdentityEntity;
+pub use oauth_state::Entity as OAuthStateEntity;
 
 // 用于查询构建的列定义
 pub use user::Column as UserColumn;
 pub use template::Column as TemplateColumn;
+pub use template_version::Column as TemplateVersionColumn;
+pub use location::Column as LocationColumn;
 pub use user_checklist::Column as UserChecklistColumn;
+pub use refresh_token::Column as RefreshTokenColumn;
+pub use verification_code::Column as VerificationCodeColumn;
+pub use password_reset_token::Column as PasswordResetTokenColumn;
+pub use login_attempt::Column as LoginAttemptColumn;
+pub use login_lockout::Column as LoginLockoutColumn;
+pub use orphaned_file::Column as OrphanedFileColumn;
+pub use user_identity::Column as UserIdentityColumn;
+pub use oauth_state::Column as OAuthStateColumn;
 
 // ==================== 模板相关导出 ====================
 // - Model: 经验模板实体（SeaORM Model）
 // - TemplateStep: 模板中的单个步骤
-// - LocationTag: 地理位置标签枚举
 // - CreateTemplateDto: 创建模板DTO
 // - UpdateTemplateDto: 更新模板DTO
 // - TemplateSearchQuery: 模板搜索查询DTO
+// - SetOfficialDto: 设置官方模板DTO（管理员）
 pub use template::{
     Model as Template,
-    TemplateStep, LocationTag, 
-    CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery
+    TemplateStep,
+    CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery, SetOfficialDto
 };
 
+// ==================== 模板搜索相关导出 ====================
+// - TemplateSearchResult: 搜索结果（模板 + 相关度 + 高亮）
+// - SearchHighlight: 高亮后的标题和片段
+// - parse_keywords / highlight: 关键词拆分和高亮
+pub use search::{
+    TemplateSearchResult, SearchHighlight,
+    parse_keywords, highlight, MAX_SEARCH_KEYWORDS
+};
+
+// ==================== 模板版本相关导出 ====================
+// - Model: 模板版本快照实体（SeaORM Model）
+pub use template_version::Model as TemplateVersion;
+
+// ==================== 地区相关导出 ====================
+// - Model: 地区实体（SeaORM Model）
+// - LocationLevel: 地区层级（country / province / city）
+// - LocationQuery: 地区查询参数
+pub use location::{Model as Location, LocationLevel, LocationQuery};
+
 // ==================== 用户相关导出 ====================
 // - Model: 用户数据库实体（SeaORM Model）
 // - UserProfile: 用户公开资料（不含敏感信息）
@@ -91,22 +196,137 @@ pub use template::{
 // - LoginDto: 用户登录DTO
 // - UpdateProfileDto: 更新用户资料DTO
 // - AuthResponse: 认证响应（包含用户信息和JWT token）
+// - UserRole: 用户角色（user / contributor / city_curator / admin）
+// - Actor: 发起请求的用户及其角色
+// - GrantRoleDto: 授予角色DTO
+// - ChangePasswordDto: 修改密码DTO
+// - UNUSABLE_PASSWORD_HASH: 没有密码的账号的密码哈希
+// - DELETED_USER_ID: 占位用户"已注销用户"的ID
 pub use user::{
     Model as User,
     UserProfile, 
-    RegisterDto, LoginDto, UpdateProfileDto, AuthResponse
+    RegisterDto, LoginDto, UpdateProfileDto, AuthResponse,
+    UserRole, Actor, GrantRoleDto, ChangePasswordDto, UNUSABLE_PASSWORD_HASH, DELETED_USER_ID
+};
+
+// ==================== 头像相关导出 ====================
+// - AvatarUploadResponse / AvatarVariant: 上传头像的响应（各尺寸的地址）
+// - AvatarUploadForm: 上传头像的表单（只用于API文档）
+// - AVATAR_SIZES: 生成的头像尺寸
+// - AVATAR_MAX_BYTES / AVATAR_MAX_DIMENSION / AVATAR_CONTENT_TYPES: 上传限制
+pub use avatar::{
+    AvatarUploadResponse, AvatarVariant, AvatarUploadForm,
+    AVATAR_SIZES, AVATAR_MAX_BYTES, AVATAR_MAX_DIMENSION, AVATAR_CONTENT_TYPES
+};
+
+// ==================== 注销账号和数据导出相关导出 ====================
+// - DeleteAccountDto: 注销账号DTO
+// - AccountDeletionResponse: 注销申请的响应（彻底删除的时间）
+// - AccountExport / AccountExportProfile: 导出的个人数据
+// - ExportFormat / ExportQuery: 导出格式（json / zip）
+// - ACCOUNT_EXPORT_VERSION: 导出文件的格式版本
+pub use account::{
+    DeleteAccountDto, AccountDeletionResponse,
+    AccountExport, AccountExportProfile, ExportFormat, ExportQuery,
+    ACCOUNT_EXPORT_VERSION
+};
+
+// ==================== 待清理文件相关导出 ====================
+// - OrphanedFile: 彻底删除账号后未能删除的对象存储文件（SeaORM Model）
+pub use orphaned_file::Model as OrphanedFile;
+
+// ==================== 刷新令牌相关导出 ====================
+// - Model: 刷新令牌实体（SeaORM Model）
+// - RefreshTokenDto: 刷新令牌/退出登录DTO
+pub use refresh_token::{Model as RefreshToken, RefreshTokenDto};
+
+// ==================== 短信验证码相关导出 ====================
+// - Model: 短信验证码实体（SeaORM Model）
+// - SendCodeDto / SendCodeResponse: 发送验证码请求和响应
+// - PhoneLoginDto: 验证码登录DTO
+// - CODE_TTL_SECONDS 等: 有效期、输错次数和发送频率限制
+pub use verification_code::{
+    Model as VerificationCode,
+    SendCodeDto, SendCodeResponse, PhoneLoginDto,
+    CODE_TTL_SECONDS, MAX_CODE_ATTEMPTS, RESEND_INTERVAL_SECONDS, MAX_CODES_PER_HOUR
+};
+
+// ==================== 找回密码相关导出 ====================
+// - Model: 找回密码令牌实体（SeaORM Model）
+// - ForgotPasswordDto: 申请找回密码DTO
+// - ResetPasswordDto: 重置密码DTO
+// - RESET_TOKEN_TTL_SECONDS / RESET_REQUEST_INTERVAL_SECONDS: 有效期和发送间隔
+pub use password_reset_token::{
+    Model as PasswordResetToken,
+    ForgotPasswordDto, ResetPasswordDto,
+    RESET_TOKEN_TTL_SECONDS, RESET_REQUEST_INTERVAL_SECONDS
+};
+
+// ==================== 登录防暴力破解相关导出 ====================
+// - LoginAttempt: 按账号/IP的登录失败计数实体（SeaORM Model）
+// - LoginScope: 统计维度（account / ip）
+// - LoginLockout: 锁定审计记录实体（SeaORM Model）
+// - LOGIN_FAILURE_WINDOW_SECONDS 等: 统计窗口、退避上限和锁定时长
+pub use login_attempt::{
+    Model as LoginAttempt,
+    LoginScope, backoff_seconds,
+    LOGIN_FAILURE_WINDOW_SECONDS, LOGIN_MAX_BACKOFF_SECONDS, LOGIN_LOCKOUT_SECONDS
+};
+pub use login_lockout::Model as LoginLockout;
+
+// ==================== 第三方登录相关导出 ====================
+// - UserIdentity: 第三方账号绑定实体（SeaORM Model）
+// - OAuthState: 进行中的授权请求实体（SeaORM Model）
+// - AuthorizationUrlResponse: 授权地址响应
+// - OAuthCallbackDto: 授权回调DTO
+// - OAUTH_STATE_TTL_SECONDS: 授权请求有效期
+pub use user_identity::Model as UserIdentity;
+pub use oauth_state::{
+    Model as OAuthState,
+    AuthorizationUrlResponse, OAuthCallbackDto, OAUTH_STATE_TTL_SECONDS
 };
 
 // ==================== 用户清单相关导出 ====================
 // - Model: 用户清单实体（SeaORM Model）
+// - ChecklistStep: 清单中的单个步骤（可自定义）
 // - ChecklistProgress: 清单整体进度统计
 // - StepProgress: 单个步骤进度
+// - StepStatus: 步骤进度 + 依赖状态（响应视图）
 // - ForkTemplateDto: Fork模板DTO
 // - UpdateStepDto: 更新步骤DTO
+// - AddChecklistStepDto / UpdateChecklistStepDto / ReorderChecklistStepsDto: 自定义步骤DTO
 // - UserChecklistResponse: 用户清单响应（包含清单和进度）
 pub use user_checklist::{
     Model as UserChecklist,
-    ChecklistProgress, StepProgress,
-    ForkTemplateDto, UpdateStepDto, UserChecklistResponse
+    ChecklistStep, ChecklistProgress, StepProgress, StepStatus,
+    ForkTemplateDto, UpdateStepDto,
+    AddChecklistStepDto, UpdateChecklistStepDto, ReorderChecklistStepsDto,
+    UserChecklistResponse
+};
+
+// ==================== 清单同步相关导出 ====================
+// - plan_sync: 计算清单与来源模板的三方合并
+// - SyncPlan: 合并结果（报告 + 重建后的进度）
+// - ChecklistSyncReport: 同步报告（保留/新增/移除的步骤）
+// - SyncedStep: 报告中的单个步骤
+// - ChecklistSyncResponse: 应用同步后的响应
+pub use checklist_sync::{
+    plan_sync, SyncPlan,
+    ChecklistSyncReport, SyncedStep, ChecklistSyncResponse
 };
 
+// ==================== 步骤依赖图相关导出 ====================
+// - StepState: 步骤状态（locked / available / done）
+// - validate_dependencies: 校验模板步骤依赖（悬空引用、成环）
+// - unfinished_prerequisites: 查找未完成的前置步骤
+pub use flow::{StepState, validate_dependencies, unfinished_prerequisites};
+
+// ==================== 模板继承相关导出 ====================
+// - apply_layer: 将子模板的步骤叠加到父模板的有效步骤上
+// - ResolvedTemplate: 解析继承后的模板（含有效步骤）
+// - MAX_INHERITANCE_DEPTH: 继承链最大深度
+pub use inheritance::{apply_layer, ResolvedTemplate, MAX_INHERITANCE_DEPTH};
+
+// ==================== 资源归属相关导出 ====================
+// - Owned: 有所有者的资源，用于统一的归属校验
+pub use ownership::Owned;
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use sea_orm::entity::prelude::*;
+use utoipa::ToSchema;
+
+/// 地区层级
+/// 
+/// 直辖市（北京、上海、天津、重庆）是`city`，直接挂在全国下面。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(20))")]
+#[serde(rename_all = "lowercase")]
+pub enum LocationLevel {
+    /// 国家（全国通用）
+    #[sea_orm(string_value = "country")]
+    Country,
+    
+    /// 省级行政区
+    #[sea_orm(string_value = "province")]
+    Province,
+    
+    /// 城市
+    #[sea_orm(string_value = "city")]
+    City,
+}
+
+/// 地区（数据库实体）
+/// 
+/// 取代原来写死的`LocationTag`枚举，模板的`location_tag`和用户的`home_city`都引用这里的`code`。
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `locations`，初始数据由迁移写入
+/// 
+/// ## 层级示例
+/// 
+/// ```text
+/// CN（中国）
+/// ├── CN-BJ（北京，直辖市）
+/// ├── CN-GD（广东）
+/// │   ├── CN-GZ（广州）
+/// │   └── CN-SZ（深圳）
+/// └── CN-ZJ（浙江）
+///     └── CN-HZ（杭州）
+/// ```
+/// 
+/// 按地区搜索模板时，会同时匹配它的所有上级：搜索广州（CN-GZ）会返回
+/// CN-GZ、CN-GD 和 CN 的模板。
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, ToSchema)]
+#[sea_orm(table_name = "locations")]
+pub struct Model {
+    /// 地区代码（如"CN-GZ"）
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub code: String,
+    
+    /// 上级地区代码（全国为None）
+    pub parent_code: Option<String>,
+    
+    /// 地区层级
+    pub level: LocationLevel,
+    
+    /// 中文名（如"广州"）
+    pub name: String,
+    
+    /// 拼音（小写、无声调，如"guangzhou"）
+    pub pinyin: String,
+    
+    /// 别名（JSON字符串数组，如["广州市", "穗", "羊城"]）
+    #[sea_orm(column_type = "Json")]
+    pub aliases: Json,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn get_aliases(&self) -> Vec<String> {
+        serde_json::from_value(self.aliases.clone()).unwrap_or_default()
+    }
+    
+    /// 关键词是否命中代码、中文名、拼音或别名（忽略大小写）
+    pub fn matches(&self, keyword: &str) -> bool {
+        let keyword = keyword.trim().to_lowercase();
+        
+        self.code.to_lowercase() == keyword
+            || self.name.contains(&keyword)
+            || self.pinyin.starts_with(&keyword)
+            || self.get_aliases().iter().any(|alias| alias.contains(&keyword))
+    }
+}
+
+/// 地区查询参数
+/// 
+/// 用于GET /api/locations接口的查询参数，两个参数都不传时返回全部地区。
+/// 
+/// ## 示例
+/// 
+/// ```text
+/// # 广东省下的城市
+/// GET /api/locations?parent=CN-GD
+/// 
+/// # 按中文名、拼音或别名查找
+/// GET /api/locations?keyword=guang
+/// GET /api/locations?keyword=魔都
+/// ```
+#[derive(Debug, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct LocationQuery {
+    /// 只返回该地区的直接下级
+    pub parent: Option<String>,
+    
+    /// 关键词（匹配代码、中文名、拼音前缀或别名）
+    pub keyword: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_name_pinyin_and_aliases() {
+        let guangzhou = Model {
+            code: "CN-GZ".to_string(),
+            parent_code: Some("CN-GD".to_string()),
+            level: LocationLevel::City,
+            name: "广州".to_string(),
+            pinyin: "guangzhou".to_string(),
+            aliases: serde_json::json!(["广州市", "羊城"]),
+        };
+
+        assert!(guangzhou.matches("cn-gz"));
+        assert!(guangzhou.matches("广州"));
+        assert!(guangzhou.matches("Guang"));
+        assert!(guangzhou.matches("羊城"));
+        assert!(!guangzhou.matches("zhou"));
+    }
+}
@@ -0,0 +1,163 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// 连续失败的统计窗口（秒）：距离上次失败超过该时间后重新计数
+pub const LOGIN_FAILURE_WINDOW_SECONDS: i64 = 900;
+
+/// 退避等待时间的上限（秒）
+pub const LOGIN_MAX_BACKOFF_SECONDS: i64 = 60;
+
+/// 锁定时长（秒）
+pub const LOGIN_LOCKOUT_SECONDS: i64 = 900;
+
+/// 登录失败的统计维度
+/// 
+/// - `account`: 按登录账号（小写邮箱或手机号）统计，账号不存在时同样计数，不泄露账号是否存在
+/// - `ip`: 按客户端IP统计，防止同一来源轮流尝试大量账号（撞库）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(20))")]
+#[serde(rename_all = "snake_case")]
+pub enum LoginScope {
+    /// 按账号
+    #[sea_orm(string_value = "account")]
+    Account,
+    
+    /// 按客户端IP
+    #[sea_orm(string_value = "ip")]
+    Ip,
+}
+
+impl LoginScope {
+    /// 不需要等待的连续失败次数，超过后每次失败的等待时间翻倍（1秒、2秒、4秒……）
+    /// 
+    /// 同一IP后面可能有很多用户（公司、学校的NAT），所以IP的限制宽松得多
+    pub fn free_failures(self) -> i32 {
+        match self {
+            LoginScope::Account => 3,
+            LoginScope::Ip => 20,
+        }
+    }
+
+    /// 连续失败多少次后锁定`LOGIN_LOCKOUT_SECONDS`秒
+    pub fn lockout_threshold(self) -> i32 {
+        match self {
+            LoginScope::Account => 10,
+            LoginScope::Ip => 50,
+        }
+    }
+}
+
+/// 登录失败计数（数据库实体）
+/// 
+/// 每个账号、每个IP各一条记录，记录统计窗口内的连续失败次数。
+/// 
+/// ## 核心概念
+/// 
+/// - 连续失败超过`free_failures`次后指数退避：下次尝试前依次等待1、2、4……秒（最多`LOGIN_MAX_BACKOFF_SECONDS`秒）
+/// - 连续失败达到`lockout_threshold`次后锁定，锁定期间直接拒绝，不校验密码
+/// - 账号登录成功后清零该账号的计数；IP的计数不会因为登录成功而清零
+/// - 距离上次失败超过`LOGIN_FAILURE_WINDOW_SECONDS`秒后重新计数
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `login_attempts`，`(scope, subject)` 唯一
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "login_attempts")]
+pub struct Model {
+    /// 记录唯一标识
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// 统计维度
+    pub scope: LoginScope,
+    
+    /// 账号（小写邮箱或手机号）或IP地址
+    pub subject: String,
+    
+    /// 统计窗口内的连续失败次数
+    pub failures: i32,
+    
+    /// 最近一次失败的时间
+    pub last_failed_at: DateTime<Utc>,
+    
+    /// 锁定截止时间（未锁定时为空）
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 还要等待多少秒才能再次尝试登录（`None`表示现在就可以尝试）
+    pub fn retry_after(&self, now: DateTime<Utc>) -> Option<i64> {
+        let until = match self.locked_until {
+            Some(locked_until) if locked_until > now => locked_until,
+            _ if self.last_failed_at + Duration::seconds(LOGIN_FAILURE_WINDOW_SECONDS) <= now => return None,
+            _ => self.last_failed_at + Duration::seconds(backoff_seconds(self.scope, self.failures)),
+        };
+
+        // Round up so clients never retry a moment too early
+        let wait_ms = (until - now).num_milliseconds();
+        (wait_ms > 0).then(|| (wait_ms + 999) / 1000)
+    }
+
+    /// 这次失败后是否应该锁定
+    pub fn should_lock(&self) -> bool {
+        self.failures >= self.scope.lockout_threshold()
+    }
+}
+
+/// 连续失败`failures`次后，下次尝试前需要等待的秒数
+pub fn backoff_seconds(scope: LoginScope, failures: i32) -> i64 {
+    let over = failures - scope.free_failures();
+    if over <= 0 {
+        return 0;
+    }
+
+    // 2^(over - 1); 2^6 already exceeds the cap, so larger exponents are clamped instead of shifted
+    let exp = (over - 1).min(6) as u32;
+    (1i64 << exp).min(LOGIN_MAX_BACKOFF_SECONDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(now: DateTime<Utc>, failures: i32, seconds_ago: i64, locked_for: Option<i64>) -> Model {
+        Model {
+            id: Uuid::new_v4(),
+            scope: LoginScope::Account,
+            subject: "user@example.com".to_string(),
+            failures,
+            last_failed_at: now - Duration::seconds(seconds_ago),
+            locked_until: locked_for.map(|s| now + Duration::seconds(s)),
+        }
+    }
+
+    #[test]
+    fn backs_off_exponentially_then_locks() {
+        assert_eq!(backoff_seconds(LoginScope::Account, 3), 0);
+        assert_eq!(backoff_seconds(LoginScope::Account, 4), 1);
+        assert_eq!(backoff_seconds(LoginScope::Account, 6), 4);
+        assert_eq!(backoff_seconds(LoginScope::Account, 100), LOGIN_MAX_BACKOFF_SECONDS);
+        assert_eq!(backoff_seconds(LoginScope::Ip, 10), 0);
+
+        // Counters keep growing after a lockout ends; the wait must never wrap around
+        assert_eq!(backoff_seconds(LoginScope::Ip, 84), LOGIN_MAX_BACKOFF_SECONDS);
+        assert_eq!(backoff_seconds(LoginScope::Account, 67), LOGIN_MAX_BACKOFF_SECONDS);
+        assert_eq!(backoff_seconds(LoginScope::Account, i32::MAX), LOGIN_MAX_BACKOFF_SECONDS);
+
+        let now = Utc::now();
+        assert_eq!(attempt(now, 3, 0, None).retry_after(now), None);
+        assert_eq!(attempt(now, 6, 1, None).retry_after(now), Some(3));
+        assert_eq!(attempt(now, 10, 0, Some(600)).retry_after(now), Some(600));
+
+        // Old failures no longer count
+        assert_eq!(attempt(now, 9, LOGIN_FAILURE_WINDOW_SECONDS, None).retry_after(now), None);
+        assert!(attempt(now, 10, 0, None).should_lock());
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+
+use crate::login_attempt::LoginScope;
+
+/// 登录锁定审计记录（数据库实体）
+/// 
+/// 账号或IP因为连续登录失败被锁定时写入一条，只增不改，用于排查撞库和误锁。
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `login_lockouts`，`created_at` 有索引；用户删除后`user_id`置空
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "login_lockouts")]
+pub struct Model {
+    /// 记录唯一标识
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// 被锁定的维度
+    pub scope: LoginScope,
+    
+    /// 被锁定的账号（小写邮箱或手机号）或IP地址
+    pub subject: String,
+    
+    /// 账号对应的用户（按IP锁定、或账号不存在时为空）
+    pub user_id: Option<Uuid>,
+    
+    /// 触发锁定的那次请求的客户端IP
+    pub ip: Option<String>,
+    
+    /// 锁定时的连续失败次数
+    pub failures: i32,
+    
+    /// 锁定截止时间
+    pub locked_until: DateTime<Utc>,
+    
+    /// 锁定时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
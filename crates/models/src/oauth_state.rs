@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// 授权请求的有效期（秒），用户需要在这段时间内完成第三方授权
+pub const OAUTH_STATE_TTL_SECONDS: i64 = 600;
+
+/// 进行中的第三方授权请求（数据库实体）
+/// 
+/// 发起授权时保存，回调时按`state`取出并删除，每个`state`只能使用一次。
+/// 
+/// ## 核心概念
+/// 
+/// - `state`: 随授权地址发给提供方，回调时原样带回，防止CSRF
+/// - `code_verifier`: PKCE校验码，只保存在服务端，换取令牌时发送
+/// - `nonce`: 写入ID令牌，防止ID令牌被重放
+/// - `user_id`: 绑定第三方账号时是发起绑定的用户；登录时为空
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `oauth_states`，主键为`state`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "oauth_states")]
+pub struct Model {
+    /// 授权请求标识（随机字符串）
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub state: String,
+    
+    /// 提供方名称
+    pub provider: String,
+    
+    /// PKCE校验码
+    pub code_verifier: String,
+    
+    /// ID令牌中应当出现的nonce
+    pub nonce: String,
+    
+    /// 发起绑定的用户（登录时为None）
+    pub user_id: Option<Uuid>,
+    
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+    
+    /// 发起时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 是否已过期
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// 授权地址响应
+/// 
+/// 客户端把用户带到`authorization_url`，提供方授权后跳转回配置的回调地址，
+/// 回调地址的查询参数中带有`code`和`state`。
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthorizationUrlResponse {
+    /// 提供方的授权地址（已包含`state`、`nonce`和PKCE质询码）
+    pub authorization_url: String,
+    
+    /// 授权请求标识，回调时与`code`一起提交
+    pub state: String,
+    
+    /// 授权请求的有效期（秒）
+    pub expires_in: i64,
+}
+
+/// 第三方授权回调数据传输对象（DTO）
+/// 
+/// 把回调地址查询参数中的`code`和`state`原样提交。
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "code": "4/0AX4XfWh...", "state": "pK3v..." }
+/// ```
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct OAuthCallbackDto {
+    /// 提供方返回的授权码
+    #[validate(length(min = 1, max = 2048))]
+    pub code: String,
+    
+    /// 授权请求标识
+    #[validate(length(min = 1, max = 64))]
+    pub state: String,
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+/// 待清理的对象存储文件（数据库实体）
+/// 
+/// 彻底删除账号后删除头像文件失败时写入一条，记录对象键前缀；
+/// 之后每次运行彻底删除任务都会重试，删除成功后移除记录。
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `orphaned_files`，前缀是主键（重复记录同一前缀时忽略）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "orphaned_files")]
+pub struct Model {
+    /// 对象键前缀（如`avatars/{user_id}`）
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub prefix: String,
+    
+    /// 记录时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,40 @@
+use uuid::Uuid;
+
+/// 归属于某个用户的资源
+/// 
+/// 清单、模板等资源都有一个“所有者”，只有所有者可以查看或修改（模板的查看是公开的，修改受限）。
+/// 实现该trait后，服务层可以用同一套逻辑做归属校验，不必在每个方法里手写比较。
+/// 
+/// ## 示例
+/// 
+/// ```rust
+/// # use models::Owned;
+/// # use uuid::Uuid;
+/// # struct UserChecklist { user_id: Uuid }
+/// # struct Actor { user_id: Uuid }
+/// impl Owned for UserChecklist {
+///     const KIND: &'static str = "checklist";
+/// 
+///     fn owner_id(&self) -> Uuid {
+///         self.user_id
+///     }
+/// }
+/// 
+/// # let checklist = UserChecklist { user_id: Uuid::new_v4() };
+/// # let current_user = Actor { user_id: Uuid::new_v4() };
+/// if !checklist.is_owned_by(current_user.user_id) {
+///     // 403
+/// }
+/// ```
+pub trait Owned {
+    /// 资源类型名称，用于错误信息（如"checklist"）
+    const KIND: &'static str;
+    
+    /// 所有者的用户ID
+    fn owner_id(&self) -> Uuid;
+    
+    /// 资源是否属于指定用户
+    fn is_owned_by(&self, user_id: Uuid) -> bool {
+        self.owner_id() == user_id
+    }
+}
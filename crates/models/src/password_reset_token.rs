@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// 找回密码令牌有效期（秒）
+pub const RESET_TOKEN_TTL_SECONDS: i64 = 1800;
+
+/// 同一用户两次发送找回密码邮件之间的最短间隔（秒）
+pub const RESET_REQUEST_INTERVAL_SECONDS: i64 = 60;
+
+/// 找回密码令牌（数据库实体）
+/// 
+/// 用户忘记密码时，向注册邮箱发送带令牌的链接，凭令牌设置新密码。
+/// 
+/// ## 核心概念
+/// 
+/// - 数据库只保存令牌的SHA-256哈希，明文只出现在邮件中
+/// - 每个用户只有最新的一个令牌有效，发送新令牌时旧令牌作废
+/// - 令牌只能使用一次，使用后立即标记`consumed_at`
+/// - 重置成功后吊销该用户的所有刷新令牌（退出所有设备）
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `password_reset_tokens`，`token_hash` 唯一，`(user_id, created_at)` 有索引
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "password_reset_tokens")]
+pub struct Model {
+    /// 令牌记录唯一标识
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// 所属用户ID
+    pub user_id: Uuid,
+    
+    /// 令牌的SHA-256哈希（十六进制）
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+    
+    /// 使用时间（重置成功或被新令牌取代时设置）
+    pub consumed_at: Option<DateTime<Utc>>,
+    
+    /// 发送时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 是否还能用于重置密码（未使用、未过期）
+    pub fn is_usable(&self) -> bool {
+        self.consumed_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+/// 申请找回密码数据传输对象（DTO）
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "email": "user@example.com" }
+/// ```
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ForgotPasswordDto {
+    /// 注册邮箱
+    #[validate(email)]
+    pub email: String,
+}
+
+/// 重置密码数据传输对象（DTO）
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "token": "<邮件链接中的token>", "new_password": "newpassword123" }
+/// ```
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResetPasswordDto {
+    /// 邮件链接中的令牌
+    #[validate(length(min = 1, max = 128))]
+    pub token: String,
+    
+    /// 新密码（6-100个字符）
+    #[validate(length(min = 6, max = 100))]
+    pub new_password: String,
+}
@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// 刷新令牌（数据库实体）
+/// 
+/// 访问令牌（JWT）有效期很短，过期后客户端用刷新令牌换取新的访问令牌。
+/// 
+/// ## 核心概念
+/// 
+/// - 数据库只保存令牌的SHA-256哈希，明文只在签发时返回给客户端一次
+/// - 每次刷新都会**轮换**：旧令牌被吊销（`replaced_by`指向新令牌），同时签发新令牌
+/// - 同一次登录轮换出的所有令牌属于同一个`family_id`
+/// - 已吊销的令牌再次出现说明被盗用（重用检测），整个family都会被吊销
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `refresh_tokens`，`token_hash` 唯一
+/// 
+/// ## 生命周期
+/// 
+/// ```text
+/// 登录/注册     → 新family的第一个令牌
+/// 刷新          → 吊销旧令牌，签发同family的新令牌
+/// 退出登录      → 吊销该令牌所在的family
+/// 退出所有设备  → 吊销该用户的所有令牌
+/// ```
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "refresh_tokens")]
+pub struct Model {
+    /// 令牌记录唯一标识
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// 所属用户ID
+    pub user_id: Uuid,
+    
+    /// 令牌家族（同一次登录轮换出的令牌共享）
+    pub family_id: Uuid,
+    
+    /// 令牌的SHA-256哈希（十六进制）
+    #[sea_orm(unique)]
+    pub token_hash: String,
+    
+    /// 签发时客户端的User-Agent（用于区分设备）
+    pub user_agent: Option<String>,
+    
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+    
+    /// 吊销时间（未吊销为None）
+    pub revoked_at: Option<DateTime<Utc>>,
+    
+    /// 轮换后的新令牌ID（因刷新而吊销时才有）
+    pub replaced_by: Option<Uuid>,
+    
+    /// 签发时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 是否已过期
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// 刷新令牌数据传输对象（DTO）
+/// 
+/// 用于刷新访问令牌和退出登录
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "refresh_token": "q1w2e3..." }
+/// ```
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RefreshTokenDto {
+    /// 登录、注册或上次刷新时返回的刷新令牌
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
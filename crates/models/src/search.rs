@@ -0,0 +1,189 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::template::Model as Template;
+
+/// 单次搜索最多使用的关键词数量
+pub const MAX_SEARCH_KEYWORDS: usize = 5;
+
+/// 高亮片段中关键词前后保留的字符数
+const SNIPPET_CONTEXT: usize = 30;
+
+/// 搜索结果中的单个模板
+/// 
+/// 模板字段平铺在顶层（与`Template`相同），额外带上相关度和高亮。
+/// 
+/// ## 响应示例
+/// 
+/// ```json
+/// {
+///   "id": "uuid",
+///   "title": "第一次在北京租房",
+///   "location_tag": "CN-BJ",
+///   ...,
+///   "score": 4.6,
+///   "highlight": {
+///     "title": "第一次在北京<mark>租房</mark>",
+///     "snippet": "…签约前核对房东的<mark>房产证</mark>和身份证…"
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TemplateSearchResult {
+    /// 模板本身
+    #[serde(flatten)]
+    pub template: Template,
+    
+    /// 相关度（越大越相关；没有关键词时为0）
+    pub score: f64,
+    
+    /// 关键词高亮（没有关键词时为null）
+    pub highlight: Option<SearchHighlight>,
+}
+
+/// 搜索高亮
+/// 
+/// 命中的关键词用`<mark>`包裹，其余文本已做HTML转义，可以直接渲染。
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SearchHighlight {
+    /// 高亮后的标题
+    pub title: String,
+    
+    /// 描述或步骤中第一处命中附近的片段（只在标题中命中时为null）
+    pub snippet: Option<String>,
+}
+
+/// 把搜索框输入拆成关键词
+/// 
+/// 按空白拆分，忽略大小写去重，最多保留`MAX_SEARCH_KEYWORDS`个。
+/// 多个关键词之间是“且”的关系。
+pub fn parse_keywords(raw: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = Vec::new();
+
+    for word in raw.split_whitespace() {
+        if keywords.len() == MAX_SEARCH_KEYWORDS {
+            break;
+        }
+        if !keywords.iter().any(|k| k.to_lowercase() == word.to_lowercase()) {
+            keywords.push(word.to_string());
+        }
+    }
+
+    keywords
+}
+
+/// 为模板生成关键词高亮
+/// 
+/// 片段依次在描述、步骤标题、步骤说明中查找第一处命中。
+pub fn highlight(template: &Template, keywords: &[String]) -> SearchHighlight {
+    let steps = template.get_steps().unwrap_or_default();
+    let candidates = std::iter::once(template.description.clone()).chain(
+        steps
+            .into_iter()
+            .flat_map(|s| std::iter::once(s.title).chain(s.description)),
+    );
+
+    let snippet = candidates
+        .map(|text| text.chars().collect::<Vec<char>>())
+        .find_map(|text| {
+            let (start, len) = first_match(&text, keywords)?;
+            let from = start.saturating_sub(SNIPPET_CONTEXT);
+            let to = (start + len + SNIPPET_CONTEXT).min(text.len());
+
+            let mut snippet = mark(&text[from..to], keywords);
+            if from > 0 {
+                snippet.insert(0, '…');
+            }
+            if to < text.len() {
+                snippet.push('…');
+            }
+            Some(snippet)
+        });
+
+    SearchHighlight {
+        title: mark(&template.title.chars().collect::<Vec<char>>(), keywords),
+        snippet,
+    }
+}
+
+/// 忽略大小写比较单个字符
+fn same_char(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// 在`text`中从`from`开始找最早（同一位置取最长）的关键词命中，返回（起点，长度）
+fn match_at(text: &[char], from: usize, keywords: &[Vec<char>]) -> Option<(usize, usize)> {
+    (from..text.len()).find_map(|start| {
+        keywords
+            .iter()
+            .filter(|k| {
+                !k.is_empty()
+                    && start + k.len() <= text.len()
+                    && k.iter().zip(&text[start..]).all(|(a, b)| same_char(*a, *b))
+            })
+            .map(|k| k.len())
+            .max()
+            .map(|len| (start, len))
+    })
+}
+
+fn first_match(text: &[char], keywords: &[String]) -> Option<(usize, usize)> {
+    let keywords: Vec<Vec<char>> = keywords.iter().map(|k| k.chars().collect()).collect();
+    match_at(text, 0, &keywords)
+}
+
+/// 转义HTML并用`<mark>`包裹所有命中
+fn mark(text: &[char], keywords: &[String]) -> String {
+    let keywords: Vec<Vec<char>> = keywords.iter().map(|k| k.chars().collect()).collect();
+    let mut out = String::with_capacity(text.len() * 3);
+    let mut pos = 0;
+
+    while let Some((start, len)) = match_at(text, pos, &keywords) {
+        escape_into(&mut out, &text[pos..start]);
+        out.push_str("<mark>");
+        escape_into(&mut out, &text[start..start + len]);
+        out.push_str("</mark>");
+        pos = start + len;
+    }
+    escape_into(&mut out, &text[pos..]);
+
+    out
+}
+
+fn escape_into(out: &mut String, text: &[char]) {
+    for c in text {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(*c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn parses_and_dedupes_keywords() {
+        assert_eq!(parse_keywords("  租房  北京 租房 Offer offer "), ["租房", "北京", "Offer"]);
+        assert_eq!(parse_keywords("a b c d e f g").len(), MAX_SEARCH_KEYWORDS);
+    }
+
+    #[test]
+    fn marks_every_match_case_insensitively_and_escapes_html() {
+        let keywords = vec!["offer".to_string(), "租房".to_string()];
+
+        assert_eq!(
+            mark(&chars("拿到Offer后<先>租房"), &keywords),
+            "拿到<mark>Offer</mark>后&lt;先&gt;<mark>租房</mark>"
+        );
+        assert_eq!(mark(&chars("没有命中"), &keywords), "没有命中");
+    }
+}
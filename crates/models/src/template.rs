@@ -5,73 +5,7 @@ use uuid::Uuid;
 use validator::Validate;
 use utoipa::ToSchema;
 
-/// 地理位置标签（枚举类型，SeaORM 存储为字符串）
-/// 
-/// 用于标识模板的地域属性，支持通用模板和城市特定模板。
-/// 
-/// ## 设计理念
-/// 
-/// 同一个"第一次"体验在不同城市可能有显著差异：
-/// - "第一次租房"：北京和上海的流程、注意事项不同
-/// - "第一次办证"：各地政策和办事地点不同
-/// 
-/// ## 标签层级
-/// 
-/// - `CN`: 全国通用模板（如"第一次面试"）
-/// - `CN-{城市}`: 城市特定模板（如"第一次在北京租房"）
-/// 
-/// ## 使用示例
-/// 
-/// ```rust
-/// // 创建通用模板
-/// let tag = LocationTag::China;  // "CN"
-/// 
-/// // 创建北京专属模板
-/// let tag = LocationTag::Beijing;  // "CN-BJ"
-/// ```
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
-pub enum LocationTag {
-    /// 全国通用模板
-    /// 
-    /// 适用于不受地域限制的经验，如：
-    /// - 第一次求职面试
-    /// - 第一次理财
-    /// - 第一次独立生活
-    #[serde(rename = "CN")]
-    China,
-    
-    /// 北京专属模板（CN-BJ）
-    /// 
-    /// 例如：第一次在北京租房、办理北京居住证
-    #[serde(rename = "CN-BJ")]
-    Beijing,
-    
-    /// 上海专属模板（CN-SH）
-    #[serde(rename = "CN-SH")]
-    Shanghai,
-    
-    /// 广州专属模板（CN-GZ）
-    #[serde(rename = "CN-GZ")]
-    Guangzhou,
-    
-    /// 深圳专属模板（CN-SZ）
-    #[serde(rename = "CN-SZ")]
-    Shenzhen,
-    
-    // 未来可以继续添加更多城市
-}
-
-impl std::fmt::Display for LocationTag {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            LocationTag::China => write!(f, "CN"),
-            LocationTag::Beijing => write!(f, "CN-BJ"),
-            LocationTag::Shanghai => write!(f, "CN-SH"),
-            LocationTag::Guangzhou => write!(f, "CN-GZ"),
-            LocationTag::Shenzhen => write!(f, "CN-SZ"),
-        }
-    }
-}
+use crate::ownership::Owned;
 
 /// 模板步骤（单个步骤的定义）
 /// 
@@ -79,21 +13,32 @@ impl std::fmt::Display for LocationTag {
 /// 
 /// ## 字段说明
 /// 
+/// - `id`: 步骤的稳定标识（UUID），模板修改、步骤重排后保持不变
 /// - `title`: 步骤标题（1-500字符）
 /// - `description`: 步骤详细说明（可选）
 /// - `order`: 步骤顺序（从0开始）
+/// - `depends_on`: 前置步骤的`id`列表（可选），构成步骤依赖图（Flow）
+/// - `overrides` / `removed`: 子模板中覆盖或移除父模板步骤（见模板继承）
 /// 
 /// ## 示例
 /// 
 /// ```json
 /// {
+///   "id": "7d9f3c1e-2b4a-4c8e-9f1a-0b2c3d4e5f60",
 ///   "title": "确定租房预算和区域",
 ///   "description": "根据工作地点和收入，确定可接受的租金范围和通勤距离",
 ///   "order": 0
 /// }
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate, ToSchema)]
 pub struct TemplateStep {
+    /// 步骤唯一标识
+    /// 
+    /// 创建模板时可以省略，由服务端生成；
+    /// 更新模板时应原样回传，用户清单的进度通过它关联到步骤
+    #[serde(default = "Uuid::new_v4")]
+    pub id: Uuid,
+    
     /// 步骤标题（简短描述要做什么）
     #[validate(length(min = 1, max = 500))]
     pub title: String,
@@ -105,6 +50,24 @@ pub struct TemplateStep {
     /// 
     /// 建议按照实际操作的时间顺序排列
     pub order: i32,
+    
+    /// 前置步骤ID列表
+    /// 
+    /// 这些步骤全部完成后，当前步骤才可以开始。
+    /// 只能引用同一模板中的步骤，且不能成环
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+    
+    /// 覆盖的父模板步骤ID（仅子模板使用）
+    /// 
+    /// 设置后，本步骤在父模板步骤的原位置替换其内容，
+    /// 解析后的步骤沿用父模板步骤的`id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<Uuid>,
+    
+    /// 与`overrides`一起使用，表示移除该父模板步骤
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub removed: bool,
 }
 
 /// 经验模板（数据库实体）
@@ -154,12 +117,12 @@ pub struct Model {
     /// 模板描述（介绍这个模板的用途和适用场景）
     pub description: String,
     
-    /// 地理位置标签（存储为文本，可解析为LocationTag）
+    /// 地理位置标签（`locations`表中的地区代码）
     /// 
     /// 值：
     /// - "CN": 全国通用
+    /// - "CN-GD": 广东省内通用
     /// - "CN-BJ": 北京专属
-    /// - "CN-SH": 上海专属
     pub location_tag: String,
     
     /// 步骤列表（JSON数组，存储在数据库的JSONB字段）
@@ -170,10 +133,9 @@ pub struct Model {
     
     /// 父模板ID（用于模板继承）
     /// 
-    /// **未来功能**：
     /// - 通用模板（CN）作为父模板
-    /// - 城市模板（CN-BJ）继承父模板并添加本地化步骤
-    /// - V0.0.1版本暂不实现继承逻辑
+    /// - 城市模板（CN-BJ）继承父模板，`steps`中只写新增、覆盖或移除的步骤
+    /// - 有效步骤通过`GET /api/templates/:id/resolved`查看，Fork时使用有效步骤
     pub parent_id: Option<Uuid>,
     
     /// 创建时间
@@ -197,6 +159,20 @@ pub struct Model {
     /// 
     /// 官方模板会优先展示，并有特殊标识
     pub is_official: bool,
+    
+    /// 当前版本号（从1开始，每次发布新内容时递增）
+    /// 
+    /// 历史版本的完整快照保存在`template_versions`表中
+    pub version: i32,
+    
+    /// 删除时间（软删除标记）
+    /// 
+    /// - `None`: 模板正常可用
+    /// - `Some(timestamp)`: 模板已被创建者或管理员删除
+    /// 
+    /// 已删除的模板不会出现在列表和搜索结果中，也不能再被Fork，
+    /// 但记录仍然保留，已经Fork过它的用户清单不受影响。
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -209,6 +185,8 @@ pub enum Relation {
     User,
     #[sea_orm(has_many = "super::user_checklist::Entity")]
     Checklists,
+    #[sea_orm(has_many = "super::template_version::Entity")]
+    Versions,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -223,10 +201,30 @@ impl Related<super::user_checklist::Entity> for Entity {
     }
 }
 
+impl Related<super::template_version::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Versions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
+/// 模板属于它的创建者（只有创建者可以修改和删除）
+impl Owned for Model {
+    const KIND: &'static str = "template";
+    
+    fn owner_id(&self) -> Uuid {
+        self.created_by
+    }
+}
+
 /// 辅助函数：从 Model 获取步骤列表
 impl Model {
+    /// 模板是否已被（软）删除
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+    
     pub fn get_steps(&self) -> Result<Vec<TemplateStep>, serde_json::Error> {
         serde_json::from_value(self.steps.clone())
     }
@@ -235,6 +233,60 @@ impl Model {
         self.steps = serde_json::to_value(steps)?;
         Ok(())
     }
+    
+    /// 为新步骤列表沿用已有步骤的ID
+    /// 
+    /// 更新模板时，客户端可能没有回传步骤`id`（反序列化时会生成新ID）。
+    /// 对于ID不属于当前模板的步骤，如果当前模板中有标题完全相同、
+    /// 且没有被其他新步骤引用的步骤，就沿用它的ID，避免用户进度丢失。
+    pub fn carry_over_step_ids(&self, steps: &mut [TemplateStep]) -> Result<(), serde_json::Error> {
+        let existing = self.get_steps()?;
+        let mut claimed: Vec<Uuid> = steps
+            .iter()
+            .filter(|s| existing.iter().any(|e| e.id == s.id))
+            .map(|s| s.id)
+            .collect();
+        
+        let mut renamed: Vec<(Uuid, Uuid)> = Vec::new();
+        for step in steps.iter_mut() {
+            if claimed.contains(&step.id) {
+                continue;
+            }
+            
+            if let Some(old) = existing
+                .iter()
+                .find(|e| e.title == step.title && !claimed.contains(&e.id))
+            {
+                renamed.push((step.id, old.id));
+                step.id = old.id;
+                claimed.push(old.id);
+            }
+        }
+        
+        // 依赖关系跟随改过的ID
+        for step in steps.iter_mut() {
+            for dep in step.depends_on.iter_mut() {
+                if let Some((_, new_id)) = renamed.iter().find(|(old_id, _)| old_id == dep) {
+                    *dep = *new_id;
+                }
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// 校验步骤ID不重复
+/// 
+/// 进度按步骤ID记录，重复的ID会让两个步骤共享同一个完成状态
+fn validate_step_ids(steps: &[TemplateStep]) -> Result<(), validator::ValidationError> {
+    let mut seen = std::collections::HashSet::new();
+    
+    if steps.iter().all(|step| seen.insert(step.id)) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("duplicate_step_id").with_message("Step ids must be unique".into()))
+    }
 }
 
 /// 创建模板DTO
@@ -245,8 +297,8 @@ impl Model {
 /// 
 /// - `title`: 1-200字符
 /// - `description`: 1-2000字符
-/// - `location_tag`: 有效的地理标签（CN、CN-BJ等）
-/// - `steps`: 至少包含1个步骤
+/// - `location_tag`: `locations`表中存在的地区代码（CN、CN-GD、CN-BJ等）
+/// - `steps`: 至少包含1个步骤，步骤`id`不能重复
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateTemplateDto {
     /// 模板标题
@@ -260,8 +312,8 @@ pub struct CreateTemplateDto {
     /// 地理位置标签
     pub location_tag: String,
     
-    /// 步骤列表（至少1个）
-    #[validate(length(min = 1))]
+    /// 步骤列表（至少1个，步骤ID不能重复）
+    #[validate(length(min = 1), custom(function = "validate_step_ids"))]
     pub steps: Vec<TemplateStep>,
     
     /// 父模板ID（可选，用于模板继承）
@@ -274,9 +326,17 @@ pub struct CreateTemplateDto {
 /// 
 /// 所有字段都是可选的，只更新提供的字段。
 /// 
+/// ## 验证规则
+/// 
+/// 与`CreateTemplateDto`保持一致：
+/// - `title`: 1-200字符
+/// - `description`: 1-2000字符
+/// - `steps`: 如果提供，至少包含1个步骤，步骤`id`不能重复
+/// 
 /// ## 注意事项
 /// 
-/// - V0.0.1版本暂未实现此功能
+/// - 只有模板创建者可以更新模板
+/// - 修改`title`、`description`或`steps`会发布一个新版本（版本号+1）
 /// - 更新模板会影响所有基于该模板的清单吗？
 ///   答：不会，Fork的是快照，不受模板更新影响
 #[derive(Debug, Deserialize, Validate, ToSchema)]
@@ -292,8 +352,31 @@ pub struct UpdateTemplateDto {
     /// 新地理标签
     pub location_tag: Option<String>,
     
-    /// 新步骤列表
+    /// 新步骤列表（整体替换，至少1个）
+    /// 
+    /// 保留的步骤需要带上原来的`id`，否则会被视为新步骤
+    #[validate(length(min = 1), custom(function = "validate_step_ids"))]
     pub steps: Option<Vec<TemplateStep>>,
+    
+    /// 新父模板ID
+    /// 
+    /// 不能形成继承环（例如把父模板的父模板设为自己）
+    pub parent_id: Option<Uuid>,
+}
+
+/// 设置官方模板数据传输对象（DTO）
+/// 
+/// 用于`PUT /api/admin/templates/:id/official`（仅管理员）
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "is_official": true }
+/// ```
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetOfficialDto {
+    /// 是否为官方模板
+    pub is_official: bool,
 }
 
 /// 模板搜索查询DTO
@@ -302,7 +385,7 @@ pub struct UpdateTemplateDto {
 /// 
 /// ## 查询参数
 /// 
-/// - `keyword`: 关键词（在标题和描述中搜索）
+/// - `keyword`: 关键词（检索标题、描述和步骤内容，多个关键词用空格分隔）
 /// - `location_tag`: 地理标签过滤
 /// - `page`: 页码（默认1）
 /// - `page_size`: 每页数量（默认20）
@@ -321,12 +404,12 @@ pub struct UpdateTemplateDto {
 /// ```
 #[derive(Debug, Deserialize, ToSchema, utoipa::IntoParams)]
 pub struct TemplateSearchQuery {
-    /// 搜索关键词（模糊匹配标题和描述）
+    /// 搜索关键词，空格分隔的多个关键词必须全部命中（最多5个）
     pub keyword: Option<String>,
     
-    /// 地理标签过滤（精确匹配）
+    /// 地理标签过滤（地区代码）
     /// 
-    /// 搜索某城市时，会同时返回该城市和通用（CN）的模板
+    /// 搜索某城市时，会同时返回该城市、所属省份和通用（CN）的模板
     pub location_tag: Option<String>,
     
     /// 页码（从1开始）
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sea_orm::entity::prelude::*;
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+use crate::template::TemplateStep;
+
+/// 模板版本快照（数据库实体）
+/// 
+/// 每次发布模板（创建或修改标题/描述/步骤/父模板）时，都会写入一条不可变的版本记录。
+/// 
+/// ## 核心概念
+/// 
+/// - `templates` 表只保存**最新**内容，`version` 字段记录当前版本号
+/// - `template_versions` 表保存**每一个**历史版本的完整快照，写入后不再修改
+/// - 用户清单通过 `source_template_version` 记录Fork时的版本
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `template_versions`，`(template_id, version)` 唯一
+/// 
+/// ## 版本号规则
+/// 
+/// ```text
+/// 创建模板            → version = 1
+/// 修改标题/描述/步骤  → version + 1
+/// 更换父模板          → version + 1
+/// 仅修改地理标签      → 版本号不变
+/// ```
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, ToSchema)]
+#[sea_orm(table_name = "template_versions")]
+pub struct Model {
+    /// 版本记录唯一标识
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// 所属模板ID
+    pub template_id: Uuid,
+    
+    /// 版本号（从1开始，单调递增）
+    pub version: i32,
+    
+    /// 该版本的模板标题
+    pub title: String,
+    
+    /// 该版本的模板描述
+    pub description: String,
+    
+    /// 该版本的步骤列表（JSON数组）
+    #[sea_orm(column_type = "Json")]
+    pub steps: Json,
+    
+    /// 该版本继承的父模板ID（`steps`是相对它的差异）
+    pub parent_id: Option<Uuid>,
+    
+    /// 发布该版本的用户ID
+    pub created_by: Uuid,
+    
+    /// 版本发布时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::template::Entity",
+        from = "Column::TemplateId",
+        to = "super::template::Column::Id"
+    )]
+    Template,
+}
+
+impl Related<super::template::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Template.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// 辅助函数：从 Model 获取步骤列表
+impl Model {
+    pub fn get_steps(&self) -> Result<Vec<TemplateStep>, serde_json::Error> {
+        serde_json::from_value(self.steps.clone())
+    }
+}
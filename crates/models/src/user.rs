@@ -13,17 +13,21 @@ use utoipa::ToSchema;
 /// - `id`: 用户唯一标识（UUID）
 /// - `phone`: 手机号（可选，用于登录）
 /// - `email`: 邮箱（可选，用于登录）
-/// - `password_hash`: 密码哈希（bcrypt加密，永不返回给客户端）
+/// - `password_hash`: 密码哈希（Argon2id，早期账号为bcrypt；永不返回给客户端；没有密码时为`UNUSABLE_PASSWORD_HASH`）
 /// - `nickname`: 用户昵称（显示名称）
 /// - `avatar_url`: 头像URL（可选）
 /// - `home_city`: 常驻城市（如"CN-BJ"，用于个性化推荐）
+/// - `role`: 角色（见`UserRole`，默认`user`）
+/// - `role_scope`: 城市主编负责的地区（只有`city_curator`有）
 /// - `created_at`: 创建时间
 /// - `updated_at`: 更新时间
+/// - `deletion_requested_at`: 申请注销的时间（宽限期结束后彻底删除）
 /// 
 /// ## 安全性
-/// - 密码使用bcrypt加密存储，成本因子为DEFAULT_COST
+/// - 密码使用Argon2id加密存储；早期的bcrypt哈希在登录成功时自动升级
 /// - 手机号和邮箱至少需要提供一个（数据库约束）
 /// - 手机号和邮箱都有唯一索引，防止重复注册
+/// - 角色只能由管理员通过`/api/admin/users/:id/role`修改
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, ToSchema)]
 #[sea_orm(table_name = "users")]
 pub struct Model {
@@ -37,7 +41,7 @@ pub struct Model {
     /// 邮箱地址（可选）
     pub email: Option<String>,
     
-    /// 密码哈希值（bcrypt加密，不可逆）
+    /// 密码哈希值（PHC格式，`$argon2id$...`或`$2b$...`，不可逆）
     #[serde(skip_serializing)]  // 序列化时跳过此字段
     pub password_hash: String,
     
@@ -51,11 +55,25 @@ pub struct Model {
     /// 用于根据用户位置推荐相关模板
     pub home_city: Option<String>,
     
+    /// 角色
+    pub role: UserRole,
+    
+    /// 角色的地区范围（如"CN-GD"）
+    /// 
+    /// 只有`city_curator`有，负责该地区及其下级地区的模板
+    pub role_scope: Option<String>,
+    
     /// 账户创建时间
     pub created_at: DateTime<Utc>,
     
     /// 最后更新时间
     pub updated_at: DateTime<Utc>,
+    
+    /// 申请注销的时间
+    /// 
+    /// - `None`: 正常账号
+    /// - `Some(timestamp)`: 已申请注销，宽限期内登录即撤销；宽限期结束后由后台任务彻底删除
+    pub deletion_requested_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -80,6 +98,102 @@ impl Related<super::user_checklist::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+/// 没有密码的账号的`password_hash`
+/// 
+/// 通过第三方登录自动注册的账号没有密码。`!`不属于任何密码哈希算法，任何密码都无法匹配。
+pub const UNUSABLE_PASSWORD_HASH: &str = "!";
+
+/// 占位用户"已注销用户"的ID（由数据库迁移创建）
+/// 
+/// 账号被彻底删除时，它创建的模板和模板版本转给这个用户，其他用户Fork的清单不受影响。
+/// 占位用户没有密码，也没有可用的手机号和邮箱，无法登录。
+pub const DELETED_USER_ID: Uuid = Uuid::nil();
+
+impl Model {
+    /// 是否设置了密码（能否用密码登录）
+    pub fn has_password(&self) -> bool {
+        self.password_hash != UNUSABLE_PASSWORD_HASH
+    }
+    
+    /// 申请注销后，账号将被彻底删除的时间（没有申请注销时为`None`）
+    pub fn purge_after(&self, grace_days: i64) -> Option<DateTime<Utc>> {
+        self.deletion_requested_at
+            .map(|requested_at| requested_at + chrono::Duration::days(grace_days))
+    }
+}
+
+/// 用户角色
+/// 
+/// 权限从低到高：
+/// - `user`: 普通用户，浏览模板、Fork清单
+/// - `contributor`: 贡献者，可以创建模板（V0.0.1阶段模板只由内部人员创建）
+/// - `city_curator`: 城市主编，可以创建、修改和删除负责地区（`role_scope`）及其下级地区的模板
+/// - `admin`: 管理员，可以修改任何模板、设置官方模板、授予角色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(20))")]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+    /// 普通用户
+    #[default]
+    #[sea_orm(string_value = "user")]
+    User,
+    
+    /// 贡献者
+    #[sea_orm(string_value = "contributor")]
+    Contributor,
+    
+    /// 城市主编（限定地区）
+    #[sea_orm(string_value = "city_curator")]
+    CityCurator,
+    
+    /// 管理员
+    #[sea_orm(string_value = "admin")]
+    Admin,
+}
+
+impl UserRole {
+    fn rank(self) -> u8 {
+        match self {
+            UserRole::User => 0,
+            UserRole::Contributor => 1,
+            UserRole::CityCurator => 2,
+            UserRole::Admin => 3,
+        }
+    }
+    
+    /// 是否拥有`required`角色的权限（高级角色包含低级角色的权限）
+    pub fn includes(self, required: UserRole) -> bool {
+        self.rank() >= required.rank()
+    }
+    
+    /// 该角色是否需要地区范围
+    pub fn is_scoped(self) -> bool {
+        self == UserRole::CityCurator
+    }
+}
+
+/// 发起请求的用户（来自JWT中的声明）
+/// 
+/// 服务层用它判断角色权限；角色变更后需要刷新令牌或重新登录才会生效。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Actor {
+    /// 用户ID
+    pub user_id: Uuid,
+    
+    /// 角色
+    pub role: UserRole,
+    
+    /// 角色的地区范围（只有城市主编有）
+    pub role_scope: Option<String>,
+}
+
+impl Actor {
+    /// 是否是管理员
+    pub fn is_admin(&self) -> bool {
+        self.role == UserRole::Admin
+    }
+}
+
 /// 用户公开资料（安全的用户信息）
 /// 
 /// 该结构体用于API响应，不包含敏感信息（如密码哈希、手机号、邮箱）
@@ -101,6 +215,12 @@ pub struct UserProfile {
     
     /// 常驻城市
     pub home_city: Option<String>,
+    
+    /// 角色
+    pub role: UserRole,
+    
+    /// 角色的地区范围（只有城市主编有）
+    pub role_scope: Option<String>,
 }
 
 impl From<Model> for UserProfile {
@@ -111,6 +231,8 @@ impl From<Model> for UserProfile {
             nickname: user.nickname,
             avatar_url: user.avatar_url,
             home_city: user.home_city,
+            role: user.role,
+            role_scope: user.role_scope,
         }
     }
 }
@@ -172,12 +294,30 @@ pub struct UpdateProfileDto {
     pub avatar_url: Option<String>,
     
     /// 新常驻城市
+    /// 
+    /// 必须是`locations`表中层级为`city`的地区代码（如"CN-GZ"）
     pub home_city: Option<String>,
 }
 
-/// 认证响应（注册/登录成功后的响应）
+/// 修改密码数据传输对象（DTO）
 /// 
-/// 包含用户信息和JWT token
+/// ## 验证规则
+/// - `current_password`: 当前密码
+/// - `new_password`: 6-100个字符
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ChangePasswordDto {
+    /// 当前密码
+    #[validate(length(min = 1))]
+    pub current_password: String,
+    
+    /// 新密码（明文，仅用于传输，存储时会加密）
+    #[validate(length(min = 6, max = 100))]
+    pub new_password: String,
+}
+
+/// 认证响应（注册/登录/刷新成功后的响应）
+/// 
+/// 包含用户信息、JWT访问令牌和刷新令牌
 #[derive(Debug, Serialize, ToSchema)]
 pub struct AuthResponse {
     /// 用户公开资料
@@ -188,5 +328,48 @@ pub struct AuthResponse {
     /// 客户端应将其存储并在后续请求中通过
     /// `Authorization: Bearer <token>` 头发送
     pub token: String,
+    
+    /// 访问令牌的有效期（秒）
+    pub expires_in: i64,
+    
+    /// 刷新令牌
+    /// 
+    /// 访问令牌过期后通过`POST /api/auth/refresh`换取新的令牌对，
+    /// 每个刷新令牌只能使用一次
+    pub refresh_token: String,
+}
+
+/// 授予角色数据传输对象（DTO）
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "role": "city_curator", "role_scope": "CN-GZ" }
+/// { "role": "contributor" }
+/// ```
+/// 
+/// ## 规则
+/// - `city_curator`必须提供`role_scope`（`locations`表中的地区代码）
+/// - 其他角色不能提供`role_scope`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GrantRoleDto {
+    /// 新角色
+    pub role: UserRole,
+    
+    /// 地区范围（只用于`city_curator`）
+    pub role_scope: Option<String>,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_roles_include_lower_ones() {
+        assert!(UserRole::Admin.includes(UserRole::CityCurator));
+        assert!(UserRole::CityCurator.includes(UserRole::Contributor));
+        assert!(UserRole::Contributor.includes(UserRole::Contributor));
+        assert!(!UserRole::User.includes(UserRole::Contributor));
+        assert!(!UserRole::CityCurator.includes(UserRole::Admin));
+    }
+}
@@ -2,15 +2,93 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sea_orm::entity::prelude::*;
 use uuid::Uuid;
+use validator::Validate;
 use utoipa::ToSchema;
 
+use crate::flow::{self, StepState};
+use crate::ownership::Owned;
+use crate::template::TemplateStep;
+
+/// 清单中的单个步骤
+/// 
+/// Fork时从模板复制而来，之后归清单所有，用户可以自由修改。
+/// 
+/// ## 字段说明
+/// 
+/// - `id`: 步骤ID（来自模板的步骤沿用模板步骤的`id`，自定义步骤新生成）
+/// - `title` / `description`: 步骤内容，可被用户改写
+/// - `order`: 步骤在清单中的顺序（从0开始）
+/// - `is_custom`: 是否为用户自己添加的步骤
+/// - `hidden`: 是否被用户隐藏（隐藏的步骤不计入进度）
+/// - `depends_on`: 前置步骤ID列表（从模板复制）
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// {
+///   "id": "7d9f3c1e-2b4a-4c8e-9f1a-0b2c3d4e5f60",
+///   "title": "确定租房预算和区域",
+///   "description": "根据工作地点和收入确定预算",
+///   "order": 0,
+///   "is_custom": false,
+///   "hidden": false
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ChecklistStep {
+    /// 步骤ID
+    pub id: Uuid,
+    
+    /// 步骤标题
+    pub title: String,
+    
+    /// 步骤详细说明
+    pub description: Option<String>,
+    
+    /// 步骤顺序（从0开始）
+    pub order: i32,
+    
+    /// 是否为用户自定义的步骤
+    /// 
+    /// 自定义步骤不参与上游模板同步
+    #[serde(default)]
+    pub is_custom: bool,
+    
+    /// 是否已隐藏
+    /// 
+    /// 隐藏的步骤保留在清单中（可以恢复），但不展示、不计入进度
+    #[serde(default)]
+    pub hidden: bool,
+    
+    /// 前置步骤ID列表
+    /// 
+    /// 隐藏或删除的前置步骤视为已满足
+    #[serde(default)]
+    pub depends_on: Vec<Uuid>,
+}
+
+impl ChecklistStep {
+    /// 从模板步骤创建清单步骤
+    pub fn from_template(step: &TemplateStep) -> Self {
+        Self {
+            id: step.id,
+            title: step.title.clone(),
+            description: step.description.clone(),
+            order: step.order,
+            is_custom: false,
+            hidden: false,
+            depends_on: step.depends_on.clone(),
+        }
+    }
+}
+
 /// 单个步骤的完成状态
 /// 
 /// 记录用户清单中每个步骤的完成情况。
 /// 
 /// ## 字段说明
 /// 
-/// - `step_index`: 步骤索引（对应模板中的order字段）
+/// - `step_id`: 步骤ID（对应模板步骤的`id`）
 /// - `completed`: 是否已完成
 /// - `completed_at`: 完成时间（完成时记录，未完成为None）
 /// 
@@ -18,7 +96,7 @@ use utoipa::ToSchema;
 /// 
 /// ```json
 /// {
-///   "step_index": 0,
+///   "step_id": "7d9f3c1e-2b4a-4c8e-9f1a-0b2c3d4e5f60",
 ///   "completed": true,
 ///   "completed_at": "2024-10-21T12:34:56Z"
 /// }
@@ -31,10 +109,11 @@ use utoipa::ToSchema;
 /// - 展示完成历史："你在3天前完成了这一步"
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct StepProgress {
-    /// 步骤索引（从0开始）
+    /// 步骤ID
     /// 
-    /// 对应模板步骤中的`order`字段
-    pub step_index: i32,
+    /// 对应模板步骤的`id`字段。使用稳定的ID而不是位置索引，
+    /// 模板插入或重排步骤后，完成状态仍然落在正确的步骤上
+    pub step_id: Uuid,
     
     /// 是否已完成
     /// 
@@ -51,14 +130,42 @@ pub struct StepProgress {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+/// 步骤的完成状态和依赖状态（响应视图）
+/// 
+/// 在`StepProgress`的基础上附加根据依赖图计算出的状态，不写入数据库。
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// {
+///   "step_id": "uuid-3",
+///   "completed": false,
+///   "completed_at": null,
+///   "state": "locked",
+///   "blocked_by": ["uuid-2"]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StepStatus {
+    /// 完成状态
+    #[serde(flatten)]
+    pub progress: StepProgress,
+    
+    /// 依赖状态：locked / available / done
+    pub state: StepState,
+    
+    /// 尚未完成的前置步骤ID（仅`locked`时非空）
+    pub blocked_by: Vec<Uuid>,
+}
+
 /// 清单整体进度统计
 /// 
 /// 计算并展示用户清单的完成进度。
 /// 
 /// ## 字段说明
 /// 
-/// - `steps`: 所有步骤的完成状态
-/// - `total_steps`: 总步骤数
+/// - `steps`: 所有可见步骤的完成状态和依赖状态（按清单顺序）
+/// - `total_steps`: 总步骤数（不含隐藏的步骤）
 /// - `completed_steps`: 已完成步骤数
 /// - `progress_percentage`: 完成百分比（0-100）
 /// 
@@ -89,10 +196,10 @@ pub struct StepProgress {
 /// - 鼓励语："还有7步，加油！"
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ChecklistProgress {
-    /// 所有步骤的状态详情
-    pub steps: Vec<StepProgress>,
+    /// 可见步骤的状态详情（按清单顺序）
+    pub steps: Vec<StepStatus>,
     
-    /// 总步骤数
+    /// 总步骤数（不含隐藏的步骤）
     pub total_steps: i32,
     
     /// 已完成的步骤数
@@ -110,13 +217,14 @@ pub struct ChecklistProgress {
 /// 
 /// 1. **清单 vs 模板**:
 ///    - 模板（Template）：公共的指南，只读
-///    - 清单（UserChecklist）：用户的个人副本，可修改进度
+///    - 清单（UserChecklist）：用户的个人副本，拥有自己的步骤列表和进度
 /// 
 /// 2. **Fork机制**:
 ///    - 用户看到感兴趣的模板
 ///    - 点击"开始"按钮Fork模板
 ///    - 系统创建UserChecklist，复制模板的标题和步骤
 ///    - 所有步骤初始状态为未完成
+///    - 之后用户可以添加、隐藏、删除、重排和改写步骤
 /// 
 /// 3. **进度追踪**:
 ///    - 用户逐步勾选完成的步骤
@@ -162,19 +270,45 @@ pub struct Model {
     /// - 未来可以对比模板更新
     pub source_template_id: Uuid,
     
+    /// Fork时来源模板的版本号
+    /// 
+    /// 对应`template_versions`中的快照，用于：
+    /// - 查看清单基于的原始步骤
+    /// - 与模板最新版本对比，同步上游更新
+    pub source_template_version: i32,
+    
     /// 清单标题
     /// 
     /// Fork时从模板复制而来，是模板的快照。
     /// 即使模板标题后续修改，清单标题不受影响。
     pub title: String,
     
+    /// 清单的步骤列表（JSON数组，存储在数据库的JSONB字段）
+    /// 
+    /// Fork时从模板复制，之后由用户维护：
+    /// ```json
+    /// [
+    ///   { "id": "uuid-1", "title": "确定预算", "description": null, "order": 0, "is_custom": false, "hidden": false },
+    ///   { "id": "uuid-9", "title": "问问同事的经验", "description": null, "order": 1, "is_custom": true, "hidden": false }
+    /// ]
+    /// ```
+    #[sea_orm(column_type = "Json")]
+    pub steps: Json,
+    
+    /// 同步基准：上游模板的有效步骤快照（JSON数组，存储在数据库的JSONB字段）
+    /// 
+    /// Fork或上一次同步时，模板（沿继承链解析后）的步骤列表。
+    /// 同步上游更新时作为三方合并的基准。
+    #[sea_orm(column_type = "Json")]
+    pub base_steps: Json,
+    
     /// 进度状态（JSON数组，存储在数据库的JSONB字段）
     /// 
     /// 记录每个步骤的完成情况：
     /// ```json
     /// [
-    ///   { "step_index": 0, "completed": true, "completed_at": "..." },
-    ///   { "step_index": 1, "completed": false, "completed_at": null }
+    ///   { "step_id": "uuid-1", "completed": true, "completed_at": "..." },
+    ///   { "step_id": "uuid-2", "completed": false, "completed_at": null }
     /// ]
     /// ```
     #[sea_orm(column_type = "Json")]
@@ -217,8 +351,30 @@ impl Related<super::template::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
-/// 辅助函数：从 Model 获取步骤进度列表
+/// 清单属于Fork它的用户
+impl Owned for Model {
+    const KIND: &'static str = "checklist";
+    
+    fn owner_id(&self) -> Uuid {
+        self.user_id
+    }
+}
+
+/// 辅助函数：从 Model 获取步骤列表和步骤进度列表
 impl Model {
+    pub fn get_steps(&self) -> Result<Vec<ChecklistStep>, serde_json::Error> {
+        serde_json::from_value(self.steps.clone())
+    }
+    
+    pub fn set_steps(&mut self, steps: Vec<ChecklistStep>) -> Result<(), serde_json::Error> {
+        self.steps = serde_json::to_value(steps)?;
+        Ok(())
+    }
+    
+    pub fn get_base_steps(&self) -> Result<Vec<TemplateStep>, serde_json::Error> {
+        serde_json::from_value(self.base_steps.clone())
+    }
+    
     pub fn get_progress(&self) -> Result<Vec<StepProgress>, serde_json::Error> {
         serde_json::from_value(self.progress_status.clone())
     }
@@ -250,12 +406,33 @@ impl Model {
     /// 
     /// ## 计算逻辑
     /// 
-    /// 1. 统计总步骤数（progress_status.len()）
-    /// 2. 统计已完成步骤数（completed=true的数量）
+    /// 1. 按清单顺序取出所有未隐藏的步骤，查找各自的进度
+    ///    （没有进度记录的步骤视为未完成）
+    /// 2. 统计总步骤数和已完成步骤数（隐藏的步骤不计入）
     /// 3. 计算百分比：(已完成 / 总数) × 100
-    /// 4. 返回完整的进度信息
+    /// 4. 根据依赖图标记每个步骤为 locked / available / done
+    /// 5. 返回完整的进度信息
     pub fn calculate_progress(&self) -> Result<ChecklistProgress, serde_json::Error> {
-        let progress_status = self.get_progress()?;
+        let mut steps = self.get_steps()?;
+        let progress_list = self.get_progress()?;
+        steps.sort_by_key(|s| s.order);
+        
+        // 可见步骤的进度（按清单顺序）
+        let progress_status: Vec<StepProgress> = steps
+            .iter()
+            .filter(|step| !step.hidden)
+            .map(|step| {
+                progress_list
+                    .iter()
+                    .find(|p| p.step_id == step.id)
+                    .cloned()
+                    .unwrap_or(StepProgress {
+                        step_id: step.id,
+                        completed: false,
+                        completed_at: None,
+                    })
+            })
+            .collect();
         
         // 总步骤数
         let total = progress_status.len() as i32;
@@ -263,6 +440,29 @@ impl Model {
         // 已完成步骤数（筛选completed=true的步骤）
         let completed = progress_status.iter().filter(|s| s.completed).count() as i32;
         
+        // 根据依赖图计算每个步骤的状态
+        let statuses = steps
+            .iter()
+            .filter(|step| !step.hidden)
+            .zip(progress_status)
+            .map(|(step, progress)| {
+                let blocked_by = flow::unfinished_prerequisites(&steps, &progress_list, step);
+                let state = if progress.completed {
+                    StepState::Done
+                } else if blocked_by.is_empty() {
+                    StepState::Available
+                } else {
+                    StepState::Locked
+                };
+                
+                StepStatus {
+                    progress,
+                    state,
+                    blocked_by: if state == StepState::Locked { blocked_by } else { Vec::new() },
+                }
+            })
+            .collect();
+        
         // 计算完成百分比（避免除以0）
         let percentage = if total > 0 {
             (completed as f32 / total as f32) * 100.0
@@ -271,7 +471,7 @@ impl Model {
         };
 
         Ok(ChecklistProgress {
-            steps: progress_status,
+            steps: statuses,
             total_steps: total,
             completed_steps: completed,
             progress_percentage: percentage,
@@ -312,30 +512,109 @@ pub struct ForkTemplateDto {
 /// 
 /// ```json
 /// {
-///   "step_index": 0,
-///   "completed": true
+///   "step_id": "7d9f3c1e-2b4a-4c8e-9f1a-0b2c3d4e5f60",
+///   "completed": true,
+///   "force": false
 /// }
 /// ```
 /// 
 /// ## 业务逻辑
 /// 
 /// 1. 查找指定清单的progress_status
-/// 2. 定位step_index对应的步骤
-/// 3. 更新completed字段
-/// 4. 如果设为完成，记录当前时间到completed_at
-/// 5. 如果取消完成，清空completed_at
-/// 6. 保存到数据库
-/// 7. 返回更新后的清单和新进度
+/// 2. 定位step_id对应的步骤
+/// 3. 标记完成时检查前置步骤，未全部完成且没有`force`时拒绝
+/// 4. 更新completed字段
+/// 5. 如果设为完成，记录当前时间到completed_at
+/// 6. 如果取消完成，清空completed_at
+/// 7. 保存到数据库
+/// 8. 返回更新后的清单和新进度
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateStepDto {
-    /// 要更新的步骤索引（从0开始）
-    pub step_index: i32,
+    /// 要更新的步骤ID（模板步骤的`id`）
+    pub step_id: Uuid,
     
     /// 新的完成状态
     /// 
     /// - `true`: 标记为已完成
     /// - `false`: 标记为未完成
     pub completed: bool,
+    
+    /// 强制完成
+    /// 
+    /// 默认情况下，前置步骤未完成的步骤不能标记为完成；
+    /// 设为`true`可以跳过这个检查（例如用户确实已经线下办完）
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// 添加自定义步骤DTO
+/// 
+/// 用于POST /api/checklists/:id/steps接口，在清单中插入一个用户自己写的步骤。
+/// 
+/// ## 请求体示例
+/// 
+/// ```json
+/// {
+///   "title": "问问同事的租房经验",
+///   "description": "特别是通勤时间",
+///   "position": 1
+/// }
+/// ```
+/// 
+/// ## 字段说明
+/// 
+/// - `position`: 插入位置（从0开始），省略或超出范围时追加到末尾
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AddChecklistStepDto {
+    /// 步骤标题
+    #[validate(length(min = 1, max = 500))]
+    pub title: String,
+    
+    /// 步骤详细说明
+    pub description: Option<String>,
+    
+    /// 插入位置（从0开始）
+    pub position: Option<i32>,
+}
+
+/// 修改清单步骤DTO
+/// 
+/// 用于PATCH /api/checklists/:id/steps/:step_id接口，只更新提供的字段。
+/// 
+/// ## 请求体示例
+/// 
+/// ```json
+/// { "title": "确定预算（不超过月薪1/3）" }
+/// { "hidden": true }
+/// ```
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateChecklistStepDto {
+    /// 新标题
+    #[validate(length(min = 1, max = 500))]
+    pub title: Option<String>,
+    
+    /// 新说明
+    pub description: Option<String>,
+    
+    /// 隐藏（true）或恢复（false）步骤
+    pub hidden: Option<bool>,
+}
+
+/// 重排清单步骤DTO
+/// 
+/// 用于PUT /api/checklists/:id/steps/order接口。
+/// 
+/// `step_ids`必须恰好包含清单中的每个步骤（含隐藏的步骤）各一次。
+/// 
+/// ## 请求体示例
+/// 
+/// ```json
+/// { "step_ids": ["uuid-2", "uuid-1", "uuid-3"] }
+/// ```
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReorderChecklistStepsDto {
+    /// 新顺序下的步骤ID列表
+    pub step_ids: Vec<Uuid>,
 }
 
 /// 用户清单响应DTO
@@ -350,10 +629,15 @@ pub struct UpdateStepDto {
 ///     "id": "uuid",
 ///     "user_id": "uuid",
 ///     "source_template_id": "uuid",
+///     "source_template_version": 1,
 ///     "title": "第一次在北京租房",
+///     "steps": [
+///       { "id": "uuid-1", "title": "确定预算", "description": null, "order": 0, "is_custom": false, "hidden": false },
+///       { "id": "uuid-2", "title": "寻找房源", "description": null, "order": 1, "is_custom": false, "hidden": false }
+///     ],
 ///     "progress_status": [
-///       { "step_index": 0, "completed": true, "completed_at": "..." },
-///       { "step_index": 1, "completed": false, "completed_at": null }
+///       { "step_id": "uuid-1", "completed": true, "completed_at": "..." },
+///       { "step_id": "uuid-2", "completed": false, "completed_at": null }
 ///     ],
 ///     "created_at": "...",
 ///     "updated_at": "..."
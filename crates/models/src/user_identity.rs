@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use utoipa::ToSchema;
+
+/// 第三方账号绑定（数据库实体）
+/// 
+/// 把第三方登录提供方（OIDC）的账号关联到`users`中的用户。
+/// 
+/// ## 核心概念
+/// 
+/// - `(provider, subject)` 唯一：一个第三方账号只能绑定一个用户
+/// - `(user_id, provider)` 唯一：每个用户在每个提供方只能绑定一个账号
+/// - 首次用第三方账号登录时自动注册并绑定；已有账号在`/api/users/me/identities`中绑定或解绑
+/// - 不会按邮箱自动关联已有用户（邮箱相同不代表是同一个人）
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `user_identities`
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize, ToSchema)]
+#[sea_orm(table_name = "user_identities")]
+pub struct Model {
+    /// 绑定记录唯一标识
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// 用户ID
+    pub user_id: Uuid,
+    
+    /// 提供方名称（如"google"）
+    pub provider: String,
+    
+    /// 提供方内的用户标识（ID令牌的`sub`）
+    pub subject: String,
+    
+    /// 绑定时提供方返回的邮箱（只用于展示）
+    pub email: Option<String>,
+    
+    /// 绑定时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
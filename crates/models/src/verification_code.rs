@@ -0,0 +1,148 @@
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+use utoipa::ToSchema;
+
+/// 验证码有效期（秒）
+pub const CODE_TTL_SECONDS: i64 = 300;
+
+/// 同一个验证码最多允许输错的次数，超过后必须重新获取
+pub const MAX_CODE_ATTEMPTS: i32 = 5;
+
+/// 同一手机号两次发送之间的最短间隔（秒）
+pub const RESEND_INTERVAL_SECONDS: i64 = 60;
+
+/// 同一手机号每小时最多发送的验证码数量
+pub const MAX_CODES_PER_HOUR: u64 = 5;
+
+/// 手机号格式：1开头的11位ASCII数字（`\d`会匹配全角等其他数字）
+pub static PHONE_PATTERN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^1[0-9]{10}$").unwrap());
+
+/// 短信验证码（数据库实体）
+/// 
+/// 手机号验证码登录：先发送验证码，再用验证码登录（手机号未注册时自动注册）。
+/// 
+/// ## 核心概念
+/// 
+/// - 数据库只保存验证码的哈希，明文只通过短信发送
+/// - 每个手机号只有最新的一条验证码有效，发送新验证码时旧验证码作废
+/// - 验证成功后立即标记`consumed_at`，同一验证码不能重复使用
+/// - 输错`MAX_CODE_ATTEMPTS`次后作废
+/// 
+/// ## 数据库表
+/// 
+/// 对应表: `verification_codes`，`(phone, created_at)` 有索引
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "verification_codes")]
+pub struct Model {
+    /// 验证码记录唯一标识
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    
+    /// 接收验证码的手机号
+    pub phone: String,
+    
+    /// 验证码的SHA-256哈希（十六进制，与手机号一起计算）
+    pub code_hash: String,
+    
+    /// 已输错的次数
+    pub attempts: i32,
+    
+    /// 过期时间
+    pub expires_at: DateTime<Utc>,
+    
+    /// 使用时间（登录成功或被新验证码取代时设置）
+    pub consumed_at: Option<DateTime<Utc>>,
+    
+    /// 发送时间
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// 是否还能用于登录（未使用、未过期、未超过输错次数）
+    pub fn is_usable(&self) -> bool {
+        self.consumed_at.is_none()
+            && self.expires_at > Utc::now()
+            && self.attempts < MAX_CODE_ATTEMPTS
+    }
+}
+
+/// 发送验证码数据传输对象（DTO）
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "phone": "13800138000" }
+/// ```
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SendCodeDto {
+    /// 手机号（1开头的11位数字）
+    #[validate(regex(path = *PHONE_PATTERN))]
+    pub phone: String,
+}
+
+/// 发送验证码响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SendCodeResponse {
+    /// 验证码有效期（秒）
+    pub expires_in: i64,
+    
+    /// 多少秒后可以重新发送
+    pub resend_after: i64,
+}
+
+/// 验证码登录数据传输对象（DTO）
+/// 
+/// 手机号未注册时自动注册，`nickname`只在自动注册时使用。
+/// 
+/// ## 示例
+/// 
+/// ```json
+/// { "phone": "13800138000", "code": "123456", "nickname": "张三" }
+/// ```
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct PhoneLoginDto {
+    /// 手机号（1开头的11位数字）
+    #[validate(regex(path = *PHONE_PATTERN))]
+    pub phone: String,
+    
+    /// 短信验证码
+    #[validate(length(min = 1, max = 10))]
+    pub code: String,
+    
+    /// 自动注册时使用的昵称（默认为"用户" + 手机号后4位）
+    #[validate(length(min = 1, max = 50))]
+    pub nickname: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn login(phone: &str) -> PhoneLoginDto {
+        PhoneLoginDto { phone: phone.to_string(), code: "123456".to_string(), nickname: None }
+    }
+
+    #[test]
+    fn phones_must_be_eleven_ascii_digits() {
+        assert!(login("13800138000").validate().is_ok());
+        assert!(SendCodeDto { phone: "13800138000".to_string() }.validate().is_ok());
+
+        // 11 characters each, but not a phone number
+        for phone in ["1٣٨٠٠١٣٨٠٠٠", "１３８００１３８０００", "1380013800a", "23800138000", "手机号码一二三四五六七"] {
+            assert!(login(phone).validate().is_err(), "{} was accepted", phone);
+            assert!(SendCodeDto { phone: phone.to_string() }.validate().is_err(), "{} was accepted", phone);
+        }
+        assert!(login("1380013800").validate().is_err());
+    }
+}
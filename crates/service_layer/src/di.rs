@@ -1,17 +1,37 @@
-use auth::{JwtService, JwtServiceImpl, PasswordService, PasswordServiceImpl};
-use common::AppConfig;
+use auth::{
+    JwtService, JwtServiceImpl, PasswordService, PasswordServiceImpl, SmsSender, LogSmsSender, DisabledSmsSender,
+    Mailer, SmtpMailer, CaptureMailer,
+    OidcProvider, OidcProviderImpl, HttpClient, DefaultHttpClient,
+};
+use common::{AppConfig, SmsBackend, StorageBackend};
 use db::{
     TemplateRepository, TemplateRepositoryImpl,
+    LocationRepository, LocationRepositoryImpl,
     UserRepository, UserRepositoryImpl,
+    RefreshTokenRepository, RefreshTokenRepositoryImpl,
+    VerificationCodeRepository, VerificationCodeRepositoryImpl,
+    PasswordResetTokenRepository, PasswordResetTokenRepositoryImpl,
+    LoginAttemptRepository, LoginAttemptRepositoryImpl,
+    AccountRepository, AccountRepositoryImpl,
+    UserIdentityRepository, UserIdentityRepositoryImpl,
+    OAuthStateRepository, OAuthStateRepositoryImpl,
     UserChecklistRepository, UserChecklistRepositoryImpl,
 };
 use crate::services::{
     TemplateService, TemplateServiceImpl,
     UserService, UserServiceImpl,
+    SessionService, SessionServiceImpl,
+    LoginThrottleService, LoginThrottleServiceImpl,
+    CredentialService, CredentialServiceImpl,
+    AccountService, AccountServiceImpl,
+    AvatarService, AvatarServiceImpl,
+    OAuthService, OAuthServiceImpl,
     ChecklistService, ChecklistServiceImpl,
+    LocationService, LocationServiceImpl,
 };
 use sea_orm::DatabaseConnection;
 use std::sync::Arc;
+use storage::{ObjectStorage, LocalStorage, S3Storage};
 
 /// 应用程序依赖注入容器
 /// 
@@ -23,9 +43,22 @@ use std::sync::Arc;
 /// ## 架构层次：
 /// ```
 /// AppModule（应用模块）
-///   ├── TemplateService（模板服务）      → 依赖 TemplateRepository
-///   ├── UserService（用户服务）          → 依赖 UserRepository, JwtService, PasswordService
-///   └── ChecklistService（清单服务）     → 依赖 UserChecklistRepository, TemplateRepository
+///   ├── TemplateService（模板服务）      → 依赖 TemplateRepository, LocationRepository, UserRepository
+///   ├── SessionService（会话服务）       → 依赖 RefreshTokenRepository, UserRepository, JwtService
+///   ├── LoginThrottleService（登录限流服务）→ 依赖 LoginAttemptRepository
+///   ├── UserService（用户服务）          → 依赖 UserRepository, LocationRepository, VerificationCodeRepository,
+///   │                                       PasswordService, SmsSender, SessionService, LoginThrottleService
+///   ├── CredentialService（密码服务）   → 依赖 UserRepository, PasswordResetTokenRepository, PasswordService,
+///   │                                       Mailer, SessionService, LoginThrottleService
+///   ├── AccountService（账号服务）       → 依赖 UserRepository, AccountRepository, UserChecklistRepository,
+///   │                                       UserIdentityRepository, PasswordService, SessionService,
+///   │                                       LoginThrottleService, ObjectStorage
+///   ├── AvatarService（头像服务）        → 依赖 UserRepository, ObjectStorage
+///   ├── OAuthService（第三方登录服务）   → 依赖 OidcProvider（每个提供方一个）, OAuthStateRepository,
+///   │                                       UserIdentityRepository, UserRepository, SessionService
+///   ├── ChecklistService（清单服务）     → 依赖 UserChecklistRepository, TemplateRepository
+///   ├── LocationService（地区服务）      → 依赖 LocationRepository
+///   └── JwtService（JWT服务）            → 认证提取器直接使用
 /// ```
 /// 
 /// ## 依赖注入的好处：
@@ -40,8 +73,29 @@ pub struct AppModule {
     /// 用户服务：处理用户注册、登录、认证等业务逻辑
     pub user_service: Arc<dyn UserService>,
     
+    /// 会话服务：签发、刷新和吊销访问令牌/刷新令牌
+    pub session_service: Arc<dyn SessionService>,
+    
+    /// 密码服务：修改密码、通过邮件找回密码
+    pub credential_service: Arc<dyn CredentialService>,
+    
+    /// 账号服务：导出个人数据、注销账号、彻底删除宽限期已过的账号
+    pub account_service: Arc<dyn AccountService>,
+    
+    /// 头像服务：上传头像（校验、去除EXIF、生成多个尺寸）
+    pub avatar_service: Arc<dyn AvatarService>,
+    
+    /// 第三方登录服务：OAuth2/OIDC登录、绑定和解绑第三方账号
+    pub oauth_service: Arc<dyn OAuthService>,
+    
     /// 清单服务：处理用户清单的fork、进度追踪等业务逻辑
     pub checklist_service: Arc<dyn ChecklistService>,
+    
+    /// 地区服务：提供地区列表和查询
+    pub location_service: Arc<dyn LocationService>,
+    
+    /// JWT服务：认证提取器用它验证请求中的访问令牌
+    pub jwt_service: Arc<dyn JwtService>,
 }
 
 impl AppModule {
@@ -62,68 +116,359 @@ impl AppModule {
     /// ## 示例
     /// ```rust
     /// let db = create_sea_orm_connection(&db_url, 5).await?;
-    /// let config = AppConfig::from_env()?;
+    /// let config = AppConfig::load(&ConfigOptions::default())?;
     /// let app_module = AppModule::new(db, config);
     /// 
     /// // 现在可以使用服务了
     /// app_module.user_service.register(dto).await?;
     /// ```
     pub fn new(db: DatabaseConnection, config: AppConfig) -> Self {
+        Self::builder(config).database(db).build()
+    }
+
+    /// 创建依赖注入容器的构建器，可以替换任意Repository的实现
+    /// 
+    /// 没有替换的Repository使用`database`设置的数据库连接创建SeaORM实现。
+    /// 
+    /// ## 示例（Service层测试，使用`db`的`testing`特性提供的内存实现）
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use db::memory::{InMemoryTemplateRepository, InMemoryUserRepository};
+    /// # use service_layer::AppModule;
+    /// # fn example(config: common::AppConfig) {
+    /// let users = Arc::new(InMemoryUserRepository::new());
+    /// let app_module = AppModule::builder(config)
+    ///     .user_repository(users.clone())
+    ///     .template_repository(Arc::new(InMemoryTemplateRepository::new()))
+    ///     .build();
+    /// # }
+    /// ```
+    pub fn builder(config: AppConfig) -> AppModuleBuilder {
+        AppModuleBuilder::new(config)
+    }
+}
+
+/// `AppModule`的构建器
+/// 
+/// 默认所有Repository都使用SeaORM实现；测试中可以只替换用到的Repository
+/// （如`db`的`testing`特性提供的内存实现），不需要数据库。
+/// 
+/// 没有调用`database`时使用未连接的数据库连接：没有替换的Repository一旦被调用就会panic，
+/// 在测试中意味着用到了没有准备的依赖。
+pub struct AppModuleBuilder {
+    config: AppConfig,
+    db: DatabaseConnection,
+    template_repo: Option<Arc<dyn TemplateRepository>>,
+    user_repo: Option<Arc<dyn UserRepository>>,
+    refresh_token_repo: Option<Arc<dyn RefreshTokenRepository>>,
+    verification_code_repo: Option<Arc<dyn VerificationCodeRepository>>,
+    password_reset_repo: Option<Arc<dyn PasswordResetTokenRepository>>,
+    login_attempt_repo: Option<Arc<dyn LoginAttemptRepository>>,
+    account_repo: Option<Arc<dyn AccountRepository>>,
+    user_identity_repo: Option<Arc<dyn UserIdentityRepository>>,
+    oauth_state_repo: Option<Arc<dyn OAuthStateRepository>>,
+    checklist_repo: Option<Arc<dyn UserChecklistRepository>>,
+    location_repo: Option<Arc<dyn LocationRepository>>,
+    mailer: Option<Arc<dyn Mailer>>,
+}
+
+impl AppModuleBuilder {
+    fn new(config: AppConfig) -> Self {
+        Self {
+            config,
+            db: DatabaseConnection::default(),
+            template_repo: None,
+            user_repo: None,
+            refresh_token_repo: None,
+            verification_code_repo: None,
+            password_reset_repo: None,
+            login_attempt_repo: None,
+            account_repo: None,
+            user_identity_repo: None,
+            oauth_state_repo: None,
+            checklist_repo: None,
+            location_repo: None,
+            mailer: None,
+        }
+    }
+
+    /// 设置数据库连接，用于创建没有替换的Repository
+    pub fn database(mut self, db: DatabaseConnection) -> Self {
+        self.db = db;
+        self
+    }
+
+    /// 替换模板数据访问的实现
+    pub fn template_repository(mut self, repo: Arc<dyn TemplateRepository>) -> Self {
+        self.template_repo = Some(repo);
+        self
+    }
+
+    /// 替换用户数据访问的实现
+    pub fn user_repository(mut self, repo: Arc<dyn UserRepository>) -> Self {
+        self.user_repo = Some(repo);
+        self
+    }
+
+    /// 替换刷新令牌数据访问的实现
+    pub fn refresh_token_repository(mut self, repo: Arc<dyn RefreshTokenRepository>) -> Self {
+        self.refresh_token_repo = Some(repo);
+        self
+    }
+
+    /// 替换短信验证码数据访问的实现
+    pub fn verification_code_repository(mut self, repo: Arc<dyn VerificationCodeRepository>) -> Self {
+        self.verification_code_repo = Some(repo);
+        self
+    }
+
+    /// 替换找回密码令牌数据访问的实现
+    pub fn password_reset_repository(mut self, repo: Arc<dyn PasswordResetTokenRepository>) -> Self {
+        self.password_reset_repo = Some(repo);
+        self
+    }
+
+    /// 替换登录失败计数数据访问的实现
+    pub fn login_attempt_repository(mut self, repo: Arc<dyn LoginAttemptRepository>) -> Self {
+        self.login_attempt_repo = Some(repo);
+        self
+    }
+
+    /// 替换账号数据访问的实现
+    pub fn account_repository(mut self, repo: Arc<dyn AccountRepository>) -> Self {
+        self.account_repo = Some(repo);
+        self
+    }
+
+    /// 替换第三方账号绑定数据访问的实现
+    pub fn user_identity_repository(mut self, repo: Arc<dyn UserIdentityRepository>) -> Self {
+        self.user_identity_repo = Some(repo);
+        self
+    }
+
+    /// 替换第三方授权请求数据访问的实现
+    pub fn oauth_state_repository(mut self, repo: Arc<dyn OAuthStateRepository>) -> Self {
+        self.oauth_state_repo = Some(repo);
+        self
+    }
+
+    /// 替换清单数据访问的实现
+    pub fn checklist_repository(mut self, repo: Arc<dyn UserChecklistRepository>) -> Self {
+        self.checklist_repo = Some(repo);
+        self
+    }
+
+    /// 替换地区数据访问的实现
+    pub fn location_repository(mut self, repo: Arc<dyn LocationRepository>) -> Self {
+        self.location_repo = Some(repo);
+        self
+    }
+
+    /// 替换邮件服务（默认按`mail`配置使用`SmtpMailer`或`CaptureMailer`）
+    pub fn mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mailer = Some(mailer);
+        self
+    }
+
+    /// 按照依赖层次顺序创建所有服务实例：
+    /// 1. **Repository层**（数据访问层）- 负责数据库操作
+    /// 2. **Infrastructure层**（基础设施层）- 负责认证、加密等
+    /// 3. **Service层**（业务逻辑层）- 负责核心业务逻辑
+    pub fn build(self) -> AppModule {
         // ==================== 第1层：数据访问层（Repository） ====================
-        // Repository负责与数据库交互，执行CRUD操作
+        // Repository负责与数据库交互，执行CRUD操作；构建器中替换过的直接使用
+        let db = self.db;
+        let config = self.config;
         
         // 模板数据访问：负责templates表的所有数据库操作
-        let template_repo = Arc::new(TemplateRepositoryImpl::new(db.clone())) 
-            as Arc<dyn TemplateRepository>;
+        let template_repo = self.template_repo
+            .unwrap_or_else(|| Arc::new(TemplateRepositoryImpl::new(db.clone())));
         
         // 用户数据访问：负责users表的所有数据库操作
-        let user_repo = Arc::new(UserRepositoryImpl::new(db.clone())) 
-            as Arc<dyn UserRepository>;
+        let user_repo = self.user_repo
+            .unwrap_or_else(|| Arc::new(UserRepositoryImpl::new(db.clone())));
+        
+        // 刷新令牌数据访问：负责refresh_tokens表的所有数据库操作
+        let refresh_token_repo = self.refresh_token_repo
+            .unwrap_or_else(|| Arc::new(RefreshTokenRepositoryImpl::new(db.clone())));
+        
+        // 短信验证码数据访问：负责verification_codes表的所有数据库操作
+        let verification_code_repo = self.verification_code_repo
+            .unwrap_or_else(|| Arc::new(VerificationCodeRepositoryImpl::new(db.clone())));
+        
+        // 找回密码令牌数据访问：负责password_reset_tokens表的所有数据库操作
+        let password_reset_repo = self.password_reset_repo
+            .unwrap_or_else(|| Arc::new(PasswordResetTokenRepositoryImpl::new(db.clone())));
+        
+        // 登录失败计数数据访问：负责login_attempts和login_lockouts表的所有数据库操作
+        let login_attempt_repo = self.login_attempt_repo
+            .unwrap_or_else(|| Arc::new(LoginAttemptRepositoryImpl::new(db.clone())));
+        
+        // 账号数据访问：导出用户创建的内容、彻底删除注销的账号（跨多张表）
+        let account_repo = self.account_repo
+            .unwrap_or_else(|| Arc::new(AccountRepositoryImpl::new(db.clone())));
+        
+        // 第三方账号绑定数据访问：负责user_identities表的所有数据库操作
+        let user_identity_repo = self.user_identity_repo
+            .unwrap_or_else(|| Arc::new(UserIdentityRepositoryImpl::new(db.clone())));
+        
+        // 第三方授权请求数据访问：负责oauth_states表的所有数据库操作
+        let oauth_state_repo = self.oauth_state_repo
+            .unwrap_or_else(|| Arc::new(OAuthStateRepositoryImpl::new(db.clone())));
         
         // 清单数据访问：负责user_checklists表的所有数据库操作
-        let checklist_repo = Arc::new(UserChecklistRepositoryImpl::new(db.clone())) 
-            as Arc<dyn UserChecklistRepository>;
+        let checklist_repo = self.checklist_repo
+            .unwrap_or_else(|| Arc::new(UserChecklistRepositoryImpl::new(db.clone())));
+        
+        // 地区数据访问：负责locations表的只读查询
+        let location_repo = self.location_repo
+            .unwrap_or_else(|| Arc::new(LocationRepositoryImpl::new(db.clone())));
 
         // ==================== 第2层：基础设施层（Infrastructure） ====================
         // 提供认证、加密等基础功能
         
-        // JWT服务：负责生成和验证JWT token
-        let jwt_service = Arc::new(JwtServiceImpl::new(
-            config.jwt.secret.clone(),
-            config.jwt.expiration,
-        )) as Arc<dyn JwtService>;
+        // JWT服务：负责生成和验证JWT访问令牌（按kid选择验证密钥，支持密钥轮换）
+        let jwt_service = Arc::new(
+            JwtServiceImpl::from_config(&config.jwt).expect("❌ JWT密钥无效，请检查JWT_*配置"),
+        ) as Arc<dyn JwtService>;
+        
+        // 密码服务：负责密码的加密和验证（Argon2id，兼容验证旧的bcrypt哈希）
+        let password_service = Arc::new(
+            PasswordServiceImpl::new(&config.password).expect("❌ Argon2id参数无效，请检查PASSWORD_ARGON2_*配置"),
+        ) as Arc<dyn PasswordService>;
+        
+        // 短信服务：发送登录验证码（`log`只写日志，生产环境的配置校验要求设置为`disabled`）
+        let sms_sender = match config.sms.backend {
+            SmsBackend::Log => Arc::new(LogSmsSender::new()) as Arc<dyn SmsSender>,
+            SmsBackend::Disabled => Arc::new(DisabledSmsSender) as Arc<dyn SmsSender>,
+        };
+        
+        // 邮件服务：配置了SMTP服务器时通过SMTP发送，否则只写日志（和MAIL_CAPTURE_DIR）；
+        // 生产环境的配置校验要求配置SMTP服务器
+        let mailer = self.mailer.unwrap_or_else(|| match (&config.mail.smtp, &config.mail.capture_dir) {
+            (Some(smtp), _) => Arc::new(
+                SmtpMailer::new(smtp.clone(), config.mail.from.clone()).expect("❌ SMTP服务器地址无效，请检查SMTP_*配置"),
+            ) as Arc<dyn Mailer>,
+            (None, Some(dir)) => Arc::new(CaptureMailer::with_dir(dir, &config.mail.from)) as Arc<dyn Mailer>,
+            (None, None) => Arc::new(CaptureMailer::new()) as Arc<dyn Mailer>,
+        });
         
-        // 密码服务：负责密码的加密和验证（使用bcrypt）
-        let password_service = Arc::new(PasswordServiceImpl::new()) 
-            as Arc<dyn PasswordService>;
+        // 第三方登录提供方：每个配置的OIDC提供方一个实例，共用一个HTTP客户端
+        let http_client = Arc::new(DefaultHttpClient::new()) 
+            as Arc<dyn HttpClient>;
+        let oidc_providers = config.oidc_providers
+            .iter()
+            .map(|provider| {
+                Arc::new(OidcProviderImpl::new(provider.clone(), http_client.clone())) as Arc<dyn OidcProvider>
+            })
+            .collect();
+        
+        // 对象存储：保存用户上传的头像，本地目录或S3兼容的对象存储（复用后端HTTP客户端）
+        let object_storage = match &config.storage.backend {
+            StorageBackend::Local { dir } => {
+                Arc::new(LocalStorage::new(dir, &config.storage.public_base_url)) as Arc<dyn ObjectStorage>
+            }
+            StorageBackend::S3(s3) => {
+                Arc::new(S3Storage::new(s3.clone(), &config.storage.public_base_url, http_client.clone())) as Arc<dyn ObjectStorage>
+            }
+        };
 
         // ==================== 第3层：业务逻辑层（Service） ====================
         // 实现核心业务逻辑，依赖注入下层服务
         
         // 模板服务：处理模板的创建、搜索、查询等业务逻辑
         let template_service = Arc::new(TemplateServiceImpl::new(
-            template_repo.clone()  // 注入：模板数据访问
+            template_repo.clone(),      // 注入：模板数据访问
+            location_repo.clone(),      // 注入：地区数据访问（校验和扩展地理标签）
+            user_repo.clone(),          // 注入：用户数据访问（按常驻城市排序）
         )) as Arc<dyn TemplateService>;
         
+        // 会话服务：签发和轮换令牌，所有登录方式共用
+        let session_service = Arc::new(SessionServiceImpl::new(
+            refresh_token_repo.clone(), // 注入：刷新令牌数据访问
+            user_repo.clone(),          // 注入：用户数据访问（刷新时读取最新角色）
+            jwt_service.clone(),        // 注入：JWT服务
+            config.jwt.refresh_expiration, // 刷新令牌有效期
+        )) as Arc<dyn SessionService>;
+        
+        // 登录限流服务：按账号和IP统计登录失败次数，退避和锁定
+        let login_throttle = Arc::new(LoginThrottleServiceImpl::new(
+            login_attempt_repo.clone(), // 注入：登录失败计数数据访问
+        )) as Arc<dyn LoginThrottleService>;
+        
         // 用户服务：处理用户注册、登录、认证等业务逻辑
         let user_service = Arc::new(UserServiceImpl::new(
             user_repo.clone(),          // 注入：用户数据访问
-            jwt_service.clone(),        // 注入：JWT服务
+            location_repo.clone(),      // 注入：地区数据访问（校验常驻城市）
+            verification_code_repo.clone(), // 注入：短信验证码数据访问
             password_service.clone(),   // 注入：密码服务
+            sms_sender.clone(),         // 注入：短信服务
+            session_service.clone(),    // 注入：会话服务（签发令牌）
+            login_throttle.clone(),     // 注入：登录限流服务（防暴力破解）
         )) as Arc<dyn UserService>;
         
+        // 密码服务：修改密码、通过邮件找回密码
+        let credential_service = Arc::new(CredentialServiceImpl::new(
+            user_repo.clone(),          // 注入：用户数据访问
+            password_reset_repo.clone(), // 注入：找回密码令牌数据访问
+            password_service.clone(),   // 注入：密码服务（验证和加密密码）
+            mailer.clone(),             // 注入：邮件服务（发送找回密码邮件）
+            session_service.clone(),    // 注入：会话服务（退出所有设备、签发新令牌）
+            login_throttle.clone(),     // 注入：登录限流服务（当前密码输错时计数）
+            config.mail.password_reset_url.clone(), // 找回密码页面地址
+        )) as Arc<dyn CredentialService>;
+        
+        // 账号服务：导出个人数据、注销账号（宽限期后彻底删除）
+        let account_service = Arc::new(AccountServiceImpl::new(
+            user_repo.clone(),          // 注入：用户数据访问
+            account_repo.clone(),       // 注入：账号数据访问（导出创建的模板、彻底删除）
+            checklist_repo.clone(),     // 注入：清单数据访问（导出清单）
+            user_identity_repo.clone(), // 注入：第三方账号绑定数据访问（导出绑定）
+            password_service.clone(),   // 注入：密码服务（注销前确认密码）
+            session_service.clone(),    // 注入：会话服务（注销后退出所有设备）
+            login_throttle.clone(),     // 注入：登录限流服务（确认密码输错时计数）
+            object_storage.clone(),     // 注入：对象存储（彻底删除时删除头像文件）
+            config.account.deletion_grace_days, // 注销宽限期
+        )) as Arc<dyn AccountService>;
+        
+        // 头像服务：校验和处理上传的图片，保存到对象存储
+        let avatar_service = Arc::new(AvatarServiceImpl::new(
+            user_repo.clone(),          // 注入：用户数据访问（保存头像地址）
+            object_storage.clone(),     // 注入：对象存储
+        )) as Arc<dyn AvatarService>;
+        
+        // 第三方登录服务：授权、回调登录（首次登录自动注册）、绑定和解绑
+        let oauth_service = Arc::new(OAuthServiceImpl::new(
+            oidc_providers,             // 注入：OIDC提供方
+            oauth_state_repo.clone(),   // 注入：授权请求数据访问
+            user_identity_repo.clone(), // 注入：第三方账号绑定数据访问
+            user_repo.clone(),          // 注入：用户数据访问
+            session_service.clone(),    // 注入：会话服务（签发令牌）
+        )) as Arc<dyn OAuthService>;
+        
         // 清单服务：处理清单fork、进度追踪等业务逻辑
         let checklist_service = Arc::new(ChecklistServiceImpl::new(
             checklist_repo.clone(),     // 注入：清单数据访问
             template_repo.clone(),      // 注入：模板数据访问（需要读取模板）
         )) as Arc<dyn ChecklistService>;
+        
+        // 地区服务：提供地区列表
+        let location_service = Arc::new(LocationServiceImpl::new(
+            location_repo.clone(),      // 注入：地区数据访问
+        )) as Arc<dyn LocationService>;
 
         // 返回完整的依赖注入容器
-        Self {
+        AppModule {
             template_service,
             user_service,
+            session_service,
+            credential_service,
+            account_service,
+            avatar_service,
+            oauth_service,
             checklist_service,
+            location_service,
+            jwt_service,
         }
     }
 }
-
@@ -34,7 +34,8 @@
 ///   - `user_service`: 用户注册、登录、资料管理
 ///   - `template_service`: 模板CRUD和搜索
 ///   - `checklist_service`: 清单Fork和进度追踪
-/// - `di`: 依赖注入容器（AppModule）
+///   - `location_service`: 地区列表
+/// - `di`: 依赖注入容器（AppModule）和它的构建器（可替换Repository的实现）
 /// 
 /// ## 依赖注入
 /// 
@@ -43,6 +44,11 @@
 /// let app_module = AppModule::new(pool, config);
 /// let template_service = &app_module.template_service;
 /// ```
+/// 
+/// ## 测试
+/// 
+/// `tests/`中的测试不连接数据库：通过`AppModule::builder`注入`db::memory`中的内存Repository
+/// （`db`的`testing`特性），驱动真实的Service实现。
 
 pub mod services;
 pub mod di;
@@ -51,6 +57,7 @@ pub use services::{
     TemplateService,
     UserService,
     ChecklistService,
+    LocationService,
 };
-pub use di::AppModule;
+pub use di::{AppModule, AppModuleBuilder};
 
@@ -0,0 +1,241 @@
+use async_trait::async_trait;
+use common::{AppResult, AppError};
+use models::{
+    AccountExport, AccountDeletionResponse, DeleteAccountDto, ACCOUNT_EXPORT_VERSION, DELETED_USER_ID,
+};
+use db::{UserRepository, AccountRepository, UserChecklistRepository, UserIdentityRepository};
+use auth::PasswordService;
+use chrono::{Datelike, Duration, Timelike, Utc};
+use serde::Serialize;
+use storage::ObjectStorage;
+use std::io::{Cursor, Write};
+use std::net::IpAddr;
+use std::sync::Arc;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::{LoginThrottleService, SessionService};
+use super::login_throttle_service::verify_current_password;
+
+/// Accounts purged per query by `purge_due_accounts`
+const PURGE_BATCH_SIZE: u64 = 100;
+
+/// Personal data export and account deletion with a grace period
+#[async_trait]
+pub trait AccountService: Send + Sync {
+    async fn export(&self, user_id: Uuid) -> AppResult<AccountExport>;
+    /// The same data as `export`, one JSON file per section in a ZIP archive
+    async fn export_archive(&self, user_id: Uuid) -> AppResult<Vec<u8>>;
+    /// Schedules the account for deletion and signs out every device.
+    /// Signing in again before the grace period ends cancels it. Wrong passwords count towards the login limits
+    async fn request_deletion(&self, user_id: Uuid, dto: DeleteAccountDto, ip: Option<IpAddr>) -> AppResult<AccountDeletionResponse>;
+    /// Permanently deletes accounts whose grace period has ended, then their avatar files; returns how many.
+    /// Files that could not be deleted on an earlier run are retried first
+    async fn purge_due_accounts(&self) -> AppResult<usize>;
+}
+
+pub struct AccountServiceImpl {
+    user_repo: Arc<dyn UserRepository>,
+    account_repo: Arc<dyn AccountRepository>,
+    checklist_repo: Arc<dyn UserChecklistRepository>,
+    user_identity_repo: Arc<dyn UserIdentityRepository>,
+    password_service: Arc<dyn PasswordService>,
+    session_service: Arc<dyn SessionService>,
+    login_throttle: Arc<dyn LoginThrottleService>,
+    storage: Arc<dyn ObjectStorage>,
+    deletion_grace_days: i64,
+}
+
+impl AccountServiceImpl {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_repo: Arc<dyn UserRepository>,
+        account_repo: Arc<dyn AccountRepository>,
+        checklist_repo: Arc<dyn UserChecklistRepository>,
+        user_identity_repo: Arc<dyn UserIdentityRepository>,
+        password_service: Arc<dyn PasswordService>,
+        session_service: Arc<dyn SessionService>,
+        login_throttle: Arc<dyn LoginThrottleService>,
+        storage: Arc<dyn ObjectStorage>,
+        deletion_grace_days: i64,
+    ) -> Self {
+        Self {
+            user_repo,
+            account_repo,
+            checklist_repo,
+            user_identity_repo,
+            password_service,
+            session_service,
+            login_throttle,
+            storage,
+            deletion_grace_days,
+        }
+    }
+
+    /// Deletes the files under `prefix`; on failure they are recorded and retried on the next purge run
+    async fn delete_files(&self, prefix: &str) {
+        let Err(e) = self.storage.delete_prefix(prefix).await else { return };
+        tracing::warn!(prefix, error = %e, "Failed to delete files, will retry");
+
+        if let Err(e) = self.account_repo.record_orphaned_files(prefix).await {
+            tracing::error!(prefix, error = %e, "Failed to record files for a later retry");
+        }
+    }
+
+    async fn retry_orphaned_files(&self) -> AppResult<()> {
+        for file in self.account_repo.find_orphaned_files(PURGE_BATCH_SIZE).await? {
+            match self.storage.delete_prefix(&file.prefix).await {
+                Ok(()) => self.account_repo.delete_orphaned_files(&file.prefix).await?,
+                Err(e) => tracing::warn!(prefix = %file.prefix, error = %e, "Failed to delete orphaned files"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct ExportManifest {
+    format_version: u32,
+    exported_at: chrono::DateTime<Utc>,
+    user_id: Uuid,
+    files: &'static [&'static str],
+}
+
+fn write_json<T: Serialize>(zip: &mut ZipWriter<Cursor<Vec<u8>>>, name: &str, value: &T, options: SimpleFileOptions) -> AppResult<()> {
+    let json = serde_json::to_vec_pretty(value)
+        .map_err(|e| AppError::InternalError(format!("Failed to serialize {}: {}", name, e)))?;
+
+    zip.start_file(name, options)
+        .map_err(|e| AppError::InternalError(format!("Failed to write {}: {}", name, e)))?;
+    zip.write_all(&json)
+        .map_err(|e| AppError::InternalError(format!("Failed to write {}: {}", name, e)))
+}
+
+#[async_trait]
+impl AccountService for AccountServiceImpl {
+    async fn export(&self, user_id: Uuid) -> AppResult<AccountExport> {
+        let user = self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))?;
+
+        Ok(AccountExport {
+            format_version: ACCOUNT_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            identities: self.user_identity_repo.find_by_user(user_id).await?,
+            checklists: self.checklist_repo.find_by_user(user_id).await?,
+            templates: self.account_repo.find_authored_templates(user_id).await?,
+            template_versions: self.account_repo.find_authored_versions(user_id).await?,
+            profile: user.into(),
+        })
+    }
+
+    async fn export_archive(&self, user_id: Uuid) -> AppResult<Vec<u8>> {
+        let export = self.export(user_id).await?;
+
+        // Entries carry the export time instead of the ZIP epoch (1980-01-01)
+        let at = export.exported_at;
+        let mut options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        if let Ok(modified) = zip::DateTime::from_date_and_time(
+            at.year() as u16, at.month() as u8, at.day() as u8,
+            at.hour() as u8, at.minute() as u8, at.second() as u8,
+        ) {
+            options = options.last_modified_time(modified);
+        }
+
+        let manifest = ExportManifest {
+            format_version: export.format_version,
+            exported_at: export.exported_at,
+            user_id,
+            files: &["profile.json", "identities.json", "checklists.json", "templates.json", "template_versions.json"],
+        };
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        write_json(&mut zip, "manifest.json", &manifest, options)?;
+        write_json(&mut zip, "profile.json", &export.profile, options)?;
+        write_json(&mut zip, "identities.json", &export.identities, options)?;
+        write_json(&mut zip, "checklists.json", &export.checklists, options)?;
+        write_json(&mut zip, "templates.json", &export.templates, options)?;
+        write_json(&mut zip, "template_versions.json", &export.template_versions, options)?;
+
+        let archive = zip.finish()
+            .map_err(|e| AppError::InternalError(format!("Failed to finish export archive: {}", e)))?;
+
+        Ok(archive.into_inner())
+    }
+
+    async fn request_deletion(&self, user_id: Uuid, dto: DeleteAccountDto, ip: Option<IpAddr>) -> AppResult<AccountDeletionResponse> {
+        let user = self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .filter(|user| user.id != DELETED_USER_ID)
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))?;
+
+        // A stolen access token alone must not be enough to delete an account with a password
+        if user.has_password() {
+            let password = dto.password.as_deref().unwrap_or_default();
+            let is_valid = verify_current_password(
+                self.login_throttle.as_ref(),
+                self.password_service.as_ref(),
+                &user,
+                password,
+                ip,
+            )
+            .await?;
+            if !is_valid {
+                return Err(AppError::AuthError("Current password is incorrect".to_string()));
+            }
+        }
+
+        // Asking again keeps the original schedule
+        let user = match user.deletion_requested_at {
+            Some(_) => user,
+            None => self.user_repo.set_deletion_requested_at(user_id, Some(Utc::now())).await?,
+        };
+
+        self.session_service.logout_all(user_id).await?;
+
+        let deletion_requested_at = user.deletion_requested_at.unwrap_or_else(Utc::now);
+        tracing::info!(user_id = %user_id, "Account deletion requested");
+
+        Ok(AccountDeletionResponse {
+            deletion_requested_at,
+            purge_after: deletion_requested_at + Duration::days(self.deletion_grace_days),
+        })
+    }
+
+    async fn purge_due_accounts(&self) -> AppResult<usize> {
+        self.retry_orphaned_files().await?;
+
+        let requested_before = Utc::now() - Duration::days(self.deletion_grace_days);
+        let mut purged = 0;
+
+        loop {
+            let due = self.account_repo.find_due_for_purge(requested_before, PURGE_BATCH_SIZE).await?;
+            let batch_size = due.len();
+            let mut progressed = false;
+
+            for user in due {
+                // One failing account must not hold up the rest; it is retried on the next run
+                match self.account_repo.purge(user.id, requested_before).await {
+                    Ok(true) => {
+                        tracing::info!(user_id = %user.id, "Account purged after deletion grace period");
+                        purged += 1;
+                        progressed = true;
+                        // Only now: a sign-in before the purge cancels the deletion and keeps the avatar
+                        self.delete_files(&format!("avatars/{}", user.id)).await;
+                    }
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!(user_id = %user.id, error = %e, "Failed to purge account"),
+                }
+            }
+
+            if !progressed || (batch_size as u64) < PURGE_BATCH_SIZE {
+                return Ok(purged);
+            }
+        }
+    }
+}
+
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use common::{AppResult, AppError};
+use models::{
+    AvatarUploadResponse, AvatarVariant, UpdateProfileDto,
+    AVATAR_SIZES, AVATAR_MAX_BYTES, AVATAR_MAX_DIMENSION, AVATAR_CONTENT_TYPES,
+};
+use db::UserRepository;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader, Limits, Rgb, RgbImage};
+use std::io::Cursor;
+use std::sync::Arc;
+use storage::ObjectStorage;
+use uuid::Uuid;
+
+/// JPEG quality of the stored variants
+const AVATAR_JPEG_QUALITY: u8 = 85;
+
+/// Avatar upload: validates the image, strips its metadata and stores resized copies
+#[async_trait]
+pub trait AvatarService: Send + Sync {
+    /// Stores the image as the user's avatar and removes the previously uploaded one.
+    /// `content_type` is the type declared by the client, checked against the file content
+    async fn upload(&self, user_id: Uuid, data: Vec<u8>, content_type: Option<String>) -> AppResult<AvatarUploadResponse>;
+}
+
+pub struct AvatarServiceImpl {
+    user_repo: Arc<dyn UserRepository>,
+    storage: Arc<dyn ObjectStorage>,
+}
+
+impl AvatarServiceImpl {
+    pub fn new(user_repo: Arc<dyn UserRepository>, storage: Arc<dyn ObjectStorage>) -> Self {
+        Self { user_repo, storage }
+    }
+
+    /// Best effort: a leftover file only costs storage, so failures are logged
+    async fn delete_keys(&self, keys: &[String]) {
+        for key in keys {
+            if let Err(e) = self.storage.delete(key).await {
+                tracing::warn!(key = %key, error = %e, "Failed to delete avatar file");
+            }
+        }
+    }
+
+    /// Keys of an earlier upload by this user, found from the stored avatar URL
+    fn previous_upload_keys(&self, user_id: Uuid, avatar_url: &str) -> Vec<String> {
+        let prefix = format!("avatars/{}/", user_id);
+        let Some(dir) = self.storage
+            .key_from_url(avatar_url)
+            .filter(|key| key.starts_with(&prefix))
+            .and_then(|key| key.rsplit_once('/').map(|(dir, _)| dir.to_string()))
+        else {
+            // Avatars hosted elsewhere (set before uploads existed) are left alone
+            return Vec::new();
+        };
+
+        AVATAR_SIZES.iter().map(|size| variant_key(&dir, *size)).collect()
+    }
+}
+
+fn variant_key(dir: &str, size: u32) -> String {
+    format!("{}/{}.jpg", dir, size)
+}
+
+/// Decodes the upload and renders one JPEG per entry of `AVATAR_SIZES`.
+/// 
+/// Re-encoding from pixels drops every metadata block (EXIF location, camera
+/// serial numbers, embedded thumbnails); the EXIF orientation is applied first
+/// so the result is upright.
+fn render_variants(data: &[u8]) -> AppResult<Vec<(u32, Vec<u8>)>> {
+    let mut reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|e| AppError::InternalError(format!("Failed to read avatar: {}", e)))?;
+
+    if !matches!(reader.format(), Some(ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP)) {
+        return Err(AppError::ValidationError("Avatar must be a JPEG, PNG or WebP image".to_string()));
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(AVATAR_MAX_DIMENSION);
+    limits.max_image_height = Some(AVATAR_MAX_DIMENSION);
+    reader.limits(limits);
+
+    let invalid = |e: ImageError| match e {
+        ImageError::Limits(_) => AppError::ValidationError(format!(
+            "Avatar must be at most {0}x{0} pixels",
+            AVATAR_MAX_DIMENSION
+        )),
+        e => AppError::ValidationError(format!("Invalid image: {}", e)),
+    };
+
+    let mut decoder = reader.into_decoder().map_err(invalid)?;
+    let orientation = decoder.orientation().map_err(invalid)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    image.apply_orientation(orientation);
+
+    // JPEG has no alpha channel: transparent areas become white
+    let rgba = image.to_rgba8();
+    let flattened = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u32 * a as u32 + 255 * (255 - a as u32)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    });
+    let image = DynamicImage::ImageRgb8(flattened);
+
+    AVATAR_SIZES
+        .iter()
+        .map(|&size| {
+            // Square crop around the centre; small images are not upscaled
+            let side = size.min(image.width()).min(image.height());
+            let resized = image.resize_to_fill(side, side, FilterType::Lanczos3).to_rgb8();
+
+            let mut jpeg = Vec::new();
+            JpegEncoder::new_with_quality(&mut jpeg, AVATAR_JPEG_QUALITY)
+                .encode_image(&resized)
+                .map_err(|e| AppError::InternalError(format!("Failed to encode avatar: {}", e)))?;
+
+            Ok((size, jpeg))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl AvatarService for AvatarServiceImpl {
+    async fn upload(&self, user_id: Uuid, data: Vec<u8>, content_type: Option<String>) -> AppResult<AvatarUploadResponse> {
+        if data.len() > AVATAR_MAX_BYTES {
+            return Err(AppError::PayloadTooLarge(format!(
+                "Avatar must be at most {} MiB",
+                AVATAR_MAX_BYTES / 1024 / 1024
+            )));
+        }
+        if let Some(content_type) = content_type.as_deref() {
+            let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+            if !AVATAR_CONTENT_TYPES.contains(&essence.as_str()) {
+                return Err(AppError::ValidationError(format!(
+                    "Unsupported avatar type {}; use one of {}",
+                    content_type,
+                    AVATAR_CONTENT_TYPES.join(", ")
+                )));
+            }
+        }
+
+        let user = self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))?;
+
+        // Decoding and resizing are CPU-bound
+        let variants = tokio::task::spawn_blocking(move || render_variants(&data))
+            .await
+            .map_err(|e| AppError::InternalError(format!("Avatar processing task failed: {}", e)))??;
+
+        // A fresh directory per upload, so cached URLs of the old avatar never show the new one
+        let dir = format!("avatars/{}/{}", user_id, Uuid::new_v4().simple());
+        let mut stored = Vec::new();
+        for (size, jpeg) in variants {
+            let key = variant_key(&dir, size);
+            if let Err(e) = self.storage.put(&key, jpeg, "image/jpeg").await {
+                self.delete_keys(&stored).await;
+                return Err(e);
+            }
+            stored.push(key);
+        }
+
+        let avatar_url = self.storage.public_url(&stored[0]);
+        let dto = UpdateProfileDto {
+            nickname: None,
+            avatar_url: Some(avatar_url.clone()),
+            home_city: None,
+        };
+        if let Err(e) = self.user_repo.update_profile(user_id, dto).await {
+            self.delete_keys(&stored).await;
+            return Err(e);
+        }
+
+        if let Some(previous) = user.avatar_url.as_deref() {
+            self.delete_keys(&self.previous_upload_keys(user_id, previous)).await;
+        }
+
+        tracing::info!(user_id = %user_id, "Avatar uploaded");
+
+        Ok(AvatarUploadResponse {
+            avatar_url,
+            variants: AVATAR_SIZES
+                .iter()
+                .zip(&stored)
+                .map(|(&size, key)| AvatarVariant { size, url: self.storage.public_url(key) })
+                .collect(),
+        })
+    }
+}
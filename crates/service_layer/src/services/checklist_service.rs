@@ -1,16 +1,30 @@
 use async_trait::async_trait;
 use common::{AppResult, AppError};
-use models::{UserChecklistResponse, ForkTemplateDto, UpdateStepDto};
+use models::{
+    UserChecklist, UserChecklistResponse, ForkTemplateDto, UpdateStepDto,
+    ChecklistStep, StepProgress, unfinished_prerequisites,
+    AddChecklistStepDto, UpdateChecklistStepDto, ReorderChecklistStepsDto,
+    ChecklistSyncReport, ChecklistSyncResponse, SyncPlan, plan_sync,
+};
+use validator::Validate;
 use db::{UserChecklistRepository, TemplateRepository};
+use crate::services::template_resolver::resolve_steps;
+use crate::services::ownership::authorize;
 use std::sync::Arc;
 use uuid::Uuid;
 
 #[async_trait]
 pub trait ChecklistService: Send + Sync {
     async fn fork_template(&self, user_id: Uuid, dto: ForkTemplateDto) -> AppResult<UserChecklistResponse>;
-    async fn get_checklist(&self, checklist_id: Uuid) -> AppResult<UserChecklistResponse>;
+    async fn get_checklist(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<UserChecklistResponse>;
     async fn get_user_checklists(&self, user_id: Uuid) -> AppResult<Vec<UserChecklistResponse>>;
-    async fn update_step(&self, checklist_id: Uuid, dto: UpdateStepDto) -> AppResult<UserChecklistResponse>;
+    async fn update_step(&self, checklist_id: Uuid, user_id: Uuid, dto: UpdateStepDto) -> AppResult<UserChecklistResponse>;
+    async fn preview_sync(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<ChecklistSyncReport>;
+    async fn apply_sync(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<ChecklistSyncResponse>;
+    async fn add_custom_step(&self, checklist_id: Uuid, user_id: Uuid, dto: AddChecklistStepDto) -> AppResult<UserChecklistResponse>;
+    async fn edit_step(&self, checklist_id: Uuid, user_id: Uuid, step_id: Uuid, dto: UpdateChecklistStepDto) -> AppResult<UserChecklistResponse>;
+    async fn remove_step(&self, checklist_id: Uuid, user_id: Uuid, step_id: Uuid) -> AppResult<UserChecklistResponse>;
+    async fn reorder_steps(&self, checklist_id: Uuid, user_id: Uuid, dto: ReorderChecklistStepsDto) -> AppResult<UserChecklistResponse>;
 }
 
 pub struct ChecklistServiceImpl {
@@ -28,6 +42,80 @@ impl ChecklistServiceImpl {
             template_repo,
         }
     }
+
+    /// Load a checklist that `user_id` is allowed to read and modify.
+    async fn find_owned(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<UserChecklist> {
+        let checklist = self.checklist_repo.find_by_id(checklist_id).await?;
+
+        authorize(checklist, checklist_id, user_id)
+    }
+
+    /// Persist a modified step list, keeping `order` contiguous and progress in step order
+    async fn save_steps(&self, checklist: UserChecklist, mut steps: Vec<ChecklistStep>) -> AppResult<UserChecklistResponse> {
+        let ids: Vec<Uuid> = steps.iter().map(|s| s.id).collect();
+        for (order, step) in steps.iter_mut().enumerate() {
+            step.order = order as i32;
+            // Dependencies on deleted steps no longer apply
+            step.depends_on.retain(|dep| ids.contains(dep));
+        }
+
+        // Progress of deleted steps is dropped, new steps start as not completed
+        let old_progress = checklist.get_progress()?;
+        let progress = steps
+            .iter()
+            .map(|step| {
+                old_progress
+                    .iter()
+                    .find(|p| p.step_id == step.id)
+                    .cloned()
+                    .unwrap_or(StepProgress {
+                        step_id: step.id,
+                        completed: false,
+                        completed_at: None,
+                    })
+            })
+            .collect();
+
+        let checklist = self.checklist_repo
+            .update_steps(checklist.id, steps, progress)
+            .await?;
+
+        let progress = checklist.calculate_progress()?;
+
+        Ok(UserChecklistResponse {
+            checklist,
+            progress,
+        })
+    }
+
+    /// Three-way merge between the last synced steps, the resolved template and the user's checklist
+    async fn plan_sync(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<(UserChecklist, SyncPlan)> {
+        let checklist = self.find_owned(checklist_id, user_id).await?;
+
+        let template = self.template_repo
+            .find_by_id(checklist.source_template_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Template {} not found", checklist.source_template_id)))?;
+
+        if template.is_deleted() {
+            return Err(AppError::NotFound(format!("Template {} has been deleted", template.id)));
+        }
+
+        // Parent templates can change without bumping this template's version,
+        // so both sides are compared as resolved steps
+        let (current, _) = resolve_steps(self.template_repo.as_ref(), &template).await?;
+
+        let plan = plan_sync(
+            &checklist.get_base_steps()?,
+            &current,
+            &checklist.get_steps()?,
+            &checklist.get_progress()?,
+            checklist.source_template_version,
+            template.version,
+        );
+
+        Ok((checklist, plan))
+    }
 }
 
 #[async_trait]
@@ -39,9 +127,17 @@ impl ChecklistService for ChecklistServiceImpl {
             .await?
             .ok_or_else(|| AppError::NotFound(format!("Template {} not found", dto.template_id)))?;
 
+        // Deleted templates can no longer be forked
+        if template.is_deleted() {
+            return Err(AppError::NotFound(format!("Template {} has been deleted", dto.template_id)));
+        }
+
+        // Snapshot the steps with inheritance applied
+        let (steps, _) = resolve_steps(self.template_repo.as_ref(), &template).await?;
+
         // Create checklist from template
         let checklist = self.checklist_repo
-            .create_from_template(user_id, &template)
+            .create_from_template(user_id, &template, &steps)
             .await?;
 
         // Calculate progress
@@ -53,11 +149,8 @@ impl ChecklistService for ChecklistServiceImpl {
         })
     }
 
-    async fn get_checklist(&self, checklist_id: Uuid) -> AppResult<UserChecklistResponse> {
-        let checklist = self.checklist_repo
-            .find_by_id(checklist_id)
-            .await?
-            .ok_or_else(|| AppError::NotFound(format!("Checklist {} not found", checklist_id)))?;
+    async fn get_checklist(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<UserChecklistResponse> {
+        let checklist = self.find_owned(checklist_id, user_id).await?;
 
         let progress = checklist.calculate_progress()?;
 
@@ -84,9 +177,34 @@ impl ChecklistService for ChecklistServiceImpl {
         responses
     }
 
-    async fn update_step(&self, checklist_id: Uuid, dto: UpdateStepDto) -> AppResult<UserChecklistResponse> {
+    async fn update_step(&self, checklist_id: Uuid, user_id: Uuid, dto: UpdateStepDto) -> AppResult<UserChecklistResponse> {
+        let checklist = self.find_owned(checklist_id, user_id).await?;
+
+        // A step can only be completed once its prerequisites are done, unless forced
+        if dto.completed && !dto.force {
+            let steps = checklist.get_steps()?;
+            let step = steps
+                .iter()
+                .find(|s| s.id == dto.step_id)
+                .ok_or_else(|| AppError::NotFound(format!("Step {} not found", dto.step_id)))?;
+
+            let unfinished = unfinished_prerequisites(&steps, &checklist.get_progress()?, step);
+            if !unfinished.is_empty() {
+                let titles: Vec<&str> = steps
+                    .iter()
+                    .filter(|s| unfinished.contains(&s.id))
+                    .map(|s| s.title.as_str())
+                    .collect();
+                return Err(AppError::ValidationError(format!(
+                    "Step '{}' has unfinished prerequisites: {}",
+                    step.title,
+                    titles.join(", ")
+                )));
+            }
+        }
+
         let checklist = self.checklist_repo
-            .update_step_status(checklist_id, dto.step_index, dto.completed)
+            .update_step_status(checklist_id, dto.step_id, dto.completed)
             .await?;
 
         let progress = checklist.calculate_progress()?;
@@ -96,5 +214,124 @@ impl ChecklistService for ChecklistServiceImpl {
             progress,
         })
     }
+
+    async fn preview_sync(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<ChecklistSyncReport> {
+        let (_, plan) = self.plan_sync(checklist_id, user_id).await?;
+
+        Ok(plan.report)
+    }
+
+    async fn apply_sync(&self, checklist_id: Uuid, user_id: Uuid) -> AppResult<ChecklistSyncResponse> {
+        let (checklist, plan) = self.plan_sync(checklist_id, user_id).await?;
+
+        // Nothing to write when already on the latest version
+        let checklist = if plan.report.up_to_date {
+            checklist
+        } else {
+            self.checklist_repo
+                .apply_sync(checklist_id, plan.report.to_version, plan.base_steps, plan.steps, plan.progress)
+                .await?
+        };
+
+        let progress = checklist.calculate_progress()?;
+
+        Ok(ChecklistSyncResponse {
+            report: plan.report,
+            checklist: UserChecklistResponse {
+                checklist,
+                progress,
+            },
+        })
+    }
+
+    async fn add_custom_step(&self, checklist_id: Uuid, user_id: Uuid, dto: AddChecklistStepDto) -> AppResult<UserChecklistResponse> {
+        dto.validate()?;
+
+        let checklist = self.find_owned(checklist_id, user_id).await?;
+        let mut steps = sorted_steps(&checklist)?;
+
+        // Out-of-range or missing positions append to the end
+        let position = dto.position
+            .filter(|p| *p >= 0)
+            .map(|p| (p as usize).min(steps.len()))
+            .unwrap_or(steps.len());
+
+        steps.insert(position, ChecklistStep {
+            id: Uuid::new_v4(),
+            title: dto.title,
+            description: dto.description,
+            order: position as i32,
+            is_custom: true,
+            hidden: false,
+            depends_on: Vec::new(),
+        });
+
+        self.save_steps(checklist, steps).await
+    }
+
+    async fn edit_step(&self, checklist_id: Uuid, user_id: Uuid, step_id: Uuid, dto: UpdateChecklistStepDto) -> AppResult<UserChecklistResponse> {
+        dto.validate()?;
+
+        let checklist = self.find_owned(checklist_id, user_id).await?;
+        let mut steps = sorted_steps(&checklist)?;
+
+        let step = steps
+            .iter_mut()
+            .find(|s| s.id == step_id)
+            .ok_or_else(|| AppError::NotFound(format!("Step {} not found", step_id)))?;
+
+        if let Some(title) = dto.title {
+            step.title = title;
+        }
+        if let Some(description) = dto.description {
+            step.description = Some(description);
+        }
+        if let Some(hidden) = dto.hidden {
+            step.hidden = hidden;
+        }
+
+        self.save_steps(checklist, steps).await
+    }
+
+    async fn remove_step(&self, checklist_id: Uuid, user_id: Uuid, step_id: Uuid) -> AppResult<UserChecklistResponse> {
+        let checklist = self.find_owned(checklist_id, user_id).await?;
+        let mut steps = sorted_steps(&checklist)?;
+
+        let before = steps.len();
+        steps.retain(|s| s.id != step_id);
+        if steps.len() == before {
+            return Err(AppError::NotFound(format!("Step {} not found", step_id)));
+        }
+
+        self.save_steps(checklist, steps).await
+    }
+
+    async fn reorder_steps(&self, checklist_id: Uuid, user_id: Uuid, dto: ReorderChecklistStepsDto) -> AppResult<UserChecklistResponse> {
+        let checklist = self.find_owned(checklist_id, user_id).await?;
+        let steps = sorted_steps(&checklist)?;
+
+        // The new order must be a permutation of the existing steps
+        let mut seen = std::collections::HashSet::new();
+        let is_permutation = dto.step_ids.len() == steps.len()
+            && dto.step_ids.iter().all(|id| seen.insert(*id) && steps.iter().any(|s| s.id == *id));
+        if !is_permutation {
+            return Err(AppError::ValidationError(
+                "step_ids must list every step of the checklist exactly once".to_string(),
+            ));
+        }
+
+        let reordered = dto.step_ids
+            .iter()
+            .filter_map(|id| steps.iter().find(|s| s.id == *id).cloned())
+            .collect();
+
+        self.save_steps(checklist, reordered).await
+    }
 }
 
+/// The checklist's steps in display order
+fn sorted_steps(checklist: &UserChecklist) -> AppResult<Vec<ChecklistStep>> {
+    let mut steps = checklist.get_steps()?;
+    steps.sort_by_key(|s| s.order);
+    Ok(steps)
+}
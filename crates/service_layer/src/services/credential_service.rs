@@ -0,0 +1,186 @@
+use async_trait::async_trait;
+use common::{AppResult, AppError};
+use models::{
+    AuthResponse, ChangePasswordDto, ForgotPasswordDto, ResetPasswordDto,
+    RESET_TOKEN_TTL_SECONDS, RESET_REQUEST_INTERVAL_SECONDS,
+};
+use db::{UserRepository, PasswordResetTokenRepository};
+use auth::{PasswordService, Mailer, Email, generate_refresh_token, hash_refresh_token};
+use chrono::{Duration, Utc};
+use std::net::IpAddr;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{LoginThrottleService, SessionService};
+use super::login_throttle_service::verify_current_password;
+
+/// Password change and email-based password reset
+#[async_trait]
+pub trait CredentialService: Send + Sync {
+    /// Changes the password and signs out every other device; returns tokens for this one.
+    /// Wrong current passwords count towards the login limits
+    async fn change_password(&self, user_id: Uuid, dto: ChangePasswordDto, user_agent: Option<String>, ip: Option<IpAddr>) -> AppResult<AuthResponse>;
+    /// Emails a reset link in the background; returns at once whether or not the email is registered
+    async fn request_password_reset(&self, dto: ForgotPasswordDto) -> AppResult<()>;
+    async fn reset_password(&self, dto: ResetPasswordDto) -> AppResult<()>;
+}
+
+#[derive(Clone)]
+pub struct CredentialServiceImpl {
+    user_repo: Arc<dyn UserRepository>,
+    password_reset_repo: Arc<dyn PasswordResetTokenRepository>,
+    password_service: Arc<dyn PasswordService>,
+    mailer: Arc<dyn Mailer>,
+    session_service: Arc<dyn SessionService>,
+    login_throttle: Arc<dyn LoginThrottleService>,
+    /// Reset page the emailed link points to
+    password_reset_url: String,
+}
+
+impl CredentialServiceImpl {
+    pub fn new(
+        user_repo: Arc<dyn UserRepository>,
+        password_reset_repo: Arc<dyn PasswordResetTokenRepository>,
+        password_service: Arc<dyn PasswordService>,
+        mailer: Arc<dyn Mailer>,
+        session_service: Arc<dyn SessionService>,
+        login_throttle: Arc<dyn LoginThrottleService>,
+        password_reset_url: String,
+    ) -> Self {
+        Self {
+            user_repo,
+            password_reset_repo,
+            password_service,
+            mailer,
+            session_service,
+            login_throttle,
+            password_reset_url,
+        }
+    }
+
+    fn reset_email(&self, to: String, nickname: &str, token: &str) -> Email {
+        let separator = if self.password_reset_url.contains('?') { '&' } else { '?' };
+        let link = format!("{}{}token={}", self.password_reset_url, separator, token);
+
+        Email {
+            to,
+            subject: "重置阅历进度条密码".to_string(),
+            body: format!(
+                "{}，你好：\n\n\
+                 我们收到了重置你的阅历进度条账号密码的请求。请在{}分钟内打开下面的链接设置新密码：\n\n\
+                 {}\n\n\
+                 链接只能使用一次。如果这不是你本人的操作，请忽略这封邮件，你的密码不会改变。\n",
+                nickname,
+                RESET_TOKEN_TTL_SECONDS / 60,
+                link,
+            ),
+        }
+    }
+
+    /// The part of `request_password_reset` that runs in the background
+    async fn send_reset_link(&self, dto: ForgotPasswordDto) -> AppResult<()> {
+        let Some(user) = self.user_repo.find_by_email(&dto.email).await? else {
+            tracing::debug!(email = %dto.email, "Password reset requested for unknown email");
+            return Ok(());
+        };
+
+        // Repeated requests within the interval send nothing new
+        if let Some(latest) = self.password_reset_repo.find_latest(user.id).await? {
+            if latest.created_at + Duration::seconds(RESET_REQUEST_INTERVAL_SECONDS) > Utc::now() {
+                return Ok(());
+            }
+        }
+
+        // Same opaque-token scheme as refresh tokens: only the hash is stored
+        let token = generate_refresh_token();
+        self.password_reset_repo
+            .create(
+                user.id,
+                hash_refresh_token(&token),
+                Utc::now() + Duration::seconds(RESET_TOKEN_TTL_SECONDS),
+            )
+            .await?;
+
+        let email = self.reset_email(dto.email, &user.nickname, &token);
+        if let Err(e) = self.mailer.send(email).await {
+            tracing::error!(user_id = %user.id, error = %e, "Failed to send password reset email");
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CredentialService for CredentialServiceImpl {
+    async fn change_password(&self, user_id: Uuid, dto: ChangePasswordDto, user_agent: Option<String>, ip: Option<IpAddr>) -> AppResult<AuthResponse> {
+        dto.validate()?;
+
+        let user = self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))?;
+
+        // Accounts without a password set one through the reset email instead
+        let is_valid = user.has_password()
+            && verify_current_password(
+                self.login_throttle.as_ref(),
+                self.password_service.as_ref(),
+                &user,
+                &dto.current_password,
+                ip,
+            )
+            .await?;
+        if !is_valid {
+            return Err(AppError::AuthError("Current password is incorrect".to_string()));
+        }
+
+        let password_hash = self.password_service.hash_password(&dto.new_password)?;
+        let user = self.user_repo.update_password(user_id, password_hash).await?;
+
+        // Whoever knew the old password may be signed in elsewhere; this
+        // device gets a fresh session in place of the revoked one
+        self.session_service.logout_all(user_id).await?;
+        self.session_service.start(user, user_agent).await
+    }
+
+    async fn request_password_reset(&self, dto: ForgotPasswordDto) -> AppResult<()> {
+        dto.validate()?;
+
+        // Same response for unknown emails, so the endpoint can't be used to find accounts.
+        // That includes the response time: the lookup, the token and the mail server round trip
+        // only happen for registered emails, so all of it runs after the response is sent
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = service.send_reset_link(dto).await {
+                tracing::error!(error = %e, "Failed to create a password reset link");
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn reset_password(&self, dto: ResetPasswordDto) -> AppResult<()> {
+        dto.validate()?;
+
+        let invalid = || AppError::AuthError("Invalid or expired reset token".to_string());
+
+        let token = self.password_reset_repo
+            .find_by_hash(&hash_refresh_token(&dto.token))
+            .await?
+            .filter(|t| t.is_usable())
+            .ok_or_else(invalid)?;
+
+        // Lost the race against another reset with the same token.
+        // Any other link still in the user's inbox stops working as well
+        if !self.password_reset_repo.consume(token.id, token.user_id).await? {
+            return Err(invalid());
+        }
+
+        let password_hash = self.password_service.hash_password(&dto.new_password)?;
+        self.user_repo.update_password(token.user_id, password_hash).await?;
+
+        // Sign out everywhere: the reset may be recovering a compromised account
+        self.session_service.logout_all(token.user_id).await
+    }
+}
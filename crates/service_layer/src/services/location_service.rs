@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use common::AppResult;
+use models::{Location, LocationQuery};
+use db::LocationRepository;
+use std::sync::Arc;
+
+#[async_trait]
+pub trait LocationService: Send + Sync {
+    async fn list_locations(&self, query: LocationQuery) -> AppResult<Vec<Location>>;
+}
+
+pub struct LocationServiceImpl {
+    location_repo: Arc<dyn LocationRepository>,
+}
+
+impl LocationServiceImpl {
+    pub fn new(location_repo: Arc<dyn LocationRepository>) -> Self {
+        Self { location_repo }
+    }
+}
+
+#[async_trait]
+impl LocationService for LocationServiceImpl {
+    async fn list_locations(&self, query: LocationQuery) -> AppResult<Vec<Location>> {
+        self.location_repo.list(query).await
+    }
+}
@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use common::{AppResult, AppError};
+use models::{LoginScope, User, LOGIN_LOCKOUT_SECONDS};
+use db::LoginAttemptRepository;
+use auth::PasswordService;
+use chrono::{Duration, Utc};
+use std::net::IpAddr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Brute-force protection for password sign-in: failed attempts are counted
+/// per account and per client IP, with exponential backoff and a temporary lockout
+#[async_trait]
+pub trait LoginThrottleService: Send + Sync {
+    /// Fails with `TooManyRequests` while the account or IP has to wait
+    async fn check(&self, account: &str, ip: Option<IpAddr>) -> AppResult<()>;
+    /// `user_id` is `None` when the account doesn't exist; it is counted all the same
+    async fn record_failure(&self, account: &str, user_id: Option<Uuid>, ip: Option<IpAddr>) -> AppResult<()>;
+    async fn record_success(&self, account: &str) -> AppResult<()>;
+}
+
+pub struct LoginThrottleServiceImpl {
+    login_attempt_repo: Arc<dyn LoginAttemptRepository>,
+}
+
+impl LoginThrottleServiceImpl {
+    pub fn new(login_attempt_repo: Arc<dyn LoginAttemptRepository>) -> Self {
+        Self { login_attempt_repo }
+    }
+}
+
+/// Emails are case-insensitive, so `A@b.cn` and `a@b.cn` share one counter
+fn account_key(account: &str) -> String {
+    account.trim().to_lowercase()
+}
+
+fn subjects(account: &str, ip: Option<IpAddr>) -> Vec<(LoginScope, String)> {
+    let mut subjects = vec![(LoginScope::Account, account_key(account))];
+    if let Some(ip) = ip {
+        subjects.push((LoginScope::Ip, ip.to_string()));
+    }
+    subjects
+}
+
+/// Re-check the password of a signed-in user before changing the password or deleting the account.
+/// 
+/// Counted against the same per-account limits as login, for every email and phone the user signs
+/// in with, so a stolen access token can't be used to guess the password either.
+pub(crate) async fn verify_current_password(
+    throttle: &dyn LoginThrottleService,
+    password_service: &dyn PasswordService,
+    user: &User,
+    password: &str,
+    ip: Option<IpAddr>,
+) -> AppResult<bool> {
+    let accounts: Vec<&str> = [user.email.as_deref(), user.phone.as_deref()].into_iter().flatten().collect();
+
+    for account in &accounts {
+        throttle.check(account, ip).await?;
+    }
+
+    let is_valid = password_service.verify_password(password, &user.password_hash)?;
+
+    for account in &accounts {
+        if is_valid {
+            throttle.record_success(account).await?;
+        } else {
+            throttle.record_failure(account, Some(user.id), ip).await?;
+        }
+    }
+
+    Ok(is_valid)
+}
+
+#[async_trait]
+impl LoginThrottleService for LoginThrottleServiceImpl {
+    async fn check(&self, account: &str, ip: Option<IpAddr>) -> AppResult<()> {
+        let now = Utc::now();
+        let mut wait = None;
+
+        for (scope, subject) in subjects(account, ip) {
+            if let Some(attempt) = self.login_attempt_repo.find(scope, &subject).await? {
+                wait = wait.max(attempt.retry_after(now));
+            }
+        }
+
+        match wait {
+            Some(seconds) => Err(AppError::TooManyRequests(format!(
+                "Too many failed login attempts, retry in {} seconds",
+                seconds
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    async fn record_failure(&self, account: &str, user_id: Option<Uuid>, ip: Option<IpAddr>) -> AppResult<()> {
+        let ip_string = ip.map(|ip| ip.to_string());
+
+        for (scope, subject) in subjects(account, ip) {
+            let attempt = self.login_attempt_repo.record_failure(scope, &subject).await?;
+            if !attempt.should_lock() {
+                continue;
+            }
+
+            let locked_until = Utc::now() + Duration::seconds(LOGIN_LOCKOUT_SECONDS);
+            let user_id = if scope == LoginScope::Account { user_id } else { None };
+            let lockout = self.login_attempt_repo
+                .lock(&attempt, locked_until, user_id, ip_string.as_deref())
+                .await?;
+
+            if let Some(lockout) = lockout {
+                tracing::warn!(
+                    scope = ?lockout.scope,
+                    subject = %lockout.subject,
+                    user_id = ?lockout.user_id,
+                    ip = ?lockout.ip,
+                    failures = lockout.failures,
+                    locked_until = %lockout.locked_until,
+                    "Login locked after repeated failures"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn record_success(&self, account: &str) -> AppResult<()> {
+        // The IP counter stays: one valid account must not unlock guessing at others
+        self.login_attempt_repo.reset(LoginScope::Account, &account_key(account)).await
+    }
+}
@@ -1,8 +1,24 @@
 mod template_service;
 mod user_service;
+mod session_service;
+mod login_throttle_service;
+mod credential_service;
+mod account_service;
+mod avatar_service;
+mod oauth_service;
 mod checklist_service;
+mod location_service;
+mod template_resolver;
+mod ownership;
 
 pub use template_service::{TemplateService, TemplateServiceImpl};
 pub use user_service::{UserService, UserServiceImpl};
+pub use session_service::{SessionService, SessionServiceImpl};
+pub use login_throttle_service::{LoginThrottleService, LoginThrottleServiceImpl};
+pub use credential_service::{CredentialService, CredentialServiceImpl};
+pub use account_service::{AccountService, AccountServiceImpl};
+pub use avatar_service::{AvatarService, AvatarServiceImpl};
+pub use oauth_service::{OAuthService, OAuthServiceImpl};
 pub use checklist_service::{ChecklistService, ChecklistServiceImpl};
+pub use location_service::{LocationService, LocationServiceImpl};
 
@@ -0,0 +1,221 @@
+use async_trait::async_trait;
+use common::{AppResult, AppError};
+use models::{
+    User, UserIdentity, RegisterDto, AuthResponse, AuthorizationUrlResponse, OAuthCallbackDto,
+    OAUTH_STATE_TTL_SECONDS, UNUSABLE_PASSWORD_HASH,
+};
+use db::{UserRepository, UserIdentityRepository, OAuthStateRepository};
+use auth::{
+    OidcProvider, ExternalIdentity,
+    generate_oauth_state, generate_pkce_verifier, pkce_challenge,
+};
+use chrono::{Duration, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::SessionService;
+
+/// Maximum nickname length (matches `RegisterDto`)
+const MAX_NICKNAME_CHARS: usize = 50;
+
+/// Third-party (OIDC) sign-in and account linking
+#[async_trait]
+pub trait OAuthService: Send + Sync {
+    /// Names of the configured providers
+    fn providers(&self) -> Vec<String>;
+    /// Starts an authorization request; `link_user_id` is set when a signed-in user links an account
+    async fn authorize(&self, provider: &str, link_user_id: Option<Uuid>) -> AppResult<AuthorizationUrlResponse>;
+    async fn login(&self, provider: &str, dto: OAuthCallbackDto, user_agent: Option<String>) -> AppResult<AuthResponse>;
+    async fn link(&self, provider: &str, dto: OAuthCallbackDto, user_id: Uuid) -> AppResult<Vec<UserIdentity>>;
+    async fn list_identities(&self, user_id: Uuid) -> AppResult<Vec<UserIdentity>>;
+    async fn unlink(&self, provider: &str, user_id: Uuid) -> AppResult<()>;
+}
+
+pub struct OAuthServiceImpl {
+    providers: BTreeMap<String, Arc<dyn OidcProvider>>,
+    oauth_state_repo: Arc<dyn OAuthStateRepository>,
+    user_identity_repo: Arc<dyn UserIdentityRepository>,
+    user_repo: Arc<dyn UserRepository>,
+    session_service: Arc<dyn SessionService>,
+}
+
+impl OAuthServiceImpl {
+    pub fn new(
+        providers: Vec<Arc<dyn OidcProvider>>,
+        oauth_state_repo: Arc<dyn OAuthStateRepository>,
+        user_identity_repo: Arc<dyn UserIdentityRepository>,
+        user_repo: Arc<dyn UserRepository>,
+        session_service: Arc<dyn SessionService>,
+    ) -> Self {
+        Self {
+            providers: providers.into_iter().map(|p| (p.name().to_string(), p)).collect(),
+            oauth_state_repo,
+            user_identity_repo,
+            user_repo,
+            session_service,
+        }
+    }
+
+    fn provider(&self, name: &str) -> AppResult<&Arc<dyn OidcProvider>> {
+        self.providers
+            .get(name)
+            .ok_or_else(|| AppError::NotFound(format!("Unknown login provider {}", name)))
+    }
+
+    /// Redeems the state and exchanges the code; `user_id` is whoever started the flow
+    async fn complete(&self, provider: &str, dto: OAuthCallbackDto, user_id: Option<Uuid>) -> AppResult<ExternalIdentity> {
+        dto.validate()?;
+
+        let provider = self.provider(provider)?;
+        let invalid = || AppError::AuthError("Invalid or expired authorization state".to_string());
+
+        // Taking the state deletes it, so a callback can't be replayed
+        let state = self.oauth_state_repo
+            .take(&dto.state)
+            .await?
+            .ok_or_else(invalid)?;
+
+        // A state only completes the flow it was issued for: same provider,
+        // and a login state can't link accounts (or the other way round)
+        if state.is_expired() || state.provider != provider.name() || state.user_id != user_id {
+            return Err(invalid());
+        }
+
+        provider.exchange_code(&dto.code, &state.code_verifier, &state.nonce).await
+    }
+
+    /// First sign-in with an unknown external account creates a user
+    async fn register(&self, provider: &str, identity: ExternalIdentity) -> AppResult<User> {
+        // Users need a phone or an email, and an unverified email can't become a login
+        let email = identity.email
+            .filter(|_| identity.email_verified)
+            .ok_or_else(|| AppError::ValidationError(
+                "Login provider did not return a verified email address".to_string(),
+            ))?;
+
+        // Never merge into an existing account by email: the owner has to sign in and link
+        if self.user_repo.find_by_email(&email).await?.is_some() {
+            return Err(AppError::Conflict(
+                "Email already registered, sign in and link this account from your profile".to_string(),
+            ));
+        }
+
+        let nickname = identity.name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| email.split('@').next().unwrap_or_default())
+            .chars()
+            .take(MAX_NICKNAME_CHARS)
+            .collect();
+
+        let register = RegisterDto {
+            phone: None,
+            email: Some(email),
+            password: String::new(),
+            nickname,
+        };
+
+        let (user, _) = self.user_identity_repo
+            .create_with_user(register, UNUSABLE_PASSWORD_HASH.to_string(), provider, &identity.subject)
+            .await?;
+
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl OAuthService for OAuthServiceImpl {
+    fn providers(&self) -> Vec<String> {
+        self.providers.keys().cloned().collect()
+    }
+
+    async fn authorize(&self, provider: &str, link_user_id: Option<Uuid>) -> AppResult<AuthorizationUrlResponse> {
+        let provider = self.provider(provider)?;
+
+        let state = generate_oauth_state();
+        let nonce = generate_oauth_state();
+        let code_verifier = generate_pkce_verifier();
+
+        let authorization_url = provider
+            .authorization_url(&state, &nonce, &pkce_challenge(&code_verifier))
+            .await?;
+
+        self.oauth_state_repo
+            .create(
+                &state,
+                provider.name(),
+                &code_verifier,
+                &nonce,
+                link_user_id,
+                Utc::now() + Duration::seconds(OAUTH_STATE_TTL_SECONDS),
+            )
+            .await?;
+
+        Ok(AuthorizationUrlResponse {
+            authorization_url,
+            state,
+            expires_in: OAUTH_STATE_TTL_SECONDS,
+        })
+    }
+
+    async fn login(&self, provider: &str, dto: OAuthCallbackDto, user_agent: Option<String>) -> AppResult<AuthResponse> {
+        let identity = self.complete(provider, dto, None).await?;
+
+        let user = match self.user_identity_repo.find_by_subject(provider, &identity.subject).await? {
+            Some(linked) => self.user_repo
+                .find_by_id(linked.user_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("User {} not found", linked.user_id)))?,
+            None => self.register(provider, identity).await?,
+        };
+
+        self.session_service.start(user, user_agent).await
+    }
+
+    async fn link(&self, provider: &str, dto: OAuthCallbackDto, user_id: Uuid) -> AppResult<Vec<UserIdentity>> {
+        let identity = self.complete(provider, dto, Some(user_id)).await?;
+
+        match self.user_identity_repo.find_by_subject(provider, &identity.subject).await? {
+            // Linking the same account twice is a no-op
+            Some(linked) if linked.user_id == user_id => return self.list_identities(user_id).await,
+            Some(_) => {
+                return Err(AppError::Conflict(format!(
+                    "This {} account is linked to another user",
+                    provider
+                )));
+            }
+            None => {}
+        }
+
+        let identities = self.user_identity_repo.find_by_user(user_id).await?;
+        if identities.iter().any(|i| i.provider == provider) {
+            return Err(AppError::Conflict(format!(
+                "A {} account is already linked, unlink it first",
+                provider
+            )));
+        }
+
+        self.user_identity_repo
+            .create(user_id, provider, &identity.subject, identity.email)
+            .await?;
+
+        self.list_identities(user_id).await
+    }
+
+    async fn list_identities(&self, user_id: Uuid) -> AppResult<Vec<UserIdentity>> {
+        self.user_identity_repo.find_by_user(user_id).await
+    }
+
+    async fn unlink(&self, provider: &str, user_id: Uuid) -> AppResult<()> {
+        // Accounts created through a provider have no password, but they always
+        // have a verified email and can set one through the password reset email
+        if !self.user_identity_repo.delete(user_id, provider).await? {
+            return Err(AppError::NotFound(format!("No {} account linked", provider)));
+        }
+
+        Ok(())
+    }
+}
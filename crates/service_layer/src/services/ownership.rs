@@ -0,0 +1,17 @@
+use common::{AppResult, AppError};
+use models::Owned;
+use uuid::Uuid;
+
+/// Check that a loaded resource exists and belongs to `user_id`.
+/// 
+/// A missing resource is `NotFound`, someone else's resource is `Forbidden`.
+pub(crate) fn authorize<T: Owned>(resource: Option<T>, id: Uuid, user_id: Uuid) -> AppResult<T> {
+    let resource = resource
+        .ok_or_else(|| AppError::NotFound(format!("{} {} not found", T::KIND, id)))?;
+
+    if !resource.is_owned_by(user_id) {
+        return Err(AppError::Forbidden(format!("Only the owner can access this {}", T::KIND)));
+    }
+
+    Ok(resource)
+}
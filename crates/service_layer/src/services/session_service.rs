@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use common::{AppResult, AppError};
+use models::{User, AuthResponse, RefreshToken, RefreshTokenDto};
+use db::{UserRepository, RefreshTokenRepository};
+use auth::{JwtService, generate_refresh_token, hash_refresh_token};
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Access/refresh token pairs, shared by every way of signing in
+#[async_trait]
+pub trait SessionService: Send + Sync {
+    /// Signs the user in on a new device (a new refresh token family).
+    /// Also cancels a pending account deletion
+    async fn start(&self, user: User, user_agent: Option<String>) -> AppResult<AuthResponse>;
+    async fn refresh(&self, dto: RefreshTokenDto, user_agent: Option<String>) -> AppResult<AuthResponse>;
+    async fn logout(&self, dto: RefreshTokenDto) -> AppResult<()>;
+    async fn logout_all(&self, user_id: Uuid) -> AppResult<()>;
+}
+
+pub struct SessionServiceImpl {
+    refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+    user_repo: Arc<dyn UserRepository>,
+    jwt_service: Arc<dyn JwtService>,
+    /// Refresh token lifetime in seconds
+    refresh_expiration: i64,
+}
+
+impl SessionServiceImpl {
+    pub fn new(
+        refresh_token_repo: Arc<dyn RefreshTokenRepository>,
+        user_repo: Arc<dyn UserRepository>,
+        jwt_service: Arc<dyn JwtService>,
+        refresh_expiration: i64,
+    ) -> Self {
+        Self {
+            refresh_token_repo,
+            user_repo,
+            jwt_service,
+            refresh_expiration,
+        }
+    }
+
+    fn refresh_expires_at(&self) -> DateTime<Utc> {
+        Utc::now() + Duration::seconds(self.refresh_expiration)
+    }
+
+    fn auth_response(&self, user: User, refresh_token: String) -> AppResult<AuthResponse> {
+        let token = self.jwt_service.generate_token(&user)?;
+
+        Ok(AuthResponse {
+            user: user.into(),
+            token,
+            expires_in: self.jwt_service.expiration(),
+            refresh_token,
+        })
+    }
+
+    /// A revoked token coming back means it was copied: whoever holds the
+    /// live successor may be the attacker, so the whole family goes
+    async fn reject_reused(&self, token: &RefreshToken) -> AppError {
+        tracing::warn!(
+            user_id = %token.user_id,
+            family_id = %token.family_id,
+            "Revoked refresh token reused, revoking its family"
+        );
+
+        if let Err(e) = self.refresh_token_repo.revoke_family(token.family_id).await {
+            return e;
+        }
+
+        AppError::AuthError("Refresh token has been revoked".to_string())
+    }
+}
+
+#[async_trait]
+impl SessionService for SessionServiceImpl {
+    async fn start(&self, user: User, user_agent: Option<String>) -> AppResult<AuthResponse> {
+        // Signing in during the deletion grace period keeps the account
+        let user = if user.deletion_requested_at.is_some() {
+            tracing::info!(user_id = %user.id, "Account deletion cancelled by sign-in");
+            self.user_repo.set_deletion_requested_at(user.id, None).await?
+        } else {
+            user
+        };
+
+        let refresh_token = generate_refresh_token();
+        self.refresh_token_repo
+            .create(
+                user.id,
+                Uuid::new_v4(),
+                hash_refresh_token(&refresh_token),
+                user_agent,
+                self.refresh_expires_at(),
+            )
+            .await?;
+
+        self.auth_response(user, refresh_token)
+    }
+
+    async fn refresh(&self, dto: RefreshTokenDto, user_agent: Option<String>) -> AppResult<AuthResponse> {
+        dto.validate()?;
+
+        let stored = self.refresh_token_repo
+            .find_by_hash(&hash_refresh_token(&dto.refresh_token))
+            .await?
+            .ok_or_else(|| AppError::AuthError("Invalid refresh token".to_string()))?;
+
+        if stored.revoked_at.is_some() {
+            return Err(self.reject_reused(&stored).await);
+        }
+
+        if stored.is_expired() {
+            return Err(AppError::AuthError("Refresh token expired".to_string()));
+        }
+
+        // Reload the user so role changes take effect on refresh
+        let user = self.user_repo
+            .find_by_id(stored.user_id)
+            .await?
+            .ok_or_else(|| AppError::AuthError("Invalid refresh token".to_string()))?;
+
+        let refresh_token = generate_refresh_token();
+        let rotated = self.refresh_token_repo
+            .rotate(
+                stored.id,
+                hash_refresh_token(&refresh_token),
+                user_agent,
+                self.refresh_expires_at(),
+            )
+            .await?;
+
+        // Lost the race against another refresh with the same token
+        if rotated.is_none() {
+            return Err(self.reject_reused(&stored).await);
+        }
+
+        self.auth_response(user, refresh_token)
+    }
+
+    async fn logout(&self, dto: RefreshTokenDto) -> AppResult<()> {
+        dto.validate()?;
+
+        let stored = self.refresh_token_repo
+            .find_by_hash(&hash_refresh_token(&dto.refresh_token))
+            .await?
+            .ok_or_else(|| AppError::AuthError("Invalid refresh token".to_string()))?;
+
+        // Signs out the device: every token rotated from the same login
+        self.refresh_token_repo.revoke_family(stored.family_id).await?;
+
+        Ok(())
+    }
+
+    async fn logout_all(&self, user_id: Uuid) -> AppResult<()> {
+        self.refresh_token_repo.revoke_all_for_user(user_id).await?;
+
+        Ok(())
+    }
+}
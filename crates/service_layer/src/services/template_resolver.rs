@@ -0,0 +1,60 @@
+use common::{AppResult, AppError};
+use models::{Template, TemplateStep, apply_layer, MAX_INHERITANCE_DEPTH};
+use db::TemplateRepository;
+use uuid::Uuid;
+
+/// Effective steps inherited from `parent_id` and its ancestors, plus the ancestor ids
+/// (direct parent first).
+/// 
+/// `child_id` is the template that will sit below `parent_id`; meeting it while walking
+/// up the chain means the inheritance would form a cycle.
+pub(crate) async fn inherited_steps(
+    repo: &dyn TemplateRepository,
+    parent_id: Option<Uuid>,
+    child_id: Option<Uuid>,
+) -> AppResult<(Vec<TemplateStep>, Vec<Uuid>)> {
+    let mut chain: Vec<Template> = Vec::new();
+    let mut next = parent_id;
+
+    while let Some(id) = next {
+        if Some(id) == child_id || chain.iter().any(|t| t.id == id) {
+            return Err(AppError::ValidationError(format!(
+                "Template inheritance cycle detected at template {}",
+                id
+            )));
+        }
+        if chain.len() >= MAX_INHERITANCE_DEPTH {
+            return Err(AppError::ValidationError(format!(
+                "Template inheritance is deeper than {} levels",
+                MAX_INHERITANCE_DEPTH
+            )));
+        }
+
+        let parent = repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Parent template {} not found", id)))?;
+
+        next = parent.parent_id;
+        chain.push(parent);
+    }
+
+    // Apply layers from the root down
+    let mut steps = Vec::new();
+    for layer in chain.iter().rev() {
+        steps = apply_layer(&steps, &layer.get_steps()?).map_err(AppError::ValidationError)?;
+    }
+
+    Ok((steps, chain.iter().map(|t| t.id).collect()))
+}
+
+/// Effective steps of `template` after walking its whole parent chain.
+pub(crate) async fn resolve_steps(
+    repo: &dyn TemplateRepository,
+    template: &Template,
+) -> AppResult<(Vec<TemplateStep>, Vec<Uuid>)> {
+    let (inherited, ancestors) = inherited_steps(repo, template.parent_id, Some(template.id)).await?;
+    let steps = apply_layer(&inherited, &template.get_steps()?).map_err(AppError::ValidationError)?;
+
+    Ok((steps, ancestors))
+}
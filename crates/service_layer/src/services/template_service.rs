@@ -1,39 +1,176 @@
 use async_trait::async_trait;
 use common::{AppResult, AppError};
-use models::{Template, CreateTemplateDto, TemplateSearchQuery};
-use db::TemplateRepository;
+use models::{
+    Template, TemplateStep, TemplateVersion, CreateTemplateDto, UpdateTemplateDto, TemplateSearchQuery,
+    ResolvedTemplate, TemplateSearchResult, Actor, UserRole,
+    apply_layer, validate_dependencies, parse_keywords, highlight,
+};
+use db::{TemplateRepository, LocationRepository, UserRepository};
+use crate::services::template_resolver::{inherited_steps, resolve_steps};
+use crate::services::ownership::authorize;
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
 #[async_trait]
 pub trait TemplateService: Send + Sync {
-    async fn create_template(&self, dto: CreateTemplateDto, created_by: Uuid) -> AppResult<Template>;
+    async fn create_template(&self, dto: CreateTemplateDto, actor: &Actor) -> AppResult<Template>;
     async fn get_template(&self, id: Uuid) -> AppResult<Template>;
-    async fn search_templates(&self, query: TemplateSearchQuery) -> AppResult<Vec<Template>>;
+    async fn get_resolved_template(&self, id: Uuid) -> AppResult<ResolvedTemplate>;
+    async fn update_template(&self, id: Uuid, dto: UpdateTemplateDto, actor: &Actor) -> AppResult<Template>;
+    async fn delete_template(&self, id: Uuid, actor: &Actor) -> AppResult<()>;
+    async fn set_official(&self, id: Uuid, is_official: bool, actor: &Actor) -> AppResult<Template>;
+    async fn list_versions(&self, id: Uuid) -> AppResult<Vec<TemplateVersion>>;
+    async fn get_version(&self, id: Uuid, version: i32) -> AppResult<TemplateVersion>;
+    async fn search_templates(&self, query: TemplateSearchQuery, viewer: Option<Uuid>) -> AppResult<Vec<TemplateSearchResult>>;
     async fn get_templates_by_city(&self, city: String) -> AppResult<Vec<Template>>;
-    async fn list_templates(&self, page: i32, page_size: i32) -> AppResult<Vec<Template>>;
+    async fn list_templates(&self, page: i32, page_size: i32, viewer: Option<Uuid>) -> AppResult<Vec<Template>>;
 }
 
 pub struct TemplateServiceImpl {
     template_repo: Arc<dyn TemplateRepository>,
+    location_repo: Arc<dyn LocationRepository>,
+    user_repo: Arc<dyn UserRepository>,
 }
 
 impl TemplateServiceImpl {
-    pub fn new(template_repo: Arc<dyn TemplateRepository>) -> Self {
-        Self { template_repo }
+    pub fn new(
+        template_repo: Arc<dyn TemplateRepository>,
+        location_repo: Arc<dyn LocationRepository>,
+        user_repo: Arc<dyn UserRepository>,
+    ) -> Self {
+        Self {
+            template_repo,
+            location_repo,
+            user_repo,
+        }
+    }
+
+    /// A location tag and all of its ancestors, e.g. CN-GZ → [CN-GZ, CN-GD, CN].
+    async fn location_scope(&self, location_tag: &str) -> AppResult<Vec<String>> {
+        let chain = self.location_repo.find_with_ancestors(location_tag).await?;
+
+        if chain.is_empty() {
+            return Err(AppError::ValidationError(format!("Unknown location code {}", location_tag)));
+        }
+
+        Ok(chain.into_iter().map(|l| l.code).collect())
+    }
+
+    /// The viewer's home city and its ancestors, used to rank listings; empty for anonymous
+    /// viewers and users without a (registered) home city.
+    async fn home_city_scope(&self, viewer: Option<Uuid>) -> AppResult<Vec<String>> {
+        let Some(user_id) = viewer else {
+            return Ok(Vec::new());
+        };
+
+        let Some(home_city) = self.user_repo.find_by_id(user_id).await?.and_then(|u| u.home_city) else {
+            return Ok(Vec::new());
+        };
+
+        let chain = self.location_repo.find_with_ancestors(&home_city).await?;
+
+        Ok(chain.into_iter().map(|l| l.code).collect())
+    }
+
+    /// Whether `actor` is the city curator of `location_tag` or one of its ancestors.
+    async fn curates(&self, actor: &Actor, location_tag: &str) -> AppResult<bool> {
+        let scope = match (&actor.role, &actor.role_scope) {
+            (UserRole::CityCurator, Some(scope)) => scope,
+            _ => return Ok(false),
+        };
+        let chain = self.location_repo.find_with_ancestors(location_tag).await?;
+
+        Ok(chain.iter().any(|l| &l.code == scope))
+    }
+
+    /// Load a live template that `actor` is allowed to modify: admins can modify any template,
+    /// city curators the templates in their area, everyone else only their own.
+    async fn find_editable(&self, id: Uuid, actor: &Actor) -> AppResult<Template> {
+        let template = self.template_repo
+            .find_by_id(id)
+            .await?
+            .filter(|t| !t.is_deleted());
+
+        match template {
+            Some(template) if actor.is_admin() => Ok(template),
+            Some(template) if self.curates(actor, &template.location_tag).await? => Ok(template),
+            template => authorize(template, id, actor.user_id),
+        }
+    }
+
+    /// A new parent must be a live template.
+    async fn ensure_parent_exists(&self, parent_id: Uuid) -> AppResult<()> {
+        self.template_repo
+            .find_by_id(parent_id)
+            .await?
+            .filter(|t| !t.is_deleted())
+            .ok_or_else(|| AppError::NotFound(format!("Parent template {} not found", parent_id)))?;
+
+        Ok(())
+    }
+
+    /// Check a layer of steps against what it inherits: overrides must target real parent
+    /// steps, the parent chain must be acyclic and the resolved dependency graph valid.
+    /// Returns the resolved steps.
+    async fn validate_layer(&self, id: Option<Uuid>, parent_id: Option<Uuid>, steps: &[TemplateStep]) -> AppResult<Vec<TemplateStep>> {
+        let (inherited, _) = inherited_steps(self.template_repo.as_ref(), parent_id, id).await?;
+        let resolved = apply_layer(&inherited, steps).map_err(AppError::ValidationError)?;
+
+        validate_dependencies(&resolved).map_err(AppError::ValidationError)?;
+        Ok(resolved)
+    }
+
+    /// Re-resolve every template inheriting from `id` on top of its new resolved steps, so an edit
+    /// can't leave a descendant overriding or depending on a step that no longer exists.
+    async fn validate_descendants(&self, id: Uuid, resolved: Vec<TemplateStep>) -> AppResult<()> {
+        let mut pending = vec![(id, resolved)];
+        let mut visited = vec![id];
+
+        while let Some((parent_id, inherited)) = pending.pop() {
+            for child in self.template_repo.find_children(parent_id).await? {
+                if visited.contains(&child.id) {
+                    continue;
+                }
+                visited.push(child.id);
+
+                let steps = apply_layer(&inherited, &child.get_steps()?)
+                    .and_then(|steps| validate_dependencies(&steps).map(|_| steps))
+                    .map_err(|e| AppError::ValidationError(format!(
+                        "Template {} inherits from this template and would break: {}",
+                        child.id, e
+                    )))?;
+                pending.push((child.id, steps));
+            }
+        }
+
+        Ok(())
     }
 }
 
 #[async_trait]
 impl TemplateService for TemplateServiceImpl {
-    async fn create_template(&self, dto: CreateTemplateDto, created_by: Uuid) -> AppResult<Template> {
+    async fn create_template(&self, dto: CreateTemplateDto, actor: &Actor) -> AppResult<Template> {
+        // Templates are written by contributors, curators and admins only
+        if !actor.role.includes(UserRole::Contributor) {
+            return Err(AppError::Forbidden("Creating templates requires the contributor role".to_string()));
+        }
+
         // Validate input
-        dto.validate()
-            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+        dto.validate()?;
+
+        // The tag must be a registered location
+        self.location_scope(&dto.location_tag).await?;
+
+        if let Some(parent_id) = dto.parent_id {
+            self.ensure_parent_exists(parent_id).await?;
+        }
+
+        // Reject broken overrides and dangling or cyclic step dependencies
+        self.validate_layer(None, dto.parent_id, &dto.steps).await?;
 
         // Create template
-        self.template_repo.create(dto, created_by).await
+        self.template_repo.create(dto, actor.user_id).await
     }
 
     async fn get_template(&self, id: Uuid) -> AppResult<Template> {
@@ -43,16 +180,129 @@ impl TemplateService for TemplateServiceImpl {
             .ok_or_else(|| AppError::NotFound(format!("Template {} not found", id)))
     }
 
-    async fn search_templates(&self, query: TemplateSearchQuery) -> AppResult<Vec<Template>> {
-        self.template_repo.search(query).await
+    async fn get_resolved_template(&self, id: Uuid) -> AppResult<ResolvedTemplate> {
+        let template = self.get_template(id).await?;
+        let (steps, ancestors) = resolve_steps(self.template_repo.as_ref(), &template).await?;
+
+        Ok(ResolvedTemplate {
+            template,
+            ancestors,
+            steps,
+        })
+    }
+
+    async fn update_template(&self, id: Uuid, mut dto: UpdateTemplateDto, actor: &Actor) -> AppResult<Template> {
+        // Validate input (same rules as create_template)
+        dto.validate()?;
+
+        let template = self.find_editable(id, actor).await?;
+
+        if let Some(location_tag) = &dto.location_tag {
+            self.location_scope(location_tag).await?;
+
+            // A curator editing someone else's template can't move it out of their area
+            let moved = location_tag != &template.location_tag;
+            if moved
+                && !actor.is_admin()
+                && template.created_by != actor.user_id
+                && !self.curates(actor, location_tag).await?
+            {
+                return Err(AppError::Forbidden(format!("Cannot move the template to {}", location_tag)));
+            }
+        }
+
+        // Steps sent back without their id keep the id of the unchanged step they replace
+        if let Some(steps) = dto.steps.as_mut() {
+            template.carry_over_step_ids(steps)?;
+        }
+
+        if let Some(parent_id) = dto.parent_id {
+            self.ensure_parent_exists(parent_id).await?;
+        }
+
+        // Re-check the layer and everything inheriting from it whenever its steps or its parent change
+        if dto.steps.is_some() || dto.parent_id.is_some() {
+            let steps = match &dto.steps {
+                Some(steps) => steps.clone(),
+                None => template.get_steps()?,
+            };
+            let resolved = self.validate_layer(Some(id), dto.parent_id.or(template.parent_id), &steps).await?;
+            self.validate_descendants(id, resolved).await?;
+        }
+
+        // Publishes a new version when title/description/steps change
+        self.template_repo.update(id, dto, actor.user_id).await
+    }
+
+    async fn delete_template(&self, id: Uuid, actor: &Actor) -> AppResult<()> {
+        self.find_editable(id, actor).await?;
+
+        // Soft delete: existing checklists keep referencing the template
+        self.template_repo.soft_delete(id).await
+    }
+
+    async fn set_official(&self, id: Uuid, is_official: bool, actor: &Actor) -> AppResult<Template> {
+        if !actor.is_admin() {
+            return Err(AppError::Forbidden("Only admins can mark templates as official".to_string()));
+        }
+
+        let template = self.get_template(id).await?;
+        if template.is_deleted() {
+            return Err(AppError::NotFound(format!("Template {} has been deleted", id)));
+        }
+
+        // Not a content change, so no new version is published
+        self.template_repo.set_official(id, is_official).await
+    }
+
+    async fn list_versions(&self, id: Uuid) -> AppResult<Vec<TemplateVersion>> {
+        // Make sure the template exists so an unknown id is a 404 rather than an empty list
+        self.get_template(id).await?;
+
+        self.template_repo.list_versions(id).await
+    }
+
+    async fn get_version(&self, id: Uuid, version: i32) -> AppResult<TemplateVersion> {
+        self.template_repo
+            .find_version(id, version)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Template {} version {} not found", id, version)))
+    }
+
+    async fn search_templates(&self, query: TemplateSearchQuery, viewer: Option<Uuid>) -> AppResult<Vec<TemplateSearchResult>> {
+        // A city also matches its province-level and national templates, ranked in that order.
+        // An explicit location_tag takes precedence over the viewer's home city.
+        let (location_tags, preferred_locations) = match &query.location_tag {
+            Some(location_tag) => {
+                let scope = self.location_scope(location_tag).await?;
+                (scope.clone(), scope)
+            }
+            None => (Vec::new(), self.home_city_scope(viewer).await?),
+        };
+
+        let keywords = query.keyword.as_deref().map(parse_keywords).unwrap_or_default();
+        let rows = self.template_repo.search(query, location_tags, preferred_locations).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(template, score)| TemplateSearchResult {
+                highlight: (!keywords.is_empty()).then(|| highlight(&template, &keywords)),
+                template,
+                score,
+            })
+            .collect())
     }
 
     async fn get_templates_by_city(&self, city: String) -> AppResult<Vec<Template>> {
-        self.template_repo.find_by_location(city).await
+        let location_tags = self.location_scope(&city).await?;
+
+        self.template_repo.find_by_location(location_tags).await
     }
 
-    async fn list_templates(&self, page: i32, page_size: i32) -> AppResult<Vec<Template>> {
-        self.template_repo.list_all(page, page_size).await
+    async fn list_templates(&self, page: i32, page_size: i32, viewer: Option<Uuid>) -> AppResult<Vec<Template>> {
+        let preferred_locations = self.home_city_scope(viewer).await?;
+
+        self.template_repo.list_all(page, page_size, preferred_locations).await
     }
 }
 
@@ -1,57 +1,106 @@
 use async_trait::async_trait;
 use common::{AppResult, AppError};
-use models::{UserProfile, RegisterDto, LoginDto, UpdateProfileDto, AuthResponse};
-use db::UserRepository;
-use auth::{JwtService, PasswordService};
+use models::{
+    User, UserProfile, RegisterDto, LoginDto, UpdateProfileDto, AuthResponse, LocationLevel, Actor, GrantRoleDto,
+    SendCodeDto, SendCodeResponse, PhoneLoginDto,
+    CODE_TTL_SECONDS, RESEND_INTERVAL_SECONDS, MAX_CODES_PER_HOUR, UNUSABLE_PASSWORD_HASH,
+};
+use db::{UserRepository, LocationRepository, VerificationCodeRepository};
+use auth::{PasswordService, SmsSender, generate_verification_code, hash_verification_code};
+use chrono::{Duration, Utc};
+use std::net::IpAddr;
 use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
+use super::{SessionService, LoginThrottleService};
+
 #[async_trait]
 pub trait UserService: Send + Sync {
-    async fn register(&self, dto: RegisterDto) -> AppResult<AuthResponse>;
-    async fn login(&self, dto: LoginDto) -> AppResult<AuthResponse>;
+    async fn register(&self, dto: RegisterDto, user_agent: Option<String>) -> AppResult<AuthResponse>;
+    /// `ip` is the client address used for per-IP brute-force protection
+    async fn login(&self, dto: LoginDto, user_agent: Option<String>, ip: Option<IpAddr>) -> AppResult<AuthResponse>;
+    async fn send_login_code(&self, dto: SendCodeDto) -> AppResult<SendCodeResponse>;
+    async fn login_with_code(&self, dto: PhoneLoginDto, user_agent: Option<String>) -> AppResult<AuthResponse>;
     async fn get_user(&self, id: Uuid) -> AppResult<UserProfile>;
     async fn update_profile(&self, user_id: Uuid, dto: UpdateProfileDto) -> AppResult<UserProfile>;
+    async fn grant_role(&self, user_id: Uuid, dto: GrantRoleDto, actor: &Actor) -> AppResult<UserProfile>;
 }
 
 pub struct UserServiceImpl {
     user_repo: Arc<dyn UserRepository>,
-    jwt_service: Arc<dyn JwtService>,
+    location_repo: Arc<dyn LocationRepository>,
+    verification_code_repo: Arc<dyn VerificationCodeRepository>,
     password_service: Arc<dyn PasswordService>,
+    sms_sender: Arc<dyn SmsSender>,
+    session_service: Arc<dyn SessionService>,
+    login_throttle: Arc<dyn LoginThrottleService>,
 }
 
 impl UserServiceImpl {
     pub fn new(
         user_repo: Arc<dyn UserRepository>,
-        jwt_service: Arc<dyn JwtService>,
+        location_repo: Arc<dyn LocationRepository>,
+        verification_code_repo: Arc<dyn VerificationCodeRepository>,
         password_service: Arc<dyn PasswordService>,
+        sms_sender: Arc<dyn SmsSender>,
+        session_service: Arc<dyn SessionService>,
+        login_throttle: Arc<dyn LoginThrottleService>,
     ) -> Self {
         Self {
             user_repo,
-            jwt_service,
+            location_repo,
+            verification_code_repo,
             password_service,
+            sms_sender,
+            session_service,
+            login_throttle,
+        }
+    }
+}
+
+impl UserServiceImpl {
+    /// Re-hashes an old bcrypt (or weaker Argon2id) hash while the plain password is at hand.
+    /// A failure only keeps the old hash, so it never blocks the login
+    async fn upgrade_password_hash(&self, user: User, password: &str) -> User {
+        if !self.password_service.needs_rehash(&user.password_hash) {
+            return user;
+        }
+
+        let hash = match self.password_service.hash_password(password) {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::warn!(user_id = %user.id, error = %e, "Failed to rehash password");
+                return user;
+            }
+        };
+
+        match self.user_repo.update_password(user.id, hash).await {
+            Ok(upgraded) => upgraded,
+            Err(e) => {
+                tracing::warn!(user_id = %user.id, error = %e, "Failed to save upgraded password hash");
+                user
+            }
         }
     }
 }
 
 #[async_trait]
 impl UserService for UserServiceImpl {
-    async fn register(&self, dto: RegisterDto) -> AppResult<AuthResponse> {
+    async fn register(&self, dto: RegisterDto, user_agent: Option<String>) -> AppResult<AuthResponse> {
         // Validate input
-        dto.validate()
-            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+        dto.validate()?;
 
         // Check if user already exists
         if let Some(phone) = &dto.phone {
             if self.user_repo.find_by_phone(phone).await?.is_some() {
-                return Err(AppError::ValidationError("Phone already registered".to_string()));
+                return Err(AppError::Conflict("Phone already registered".to_string()));
             }
         }
 
         if let Some(email) = &dto.email {
             if self.user_repo.find_by_email(email).await?.is_some() {
-                return Err(AppError::ValidationError("Email already registered".to_string()));
+                return Err(AppError::Conflict("Email already registered".to_string()));
             }
         }
 
@@ -61,47 +110,146 @@ impl UserService for UserServiceImpl {
         // Create user
         let user = self.user_repo.create(dto, password_hash).await?;
 
-        // Generate JWT token
-        let token = self.jwt_service.generate_token(user.id)?;
-
-        Ok(AuthResponse {
-            user: user.into(),
-            token,
-        })
+        // Generate access and refresh tokens
+        self.session_service.start(user, user_agent).await
     }
 
-    async fn login(&self, dto: LoginDto) -> AppResult<AuthResponse> {
+    async fn login(&self, dto: LoginDto, user_agent: Option<String>, ip: Option<IpAddr>) -> AppResult<AuthResponse> {
         // Validate input
-        dto.validate()
-            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+        dto.validate()?;
+
+        let account = match (&dto.phone, &dto.email) {
+            (Some(phone), _) => phone.as_str(),
+            (None, Some(email)) => email.as_str(),
+            (None, None) => return Err(AppError::ValidationError("Phone or email required".to_string())),
+        };
+
+        // Backing off or locked: reject before spending a password check
+        self.login_throttle.check(account, ip).await?;
 
         // Find user
-        let user = if let Some(phone) = &dto.phone {
-            self.user_repo.find_by_phone(phone).await?
-        } else if let Some(email) = &dto.email {
-            self.user_repo.find_by_email(email).await?
+        let user = if dto.phone.is_some() {
+            self.user_repo.find_by_phone(account).await?
         } else {
-            return Err(AppError::ValidationError("Phone or email required".to_string()));
+            self.user_repo.find_by_email(account).await?
         };
 
-        let user = user.ok_or_else(|| AppError::AuthError("Invalid credentials".to_string()))?;
+        // Verify password (accounts created through a login provider have none).
+        // Unknown accounts still pay for a hash check so timing doesn't reveal them
+        let is_valid = match &user {
+            Some(user) if user.has_password() => {
+                self.password_service.verify_password(&dto.password, &user.password_hash)?
+            }
+            _ => {
+                self.password_service.dummy_verify(&dto.password);
+                false
+            }
+        };
+
+        let user = match user {
+            Some(user) if is_valid => user,
+            user => {
+                self.login_throttle
+                    .record_failure(account, user.map(|u| u.id), ip)
+                    .await?;
+                return Err(AppError::AuthError("Invalid credentials".to_string()));
+            }
+        };
+
+        self.login_throttle.record_success(account).await?;
+
+        let user = self.upgrade_password_hash(user, &dto.password).await;
+
+        // Generate access and refresh tokens
+        self.session_service.start(user, user_agent).await
+    }
 
-        // Verify password
-        let is_valid = self.password_service.verify_password(&dto.password, &user.password_hash)?;
-        
-        if !is_valid {
-            return Err(AppError::AuthError("Invalid credentials".to_string()));
+    async fn send_login_code(&self, dto: SendCodeDto) -> AppResult<SendCodeResponse> {
+        dto.validate()?;
+
+        // Nothing would deliver the code, so don't store one
+        if !self.sms_sender.is_enabled() {
+            return Err(AppError::NotFound("SMS login is not enabled".to_string()));
+        }
+
+        let now = Utc::now();
+
+        // Throttle resends per phone number
+        if let Some(latest) = self.verification_code_repo.find_latest(&dto.phone).await? {
+            let wait = (latest.created_at + Duration::seconds(RESEND_INTERVAL_SECONDS) - now).num_seconds();
+            if wait > 0 {
+                return Err(AppError::TooManyRequests(format!("Retry in {} seconds", wait)));
+            }
+        }
+
+        let sent_last_hour = self.verification_code_repo
+            .count_since(&dto.phone, now - Duration::hours(1))
+            .await?;
+        if sent_last_hour >= MAX_CODES_PER_HOUR {
+            return Err(AppError::TooManyRequests("Too many codes requested, try again later".to_string()));
         }
 
-        // Generate JWT token
-        let token = self.jwt_service.generate_token(user.id)?;
+        let code = generate_verification_code();
+        self.verification_code_repo
+            .create(
+                &dto.phone,
+                hash_verification_code(&dto.phone, &code),
+                now + Duration::seconds(CODE_TTL_SECONDS),
+            )
+            .await?;
+
+        self.sms_sender.send_code(&dto.phone, &code).await?;
 
-        Ok(AuthResponse {
-            user: user.into(),
-            token,
+        Ok(SendCodeResponse {
+            expires_in: CODE_TTL_SECONDS,
+            resend_after: RESEND_INTERVAL_SECONDS,
         })
     }
 
+    async fn login_with_code(&self, dto: PhoneLoginDto, user_agent: Option<String>) -> AppResult<AuthResponse> {
+        dto.validate()?;
+
+        // Same message for every failure so the response doesn't reveal which check failed
+        let invalid = || AppError::AuthError("Invalid or expired verification code".to_string());
+
+        let code = self.verification_code_repo
+            .find_latest(&dto.phone)
+            .await?
+            .filter(|c| c.is_usable())
+            .ok_or_else(invalid)?;
+
+        if code.code_hash != hash_verification_code(&dto.phone, &dto.code) {
+            self.verification_code_repo.increment_attempts(code.id).await?;
+            return Err(invalid());
+        }
+
+        // Lost the race against another login with the same code
+        if !self.verification_code_repo.consume(code.id).await? {
+            return Err(invalid());
+        }
+
+        let user = match self.user_repo.find_by_phone(&dto.phone).await? {
+            Some(user) => user,
+            None => {
+                // Auto-register without a password; the account signs in with codes
+                let nickname = dto.nickname.unwrap_or_else(|| {
+                    let last_four: String = dto.phone.chars().skip(dto.phone.chars().count().saturating_sub(4)).collect();
+                    format!("用户{}", last_four)
+                });
+                let register = RegisterDto {
+                    phone: Some(dto.phone),
+                    email: None,
+                    password: String::new(),
+                    nickname,
+                };
+
+                self.user_repo.create(register, UNUSABLE_PASSWORD_HASH.to_string()).await?
+            }
+        };
+
+        self.session_service.start(user, user_agent).await
+    }
+
     async fn get_user(&self, id: Uuid) -> AppResult<UserProfile> {
         let user = self.user_repo
             .find_by_id(id)
@@ -113,12 +261,58 @@ impl UserService for UserServiceImpl {
 
     async fn update_profile(&self, user_id: Uuid, dto: UpdateProfileDto) -> AppResult<UserProfile> {
         // Validate input
-        dto.validate()
-            .map_err(|e| AppError::ValidationError(e.to_string()))?;
+        dto.validate()?;
+
+        // Home city must be a registered city, not a province or the whole country
+        if let Some(home_city) = &dto.home_city {
+            let is_city = self.location_repo
+                .find_by_code(home_city)
+                .await?
+                .is_some_and(|l| l.level == LocationLevel::City);
+
+            if !is_city {
+                return Err(AppError::ValidationError(format!("Unknown city code {}", home_city)));
+            }
+        }
 
         let user = self.user_repo.update_profile(user_id, dto).await?;
 
         Ok(user.into())
     }
-}
 
+    async fn grant_role(&self, user_id: Uuid, dto: GrantRoleDto, actor: &Actor) -> AppResult<UserProfile> {
+        if !actor.is_admin() {
+            return Err(AppError::Forbidden("Only admins can grant roles".to_string()));
+        }
+
+        // Keeps at least the acting admin around
+        if user_id == actor.user_id {
+            return Err(AppError::ValidationError("Admins cannot change their own role".to_string()));
+        }
+
+        // Only city curators are scoped, and their scope must be a registered location
+        match (dto.role.is_scoped(), &dto.role_scope) {
+            (true, Some(scope)) => {
+                if self.location_repo.find_by_code(scope).await?.is_none() {
+                    return Err(AppError::ValidationError(format!("Unknown location code {}", scope)));
+                }
+            }
+            (true, None) => {
+                return Err(AppError::ValidationError("city_curator requires role_scope".to_string()));
+            }
+            (false, Some(_)) => {
+                return Err(AppError::ValidationError("role_scope is only allowed for city_curator".to_string()));
+            }
+            (false, None) => {}
+        }
+
+        self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))?;
+
+        let user = self.user_repo.set_role(user_id, dto.role, dto.role_scope).await?;
+
+        Ok(user.into())
+    }
+}
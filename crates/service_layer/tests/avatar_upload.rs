@@ -0,0 +1,171 @@
+//! 头像上传
+//!
+//! 通过`AppModule`中真实的`AvatarService`驱动内存用户Repository和`TestApp`自己目录中的`LocalStorage`，
+//! 验证EXIF方向被应用后去除、生成的尺寸和裁剪、旧头像被删除，
+//! 以及不支持的类型、超大文件和超大尺寸都在写入任何文件之前被拒绝。
+
+mod support;
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use common::AppError;
+use db::UserRepository;
+use image::codecs::jpeg::JpegEncoder;
+use image::{ImageFormat, Rgb, RgbImage};
+use models::{UpdateProfileDto, User, AVATAR_MAX_BYTES};
+use support::TestApp;
+
+const PUBLIC_BASE_URL: &str = "http://localhost:8080/uploads";
+const HOSTED_ELSEWHERE: &str = "https://example.com/hosted-elsewhere.jpg";
+
+/// 头像还在别处托管的用户
+async fn user_with_avatar(app: &TestApp) -> User {
+    let user = app.register("avatar@example.com").await;
+    let dto = UpdateProfileDto {
+        nickname: None,
+        avatar_url: Some(HOSTED_ELSEWHERE.to_string()),
+        home_city: None,
+    };
+
+    app.users.update_profile(user.id, dto).await.unwrap()
+}
+
+async fn avatar_url(app: &TestApp, user: &User) -> Option<String> {
+    app.users.find_by_id(user.id).await.unwrap().unwrap().avatar_url
+}
+
+fn path_of(app: &TestApp, url: &str) -> PathBuf {
+    app.uploads.join(url.strip_prefix(PUBLIC_BASE_URL).unwrap().trim_start_matches('/'))
+}
+
+fn stored_files(app: &TestApp) -> usize {
+    fn count(dir: &Path) -> usize {
+        std::fs::read_dir(dir).map_or(0, |entries| {
+            entries
+                .map(|entry| entry.unwrap().path())
+                .map(|path| if path.is_dir() { count(&path) } else { 1 })
+                .sum()
+        })
+    }
+    count(&app.uploads)
+}
+
+/// 左半红色、右半蓝色的JPEG，带有方向为6（顺时针旋转90°显示）的EXIF
+fn photo_with_exif(width: u32, height: u32) -> Vec<u8> {
+    let image = RgbImage::from_fn(width, height, |x, _| {
+        if x < width / 2 { Rgb([255, 0, 0]) } else { Rgb([0, 0, 255]) }
+    });
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, 95).encode_image(&image).unwrap();
+
+    // APP1 segment: "Exif\0\0" + big-endian TIFF with a single Orientation (0x0112) entry
+    let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0\x06\0\0\0\0\0\0".to_vec();
+    exif.extend_from_slice(b"GPS 39.9042N 116.4074E");
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&((exif.len() + 2) as u16).to_be_bytes());
+    segment.extend_from_slice(&exif);
+
+    // Right after the SOI marker
+    jpeg.splice(2..2, segment);
+    jpeg
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[tokio::test]
+async fn strips_exif_after_applying_orientation_and_stores_variants() {
+    let app = TestApp::new();
+    let user = user_with_avatar(&app).await;
+    let photo = photo_with_exif(720, 540);
+    assert!(contains(&photo, b"GPS 39.9042N"));
+
+    let response = app.module.avatar_service
+        .upload(user.id, photo, Some("image/jpeg".to_string()))
+        .await
+        .unwrap();
+
+    let sizes: Vec<u32> = response.variants.iter().map(|v| v.size).collect();
+    assert_eq!(sizes, [512, 256, 64]);
+    assert_eq!(response.avatar_url, response.variants[0].url);
+    assert!(response.avatar_url.starts_with(&format!("{}/avatars/{}/", PUBLIC_BASE_URL, user.id)));
+    assert_eq!(avatar_url(&app, &user).await, Some(response.avatar_url.clone()));
+
+    for variant in &response.variants {
+        let stored = std::fs::read(path_of(&app, &variant.url)).unwrap();
+        assert!(!contains(&stored, b"Exif") && !contains(&stored, b"GPS"), "metadata kept in {}", variant.url);
+
+        let image = image::load_from_memory_with_format(&stored, ImageFormat::Jpeg).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (variant.size, variant.size));
+
+        // Rotated upright, the red half is on top and the blue half at the bottom
+        let top = image.get_pixel(variant.size / 2, 2).0;
+        let bottom = image.get_pixel(variant.size / 2, variant.size - 3).0;
+        assert!(top[0] > 200 && top[2] < 60, "top of {} is {:?}", variant.url, top);
+        assert!(bottom[2] > 200 && bottom[0] < 60, "bottom of {} is {:?}", variant.url, bottom);
+    }
+}
+
+#[tokio::test]
+async fn replaces_the_previous_upload_and_does_not_upscale() {
+    let app = TestApp::new();
+    let user = user_with_avatar(&app).await;
+
+    let first = app.module.avatar_service.upload(user.id, photo_with_exif(720, 540), None).await.unwrap();
+    assert_eq!(stored_files(&app), 3);
+
+    // A small transparent PNG: kept at its own size, transparency turns white
+    let mut png = Vec::new();
+    image::RgbaImage::from_pixel(40, 30, image::Rgba([0, 0, 0, 0]))
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+    let second = app.module.avatar_service.upload(user.id, png, Some("image/png".to_string())).await.unwrap();
+
+    assert_eq!(stored_files(&app), 3);
+    for variant in &first.variants {
+        assert!(!path_of(&app, &variant.url).exists(), "{} was not deleted", variant.url);
+    }
+
+    let stored = std::fs::read(path_of(&app, &second.variants[0].url)).unwrap();
+    let image = image::load_from_memory(&stored).unwrap().to_rgb8();
+    assert_eq!(image.dimensions(), (30, 30));
+    assert!(image.get_pixel(15, 15).0.iter().all(|&c| c > 240));
+}
+
+#[tokio::test]
+async fn rejects_invalid_uploads_before_storing_anything() {
+    let app = TestApp::new();
+    let user = user_with_avatar(&app).await;
+    let upload = |data: Vec<u8>, content_type: Option<&str>| {
+        app.module.avatar_service.upload(user.id, data, content_type.map(str::to_string))
+    };
+
+    // Declared type not allowed
+    let gif = b"GIF89a\x01\0\x01\0\0\0\0;".to_vec();
+    assert!(matches!(upload(gif.clone(), Some("image/gif")).await, Err(AppError::ValidationError(_))));
+    // Content sniffed, not trusted from the declared type
+    assert!(matches!(upload(gif, Some("image/png")).await, Err(AppError::ValidationError(_))));
+    assert!(matches!(upload(b"<svg/>".to_vec(), Some("image/jpeg")).await, Err(AppError::ValidationError(_))));
+
+    // Truncated image
+    let mut broken = photo_with_exif(64, 64);
+    broken.truncate(300);
+    assert!(matches!(upload(broken, None).await, Err(AppError::ValidationError(_))));
+
+    // Too many bytes
+    let oversized = vec![0u8; AVATAR_MAX_BYTES + 1];
+    assert!(matches!(upload(oversized, None).await, Err(AppError::PayloadTooLarge(_))));
+
+    // Small file, huge canvas
+    let mut wide = Vec::new();
+    image::GrayImage::new(9000, 1)
+        .write_to(&mut Cursor::new(&mut wide), ImageFormat::Png)
+        .unwrap();
+    let error = upload(wide, None).await.unwrap_err();
+    assert!(error.to_string().contains("8192"), "{}", error);
+
+    assert_eq!(stored_files(&app), 0);
+    assert_eq!(avatar_url(&app, &user).await.as_deref(), Some(HOSTED_ELSEWHERE));
+}
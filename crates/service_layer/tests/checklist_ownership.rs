@@ -0,0 +1,162 @@
+//! 清单的归属校验
+//!
+//! 通过`AppModule`中真实的`ChecklistService`驱动内存Repository，验证跨用户的读取和修改都会被拒绝，
+//! 并且被拒绝的请求不会修改清单。
+
+mod support;
+
+use common::AppError;
+use db::{TemplateRepository, UserChecklistRepository};
+use models::{
+    AddChecklistStepDto, CreateTemplateDto, ForkTemplateDto, TemplateStep, UpdateStepDto, UserChecklist,
+};
+use support::TestApp;
+use uuid::Uuid;
+
+/// 准备一个只有一个步骤的模板，由`owner`Fork成清单
+async fn fork_checklist(app: &TestApp, owner: Uuid) -> UserChecklist {
+    let dto = CreateTemplateDto {
+        title: "第一次租房".to_string(),
+        description: "从看房到入住".to_string(),
+        location_tag: "CN".to_string(),
+        steps: vec![TemplateStep {
+            id: Uuid::new_v4(),
+            title: "看房".to_string(),
+            description: None,
+            order: 0,
+            depends_on: Vec::new(),
+            overrides: None,
+            removed: false,
+        }],
+        parent_id: None,
+    };
+    let template = app.templates.create(dto, Uuid::new_v4()).await.unwrap();
+
+    app.module.checklist_service
+        .fork_template(owner, ForkTemplateDto { template_id: template.id })
+        .await
+        .unwrap()
+        .checklist
+}
+
+fn step_id(checklist: &UserChecklist) -> Uuid {
+    checklist.get_steps().unwrap()[0].id
+}
+
+/// 清单是否还是`checklist`的样子（任何写入都会刷新`updated_at`）
+async fn unchanged(app: &TestApp, checklist: &UserChecklist) -> bool {
+    app.checklists.find_by_id(checklist.id).await.unwrap().as_ref() == Some(checklist)
+}
+
+#[tokio::test]
+async fn owner_can_read_own_checklist() {
+    let app = TestApp::new();
+    let owner = Uuid::new_v4();
+    let checklist = fork_checklist(&app, owner).await;
+
+    let response = app.module.checklist_service.get_checklist(checklist.id, owner).await.unwrap();
+
+    assert_eq!(response.checklist.id, checklist.id);
+    assert_eq!(response.progress.total_steps, 1);
+}
+
+#[tokio::test]
+async fn other_user_cannot_read_checklist() {
+    let app = TestApp::new();
+    let checklist = fork_checklist(&app, Uuid::new_v4()).await;
+
+    let result = app.module.checklist_service.get_checklist(checklist.id, Uuid::new_v4()).await;
+
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+}
+
+#[tokio::test]
+async fn missing_checklist_is_not_found() {
+    let app = TestApp::new();
+    let owner = Uuid::new_v4();
+    fork_checklist(&app, owner).await;
+
+    let result = app.module.checklist_service.get_checklist(Uuid::new_v4(), owner).await;
+
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn owner_can_tick_steps() {
+    let app = TestApp::new();
+    let owner = Uuid::new_v4();
+    let checklist = fork_checklist(&app, owner).await;
+    let dto = UpdateStepDto {
+        step_id: step_id(&checklist),
+        completed: true,
+        force: false,
+    };
+
+    let response = app.module.checklist_service.update_step(checklist.id, owner, dto).await.unwrap();
+
+    assert_eq!(response.progress.completed_steps, 1);
+    assert!(!unchanged(&app, &checklist).await);
+}
+
+#[tokio::test]
+async fn other_user_cannot_tick_steps() {
+    let app = TestApp::new();
+    let checklist = fork_checklist(&app, Uuid::new_v4()).await;
+    // `force` skips the prerequisite check, so only ownership can stop it
+    let dto = UpdateStepDto {
+        step_id: step_id(&checklist),
+        completed: true,
+        force: true,
+    };
+
+    let result = app.module.checklist_service.update_step(checklist.id, Uuid::new_v4(), dto).await;
+
+    assert!(matches!(result, Err(AppError::Forbidden(_))));
+    assert!(unchanged(&app, &checklist).await);
+}
+
+#[tokio::test]
+async fn other_user_cannot_edit_steps() {
+    let app = TestApp::new();
+    let checklist = fork_checklist(&app, Uuid::new_v4()).await;
+    let intruder = Uuid::new_v4();
+    let dto = AddChecklistStepDto {
+        title: "问问同事".to_string(),
+        description: None,
+        position: None,
+    };
+
+    let added = app.module.checklist_service.add_custom_step(checklist.id, intruder, dto).await;
+    let removed = app.module.checklist_service.remove_step(checklist.id, intruder, step_id(&checklist)).await;
+
+    assert!(matches!(added, Err(AppError::Forbidden(_))));
+    assert!(matches!(removed, Err(AppError::Forbidden(_))));
+    assert!(unchanged(&app, &checklist).await);
+}
+
+#[tokio::test]
+async fn other_user_cannot_sync_checklist() {
+    let app = TestApp::new();
+    let checklist = fork_checklist(&app, Uuid::new_v4()).await;
+    let intruder = Uuid::new_v4();
+
+    let preview = app.module.checklist_service.preview_sync(checklist.id, intruder).await;
+    let applied = app.module.checklist_service.apply_sync(checklist.id, intruder).await;
+
+    assert!(matches!(preview, Err(AppError::Forbidden(_))));
+    assert!(matches!(applied, Err(AppError::Forbidden(_))));
+    assert!(unchanged(&app, &checklist).await);
+}
+
+#[tokio::test]
+async fn checklist_lists_are_per_user() {
+    let app = TestApp::new();
+    let owner = Uuid::new_v4();
+    fork_checklist(&app, owner).await;
+
+    let own = app.module.checklist_service.get_user_checklists(owner).await.unwrap();
+    let others = app.module.checklist_service.get_user_checklists(Uuid::new_v4()).await.unwrap();
+
+    assert_eq!(own.len(), 1);
+    assert!(others.is_empty());
+}
@@ -0,0 +1,229 @@
+//! Fork模板和清单进度
+//!
+//! 通过`AppModule`中真实的`ChecklistService`驱动内存Repository，
+//! 验证Fork时的步骤快照（包括继承父模板的步骤）、已删除模板不能Fork、
+//! 进度计算（隐藏的步骤不计入）以及步骤的勾选、取消和前置步骤检查。
+
+mod support;
+
+use common::AppError;
+use db::TemplateRepository;
+use models::{
+    AddChecklistStepDto, CreateTemplateDto, ForkTemplateDto, StepState, Template, TemplateStep,
+    UpdateChecklistStepDto, UpdateStepDto,
+};
+use support::TestApp;
+use uuid::Uuid;
+
+fn step(order: i32, title: &str, depends_on: Vec<Uuid>) -> TemplateStep {
+    TemplateStep {
+        id: Uuid::new_v4(),
+        title: title.to_string(),
+        description: None,
+        order,
+        depends_on,
+        overrides: None,
+        removed: false,
+    }
+}
+
+/// 三个步骤的模板：签合同之前要先看房
+async fn renting_template(app: &TestApp) -> Template {
+    let viewing = step(1, "看房", vec![]);
+    let contract = step(2, "签合同", vec![viewing.id]);
+    let dto = CreateTemplateDto {
+        title: "第一次租房".to_string(),
+        description: "从看房到入住".to_string(),
+        location_tag: "CN".to_string(),
+        steps: vec![step(0, "确定预算", vec![]), viewing, contract],
+        parent_id: None,
+    };
+
+    app.templates.create(dto, Uuid::new_v4()).await.unwrap()
+}
+
+fn toggle(step_id: Uuid, completed: bool) -> UpdateStepDto {
+    UpdateStepDto {
+        step_id,
+        completed,
+        force: false,
+    }
+}
+
+#[tokio::test]
+async fn fork_snapshots_the_template() {
+    let app = TestApp::new();
+    let template = renting_template(&app).await;
+    let user_id = Uuid::new_v4();
+
+    let response = app.module.checklist_service
+        .fork_template(user_id, ForkTemplateDto { template_id: template.id })
+        .await
+        .unwrap();
+
+    let checklist = &response.checklist;
+    assert_eq!(checklist.user_id, user_id);
+    assert_eq!(checklist.title, "第一次租房");
+    assert_eq!(checklist.source_template_id, template.id);
+    assert_eq!(checklist.source_template_version, 1);
+
+    let template_steps = template.get_steps().unwrap();
+    let steps = checklist.get_steps().unwrap();
+    let ids: Vec<Uuid> = steps.iter().map(|s| s.id).collect();
+    assert_eq!(ids, template_steps.iter().map(|s| s.id).collect::<Vec<_>>());
+    assert_eq!(steps.iter().map(|s| s.order).collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert!(steps.iter().all(|s| !s.is_custom));
+
+    assert_eq!(response.progress.total_steps, 3);
+    assert_eq!(response.progress.completed_steps, 0);
+    assert_eq!(response.progress.progress_percentage, 0.0);
+
+    // The contract step waits for the viewing
+    let states: Vec<StepState> = response.progress.steps.iter().map(|s| s.state).collect();
+    assert_eq!(states, vec![StepState::Available, StepState::Available, StepState::Locked]);
+
+    let listed = app.module.checklist_service.get_user_checklists(user_id).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].checklist.id, checklist.id);
+}
+
+#[tokio::test]
+async fn fork_includes_inherited_steps() {
+    let app = TestApp::new();
+    let parent = renting_template(&app).await;
+    let child = app.templates
+        .create(
+            CreateTemplateDto {
+                title: "第一次在广州租房".to_string(),
+                description: "广州的补充步骤".to_string(),
+                location_tag: "CN-GZ".to_string(),
+                steps: vec![step(3, "办理居住证", vec![])],
+                parent_id: Some(parent.id),
+            },
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+
+    let response = app.module.checklist_service
+        .fork_template(Uuid::new_v4(), ForkTemplateDto { template_id: child.id })
+        .await
+        .unwrap();
+
+    let titles: Vec<String> = response.checklist.get_steps().unwrap().into_iter().map(|s| s.title).collect();
+    assert_eq!(titles, vec!["确定预算", "看房", "签合同", "办理居住证"]);
+    assert_eq!(response.checklist.get_base_steps().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn deleted_or_missing_templates_cannot_be_forked() {
+    let app = TestApp::new();
+    let template = renting_template(&app).await;
+    app.templates.soft_delete(template.id).await.unwrap();
+
+    for template_id in [template.id, Uuid::new_v4()] {
+        let result = app.module.checklist_service
+            .fork_template(Uuid::new_v4(), ForkTemplateDto { template_id })
+            .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+}
+
+#[tokio::test]
+async fn toggling_steps_updates_progress() {
+    let app = TestApp::new();
+    let template = renting_template(&app).await;
+    let user_id = Uuid::new_v4();
+    let service = &app.module.checklist_service;
+    let forked = service
+        .fork_template(user_id, ForkTemplateDto { template_id: template.id })
+        .await
+        .unwrap();
+    let checklist_id = forked.checklist.id;
+    let steps = forked.checklist.get_steps().unwrap();
+
+    let response = service.update_step(checklist_id, user_id, toggle(steps[0].id, true)).await.unwrap();
+    assert_eq!(response.progress.completed_steps, 1);
+    assert!((response.progress.progress_percentage - 100.0 / 3.0).abs() < 0.01);
+    let progress = response.checklist.get_progress().unwrap();
+    assert!(progress[0].completed && progress[0].completed_at.is_some());
+
+    // Unchecking clears the completion time
+    let response = service.update_step(checklist_id, user_id, toggle(steps[0].id, false)).await.unwrap();
+    assert_eq!(response.progress.completed_steps, 0);
+    let progress = response.checklist.get_progress().unwrap();
+    assert!(!progress[0].completed && progress[0].completed_at.is_none());
+
+    // The stored checklist has the same progress
+    let stored = service.get_checklist(checklist_id, user_id).await.unwrap();
+    assert_eq!(stored.progress.completed_steps, 0);
+
+    for step in &steps {
+        let force = UpdateStepDto { force: true, ..toggle(step.id, true) };
+        service.update_step(checklist_id, user_id, force).await.unwrap();
+    }
+    let done = service.get_checklist(checklist_id, user_id).await.unwrap();
+    assert_eq!(done.progress.completed_steps, 3);
+    assert_eq!(done.progress.progress_percentage, 100.0);
+    assert!(done.progress.steps.iter().all(|s| s.state == StepState::Done));
+}
+
+#[tokio::test]
+async fn steps_with_unfinished_prerequisites_need_force() {
+    let app = TestApp::new();
+    let template = renting_template(&app).await;
+    let user_id = Uuid::new_v4();
+    let service = &app.module.checklist_service;
+    let forked = service
+        .fork_template(user_id, ForkTemplateDto { template_id: template.id })
+        .await
+        .unwrap();
+    let checklist_id = forked.checklist.id;
+    let contract = forked.checklist.get_steps().unwrap()[2].id;
+
+    let result = service.update_step(checklist_id, user_id, toggle(contract, true)).await;
+    assert!(matches!(result, Err(AppError::ValidationError(ref m)) if m.contains("看房")));
+
+    let forced = UpdateStepDto { force: true, ..toggle(contract, true) };
+    let response = service.update_step(checklist_id, user_id, forced).await.unwrap();
+    assert_eq!(response.progress.completed_steps, 1);
+
+    let unknown = service.update_step(checklist_id, user_id, toggle(Uuid::new_v4(), true)).await;
+    assert!(matches!(unknown, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn progress_counts_custom_steps_but_not_hidden_ones() {
+    let app = TestApp::new();
+    let template = renting_template(&app).await;
+    let user_id = Uuid::new_v4();
+    let service = &app.module.checklist_service;
+    let forked = service
+        .fork_template(user_id, ForkTemplateDto { template_id: template.id })
+        .await
+        .unwrap();
+    let checklist_id = forked.checklist.id;
+    let budget = forked.checklist.get_steps().unwrap()[0].id;
+
+    let add = AddChecklistStepDto {
+        title: "买家具".to_string(),
+        description: None,
+        position: None,
+    };
+    let response = service.add_custom_step(checklist_id, user_id, add).await.unwrap();
+    assert_eq!(response.progress.total_steps, 4);
+
+    let custom = response.checklist.get_steps().unwrap().into_iter().find(|s| s.is_custom).unwrap();
+    let response = service.update_step(checklist_id, user_id, toggle(custom.id, true)).await.unwrap();
+    assert_eq!(response.progress.completed_steps, 1);
+    assert_eq!(response.progress.progress_percentage, 25.0);
+
+    let hide = UpdateChecklistStepDto {
+        title: None,
+        description: None,
+        hidden: Some(true),
+    };
+    let response = service.edit_step(checklist_id, user_id, budget, hide).await.unwrap();
+    assert_eq!(response.progress.total_steps, 3);
+    assert_eq!(response.progress.completed_steps, 1);
+}
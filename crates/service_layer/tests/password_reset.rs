@@ -0,0 +1,56 @@
+//! 找回密码
+//!
+//! 通过`AppModule`中真实的`CredentialService`和一个很慢的邮件服务，验证找回密码请求立即返回：
+//! 已注册和未注册的邮箱响应时间相同，不会因为等待邮件服务器而暴露邮箱是否注册，邮件在后台照常发出。
+
+mod support;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use auth::{Email, Mailer};
+use common::AppResult;
+use models::ForgotPasswordDto;
+use support::TestApp;
+use tokio::sync::mpsc;
+
+/// 模拟的邮件服务器往返时间
+const MAIL_LATENCY: Duration = Duration::from_millis(500);
+
+/// 等待`MAIL_LATENCY`后才"发出"邮件
+struct SlowMailer {
+    sent: mpsc::UnboundedSender<Email>,
+}
+
+#[async_trait]
+impl Mailer for SlowMailer {
+    async fn send(&self, email: Email) -> AppResult<()> {
+        tokio::time::sleep(MAIL_LATENCY).await;
+        self.sent.send(email).ok();
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn reset_requests_do_not_wait_for_the_mail_server() {
+    let (sent, mut inbox) = mpsc::unbounded_channel();
+    let app = TestApp::builder().mailer(Arc::new(SlowMailer { sent })).build();
+    app.register("ming@example.com").await;
+
+    for email in ["nobody@example.com", "ming@example.com"] {
+        let started = Instant::now();
+        app.module.credential_service
+            .request_password_reset(ForgotPasswordDto { email: email.to_string() })
+            .await
+            .unwrap();
+        assert!(started.elapsed() < MAIL_LATENCY / 5, "{} took {:?}", email, started.elapsed());
+    }
+
+    // The registered address still gets its link, the unknown one gets nothing
+    let email = tokio::time::timeout(MAIL_LATENCY * 10, inbox.recv()).await.unwrap().unwrap();
+    assert_eq!(email.to, "ming@example.com");
+    assert!(email.body.contains("token="));
+    assert_eq!(app.password_resets.all().len(), 1);
+    assert!(inbox.try_recv().is_err());
+}
@@ -0,0 +1,130 @@
+//! 注册和密码登录
+//!
+//! 通过`AppModule`中真实的`UserService`（以及它依赖的会话和登录限流服务）驱动内存Repository，
+//! 验证重复的手机号和邮箱被拒绝、登录失败不泄露账号是否存在并被计数，
+//! 以及连续失败后的退避和登录成功后的清零。
+
+mod support;
+
+use std::time::Duration;
+
+use common::AppError;
+use db::LoginAttemptRepository;
+use models::{LoginDto, LoginScope, RegisterDto};
+use support::TestApp;
+
+fn register_dto(phone: Option<&str>, email: Option<&str>) -> RegisterDto {
+    RegisterDto {
+        phone: phone.map(str::to_string),
+        email: email.map(str::to_string),
+        password: "password123".to_string(),
+        nickname: "小明".to_string(),
+    }
+}
+
+fn login_dto(email: &str, password: &str) -> LoginDto {
+    LoginDto {
+        phone: None,
+        email: Some(email.to_string()),
+        password: password.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn registration_signs_the_user_in() {
+    let app = TestApp::new();
+
+    let response = app.module.user_service
+        .register(register_dto(None, Some("ming@example.com")), Some("tests".to_string()))
+        .await
+        .unwrap();
+
+    let users = app.users.all();
+    assert_eq!(users.len(), 1);
+    assert_eq!(response.user.id, users[0].id);
+    // Only the hash is stored
+    assert_ne!(users[0].password_hash, "password123");
+
+    let claims = app.module.jwt_service.validate_token(&response.token).unwrap();
+    assert_eq!(claims.sub, users[0].id.to_string());
+
+    let tokens = app.refresh_tokens.all();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].user_id, users[0].id);
+    assert_eq!(tokens[0].user_agent.as_deref(), Some("tests"));
+}
+
+#[tokio::test]
+async fn registration_rejects_a_taken_email_or_phone() {
+    let app = TestApp::new();
+    let service = &app.module.user_service;
+    service
+        .register(register_dto(Some("13800000000"), Some("ming@example.com")), None)
+        .await
+        .unwrap();
+
+    let same_email = service.register(register_dto(None, Some("ming@example.com")), None).await;
+    assert!(matches!(same_email, Err(AppError::Conflict(ref m)) if m == "Email already registered"));
+
+    let same_phone = service.register(register_dto(Some("13800000000"), None), None).await;
+    assert!(matches!(same_phone, Err(AppError::Conflict(ref m)) if m == "Phone already registered"));
+
+    assert_eq!(app.users.all().len(), 1);
+}
+
+#[tokio::test]
+async fn login_with_the_right_password_succeeds() {
+    let app = TestApp::new();
+    let service = &app.module.user_service;
+    let registered = service.register(register_dto(None, Some("ming@example.com")), None).await.unwrap();
+
+    let response = service.login(login_dto("ming@example.com", "password123"), None, None).await.unwrap();
+
+    assert_eq!(response.user.id, registered.user.id);
+    assert_ne!(response.refresh_token, registered.refresh_token);
+    assert_eq!(app.refresh_tokens.all().len(), 2);
+}
+
+#[tokio::test]
+async fn login_failures_look_the_same_and_are_counted() {
+    let app = TestApp::new();
+    let service = &app.module.user_service;
+    service.register(register_dto(None, Some("ming@example.com")), None).await.unwrap();
+
+    let wrong_password = service.login(login_dto("ming@example.com", "wrong-password"), None, None).await;
+    let unknown_account = service.login(login_dto("nobody@example.com", "password123"), None, None).await;
+
+    for result in [wrong_password, unknown_account] {
+        assert!(matches!(result, Err(AppError::AuthError(ref m)) if m == "Invalid credentials"));
+    }
+
+    for account in ["ming@example.com", "nobody@example.com"] {
+        let attempt = app.login_attempts.find(LoginScope::Account, account).await.unwrap().unwrap();
+        assert_eq!(attempt.failures, 1);
+    }
+    // No session is started on failure
+    assert_eq!(app.refresh_tokens.all().len(), 1);
+}
+
+#[tokio::test]
+async fn repeated_failures_back_off_until_a_successful_login() {
+    let app = TestApp::new();
+    let service = &app.module.user_service;
+    service.register(register_dto(None, Some("ming@example.com")), None).await.unwrap();
+
+    // The free failures and the first one past them are still checked
+    for _ in 0..=LoginScope::Account.free_failures() {
+        let result = service.login(login_dto("ming@example.com", "wrong-password"), None, None).await;
+        assert!(matches!(result, Err(AppError::AuthError(_))));
+    }
+
+    // Now even the right password has to wait a second
+    let result = service.login(login_dto("ming@example.com", "password123"), None, None).await;
+    assert!(matches!(result, Err(AppError::TooManyRequests(_))));
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    service.login(login_dto("ming@example.com", "password123"), None, None).await.unwrap();
+
+    let attempt = app.login_attempts.find(LoginScope::Account, "ming@example.com").await.unwrap();
+    assert!(attempt.is_none());
+}
@@ -0,0 +1,166 @@
+//! Service层测试的公共部分
+//!
+//! 用`AppModule::builder`组装真实的Service，数据访问换成`db::memory`中的内存实现，
+//! 不需要数据库。测试直接读写这些内存Repository来准备数据和检查结果。
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use auth::{CaptureMailer, Mailer};
+use common::{
+    AccountConfig, AppConfig, DatabaseConfig, Environment, JwtConfig, JwtKey, JwtKeyConfig, MailConfig,
+    PasswordConfig, ServerConfig, SmsBackend, SmsConfig, StorageBackend, StorageConfig,
+};
+use db::memory::{
+    InMemoryLoginAttemptRepository, InMemoryPasswordResetTokenRepository, InMemoryRefreshTokenRepository,
+    InMemoryTemplateRepository, InMemoryUserChecklistRepository, InMemoryUserRepository,
+};
+use db::UserRepository;
+use models::{RegisterDto, User};
+use service_layer::AppModule;
+use uuid::Uuid;
+
+/// 组装好的应用和它使用的内存Repository
+pub struct TestApp {
+    pub module: AppModule,
+    pub users: Arc<InMemoryUserRepository>,
+    pub templates: Arc<InMemoryTemplateRepository>,
+    pub checklists: Arc<InMemoryUserChecklistRepository>,
+    pub refresh_tokens: Arc<InMemoryRefreshTokenRepository>,
+    pub login_attempts: Arc<InMemoryLoginAttemptRepository>,
+    pub password_resets: Arc<InMemoryPasswordResetTokenRepository>,
+    /// 本地对象存储的目录（每个`TestApp`单独一个，结束时删除）
+    pub uploads: PathBuf,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> TestAppBuilder {
+        TestAppBuilder::default()
+    }
+
+    /// 注册一个邮箱账号，返回保存的用户
+    pub async fn register(&self, email: &str) -> User {
+        let dto = RegisterDto {
+            phone: None,
+            email: Some(email.to_string()),
+            password: "password123".to_string(),
+            nickname: "小明".to_string(),
+        };
+
+        let response = self.module.user_service.register(dto, None).await.unwrap();
+        self.users.find_by_id(response.user.id).await.unwrap().unwrap()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.uploads);
+    }
+}
+
+/// 替换`TestApp`中默认的依赖
+#[derive(Default)]
+pub struct TestAppBuilder {
+    mailer: Option<Arc<dyn Mailer>>,
+}
+
+impl TestAppBuilder {
+    /// 使用指定的邮件服务（默认`CaptureMailer`）
+    pub fn mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mailer = Some(mailer);
+        self
+    }
+
+    pub fn build(self) -> TestApp {
+        let users = Arc::new(InMemoryUserRepository::new());
+        let templates = Arc::new(InMemoryTemplateRepository::new());
+        let checklists = Arc::new(InMemoryUserChecklistRepository::new());
+        let refresh_tokens = Arc::new(InMemoryRefreshTokenRepository::new());
+        let login_attempts = Arc::new(InMemoryLoginAttemptRepository::new());
+        let password_resets = Arc::new(InMemoryPasswordResetTokenRepository::new());
+        let uploads = std::env::temp_dir().join(format!("rookie-guide-service-tests-{}", Uuid::new_v4()));
+
+        let module = AppModule::builder(config(&uploads))
+            .user_repository(users.clone())
+            .template_repository(templates.clone())
+            .checklist_repository(checklists.clone())
+            .refresh_token_repository(refresh_tokens.clone())
+            .login_attempt_repository(login_attempts.clone())
+            .password_reset_repository(password_resets.clone())
+            .mailer(self.mailer.unwrap_or_else(|| Arc::new(CaptureMailer::new())))
+            .build();
+
+        TestApp {
+            module,
+            users,
+            templates,
+            checklists,
+            refresh_tokens,
+            login_attempts,
+            password_resets,
+            uploads,
+        }
+    }
+}
+
+/// 测试配置：HS256密钥，最低的Argon2参数让密码哈希足够快
+pub fn config(uploads: &Path) -> AppConfig {
+    AppConfig {
+        environment: Environment::Test,
+        server: ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            trust_forwarded_for: false,
+        },
+        database: DatabaseConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            user: "postgres".to_string(),
+            password: "unused".to_string(),
+            database_name: "rookie_guide_test".to_string(),
+            max_connections: 1,
+        },
+        jwt: JwtConfig {
+            signing_key: JwtKeyConfig {
+                kid: None,
+                key: JwtKey::Hs256 {
+                    secret: "service-test-secret".to_string(),
+                },
+            },
+            verification_keys: Vec::new(),
+            expiration: 900,
+            refresh_expiration: 3600,
+        },
+        oidc_providers: Vec::new(),
+        mail: MailConfig {
+            from: "no-reply@localhost".to_string(),
+            smtp: None,
+            capture_dir: None,
+            password_reset_url: "http://localhost:3000/reset-password".to_string(),
+        },
+        sms: SmsConfig {
+            backend: SmsBackend::Log,
+        },
+        password: PasswordConfig {
+            argon2_memory_kib: 64,
+            argon2_iterations: 1,
+            argon2_parallelism: 1,
+        },
+        account: AccountConfig {
+            deletion_grace_days: 30,
+            purge_interval_seconds: 3600,
+        },
+        storage: StorageConfig {
+            backend: StorageBackend::Local {
+                dir: uploads.display().to_string(),
+            },
+            public_base_url: "http://localhost:8080/uploads".to_string(),
+        },
+    }
+}
@@ -0,0 +1,36 @@
+/// 对象存储模块
+/// 
+/// 保存用户上传的文件（头像等），文件通过公开地址直接访问。
+/// 
+/// ## 模块结构
+/// 
+/// - `object`: 对象存储接口和键的校验
+/// - `local`: 保存在本地目录中（由API服务器的`/uploads`路由提供访问）
+/// - `s3`: S3兼容的对象存储（AWS Signature Version 4签名）
+/// 
+/// ## 使用示例
+/// 
+/// ```rust
+/// # use std::sync::Arc;
+/// # use auth::DefaultHttpClient;
+/// # use storage::{LocalStorage, ObjectStorage, S3Storage};
+/// # async fn example(s3_config: common::S3Config, public_base_url: &str, bytes: Vec<u8>) -> common::AppResult<()> {
+/// // 本地目录
+/// let storage = LocalStorage::new("./uploads", "http://localhost:8080/uploads");
+/// 
+/// // S3兼容的对象存储（复用第三方登录的后端HTTP客户端）
+/// let storage = S3Storage::new(s3_config, public_base_url, Arc::new(DefaultHttpClient::new()));
+/// 
+/// storage.put("avatars/u1/a/512.jpg", bytes, "image/jpeg").await?;
+/// let url = storage.public_url("avatars/u1/a/512.jpg");
+/// # Ok(())
+/// # }
+/// ```
+
+pub mod object;
+pub mod local;
+pub mod s3;
+
+pub use object::{ObjectStorage, validate_key};
+pub use local::LocalStorage;
+pub use s3::S3Storage;
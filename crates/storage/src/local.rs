@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use common::{AppError, AppResult};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::object::{join_url, validate_key, ObjectStorage};
+
+/// 保存在本地目录中的对象存储
+/// 
+/// 键直接作为相对于`root`的路径；文件由API服务器的`/uploads`路由（或反向代理）提供访问。
+/// 适合单机部署和开发环境，多实例部署请使用`S3Storage`。
+#[derive(Debug, Clone)]
+pub struct LocalStorage {
+    /// 保存文件的目录
+    root: PathBuf,
+    
+    /// 公开访问地址前缀（对应`root`）
+    public_base_url: String,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>, public_base_url: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            public_base_url: public_base_url.into(),
+        }
+    }
+
+    fn path_of(&self, key: &str) -> AppResult<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for LocalStorage {
+    /// 先写入临时文件再重命名，读取方不会看到写了一半的文件
+    async fn put(&self, key: &str, body: Vec<u8>, _content_type: &str) -> AppResult<()> {
+        let path = self.path_of(key)?;
+        let io_error = |e: std::io::Error| AppError::InternalError(format!("Failed to store {}: {}", key, e));
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(io_error)?;
+        }
+
+        let tmp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+        tokio::fs::write(&tmp, body).await.map_err(io_error)?;
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(io_error(e));
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        let path = self.path_of(key)?;
+
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(AppError::InternalError(format!("Failed to delete {}: {}", key, e))),
+        }
+
+        // Drop the directory once its last file is gone; fails harmlessly while it still has files
+        if let Some(dir) = path.parent().filter(|dir| *dir != self.root) {
+            let _ = tokio::fs::remove_dir(dir).await;
+        }
+
+        Ok(())
+    }
+
+    /// 前缀对应一个目录，整个删除
+    async fn delete_prefix(&self, prefix: &str) -> AppResult<()> {
+        let path = self.path_of(prefix)?;
+
+        match tokio::fs::remove_dir_all(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::InternalError(format!("Failed to delete {}: {}", prefix, e))),
+        }
+    }
+
+    fn public_url(&self, key: &str) -> String {
+        join_url(&self.public_base_url, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stores_and_deletes_files_under_the_root() {
+        let root = std::env::temp_dir().join(format!("rookie-guide-storage-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root, "http://localhost:8080/uploads/");
+
+        storage.put("avatars/u1/a/64.jpg", b"jpeg".to_vec(), "image/jpeg").await.unwrap();
+        assert_eq!(std::fs::read(root.join("avatars/u1/a/64.jpg")).unwrap(), b"jpeg");
+
+        let url = storage.public_url("avatars/u1/a/64.jpg");
+        assert_eq!(url, "http://localhost:8080/uploads/avatars/u1/a/64.jpg");
+        assert_eq!(storage.key_from_url(&url).as_deref(), Some("avatars/u1/a/64.jpg"));
+        assert_eq!(storage.key_from_url("https://example.com/a.jpg"), None);
+
+        storage.delete("avatars/u1/a/64.jpg").await.unwrap();
+        assert!(!root.join("avatars/u1/a").exists());
+        // Deleting again is not an error
+        storage.delete("avatars/u1/a/64.jpg").await.unwrap();
+
+        assert!(storage.put("../escape.jpg", Vec::new(), "image/jpeg").await.is_err());
+
+        // Deleting a prefix removes everything below it and nothing else
+        storage.put("avatars/u1/a/64.jpg", b"jpeg".to_vec(), "image/jpeg").await.unwrap();
+        storage.put("avatars/u1/b/64.jpg", b"jpeg".to_vec(), "image/jpeg").await.unwrap();
+        storage.put("avatars/u10/a/64.jpg", b"jpeg".to_vec(), "image/jpeg").await.unwrap();
+        storage.delete_prefix("avatars/u1").await.unwrap();
+        assert!(!root.join("avatars/u1").exists());
+        assert!(root.join("avatars/u10/a/64.jpg").exists());
+        storage.delete_prefix("avatars/u1").await.unwrap();
+        assert!(storage.delete_prefix("..").await.is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use common::{AppError, AppResult};
+
+/// 对象存储接口
+/// 
+/// 按键（如`avatars/{user_id}/{upload_id}/512.jpg`）保存和删除文件，
+/// 文件通过`public_url`返回的地址直接访问，不经过业务接口。
+/// 
+/// 实现：`LocalStorage`（本地目录）、`S3Storage`（S3兼容的对象存储）
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    /// 保存对象，已存在时覆盖
+    /// 
+    /// ## 错误
+    /// - `InternalError`: 键无效（见`validate_key`）或写入失败
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> AppResult<()>;
+
+    /// 删除对象，对象不存在时不报错
+    async fn delete(&self, key: &str) -> AppResult<()>;
+
+    /// 删除键以`{prefix}/`开头的所有对象（如某个用户的全部头像），没有这样的对象时不报错
+    /// 
+    /// ## 错误
+    /// - `InternalError`: `prefix`不是有效的键（见`validate_key`）或删除失败
+    async fn delete_prefix(&self, prefix: &str) -> AppResult<()>;
+
+    /// 对象的公开访问地址
+    fn public_url(&self, key: &str) -> String;
+
+    /// 由`public_url`生成的地址对应的键；不属于该存储的地址返回`None`
+    fn key_from_url(&self, url: &str) -> Option<String> {
+        url.strip_prefix(&self.public_url(""))
+            .filter(|key| validate_key(key).is_ok())
+            .map(str::to_string)
+    }
+}
+
+/// 校验对象的键
+/// 
+/// 键由`/`分隔的若干段组成，每段只能包含字母、数字、`.`、`-`、`_`，
+/// 且不能为空、`.`或`..`（本地存储中键直接作为相对路径使用）
+pub fn validate_key(key: &str) -> AppResult<()> {
+    let valid = !key.is_empty()
+        && key.len() <= 1024
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InternalError(format!("Invalid object key: {}", key)))
+    }
+}
+
+/// 拼接公开访问地址，`base`末尾的`/`会被忽略
+pub(crate) fn join_url(base: &str, key: &str) -> String {
+    format!("{}/{}", base.trim_end_matches('/'), key)
+}
+
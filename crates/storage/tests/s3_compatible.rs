@@ -0,0 +1,242 @@
+//! S3兼容存储的集成测试
+//!
+//! 在本机端口上启动一个类似MinIO的内存对象存储（路径风格的`PUT`/`GET`/`DELETE`和ListObjectsV2），
+//! 用`S3Storage`和默认的HTTP客户端上传、读取和删除对象，
+//! 并验证请求头中的签名信息和请求体哈希。签名算法本身由`s3`模块中的AWS文档示例覆盖。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use auth::{DefaultHttpClient, HttpClient, HttpRequest};
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, put};
+use axum::Router;
+use chrono::{NaiveDateTime, Utc};
+use common::S3Config;
+use sha2::{Digest, Sha256};
+use storage::{ObjectStorage, S3Storage};
+
+const BUCKET: &str = "avatars";
+const ACCESS_KEY_ID: &str = "minioadmin";
+
+/// 列出对象时每页的数量（真实服务是1000，这里用小的值覆盖分页）
+const PAGE_SIZE: usize = 2;
+
+/// 存储桶中的对象：内容类型和内容
+type Objects = Arc<Mutex<HashMap<String, (String, Vec<u8>)>>>;
+
+/// 启动模拟的对象存储，返回它的对象表和服务地址
+async fn start_stand_in() -> (Objects, String) {
+    let objects = Objects::default();
+    let app = Router::new()
+        .route("/:bucket", get(list_objects))
+        .route("/:bucket/*key", put(put_object).get(get_object).delete(delete_object))
+        .with_state(objects.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (objects, endpoint)
+}
+
+/// 像S3一样检查签名相关的请求头（不校验签名值）
+fn check_request(bucket: &str, headers: &HeaderMap, body: &[u8]) -> Result<(), (StatusCode, String)> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let error = |status: StatusCode, code: &str| (status, format!("<Error><Code>{}</Code></Error>", code));
+
+    if bucket != BUCKET {
+        return Err(error(StatusCode::NOT_FOUND, "NoSuchBucket"));
+    }
+
+    let amz_date = NaiveDateTime::parse_from_str(header("x-amz-date"), "%Y%m%dT%H%M%SZ")
+        .map_err(|_| error(StatusCode::FORBIDDEN, "AccessDenied"))?;
+    if (Utc::now().naive_utc() - amz_date).num_minutes().abs() > 15 {
+        return Err(error(StatusCode::FORBIDDEN, "RequestTimeTooSkewed"));
+    }
+
+    let credential = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}/us-east-1/s3/aws4_request, SignedHeaders=",
+        ACCESS_KEY_ID,
+        amz_date.format("%Y%m%d")
+    );
+    let authorization = header("authorization");
+    if !authorization.starts_with(&credential) || !authorization.contains(", Signature=") {
+        return Err(error(StatusCode::FORBIDDEN, "AccessDenied"));
+    }
+
+    let payload_hash: String = Sha256::digest(body).iter().map(|b| format!("{:02x}", b)).collect();
+    if header("x-amz-content-sha256") != payload_hash {
+        return Err(error(StatusCode::BAD_REQUEST, "XAmzContentSHA256Mismatch"));
+    }
+
+    Ok(())
+}
+
+async fn put_object(
+    State(objects): State<Objects>,
+    Path((bucket, key)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Err(error) = check_request(&bucket, &headers, &body) {
+        return error.into_response();
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("binary/octet-stream")
+        .to_string();
+    objects.lock().unwrap().insert(key, (content_type, body.to_vec()));
+
+    StatusCode::OK.into_response()
+}
+
+/// 公开读取，不需要签名
+async fn get_object(State(objects): State<Objects>, Path((_bucket, key)): Path<(String, String)>) -> Response {
+    match objects.lock().unwrap().get(&key) {
+        Some((content_type, body)) => ([(header::CONTENT_TYPE, content_type.clone())], body.clone()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn delete_object(
+    State(objects): State<Objects>,
+    Path((bucket, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(error) = check_request(&bucket, &headers, b"") {
+        return error.into_response();
+    }
+
+    objects.lock().unwrap().remove(&key);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// ListObjectsV2：续传令牌是下一页第一个键加上需要编码的后缀
+async fn list_objects(
+    State(objects): State<Objects>,
+    Path(bucket): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(error) = check_request(&bucket, &headers, b"") {
+        return error.into_response();
+    }
+    if query.get("list-type").map(String::as_str) != Some("2") {
+        return (StatusCode::BAD_REQUEST, "<Error><Code>InvalidArgument</Code></Error>").into_response();
+    }
+
+    let prefix = query.get("prefix").cloned().unwrap_or_default();
+    let start = query
+        .get("continuation-token")
+        .map(|token| token.trim_end_matches("+=").to_string())
+        .unwrap_or_default();
+    let mut keys: Vec<String> = objects
+        .lock()
+        .unwrap()
+        .keys()
+        .filter(|key| key.starts_with(&prefix) && **key >= start)
+        .cloned()
+        .collect();
+    keys.sort();
+
+    let next = keys.get(PAGE_SIZE).map(|key| format!("{}+=", key));
+    let contents: String = keys
+        .iter()
+        .take(PAGE_SIZE)
+        .map(|key| format!("<Contents><Key>{}</Key></Contents>", key))
+        .collect();
+    let body = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><ListBucketResult><Name>{}</Name><Prefix>{}</Prefix>\
+         <IsTruncated>{}</IsTruncated>{}{}</ListBucketResult>",
+        bucket,
+        prefix,
+        next.is_some(),
+        contents,
+        next.map(|token| format!("<NextContinuationToken>{}</NextContinuationToken>", token))
+            .unwrap_or_default()
+    );
+
+    ([(header::CONTENT_TYPE, "application/xml")], body).into_response()
+}
+
+fn config(endpoint: &str, bucket: &str) -> S3Config {
+    S3Config {
+        endpoint: endpoint.to_string(),
+        bucket: bucket.to_string(),
+        region: "us-east-1".to_string(),
+        access_key_id: ACCESS_KEY_ID.to_string(),
+        secret_access_key: "minioadmin".to_string(),
+        path_style: true,
+    }
+}
+
+#[tokio::test]
+async fn uploads_reads_and_deletes_objects() {
+    let (objects, endpoint) = start_stand_in().await;
+    let http = Arc::new(DefaultHttpClient::new());
+    let storage = S3Storage::new(config(&endpoint, BUCKET), format!("{}/{}", endpoint, BUCKET), http.clone());
+
+    let key = "avatars/u1/a/512.jpg";
+    storage.put(key, b"jpeg bytes".to_vec(), "image/jpeg").await.unwrap();
+    assert_eq!(
+        objects.lock().unwrap().get(key).cloned(),
+        Some(("image/jpeg".to_string(), b"jpeg bytes".to_vec()))
+    );
+
+    // The public URL serves the object without credentials
+    let url = storage.public_url(key);
+    assert_eq!(storage.key_from_url(&url).as_deref(), Some(key));
+    let response = http.send(HttpRequest::get(&url)).await.unwrap();
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, b"jpeg bytes");
+
+    storage.delete(key).await.unwrap();
+    assert!(objects.lock().unwrap().is_empty());
+    assert_eq!(http.send(HttpRequest::get(&url)).await.unwrap().status, 404);
+
+    // Deleting a missing object is not an error
+    storage.delete(key).await.unwrap();
+}
+
+#[tokio::test]
+async fn deletes_every_object_under_a_prefix() {
+    let (objects, endpoint) = start_stand_in().await;
+    let storage = S3Storage::new(config(&endpoint, BUCKET), format!("{}/{}", endpoint, BUCKET), Arc::new(DefaultHttpClient::new()));
+
+    // More objects than fit on one page, plus a user whose id shares the prefix
+    let keys = ["avatars/u1/a/64.jpg", "avatars/u1/a/512.jpg", "avatars/u1/b/64.jpg", "avatars/u1/b/512.jpg", "avatars/u1/c/64.jpg"];
+    for key in keys.iter().chain(&["avatars/u10/a/64.jpg"]) {
+        storage.put(key, b"jpeg".to_vec(), "image/jpeg").await.unwrap();
+    }
+
+    storage.delete_prefix("avatars/u1").await.unwrap();
+    let left: Vec<String> = objects.lock().unwrap().keys().cloned().collect();
+    assert_eq!(left, ["avatars/u10/a/64.jpg"]);
+
+    // Nothing left to delete is not an error
+    storage.delete_prefix("avatars/u1").await.unwrap();
+    assert!(storage.delete_prefix("avatars/../u10").await.is_err());
+}
+
+#[tokio::test]
+async fn reports_errors_from_the_service() {
+    let (objects, endpoint) = start_stand_in().await;
+    let storage = S3Storage::new(
+        config(&endpoint, "missing-bucket"),
+        format!("{}/missing-bucket", endpoint),
+        Arc::new(DefaultHttpClient::new()),
+    );
+
+    let error = storage.put("avatars/u1/a/512.jpg", b"jpeg".to_vec(), "image/jpeg").await.unwrap_err();
+    assert!(error.to_string().contains("NoSuchBucket"), "{}", error);
+
+    // Keys are validated before anything is sent
+    assert!(storage.put("../escape.jpg", Vec::new(), "image/jpeg").await.is_err());
+    assert!(objects.lock().unwrap().is_empty());
+}